@@ -0,0 +1,316 @@
+use futures_util::future::Abortable;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+use tokio::sync::oneshot;
+
+use crate::api_keys::ApiKeyStore;
+use crate::git::service::GitService;
+
+use crate::ai::client::{self, AiError};
+use crate::ai::commands::{emit_stream_event, resolve_key, stream_payload, AiState};
+use crate::ai::provider::LlmProvider;
+use crate::ai::types::{
+    AiStreamPayload, ChatMessage, ChatRequest, ContentPart, MessageContent, Role, StreamEvent, ToolCall, Usage,
+};
+
+use super::tools::WorkspaceTools;
+use super::types::{
+    AgentApprovalRequest, AgentOutcome, AgentRequest, AgentStep, AgentStepEvent, AgentToolRun, AgentTranscript, Approval,
+};
+
+const DEFAULT_MAX_STEPS: u32 = 10;
+const MAX_STEPS: u32 = 50;
+/// An unanswered approval counts as a refusal after this long.
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Approvals waiting for `ai_agent_approve`, by run and call id.
+#[derive(Default)]
+pub struct AgentState {
+    pending: Mutex<HashMap<(String, String), oneshot::Sender<bool>>>,
+}
+
+impl AgentState {
+    /// Asks the frontend whether `call` may run and waits for the answer.
+    async fn ask(&self, app_handle: &AppHandle, run_id: &str, call: &ToolCall) -> bool {
+        let key = (run_id.to_string(), call.id.clone());
+        let (tx, rx) = oneshot::channel();
+        match self.pending.lock() {
+            Ok(mut pending) => pending.insert(key.clone(), tx),
+            Err(_) => return false,
+        };
+
+        let request = AgentApprovalRequest {
+            run_id: run_id.to_string(),
+            call_id: call.id.clone(),
+            tool: call.name.clone(),
+            arguments: call.arguments.clone(),
+        };
+        let approved = match app_handle.emit("ai-agent-approval", &request) {
+            Ok(()) => matches!(tokio::time::timeout(APPROVAL_TIMEOUT, rx).await, Ok(Ok(true))),
+            Err(e) => {
+                eprintln!("Failed to emit ai-agent-approval event: {}", e);
+                false
+            }
+        };
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&key);
+        }
+        approved
+    }
+
+    /// Drops the approvals of a run that ended while waiting on one.
+    fn forget_run(&self, run_id: &str) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.retain(|(run, _), _| run != run_id);
+        }
+    }
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Borrowed context of one running agent.
+struct AgentRun<'a> {
+    app_handle: &'a AppHandle,
+    ai: &'a AiState,
+    llm: &'a dyn LlmProvider,
+    api_key: Option<&'a str>,
+    git: &'a GitService,
+    agents: &'a AgentState,
+    tools: &'a WorkspaceTools,
+    request: &'a AgentRequest,
+    run_id: &'a str,
+    provider: &'a str,
+    /// Usage reported so far by the step in flight; kept outside the run
+    /// future so a cancel can still account for it.
+    step_usage: Mutex<Option<Usage>>,
+}
+
+impl AgentRun<'_> {
+    /// Calls the model until it answers without tools or the step limit is
+    /// hit, recording each finished step in `transcript` as it goes.
+    async fn run(&self, transcript: &mut AgentTranscript) -> Result<AgentOutcome, AiError> {
+        let max_steps = self.request.max_steps.unwrap_or(DEFAULT_MAX_STEPS).clamp(1, MAX_STEPS);
+        for index in 0..max_steps {
+            let started_at = now_millis();
+            let request = ChatRequest {
+                model: self.request.model.clone(),
+                messages: transcript.messages.clone(),
+                max_tokens: self.request.max_tokens,
+                temperature: self.request.temperature,
+                top_p: None,
+                tools: WorkspaceTools::definitions(),
+                tool_choice: None,
+            };
+            // Only what is sent is shortened; the transcript keeps every message
+            let request = match self.ai.fit_request(self.llm, self.api_key, &request).await {
+                Some((fitted, fit)) => {
+                    emit_stream_event(self.app_handle, self.run_id, self.provider, AiStreamPayload::ContextFitted { fit });
+                    fitted
+                }
+                None => request,
+            };
+            let response = client::chat_stream(&self.ai.http, self.llm, self.api_key, &request, |event| {
+                if let StreamEvent::Usage(usage) = event {
+                    *self.step_usage.lock().unwrap_or_else(|e| e.into_inner()) = Some(*usage);
+                }
+                if let Some(payload) = stream_payload(event) {
+                    emit_stream_event(self.app_handle, self.run_id, self.provider, payload);
+                }
+            })
+            .await?;
+            self.step_usage.lock().unwrap_or_else(|e| e.into_inner()).take();
+            self.ai.record_usage(self.provider, &request.model, response.usage);
+
+            if let Some(usage) = response.usage {
+                transcript.usage.input_tokens += usage.input_tokens;
+                transcript.usage.output_tokens += usage.output_tokens;
+            }
+            let mut step = AgentStep {
+                index,
+                text: response.content.clone(),
+                tool_runs: Vec::new(),
+                finish_reason: response.finish_reason.clone(),
+                usage: response.usage,
+                started_at,
+                finished_at: 0,
+            };
+
+            if response.tool_calls.is_empty() {
+                transcript.messages.push(ChatMessage::new(Role::Assistant, response.content.clone()));
+                transcript.response = response.content;
+                self.finish_step(transcript, step);
+                return Ok(AgentOutcome::Completed);
+            }
+
+            let mut parts = Vec::new();
+            if !response.content.is_empty() {
+                parts.push(ContentPart::Text { text: response.content.clone() });
+            }
+            parts.extend(response.tool_calls.iter().cloned().map(ContentPart::ToolCall));
+
+            let mut results = Vec::new();
+            for call in &response.tool_calls {
+                let tool_run = self.run_tool(call).await;
+                results.push(ContentPart::ToolResult {
+                    tool_call_id: call.id.clone(),
+                    name: call.name.clone(),
+                    content: tool_run.output.clone(),
+                    is_error: tool_run.is_error,
+                });
+                step.tool_runs.push(tool_run);
+            }
+
+            // Added together so a cancelled run never leaves calls without results
+            transcript.messages.push(ChatMessage { role: Role::Assistant, content: MessageContent::Parts(parts) });
+            transcript.messages.push(ChatMessage { role: Role::Tool, content: MessageContent::Parts(results) });
+            transcript.response = response.content;
+            self.finish_step(transcript, step);
+        }
+        Ok(AgentOutcome::StepLimit)
+    }
+
+    async fn run_tool(&self, call: &ToolCall) -> AgentToolRun {
+        let started = Instant::now();
+        let approval = if !WorkspaceTools::is_mutating(&call.name) {
+            Approval::NotRequired
+        } else if self.agents.ask(self.app_handle, self.run_id, call).await {
+            Approval::Approved
+        } else {
+            Approval::Denied
+        };
+
+        let result = match approval {
+            Approval::Denied => Err("The user declined this action".to_string()),
+            _ => self.tools.execute(self.git, call).await,
+        };
+        let (output, is_error) = match result {
+            Ok(output) => (output, false),
+            Err(e) => (e, true),
+        };
+        AgentToolRun {
+            call: call.clone(),
+            approval,
+            output,
+            is_error,
+            duration_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+
+    fn finish_step(&self, transcript: &mut AgentTranscript, mut step: AgentStep) {
+        step.finished_at = now_millis();
+        let event = AgentStepEvent { run_id: self.run_id.to_string(), step: step.clone() };
+        if let Err(e) = self.app_handle.emit("ai-agent-step", event) {
+            eprintln!("Failed to emit ai-agent-step event: {}", e);
+        }
+        transcript.steps.push(step);
+    }
+}
+
+fn system_prompt(root: &str) -> String {
+    format!(
+        "You are a coding agent working in the project at {}. Paths you pass to tools are relative to it. \
+         Read the relevant code before changing it. Writing files needs the user's approval; \
+         if a write is declined, do not retry it and explain what you wanted to change instead.",
+        root
+    )
+}
+
+/// Runs the model in a loop with the workspace tools. Text and tool calls are
+/// streamed as `ai-stream` events under `run_id`, every finished step is sent
+/// as `ai-agent-step`, and each write first waits on an `ai-agent-approval`.
+/// Stop it with `ai_cancel(run_id)`. Failures after the run has started end
+/// up in the transcript's outcome rather than as an error.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn ai_agent_run(
+    app_handle: AppHandle,
+    state: State<'_, AiState>,
+    agents: State<'_, AgentState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    git: State<'_, GitService>,
+    provider: String,
+    request: AgentRequest,
+    run_id: Option<String>,
+) -> Result<AgentTranscript, String> {
+    let run_id = run_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let tools = WorkspaceTools::new(&request.workspace)?;
+    let llm = state.provider(&provider).map_err(|e| e.to_string())?;
+    let api_key = resolve_key(&keys, llm.as_ref(), Some(&request.workspace)).map_err(|e| e.to_string())?;
+    let (registration, guard) = state.running.start(&run_id).map_err(|e| e.to_string())?;
+
+    let mut messages = vec![ChatMessage::new(Role::System, system_prompt(&tools.root().to_string_lossy()))];
+    messages.extend(request.messages.iter().cloned());
+    let mut transcript = AgentTranscript {
+        run_id: run_id.clone(),
+        provider: provider.clone(),
+        model: request.model.clone(),
+        workspace: tools.root().to_string_lossy().to_string(),
+        steps: Vec::new(),
+        messages,
+        response: String::new(),
+        usage: Usage::default(),
+        outcome: AgentOutcome::Completed,
+        started_at: now_millis(),
+        finished_at: 0,
+    };
+
+    let run = AgentRun {
+        app_handle: &app_handle,
+        ai: &state,
+        llm: llm.as_ref(),
+        api_key: api_key.as_deref(),
+        git: &git,
+        agents: &agents,
+        tools: &tools,
+        request: &request,
+        run_id: &run_id,
+        provider: &provider,
+        step_usage: Mutex::new(None),
+    };
+    // Steps finished before a cancel or failure stay in the transcript
+    let outcome = match Abortable::new(run.run(&mut transcript), registration).await {
+        Ok(Ok(outcome)) => outcome,
+        Ok(Err(e)) => AgentOutcome::Failed { message: e.to_string() },
+        Err(_) => {
+            if let Some(usage) = run.step_usage.lock().unwrap_or_else(|e| e.into_inner()).take() {
+                state.record_usage(&provider, &request.model, Some(usage));
+                transcript.usage.input_tokens += usage.input_tokens;
+                transcript.usage.output_tokens += usage.output_tokens;
+            }
+            AgentOutcome::Cancelled
+        }
+    };
+    transcript.outcome = outcome;
+    transcript.finished_at = now_millis();
+    drop(guard);
+    agents.forget_run(&run_id);
+
+    let finish_reason = match &transcript.outcome {
+        AgentOutcome::Completed => transcript.steps.last().and_then(|s| s.finish_reason.clone()),
+        AgentOutcome::StepLimit => Some("step_limit".to_string()),
+        AgentOutcome::Cancelled => Some("cancelled".to_string()),
+        AgentOutcome::Failed { .. } => None,
+    };
+    let payload = match &transcript.outcome {
+        AgentOutcome::Failed { message } => AiStreamPayload::Error { message: message.clone() },
+        _ => AiStreamPayload::Done { finish_reason, usage: Some(transcript.usage) },
+    };
+    emit_stream_event(&app_handle, &run_id, &provider, payload);
+    Ok(transcript)
+}
+
+/// Answers an `ai-agent-approval`.
+#[tauri::command]
+pub fn ai_agent_approve(agents: State<'_, AgentState>, run_id: String, call_id: String, approved: bool) -> Result<(), String> {
+    let sender = agents
+        .pending
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&(run_id, call_id.clone()))
+        .ok_or_else(|| format!("No pending approval for call '{}'", call_id))?;
+    sender.send(approved).map_err(|_| "Approval is no longer waiting".to_string())
+}
@@ -0,0 +1,8 @@
+mod commands;
+mod tools;
+mod types;
+
+// Re-export public API
+pub use commands::*;
+pub use tools::WorkspaceTools;
+pub use types::*;
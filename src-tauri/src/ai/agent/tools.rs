@@ -0,0 +1,444 @@
+//! Workspace capabilities exposed to the model as tools.
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Component, Path, PathBuf};
+
+use crate::git::service::GitService;
+use crate::outline::OutlineSymbol;
+
+use crate::ai::types::{ToolCall, ToolDefinition};
+
+/// Tool output is cut to this many characters before it goes back to the model.
+const MAX_OUTPUT_CHARS: usize = 20_000;
+const MAX_SEARCH_MATCHES: usize = 200;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadFileArgs {
+    path: String,
+    #[serde(default)]
+    start_line: Option<usize>,
+    #[serde(default)]
+    end_line: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct WriteFileArgs {
+    path: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchArgs {
+    query: String,
+    #[serde(default)]
+    is_regex: bool,
+    #[serde(default)]
+    case_sensitive: bool,
+    #[serde(default)]
+    include: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PathArgs {
+    path: String,
+}
+
+#[derive(Deserialize)]
+struct GitDiffArgs {
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    staged: bool,
+}
+
+/// Runs tool calls against one workspace. Every path the model passes is
+/// taken relative to the workspace root and may not leave it.
+pub struct WorkspaceTools {
+    root: PathBuf,
+}
+
+impl WorkspaceTools {
+    pub fn new(workspace: &str) -> Result<Self, String> {
+        let root = Path::new(workspace)
+            .canonicalize()
+            .map_err(|e| format!("Cannot open workspace '{}': {}", workspace, e))?;
+        if !root.is_dir() {
+            return Err(format!("Workspace '{}' is not a directory", workspace));
+        }
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn definitions() -> Vec<ToolDefinition> {
+        let tool = |name: &str, description: &str, parameters: Value| ToolDefinition {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters,
+        };
+        vec![
+            tool(
+                "read_file",
+                "Read a text file. Optionally limit to a 1-based, inclusive line range.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "Path relative to the workspace root" },
+                        "startLine": { "type": "integer", "minimum": 1 },
+                        "endLine": { "type": "integer", "minimum": 1 }
+                    },
+                    "required": ["path"]
+                }),
+            ),
+            tool(
+                "search_in_files",
+                "Search the workspace for text. Returns matching lines as path:line: text.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string" },
+                        "isRegex": { "type": "boolean" },
+                        "caseSensitive": { "type": "boolean" },
+                        "include": { "type": "string", "description": "Comma separated globs of files to search, e.g. src/**/*.ts" }
+                    },
+                    "required": ["query"]
+                }),
+            ),
+            tool(
+                "get_outline",
+                "List the symbols (classes, functions, ...) declared in a JavaScript or TypeScript file.",
+                json!({
+                    "type": "object",
+                    "properties": { "path": { "type": "string" } },
+                    "required": ["path"]
+                }),
+            ),
+            tool(
+                "git_diff",
+                "Show uncommitted changes as a unified diff, for the whole workspace or one path.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string" },
+                        "staged": { "type": "boolean", "description": "Diff the index against HEAD instead of the working tree against the index" }
+                    }
+                }),
+            ),
+            tool(
+                "get_problems",
+                "List the TypeScript and ESLint errors and warnings in the workspace.",
+                json!({ "type": "object", "properties": {} }),
+            ),
+            tool(
+                "write_file",
+                "Create or overwrite a file with the given content. Requires the user's approval.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string" },
+                        "content": { "type": "string" }
+                    },
+                    "required": ["path", "content"]
+                }),
+            ),
+        ]
+    }
+
+    /// Whether the tool changes the workspace, and so needs the user's approval.
+    pub fn is_mutating(name: &str) -> bool {
+        name == "write_file"
+    }
+
+    /// Runs `call`, returning the text handed back to the model.
+    pub async fn execute(&self, git: &GitService, call: &ToolCall) -> Result<String, String> {
+        let output = match call.name.as_str() {
+            "read_file" => {
+                let args: ReadFileArgs = parse_args(call)?;
+                let path = self.resolve(&args.path)?;
+                let content = crate::fs::read_file(path.to_string_lossy().to_string())?;
+                slice_lines(&content, args.start_line, args.end_line)
+            }
+            "write_file" => {
+                let args: WriteFileArgs = parse_args(call)?;
+                let path = self.resolve(&args.path)?;
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let bytes = args.content.len();
+                crate::fs::write_file(path.to_string_lossy().to_string(), args.content)?;
+                format!("Wrote {} bytes to {}", bytes, self.relative(&path))
+            }
+            "search_in_files" => {
+                let args: SearchArgs = parse_args(call)?;
+                self.search(args).await?
+            }
+            "get_outline" => {
+                let args: PathArgs = parse_args(call)?;
+                let path = self.resolve(&args.path)?;
+                let symbols = crate::outline::get_outline(path.to_string_lossy().to_string())?;
+                let mut text = String::new();
+                format_outline(&symbols, 0, &mut text);
+                if text.is_empty() { "No symbols found".to_string() } else { text }
+            }
+            "git_diff" => {
+                let args: GitDiffArgs = parse_args(call)?;
+                self.diff(git, args.path.as_deref(), args.staged)?
+            }
+            "get_problems" => self.problems().await?,
+            other => return Err(format!("Unknown tool '{}'", other)),
+        };
+        Ok(truncate(output))
+    }
+
+    /// Resolves `path` against the root, refusing anything outside the
+    /// workspace, whether through `..` or through a symlink.
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let outside = || format!("Path '{}' is outside the workspace", path);
+        let mut resolved = PathBuf::new();
+        for component in self.root.join(path).components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                other => resolved.push(other),
+            }
+        }
+        if !resolved.starts_with(&self.root) {
+            return Err(outside());
+        }
+        // The path may not exist yet (a file about to be written), so check where its
+        // nearest present ancestor really is. `symlink_metadata` also sees dangling
+        // links, which `exists` treats as missing and writing would follow.
+        let existing = resolved.ancestors().find(|p| p.symlink_metadata().is_ok()).unwrap_or(&self.root);
+        let real = existing.canonicalize().map_err(|_| format!("Path '{}' goes through a broken symlink", path))?;
+        if !real.starts_with(&self.root) {
+            return Err(outside());
+        }
+        Ok(resolved)
+    }
+
+    fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+    }
+
+    async fn search(&self, args: SearchArgs) -> Result<String, String> {
+        let options = crate::fs::SearchOptions {
+            query: args.query,
+            is_case_sensitive: args.case_sensitive,
+            is_whole_word: false,
+            is_regex: args.is_regex,
+            include_pattern: args.include.unwrap_or_default(),
+            exclude_pattern: String::new(),
+            filter_pattern: String::new(),
+        };
+        let results = crate::fs::search_in_files(self.root.to_string_lossy().to_string(), options).await?;
+
+        let mut lines = Vec::new();
+        let total: usize = results.iter().map(|r| r.matches.len()).sum();
+        'files: for result in &results {
+            let path = self.relative(Path::new(&result.file.path));
+            for found in &result.matches {
+                if lines.len() == MAX_SEARCH_MATCHES {
+                    break 'files;
+                }
+                lines.push(format!("{}:{}: {}", path, found.line, found.line_text.trim_end()));
+            }
+        }
+        if lines.is_empty() {
+            return Ok("No matches".to_string());
+        }
+        if total > lines.len() {
+            lines.push(format!("... {} more matches", total - lines.len()));
+        }
+        Ok(lines.join("\n"))
+    }
+
+    fn diff(&self, git: &GitService, path: Option<&str>, staged: bool) -> Result<String, String> {
+        let repo = git.open(&self.root.to_string_lossy())?;
+        let mut options = git2::DiffOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        if let Some(path) = path {
+            let path = self.resolve(path)?;
+            let workdir = repo
+                .workdir()
+                .and_then(|w| w.canonicalize().ok())
+                .ok_or_else(|| "Repository has no working directory".to_string())?;
+            let pathspec = path.strip_prefix(&workdir).map_err(|_| "Path is outside the repository".to_string())?;
+            options.pathspec(pathspec);
+        }
+
+        let diff = if staged {
+            let head = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            repo.diff_tree_to_index(head.as_ref(), None, Some(&mut options))
+        } else {
+            repo.diff_index_to_workdir(None, Some(&mut options))
+        }
+        .map_err(|e| e.to_string())?;
+
+        let mut text = String::new();
+        diff.print(git2::DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                text.push(line.origin());
+            }
+            text.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })
+        .map_err(|e| e.to_string())?;
+        Ok(if text.is_empty() { "No changes".to_string() } else { text })
+    }
+
+    async fn problems(&self) -> Result<String, String> {
+        let result = crate::problems::get_problems(self.root.to_string_lossy().to_string()).await?;
+        let mut lines = vec![format!("{} errors, {} warnings", result.total_errors, result.total_warnings)];
+        for file in &result.files {
+            let path = self.relative(Path::new(&file.path));
+            for problem in &file.problems {
+                let code = problem.code.as_deref().map(|c| format!(" [{}]", c)).unwrap_or_default();
+                lines.push(format!(
+                    "{}:{}:{}: {} {}{}",
+                    path, problem.line, problem.column, problem.problem_type, problem.message, code
+                ));
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+fn parse_args<T: serde::de::DeserializeOwned>(call: &ToolCall) -> Result<T, String> {
+    serde_json::from_value(call.arguments.clone())
+        .map_err(|e| format!("Invalid arguments for {}: {}", call.name, e))
+}
+
+/// Lines `start..=end` (1-based) of `content`, with a header saying which
+/// part of the file they are. The whole content when no range is given.
+fn slice_lines(content: &str, start: Option<usize>, end: Option<usize>) -> String {
+    if start.is_none() && end.is_none() {
+        return content.to_string();
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let start = start.unwrap_or(1).max(1);
+    let end = end.unwrap_or(lines.len()).min(lines.len());
+    if start > end {
+        return format!("(no lines in range; the file has {} lines)", lines.len());
+    }
+    format!("(lines {}-{} of {})\n{}", start, end, lines.len(), lines[start - 1..end].join("\n"))
+}
+
+fn format_outline(symbols: &[OutlineSymbol], depth: usize, out: &mut String) {
+    for symbol in symbols {
+        out.push_str(&format!(
+            "{}{:?} {} (line {})\n",
+            "  ".repeat(depth),
+            symbol.kind,
+            symbol.name,
+            symbol.range.start_line
+        ));
+        if let Some(children) = &symbol.children {
+            format_outline(children, depth + 1, out);
+        }
+    }
+}
+
+fn truncate(mut output: String) -> String {
+    if output.len() <= MAX_OUTPUT_CHARS {
+        return output;
+    }
+    let mut cut = MAX_OUTPUT_CHARS;
+    while !output.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    output.truncate(cut);
+    output.push_str("\n... output truncated");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall { id: "call_0".to_string(), name: name.to_string(), arguments }
+    }
+
+    #[test]
+    fn test_resolve_stays_inside_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        let tools = WorkspaceTools::new(&dir.path().to_string_lossy()).unwrap();
+
+        assert_eq!(tools.resolve("src/../src/main.ts").unwrap(), tools.root().join("src/main.ts"));
+        assert!(tools.resolve("../outside.txt").is_err());
+        assert!(tools.resolve("src/../../outside.txt").is_err());
+        assert!(tools.resolve("/etc/passwd").is_err());
+        let inside = tools.root().join("src/new.ts");
+        assert_eq!(tools.resolve(&inside.to_string_lossy()).unwrap(), inside);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_rejects_symlink_escape() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+        let tools = WorkspaceTools::new(&dir.path().to_string_lossy()).unwrap();
+
+        assert!(tools.resolve("link/secret.txt").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_refuses_dangling_symlink_out_of_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let target = outside.path().join("planted.txt");
+        std::os::unix::fs::symlink(&target, dir.path().join("notes.txt")).unwrap();
+        let tools = WorkspaceTools::new(&dir.path().to_string_lossy()).unwrap();
+
+        assert!(tools.resolve("notes.txt").is_err());
+        let result = tools
+            .execute(&GitService::default(), &call("write_file", json!({ "path": "notes.txt", "content": "x" })))
+            .await;
+        assert!(result.is_err());
+        assert!(!target.exists());
+    }
+
+    #[tokio::test]
+    async fn test_read_and_write_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let tools = WorkspaceTools::new(&dir.path().to_string_lossy()).unwrap();
+        let git = GitService::default();
+
+        let written = tools
+            .execute(&git, &call("write_file", json!({ "path": "notes/a.txt", "content": "one\ntwo\nthree\n" })))
+            .await
+            .unwrap();
+        assert_eq!(written, "Wrote 14 bytes to notes/a.txt");
+
+        let range = tools
+            .execute(&git, &call("read_file", json!({ "path": "notes/a.txt", "startLine": 2, "endLine": 9 })))
+            .await
+            .unwrap();
+        assert_eq!(range, "(lines 2-3 of 3)\ntwo\nthree");
+
+        let err = tools.execute(&git, &call("read_file", json!({ "file": "a.txt" }))).await.unwrap_err();
+        assert!(err.starts_with("Invalid arguments for read_file"));
+        assert!(tools.execute(&git, &call("delete_everything", json!({}))).await.is_err());
+    }
+
+    #[test]
+    fn test_definitions_and_mutating_tools() {
+        let names: Vec<String> = WorkspaceTools::definitions().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["read_file", "search_in_files", "get_outline", "git_diff", "get_problems", "write_file"]);
+        assert!(WorkspaceTools::is_mutating("write_file"));
+        assert!(!WorkspaceTools::is_mutating("read_file"));
+    }
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ai::types::{ChatMessage, ToolCall, Usage};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentRequest {
+    /// Root folder the tools work in.
+    pub workspace: String,
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    /// Model calls allowed before the run stops; 10 by default, at most 50.
+    #[serde(default)]
+    pub max_steps: Option<u32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Approval {
+    /// Read-only tools run without asking.
+    NotRequired,
+    Approved,
+    /// Refused by the user, or not answered in time.
+    Denied,
+}
+
+/// One tool call and what came of it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentToolRun {
+    pub call: ToolCall,
+    pub approval: Approval,
+    pub output: String,
+    pub is_error: bool,
+    pub duration_ms: u64,
+}
+
+/// One model call and the tool calls it asked for.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentStep {
+    pub index: u32,
+    /// Text the model wrote alongside its tool calls, or its final answer.
+    pub text: String,
+    pub tool_runs: Vec<AgentToolRun>,
+    pub finish_reason: Option<String>,
+    pub usage: Option<Usage>,
+    /// Unix milliseconds.
+    pub started_at: i64,
+    pub finished_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum AgentOutcome {
+    /// The model answered without calling any more tools.
+    Completed,
+    /// `max_steps` model calls were made and the model still wanted tools.
+    StepLimit,
+    Cancelled,
+    Failed { message: String },
+}
+
+/// Everything that happened in a run. `messages` is the whole conversation,
+/// tool calls and results included, so it can be continued with another run.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentTranscript {
+    pub run_id: String,
+    pub provider: String,
+    pub model: String,
+    pub workspace: String,
+    pub steps: Vec<AgentStep>,
+    pub messages: Vec<ChatMessage>,
+    /// Text of the last step.
+    pub response: String,
+    pub usage: Usage,
+    pub outcome: AgentOutcome,
+    pub started_at: i64,
+    pub finished_at: i64,
+}
+
+/// Payload of `ai-agent-approval`. Answer with `ai_agent_approve`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentApprovalRequest {
+    pub run_id: String,
+    pub call_id: String,
+    pub tool: String,
+    pub arguments: Value,
+}
+
+/// Payload of `ai-agent-step`, sent as each step finishes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentStepEvent {
+    pub run_id: String,
+    pub step: AgentStep,
+}
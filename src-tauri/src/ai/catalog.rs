@@ -0,0 +1,119 @@
+//! Context sizes and list prices of well-known models.
+
+use serde::Serialize;
+
+use super::types::Usage;
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pricing {
+    pub input: f64,
+    pub output: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSpec {
+    pub provider: &'static str,
+    /// Model ids starting with this belong to the family; the longest match wins.
+    pub model_prefix: &'static str,
+    pub context_window: u32,
+    pub max_output_tokens: u32,
+    /// `None` when the price is not published in USD or varies by plan.
+    pub pricing: Option<Pricing>,
+}
+
+impl ModelSpec {
+    /// Cost of `usage` in USD, if the model is priced.
+    pub fn cost(&self, usage: Usage) -> Option<f64> {
+        self.pricing.map(|pricing| {
+            (usage.input_tokens as f64 * pricing.input + usage.output_tokens as f64 * pricing.output) / 1_000_000.0
+        })
+    }
+}
+
+const fn spec(
+    provider: &'static str,
+    model_prefix: &'static str,
+    context_window: u32,
+    max_output_tokens: u32,
+    pricing: Option<(f64, f64)>,
+) -> ModelSpec {
+    let pricing = match pricing {
+        Some((input, output)) => Some(Pricing { input, output }),
+        None => None,
+    };
+    ModelSpec { provider, model_prefix, context_window, max_output_tokens, pricing }
+}
+
+static MODELS: &[ModelSpec] = &[
+    spec("openai", "gpt-3.5-turbo", 16_385, 4_096, Some((0.50, 1.50))),
+    spec("openai", "gpt-4-turbo", 128_000, 4_096, Some((10.0, 30.0))),
+    spec("openai", "gpt-4o", 128_000, 16_384, Some((2.50, 10.0))),
+    spec("openai", "gpt-4o-mini", 128_000, 16_384, Some((0.15, 0.60))),
+    spec("openai", "gpt-4.1", 1_047_576, 32_768, Some((2.0, 8.0))),
+    spec("openai", "gpt-4.1-mini", 1_047_576, 32_768, Some((0.40, 1.60))),
+    spec("openai", "gpt-4.1-nano", 1_047_576, 32_768, Some((0.10, 0.40))),
+    spec("openai", "o3", 200_000, 100_000, Some((2.0, 8.0))),
+    spec("openai", "o3-mini", 200_000, 100_000, Some((1.10, 4.40))),
+    spec("openai", "o4-mini", 200_000, 100_000, Some((1.10, 4.40))),
+    spec("anthropic", "claude-3-haiku", 200_000, 4_096, Some((0.25, 1.25))),
+    spec("anthropic", "claude-3-opus", 200_000, 4_096, Some((15.0, 75.0))),
+    spec("anthropic", "claude-3-5-haiku", 200_000, 8_192, Some((0.80, 4.0))),
+    spec("anthropic", "claude-3-5-sonnet", 200_000, 8_192, Some((3.0, 15.0))),
+    spec("anthropic", "claude-3-7-sonnet", 200_000, 64_000, Some((3.0, 15.0))),
+    spec("anthropic", "claude-sonnet-4", 200_000, 64_000, Some((3.0, 15.0))),
+    spec("anthropic", "claude-opus-4", 200_000, 32_000, Some((15.0, 75.0))),
+    spec("google", "gemini-1.5-flash", 1_048_576, 8_192, Some((0.075, 0.30))),
+    spec("google", "gemini-1.5-pro", 2_097_152, 8_192, Some((1.25, 5.0))),
+    spec("google", "gemini-2.0-flash", 1_048_576, 8_192, Some((0.10, 0.40))),
+    spec("google", "gemini-2.5-flash", 1_048_576, 65_536, Some((0.30, 2.50))),
+    spec("google", "gemini-2.5-pro", 1_048_576, 65_536, Some((1.25, 10.0))),
+    spec("xai", "grok-3", 131_072, 16_384, Some((3.0, 15.0))),
+    spec("xai", "grok-3-mini", 131_072, 16_384, Some((0.30, 0.50))),
+    spec("xai", "grok-4", 256_000, 32_768, Some((3.0, 15.0))),
+    spec("zhipu", "glm-4", 128_000, 4_096, None),
+    spec("zhipu", "glm-4-flash", 128_000, 4_096, Some((0.0, 0.0))),
+    spec("yandex", "yandexgpt", 32_768, 8_000, None),
+    spec("yandex", "yandexgpt-lite", 32_768, 8_000, None),
+    spec("gigachat", "GigaChat", 32_768, 4_096, None),
+    spec("gigachat", "GigaChat-2", 131_072, 4_096, None),
+];
+
+/// Every known model family.
+pub fn models() -> &'static [ModelSpec] {
+    MODELS
+}
+
+/// The family `model` of `provider` belongs to. Yandex-style URIs
+/// (`gpt://<folder>/yandexgpt/latest`) are matched on the part after the folder.
+pub fn lookup(provider: &str, model: &str) -> Option<&'static ModelSpec> {
+    let model = match model.split_once("://") {
+        Some((_, rest)) => rest.split_once('/').map_or(rest, |(_, name)| name),
+        None => model,
+    };
+    MODELS
+        .iter()
+        .filter(|spec| spec.provider == provider && model.starts_with(spec.model_prefix))
+        .max_by_key(|spec| spec.model_prefix.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_prefers_longest_prefix() {
+        assert_eq!(lookup("openai", "gpt-4o-mini-2024-07-18").unwrap().model_prefix, "gpt-4o-mini");
+        assert_eq!(lookup("openai", "gpt-4o-2024-08-06").unwrap().model_prefix, "gpt-4o");
+        assert_eq!(lookup("yandex", "gpt://b1gfolder/yandexgpt-lite/latest").unwrap().model_prefix, "yandexgpt-lite");
+        assert!(lookup("anthropic", "gpt-4o").is_none());
+        assert!(lookup("ollama", "llama3").is_none());
+
+        let sonnet = lookup("anthropic", "claude-sonnet-4-20250514").unwrap();
+        let cost = sonnet.cost(Usage { input_tokens: 1_000_000, output_tokens: 100_000 }).unwrap();
+        assert!((cost - 4.5).abs() < 1e-9);
+        assert_eq!(lookup("yandex", "yandexgpt/latest").unwrap().cost(Usage::default()), None);
+    }
+}
@@ -0,0 +1,338 @@
+use futures_util::StreamExt;
+use reqwest::{Client, RequestBuilder, Response};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use thiserror::Error;
+
+use super::provider::LlmProvider;
+use super::stream::StreamDecoder;
+use super::types::{ChatRequest, ChatResponse, StreamEvent, ToolCall, Usage};
+
+#[derive(Error, Debug)]
+pub enum AiError {
+    #[error("HTTP request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("API request failed ({status}): {message}")]
+    ApiError { status: u16, message: String },
+    #[error("Invalid response format: {0}")]
+    InvalidResponse(String),
+    #[error("Stream error: {0}")]
+    StreamError(String),
+    #[error("{0} API key not configured")]
+    MissingApiKey(String),
+    #[error("Invalid API key: {0}")]
+    InvalidApiKey(String),
+    #[error("Unknown AI provider: {0}")]
+    UnknownProvider(String),
+    #[error("Request '{0}' is already running")]
+    RequestInProgress(String),
+    #[error("Request cancelled")]
+    Cancelled,
+}
+
+pub(crate) fn http_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(300))
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+async fn send(builder: RequestBuilder) -> Result<Response, AiError> {
+    let response = builder.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let message = response.text().await.unwrap_or_default();
+        return Err(AiError::ApiError { status: status.as_u16(), message });
+    }
+    Ok(response)
+}
+
+pub async fn chat(
+    http: &Client,
+    provider: &dyn LlmProvider,
+    api_key: Option<&str>,
+    request: &ChatRequest,
+) -> Result<ChatResponse, AiError> {
+    let credential = provider.authorize(http, api_key).await?;
+    let response = send(provider.build_request(http, credential.as_deref(), request, false)?).await?;
+    let body = response.text().await?;
+    let mut parsed = provider.parse_response(&body)?;
+    if parsed.model.is_empty() {
+        parsed.model = request.model.clone();
+    }
+    Ok(parsed)
+}
+
+/// Models the provider serves, or none if it cannot list them.
+pub async fn list_models(http: &Client, provider: &dyn LlmProvider, api_key: Option<&str>) -> Result<Vec<String>, AiError> {
+    let credential = provider.authorize(http, api_key).await?;
+    let Some(builder) = provider.models_request(http, credential.as_deref()) else {
+        return Ok(Vec::new());
+    };
+    let body = send(builder?).await?.text().await?;
+    provider.parse_models(&body)
+}
+
+/// Assembles tool calls from streamed start and argument events.
+#[derive(Default)]
+struct ToolCallAccumulator {
+    /// `(id, name, arguments)` in the order the calls started.
+    calls: Vec<(String, String, String)>,
+    /// Position in `calls` of the latest call started under each index.
+    by_index: HashMap<usize, usize>,
+}
+
+impl ToolCallAccumulator {
+    fn start(&mut self, index: usize, id: &str, name: &str) {
+        self.by_index.insert(index, self.calls.len());
+        self.calls.push((id.to_string(), name.to_string(), String::new()));
+    }
+
+    fn append(&mut self, index: usize, fragment: &str) -> Result<(), AiError> {
+        let position = self.by_index.get(&index).ok_or_else(|| {
+            AiError::InvalidResponse(format!("Arguments for tool call {} before it started", index))
+        })?;
+        self.calls[*position].2.push_str(fragment);
+        Ok(())
+    }
+
+    /// Parses the accumulated arguments, naming calls that came without an id.
+    fn finish(self) -> Result<Vec<ToolCall>, AiError> {
+        self.calls
+            .into_iter()
+            .enumerate()
+            .map(|(position, (id, name, arguments))| {
+                let arguments = if arguments.trim().is_empty() {
+                    Value::Object(Default::default())
+                } else {
+                    serde_json::from_str(&arguments).map_err(|e| {
+                        AiError::InvalidResponse(format!("Invalid arguments for tool '{}': {}", name, e))
+                    })?
+                };
+                let id = if id.is_empty() { format!("call_{}", position) } else { id };
+                Ok(ToolCall { id, name, arguments })
+            })
+            .collect()
+    }
+}
+
+/// Streams a chat, calling `on_event` for every event in arrival order, and
+/// returns the assembled response. Usage events are passed on already merged,
+/// and once the body ends every tool call is passed on whole as
+/// `StreamEvent::ToolCall`.
+pub async fn chat_stream<F>(
+    http: &Client,
+    provider: &dyn LlmProvider,
+    api_key: Option<&str>,
+    request: &ChatRequest,
+    mut on_event: F,
+) -> Result<ChatResponse, AiError>
+where
+    F: FnMut(&StreamEvent),
+{
+    let credential = provider.authorize(http, api_key).await?;
+    let response = send(provider.build_request(http, credential.as_deref(), request, true)?).await?;
+    let full_text = provider.streams_full_text();
+    let mut decoder = StreamDecoder::new(provider.stream_format());
+    let mut body = response.bytes_stream();
+    let mut result = ChatResponse { model: request.model.clone(), ..Default::default() };
+    let mut tool_calls = ToolCallAccumulator::default();
+
+    let mut handle = |event: StreamEvent, result: &mut ChatResponse, tool_calls: &mut ToolCallAccumulator| {
+        let event = match event {
+            StreamEvent::Delta(text) => {
+                let text = if full_text {
+                    let Some(added) = text.strip_prefix(result.content.as_str()) else {
+                        return Err(AiError::StreamError("Streamed text does not extend the text so far".to_string()));
+                    };
+                    added.to_string()
+                } else {
+                    text
+                };
+                if text.is_empty() {
+                    return Ok(());
+                }
+                result.content.push_str(&text);
+                StreamEvent::Delta(text)
+            }
+            StreamEvent::Usage(usage) => {
+                let merged = result.usage.get_or_insert_with(Usage::default);
+                merged.merge(usage);
+                StreamEvent::Usage(*merged)
+            }
+            StreamEvent::Done { finish_reason } => {
+                if finish_reason.is_some() {
+                    result.finish_reason = finish_reason.clone();
+                }
+                StreamEvent::Done { finish_reason }
+            }
+            StreamEvent::ToolCallStart { index, id, name } => {
+                tool_calls.start(index, &id, &name);
+                StreamEvent::ToolCallStart { index, id, name }
+            }
+            StreamEvent::ToolCallDelta { index, arguments } => {
+                tool_calls.append(index, &arguments)?;
+                StreamEvent::ToolCallDelta { index, arguments }
+            }
+            StreamEvent::ToolCall(call) => StreamEvent::ToolCall(call),
+        };
+        on_event(&event);
+        Ok::<(), AiError>(())
+    };
+
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk?;
+        for frame in decoder.push(&chunk) {
+            for event in provider.parse_stream_frame(&frame)? {
+                handle(event, &mut result, &mut tool_calls)?;
+            }
+        }
+    }
+    for frame in decoder.finish() {
+        for event in provider.parse_stream_frame(&frame)? {
+            handle(event, &mut result, &mut tool_calls)?;
+        }
+    }
+
+    result.tool_calls = tool_calls.finish()?;
+    for call in &result.tool_calls {
+        on_event(&StreamEvent::ToolCall(call.clone()));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::ai::registry::ProviderRegistry;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    /// Serves `body` with `content_type` to every request and records each
+    /// request's head and body.
+    pub(crate) fn mock_server(status: u16, content_type: &'static str, body: String) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let log = recorded.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                let mut request = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    request.push_str(&line);
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap_or(0);
+                        }
+                    }
+                }
+                let mut request_body = vec![0u8; length];
+                reader.read_exact(&mut request_body).unwrap();
+                request.push_str(&String::from_utf8_lossy(&request_body));
+                log.lock().unwrap().push(request);
+
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, content_type, body.len(), body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (base, recorded)
+    }
+
+    #[tokio::test]
+    async fn test_stream_assembles_response() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"lo\"},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":3,\"completion_tokens\":2}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let (base, recorded) = mock_server(200, "text/event-stream", body.to_string());
+        let provider = crate::ai::providers::OpenAiCompatible::new("test", "Test", &format!("{}/v1", base));
+        let request = ChatRequest { model: "m".into(), messages: vec![], ..Default::default() };
+
+        let mut deltas = Vec::new();
+        let response = chat_stream(&http_client(), &provider, Some("key"), &request, |event| {
+            if let StreamEvent::Delta(text) = event {
+                deltas.push(text.clone());
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(deltas, ["Hel", "lo"]);
+        assert_eq!(response.content, "Hello");
+        assert_eq!(response.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(response.usage, Some(Usage { input_tokens: 3, output_tokens: 2 }));
+        assert!(recorded.lock().unwrap()[0].contains("\"stream\":true"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_accumulates_tool_calls() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_a\",\"function\":{\"name\":\"read_file\",\"arguments\":\"\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"path\\\":\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"a.rs\\\"}\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":1,\"id\":\"call_b\",\"function\":{\"name\":\"get_problems\",\"arguments\":\"\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let (base, _) = mock_server(200, "text/event-stream", body.to_string());
+        let provider = crate::ai::providers::OpenAiCompatible::new("test", "Test", &format!("{}/v1", base));
+        let request = ChatRequest { model: "m".into(), ..Default::default() };
+
+        let mut completed = Vec::new();
+        let response = chat_stream(&http_client(), &provider, Some("key"), &request, |event| {
+            if let StreamEvent::ToolCall(call) = event {
+                completed.push(call.clone());
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(response.tool_calls, completed);
+        assert_eq!(completed[0], ToolCall { id: "call_a".into(), name: "read_file".into(), arguments: serde_json::json!({ "path": "a.rs" }) });
+        assert_eq!(completed[1].arguments, serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_accumulator_rejects_bad_arguments() {
+        let mut calls = ToolCallAccumulator::default();
+        assert!(calls.append(0, "{}").is_err());
+        calls.start(0, "", "x");
+        calls.append(0, "{not json").unwrap();
+        assert!(calls.finish().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_api_errors_carry_status() {
+        let (base, _) = mock_server(401, "application/json", "{\"error\":\"bad key\"}".to_string());
+        let provider = crate::ai::providers::OpenAiCompatible::new("test", "Test", &format!("{}/v1", base));
+        let request = ChatRequest { model: "m".into(), ..Default::default() };
+        match chat(&http_client(), &provider, Some("key"), &request).await {
+            Err(AiError::ApiError { status, message }) => {
+                assert_eq!(status, 401);
+                assert!(message.contains("bad key"));
+            }
+            other => panic!("unexpected {:?}", other.map(|r| r.content)),
+        }
+        assert!(matches!(
+            chat(&http_client(), &provider, None, &request).await,
+            Err(AiError::MissingApiKey(_))
+        ));
+        assert!(ProviderRegistry::with_defaults().get("nope").is_err());
+    }
+}
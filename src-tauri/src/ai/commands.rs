@@ -0,0 +1,296 @@
+use futures_util::future::Abortable;
+use reqwest::Client;
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::api_keys::{self, ApiKeyStore};
+use crate::settings::{AISettings, ContextStrategy};
+
+use super::catalog::{self, ModelSpec};
+use super::client::{self, http_client, AiError};
+use super::context::{self, ContextFit};
+use super::provider::LlmProvider;
+use super::registry::ProviderRegistry;
+use super::requests::RunningRequests;
+use super::tokens::{estimate_request, TokenEstimate};
+use super::types::{AiStreamEvent, AiStreamPayload, ChatRequest, ChatResponse, ProviderInfo, StreamEvent, Usage};
+use super::usage::{UsageLedger, UsageRecord, UsageSummary};
+
+pub struct AiState {
+    registry: RwLock<ProviderRegistry>,
+    context_strategy: RwLock<ContextStrategy>,
+    usage: Mutex<UsageLedger>,
+    pub http: Client,
+    pub running: RunningRequests,
+}
+
+impl Default for AiState {
+    fn default() -> Self {
+        Self {
+            registry: RwLock::new(ProviderRegistry::with_defaults()),
+            context_strategy: RwLock::new(ContextStrategy::default()),
+            usage: Mutex::new(UsageLedger::new()),
+            http: http_client(),
+            running: RunningRequests::default(),
+        }
+    }
+}
+
+impl AiState {
+    pub fn provider(&self, id: &str) -> Result<Arc<dyn LlmProvider>, AiError> {
+        let registry = self.registry.read().unwrap_or_else(|e| e.into_inner());
+        registry.get(id)
+    }
+
+    pub fn providers(&self) -> Vec<ProviderInfo> {
+        let registry = self.registry.read().unwrap_or_else(|e| e.into_inner());
+        registry.list()
+    }
+
+    /// Rebuilds the providers from the endpoints in settings. Requests
+    /// already running keep the provider they started with.
+    pub fn apply_settings(&self, settings: &AISettings) {
+        let registry = ProviderRegistry::with_endpoints(&settings.endpoints);
+        *self.registry.write().unwrap_or_else(|e| e.into_inner()) = registry;
+        *self.context_strategy.write().unwrap_or_else(|e| e.into_inner()) = settings.context_strategy;
+    }
+
+    /// `request` fitted into the model's context window with the configured
+    /// strategy, or `None` when it fits as is or the window is unknown.
+    /// Tokens spent on a summary are recorded like any other request.
+    pub async fn fit_request(
+        &self,
+        llm: &dyn LlmProvider,
+        api_key: Option<&str>,
+        request: &ChatRequest,
+    ) -> Option<(ChatRequest, ContextFit)> {
+        let provider = llm.info().id;
+        let spec = catalog::lookup(&provider, &request.model)?;
+        let strategy = *self.context_strategy.read().unwrap_or_else(|e| e.into_inner());
+        let fitted =
+            context::fit(&self.http, llm, api_key, strategy, spec.context_window, spec.max_output_tokens, request).await?;
+        if fitted.1.summarized {
+            self.record_usage(&provider, &request.model, fitted.1.summary_usage);
+        }
+        Some(fitted)
+    }
+
+    /// Adds a finished request to the usage ledger. Failing to write it is
+    /// logged rather than failing the request.
+    pub fn record_usage(&self, provider: &str, model: &str, usage: Option<Usage>) {
+        let Some(usage) = usage else { return };
+        let record = UsageRecord {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cost: catalog::lookup(provider, model).and_then(|spec| spec.cost(usage)),
+        };
+        let ledger = self.usage.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = ledger.record(&record) {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+/// The key for `provider` in `workspace`, or `None` when it does not need one.
+/// Cloned out so the store lock is not held across the request.
+pub(super) fn resolve_key(
+    keys: &State<'_, Mutex<ApiKeyStore>>,
+    provider: &dyn LlmProvider,
+    workspace: Option<&str>,
+) -> Result<Option<String>, AiError> {
+    let info = provider.info();
+    if !info.requires_api_key {
+        return Ok(None);
+    }
+    api_keys::get_api_key(keys, &info.id, workspace)
+        .map(Some)
+        .map_err(|_| AiError::MissingApiKey(info.id))
+}
+
+#[tauri::command]
+pub fn ai_list_providers(state: State<'_, AiState>) -> Vec<ProviderInfo> {
+    state.providers()
+}
+
+/// Models `provider` serves, as reported by its API; empty for providers
+/// that cannot list them.
+#[tauri::command]
+pub async fn ai_list_models(
+    state: State<'_, AiState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    provider: String,
+    workspace: Option<String>,
+) -> Result<Vec<String>, String> {
+    let provider = state.provider(&provider).map_err(|e| e.to_string())?;
+    let api_key = resolve_key(&keys, provider.as_ref(), workspace.as_deref()).map_err(|e| e.to_string())?;
+
+    client::list_models(&state.http, provider.as_ref(), api_key.as_deref())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn ai_chat(
+    state: State<'_, AiState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    provider: String,
+    request: ChatRequest,
+    workspace: Option<String>,
+) -> Result<ChatResponse, String> {
+    let provider = state.provider(&provider).map_err(|e| e.to_string())?;
+    let api_key = resolve_key(&keys, provider.as_ref(), workspace.as_deref()).map_err(|e| e.to_string())?;
+
+    let fitted = state.fit_request(provider.as_ref(), api_key.as_deref(), &request).await;
+    let request = fitted.map_or(request, |(request, _)| request);
+    let response = client::chat(&state.http, provider.as_ref(), api_key.as_deref(), &request)
+        .await
+        .map_err(|e| e.to_string())?;
+    state.record_usage(&provider.info().id, &request.model, response.usage);
+    Ok(response)
+}
+
+pub(super) fn emit_stream_event(app_handle: &AppHandle, request_id: &str, provider: &str, payload: AiStreamPayload) {
+    let event = AiStreamEvent {
+        request_id: request_id.to_string(),
+        provider: provider.to_string(),
+        payload,
+    };
+    if let Err(e) = app_handle.emit("ai-stream", event) {
+        eprintln!("Failed to emit ai-stream event: {}", e);
+    }
+}
+
+/// The `ai-stream` payload for a provider event.
+pub(super) fn stream_payload(event: &StreamEvent) -> Option<AiStreamPayload> {
+    let payload = match event {
+        StreamEvent::Delta(content) => AiStreamPayload::Delta { content: content.clone() },
+        StreamEvent::Usage(usage) => AiStreamPayload::Usage { usage: *usage },
+        StreamEvent::ToolCallStart { index, id, name } => AiStreamPayload::ToolCallStart {
+            index: *index,
+            id: id.clone(),
+            name: name.clone(),
+        },
+        StreamEvent::ToolCallDelta { index, arguments } => AiStreamPayload::ToolCallDelta {
+            index: *index,
+            arguments: arguments.clone(),
+        },
+        StreamEvent::ToolCall(call) => AiStreamPayload::ToolCall { tool_call: call.clone() },
+        // Usage can still follow the provider's finish event, so `done` is sent once the stream ends
+        StreamEvent::Done { .. } => return None,
+    };
+    Some(payload)
+}
+
+/// Streams a chat as `ai-stream` events tagged with `request_id`, ending with
+/// a `done` or `error` event, and returns the full response. Pass a
+/// `request_id` to be able to stop the stream with `ai_cancel`; otherwise one
+/// is generated. `workspace` selects its API key override, if it has one.
+#[tauri::command]
+pub async fn ai_chat_stream(
+    app_handle: AppHandle,
+    state: State<'_, AiState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    provider: String,
+    request: ChatRequest,
+    request_id: Option<String>,
+    workspace: Option<String>,
+) -> Result<ChatResponse, String> {
+    let request_id = request_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let (registration, guard) = state.running.start(&request_id).map_err(|e| e.to_string())?;
+    let model = request.model.clone();
+    // Outlives the stream, so a cancelled request is still accounted for
+    let usage_so_far: Mutex<Option<Usage>> = Mutex::new(None);
+
+    let stream = async {
+        let llm = state.provider(&provider)?;
+        let api_key = resolve_key(&keys, llm.as_ref(), workspace.as_deref())?;
+        let request = match state.fit_request(llm.as_ref(), api_key.as_deref(), &request).await {
+            Some((fitted, fit)) => {
+                emit_stream_event(&app_handle, &request_id, &provider, AiStreamPayload::ContextFitted { fit });
+                fitted
+            }
+            None => request,
+        };
+        let response = client::chat_stream(&state.http, llm.as_ref(), api_key.as_deref(), &request, |event| {
+            if let StreamEvent::Usage(usage) = event {
+                *usage_so_far.lock().unwrap_or_else(|e| e.into_inner()) = Some(*usage);
+            }
+            if let Some(payload) = stream_payload(event) {
+                emit_stream_event(&app_handle, &request_id, &provider, payload);
+            }
+        })
+        .await?;
+        state.record_usage(&provider, &request.model, response.usage);
+        Ok(response)
+    };
+    // Dropping the aborted future drops the response body, closing the connection
+    let result = Abortable::new(stream, registration)
+        .await
+        .unwrap_or(Err(AiError::Cancelled));
+    drop(guard);
+
+    let payload = match &result {
+        Ok(response) => AiStreamPayload::Done {
+            finish_reason: response.finish_reason.clone(),
+            usage: response.usage,
+        },
+        Err(AiError::Cancelled) => {
+            let usage = usage_so_far.into_inner().unwrap_or_else(|e| e.into_inner());
+            state.record_usage(&provider, &model, usage);
+            AiStreamPayload::Done {
+                finish_reason: Some("cancelled".to_string()),
+                usage,
+            }
+        }
+        Err(e) => AiStreamPayload::Error { message: e.to_string() },
+    };
+    emit_stream_event(&app_handle, &request_id, &provider, payload);
+    result.map_err(|e| e.to_string())
+}
+
+/// Stops a stream started with `ai_chat_stream`, or an agent run.
+#[tauri::command]
+pub fn ai_cancel(state: State<'_, AiState>, request_id: String) -> Result<(), String> {
+    if state.running.cancel(&request_id) {
+        Ok(())
+    } else {
+        Err(format!("No running request '{}'", request_id))
+    }
+}
+
+/// Context sizes and prices of the models the app knows about.
+#[tauri::command]
+pub fn ai_model_catalog() -> Vec<ModelSpec> {
+    catalog::models().to_vec()
+}
+
+/// Local estimate of the input tokens of `request`, measured against the
+/// model's context window and price where known.
+#[tauri::command]
+pub fn ai_estimate_tokens(provider: String, request: ChatRequest) -> TokenEstimate {
+    let input_tokens = estimate_request(&request);
+    let spec = catalog::lookup(&provider, &request.model);
+    let context_window = spec.map(|spec| spec.context_window);
+    TokenEstimate {
+        input_tokens,
+        context_window,
+        available_output_tokens: spec
+            .map(|spec| spec.context_window.saturating_sub(input_tokens).min(spec.max_output_tokens)),
+        input_cost: spec.and_then(|spec| spec.cost(Usage { input_tokens, output_tokens: 0 })),
+    }
+}
+
+/// Token and cost totals per day and provider, for requests from `from` up
+/// to `to` (Unix millis).
+#[tauri::command]
+pub fn ai_usage_summary(state: State<'_, AiState>, from: Option<i64>, to: Option<i64>) -> Result<UsageSummary, String> {
+    state.usage.lock().map_err(|e| e.to_string())?.summary(from, to)
+}
+
+#[tauri::command]
+pub fn ai_usage_clear(state: State<'_, AiState>) -> Result<(), String> {
+    state.usage.lock().map_err(|e| e.to_string())?.clear()
+}
@@ -0,0 +1,231 @@
+//! Fitting long histories into a model's context window.
+
+use reqwest::Client;
+use serde::Serialize;
+
+use crate::settings::ContextStrategy;
+
+use super::client::{self, AiError};
+use super::provider::LlmProvider;
+use super::tokens::{estimate_message, estimate_text, estimate_tools};
+use super::types::{ChatMessage, ChatRequest, ContentPart, MessageContent, Role, Usage};
+
+/// Reply room kept free when the request does not set `max_tokens`.
+const DEFAULT_REPLY_TOKENS: u32 = 4_096;
+/// Upper bound on the reply asked of the summarizer.
+const SUMMARY_TOKENS: u32 = 1_024;
+
+const SUMMARY_PROMPT: &str = "Summarize the conversation below for the assistant that will continue it. \
+    Keep decisions, facts, file names, code identifiers and open questions; drop pleasantries. \
+    Answer with the summary only.";
+
+/// What fitting did to a request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextFit {
+    pub dropped_messages: usize,
+    pub summarized: bool,
+    /// Tokens spent writing the summary.
+    pub summary_usage: Option<Usage>,
+}
+
+/// Tokens the messages may take: the window minus room for the reply and
+/// the tool definitions.
+fn message_budget(request: &ChatRequest, context_window: u32, max_output_tokens: u32) -> u32 {
+    let reply = request.max_tokens.unwrap_or(DEFAULT_REPLY_TOKENS).min(max_output_tokens);
+    context_window.saturating_sub(reply).saturating_sub(estimate_tools(request))
+}
+
+/// Splits `messages` into the leading system messages and the exchanges
+/// after them. An exchange starts at a user message and runs up to the next,
+/// so tool calls always stay with their results.
+fn exchanges(messages: &[ChatMessage]) -> (&[ChatMessage], Vec<&[ChatMessage]>) {
+    let start = messages.iter().take_while(|m| m.role == Role::System).count();
+    let (system, rest) = messages.split_at(start);
+    let mut groups = Vec::new();
+    let mut group_start = 0;
+    for (i, message) in rest.iter().enumerate() {
+        if message.role == Role::User && i > group_start {
+            groups.push(&rest[group_start..i]);
+            group_start = i;
+        }
+    }
+    if group_start < rest.len() {
+        groups.push(&rest[group_start..]);
+    }
+    (system, groups)
+}
+
+/// The messages to send, dropping the oldest exchanges until the rest fit
+/// in `budget`, and the dropped ones. The latest exchange is always kept.
+pub fn truncate(messages: &[ChatMessage], budget: u32) -> (Vec<ChatMessage>, Vec<ChatMessage>) {
+    let (system, groups) = exchanges(messages);
+    let cost = |group: &[ChatMessage]| group.iter().map(estimate_message).sum::<u32>();
+    let mut total = cost(system) + groups.iter().map(|g| cost(g)).sum::<u32>();
+
+    let mut first_kept = 0;
+    while total > budget && first_kept + 1 < groups.len() {
+        total -= cost(groups[first_kept]);
+        first_kept += 1;
+    }
+    let dropped = groups[..first_kept].concat();
+    let mut kept = system.to_vec();
+    kept.extend(groups[first_kept..].concat());
+    (kept, dropped)
+}
+
+/// `messages` as plain text for the summarizer, keeping only the latest
+/// `budget` tokens' worth.
+fn transcript(messages: &[ChatMessage], budget: u32) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for message in messages {
+        for part in message.content.parts() {
+            let line = match part {
+                ContentPart::Text { text } => format!("{}: {}", message.role.as_str(), text),
+                ContentPart::Image { .. } => format!("{}: [image]", message.role.as_str()),
+                ContentPart::ToolCall(call) => format!("assistant called {} with {}", call.name, call.arguments),
+                ContentPart::ToolResult { name, content, .. } => format!("{} returned: {}", name, content),
+            };
+            lines.push(line);
+        }
+    }
+    let mut used = 0;
+    let mut kept = Vec::new();
+    for line in lines.into_iter().rev() {
+        used += estimate_text(&line);
+        if used > budget {
+            break;
+        }
+        kept.push(line);
+    }
+    kept.reverse();
+    kept.join("\n\n")
+}
+
+/// `messages` with `summary` added to the system prompt, which some
+/// providers only accept as the first message.
+fn with_summary(mut messages: Vec<ChatMessage>, summary: &str) -> Vec<ChatMessage> {
+    let note = format!("Summary of the earlier conversation:\n{}", summary.trim());
+    match messages.first_mut() {
+        Some(first) if first.role == Role::System && first.content.is_text_only() => {
+            first.content = MessageContent::Text(format!("{}\n\n{}", first.content.text(), note));
+        }
+        _ => messages.insert(0, ChatMessage::new(Role::System, note)),
+    }
+    messages
+}
+
+/// `request` shortened to fit `context_window` with `strategy`, and what was
+/// done, or `None` when it already fits. A failed summary falls back to
+/// plain truncation.
+pub async fn fit(
+    http: &Client,
+    llm: &dyn LlmProvider,
+    api_key: Option<&str>,
+    strategy: ContextStrategy,
+    context_window: u32,
+    max_output_tokens: u32,
+    request: &ChatRequest,
+) -> Option<(ChatRequest, ContextFit)> {
+    if strategy == ContextStrategy::Off {
+        return None;
+    }
+    let budget = message_budget(request, context_window, max_output_tokens);
+    let (kept, dropped) = truncate(&request.messages, budget);
+    if dropped.is_empty() {
+        return None;
+    }
+    let mut fit = ContextFit { dropped_messages: dropped.len(), summarized: false, summary_usage: None };
+
+    let mut messages = kept;
+    if strategy == ContextStrategy::Summarize {
+        let prompt = ChatMessage::new(Role::System, SUMMARY_PROMPT);
+        let reply = SUMMARY_TOKENS.min(max_output_tokens);
+        let room = context_window.saturating_sub(reply).saturating_sub(estimate_message(&prompt) * 2);
+        let summary_request = ChatRequest {
+            model: request.model.clone(),
+            messages: vec![prompt, ChatMessage::new(Role::User, transcript(&dropped, room))],
+            max_tokens: Some(reply),
+            ..Default::default()
+        };
+        match client::chat(http, llm, api_key, &summary_request).await {
+            Ok(response) if !response.content.trim().is_empty() => {
+                messages = with_summary(messages, &response.content);
+                fit.summarized = true;
+                fit.summary_usage = response.usage;
+            }
+            Ok(_) => eprintln!("Summarizing the earlier conversation returned nothing; truncating instead"),
+            Err(AiError::Cancelled) => {}
+            Err(e) => eprintln!("Failed to summarize the earlier conversation, truncating instead: {}", e),
+        }
+    }
+    Some((ChatRequest { messages, ..request.clone() }, fit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::client::http_client;
+    use crate::ai::client::tests::mock_server;
+    use crate::ai::types::ToolCall;
+
+    fn history() -> Vec<ChatMessage> {
+        let call = ToolCall { id: "c1".into(), name: "read_file".into(), arguments: serde_json::json!({}) };
+        vec![
+            ChatMessage::new(Role::System, "be brief"),
+            ChatMessage::new(Role::User, "first ".repeat(100)),
+            ChatMessage { role: Role::Assistant, content: MessageContent::Parts(vec![ContentPart::ToolCall(call)]) },
+            ChatMessage {
+                role: Role::Tool,
+                content: MessageContent::Parts(vec![ContentPart::ToolResult {
+                    tool_call_id: "c1".into(),
+                    name: "read_file".into(),
+                    content: "x".repeat(400),
+                    is_error: false,
+                }]),
+            },
+            ChatMessage::new(Role::Assistant, "done"),
+            ChatMessage::new(Role::User, "second question"),
+        ]
+    }
+
+    #[test]
+    fn test_truncate_drops_whole_exchanges() {
+        let messages = history();
+        let (kept, dropped) = truncate(&messages, 10_000);
+        assert_eq!(kept, messages);
+        assert!(dropped.is_empty());
+
+        // Tool call and result go together, the system prompt stays
+        let (kept, dropped) = truncate(&messages, 50);
+        assert_eq!(dropped.len(), 4);
+        assert_eq!(kept, vec![messages[0].clone(), messages[5].clone()]);
+
+        // The latest exchange is kept even when it alone is too big
+        let (kept, _) = truncate(&messages, 0);
+        assert_eq!(kept.last().unwrap().content.text(), "second question");
+    }
+
+    #[tokio::test]
+    async fn test_summarize_replaces_dropped_exchanges() {
+        let reply = r#"{"choices":[{"message":{"role":"assistant","content":"User asked about a.rs."},"finish_reason":"stop"}],"usage":{"prompt_tokens":200,"completion_tokens":6}}"#;
+        let (base, recorded) = mock_server(200, "application/json", reply.to_string());
+        let provider = crate::ai::providers::OpenAiCompatible::new("test", "Test", &format!("{}/v1", base));
+        let request = ChatRequest { model: "m".into(), messages: history(), max_tokens: Some(100), ..Default::default() };
+
+        let http = http_client();
+        assert!(fit(&http, &provider, Some("k"), ContextStrategy::Summarize, 100_000, 4_096, &request).await.is_none());
+        assert!(fit(&http, &provider, Some("k"), ContextStrategy::Off, 300, 20, &request).await.is_none());
+
+        let (fitted, info) = fit(&http, &provider, Some("k"), ContextStrategy::Summarize, 300, 20, &request).await.unwrap();
+        assert_eq!(info, ContextFit { dropped_messages: 4, summarized: true, summary_usage: Some(Usage { input_tokens: 200, output_tokens: 6 }) });
+        assert_eq!(fitted.messages.len(), 2);
+        assert_eq!(fitted.messages[0].content.text(), "be brief\n\nSummary of the earlier conversation:\nUser asked about a.rs.");
+        assert!(recorded.lock().unwrap()[0].contains("read_file returned"));
+
+        let (truncated, info) = fit(&http, &provider, Some("k"), ContextStrategy::Truncate, 300, 20, &request).await.unwrap();
+        assert!(!info.summarized);
+        assert_eq!(truncated.messages[0].content.text(), "be brief");
+        assert_eq!(recorded.lock().unwrap().len(), 1);
+    }
+}
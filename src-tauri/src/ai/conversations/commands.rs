@@ -0,0 +1,105 @@
+use std::sync::Mutex;
+use tauri::State;
+
+use super::export;
+use super::store::ConversationStore;
+use super::types::*;
+
+/// Conversation store wrapper for Tauri
+pub struct ConversationState(pub Mutex<ConversationStore>);
+
+impl Default for ConversationState {
+    fn default() -> Self {
+        Self(Mutex::new(ConversationStore::new()))
+    }
+}
+
+/// Conversations of the workspace, most recently updated first
+#[tauri::command]
+pub fn ai_conversations_list(state: State<'_, ConversationState>, workspace: String) -> Result<Vec<ConversationSummary>, String> {
+    let store = state.0.lock().map_err(|e| e.to_string())?;
+    store.list(&workspace)
+}
+
+#[tauri::command]
+pub fn ai_conversation_create(
+    state: State<'_, ConversationState>,
+    workspace: String,
+    title: Option<String>,
+) -> Result<Conversation, String> {
+    let store = state.0.lock().map_err(|e| e.to_string())?;
+    store.create(&workspace, title)
+}
+
+#[tauri::command]
+pub fn ai_conversation_get(state: State<'_, ConversationState>, workspace: String, id: String) -> Result<Conversation, String> {
+    let store = state.0.lock().map_err(|e| e.to_string())?;
+    store.get(&workspace, &id)
+}
+
+#[tauri::command]
+pub fn ai_conversation_rename(
+    state: State<'_, ConversationState>,
+    workspace: String,
+    id: String,
+    title: String,
+) -> Result<ConversationSummary, String> {
+    let store = state.0.lock().map_err(|e| e.to_string())?;
+    store.rename(&workspace, &id, &title)
+}
+
+#[tauri::command]
+pub fn ai_conversation_delete(state: State<'_, ConversationState>, workspace: String, id: String) -> Result<(), String> {
+    let store = state.0.lock().map_err(|e| e.to_string())?;
+    store.delete(&workspace, &id)
+}
+
+/// Append messages; returns them with their assigned ids and timestamps
+#[tauri::command]
+pub fn ai_conversation_append(
+    state: State<'_, ConversationState>,
+    workspace: String,
+    id: String,
+    messages: Vec<NewMessage>,
+) -> Result<Vec<StoredMessage>, String> {
+    let store = state.0.lock().map_err(|e| e.to_string())?;
+    store.append(&workspace, &id, messages)
+}
+
+/// Copy a conversation up to and including `message_id` into a new one
+#[tauri::command]
+pub fn ai_conversation_fork(
+    state: State<'_, ConversationState>,
+    workspace: String,
+    id: String,
+    message_id: String,
+    title: Option<String>,
+) -> Result<Conversation, String> {
+    let store = state.0.lock().map_err(|e| e.to_string())?;
+    store.fork(&workspace, &id, &message_id, title)
+}
+
+#[tauri::command]
+pub fn ai_conversations_search(
+    state: State<'_, ConversationState>,
+    workspace: String,
+    query: String,
+) -> Result<Vec<ConversationSearchHit>, String> {
+    let store = state.0.lock().map_err(|e| e.to_string())?;
+    store.search(&workspace, &query)
+}
+
+/// Render a conversation as Markdown or JSON; the frontend decides where to save it
+#[tauri::command]
+pub fn ai_conversation_export(
+    state: State<'_, ConversationState>,
+    workspace: String,
+    id: String,
+    format: ExportFormat,
+) -> Result<String, String> {
+    let conversation = {
+        let store = state.0.lock().map_err(|e| e.to_string())?;
+        store.get(&workspace, &id)?
+    };
+    export::export(&conversation, format)
+}
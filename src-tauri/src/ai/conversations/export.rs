@@ -0,0 +1,114 @@
+use chrono::{TimeZone, Utc};
+
+use crate::ai::types::{ContentPart, Role};
+
+use super::types::{Conversation, ExportFormat, StoredMessage};
+
+pub fn export(conversation: &Conversation, format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Markdown => Ok(to_markdown(conversation)),
+        ExportFormat::Json => serde_json::to_string_pretty(conversation).map_err(|e| e.to_string()),
+    }
+}
+
+fn format_time(millis: i64) -> String {
+    Utc.timestamp_millis_opt(millis)
+        .single()
+        .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn heading(message: &StoredMessage) -> String {
+    let role = match message.role {
+        Role::System => "System",
+        Role::User => "User",
+        Role::Assistant => "Assistant",
+        Role::Tool => "Tool",
+    };
+    let mut details = Vec::new();
+    match (&message.provider, &message.model) {
+        (Some(provider), Some(model)) => details.push(format!("{}/{}", provider, model)),
+        (None, Some(model)) => details.push(model.clone()),
+        _ => {}
+    }
+    if let Some(usage) = message.usage {
+        details.push(format!("{} in / {} out tokens", usage.input_tokens, usage.output_tokens));
+    }
+    if details.is_empty() {
+        format!("## {}", role)
+    } else {
+        format!("## {} ({})", role, details.join(", "))
+    }
+}
+
+fn to_markdown(conversation: &Conversation) -> String {
+    let mut out = format!("# {}\n\n_Created {}", conversation.title, format_time(conversation.created_at));
+    let usage = conversation.total_usage();
+    if usage.input_tokens + usage.output_tokens > 0 {
+        out.push_str(&format!(" · {} in / {} out tokens", usage.input_tokens, usage.output_tokens));
+    }
+    out.push_str("_\n\n");
+
+    for message in &conversation.messages {
+        out.push_str(&heading(message));
+        out.push_str("\n\n");
+        for part in message.content.parts() {
+            match part {
+                ContentPart::Text { text } => out.push_str(text.trim_end()),
+                ContentPart::Image { media_type, .. } => out.push_str(&format!("_[{} image]_", media_type)),
+                ContentPart::ToolCall(call) => {
+                    let arguments = serde_json::to_string_pretty(&call.arguments).unwrap_or_default();
+                    out.push_str(&format!("**Tool call** `{}`\n\n```json\n{}\n```", call.name, arguments));
+                }
+                ContentPart::ToolResult { name, content, is_error, .. } => {
+                    let label = if is_error { "Tool error" } else { "Tool result" };
+                    out.push_str(&format!("**{}** `{}`\n\n```\n{}\n```", label, name, content.trim_end()));
+                }
+            }
+            out.push_str("\n\n");
+        }
+    }
+    format!("{}\n", out.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::types::{MessageContent, ToolCall, Usage};
+
+    fn message(role: Role, content: MessageContent) -> StoredMessage {
+        StoredMessage { id: "m".into(), role, content, model: None, provider: None, usage: None, created_at: 0 }
+    }
+
+    #[test]
+    fn test_markdown_export() {
+        let mut reply = message(Role::Assistant, MessageContent::Parts(vec![
+            ContentPart::Text { text: "Let me look.".into() },
+            ContentPart::ToolCall(ToolCall { id: "c1".into(), name: "read_file".into(), arguments: serde_json::json!({ "path": "a.rs" }) }),
+        ]));
+        reply.provider = Some("anthropic".into());
+        reply.model = Some("claude-sonnet".into());
+        reply.usage = Some(Usage { input_tokens: 10, output_tokens: 3 });
+        let conversation = Conversation {
+            id: "c".into(),
+            workspace: "/w".into(),
+            title: "Reading".into(),
+            created_at: 0,
+            updated_at: 0,
+            forked_from: None,
+            messages: vec![message(Role::User, MessageContent::Text("What is in a.rs?".into())), reply],
+        };
+
+        let markdown = export(&conversation, ExportFormat::Markdown).unwrap();
+        assert_eq!(markdown, concat!(
+            "# Reading\n\n_Created 1970-01-01 00:00 UTC · 10 in / 3 out tokens_\n\n",
+            "## User\n\nWhat is in a.rs?\n\n",
+            "## Assistant (anthropic/claude-sonnet, 10 in / 3 out tokens)\n\nLet me look.\n\n",
+            "**Tool call** `read_file`\n\n```json\n{\n  \"path\": \"a.rs\"\n}\n```\n",
+        ));
+
+        let json = export(&conversation, ExportFormat::Json).unwrap();
+        let parsed: Conversation = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, conversation);
+    }
+}
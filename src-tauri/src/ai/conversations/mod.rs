@@ -0,0 +1,9 @@
+mod commands;
+mod export;
+mod store;
+mod types;
+
+// Re-export public API
+pub use commands::*;
+pub use store::ConversationStore;
+pub use types::*;
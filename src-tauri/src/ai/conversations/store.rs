@@ -0,0 +1,360 @@
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::ai::types::{ContentPart, MessageContent, Role};
+
+use super::types::*;
+
+pub const DEFAULT_TITLE: &str = "New conversation";
+const TITLE_MAX_CHARS: usize = 60;
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Conversations saved as one JSON file each, in a folder per workspace
+/// under the app config directory.
+pub struct ConversationStore {
+    root: PathBuf,
+}
+
+impl ConversationStore {
+    pub fn new() -> Self {
+        let config_dir = dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("colbex");
+        Self::with_root(config_dir.join("conversations"))
+    }
+
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Folder of a workspace's conversations, named by a hash of its path.
+    fn workspace_dir(&self, workspace: &str) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(workspace.as_bytes());
+        let hash = format!("{:x}", hasher.finalize());
+        self.root.join(&hash[..16])
+    }
+
+    fn conversation_path(&self, workspace: &str, id: &str) -> Result<PathBuf, String> {
+        // Ids are generated UUIDs; anything else could point outside the folder
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid conversation id '{}'", id));
+        }
+        Ok(self.workspace_dir(workspace).join(format!("{}.json", id)))
+    }
+
+    pub fn get(&self, workspace: &str, id: &str) -> Result<Conversation, String> {
+        let path = self.conversation_path(workspace, id)?;
+        if !path.exists() {
+            return Err(format!("Conversation '{}' not found", id));
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read conversation: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse conversation: {}", e))
+    }
+
+    fn save(&self, conversation: &Conversation) -> Result<(), String> {
+        let path = self.conversation_path(&conversation.workspace, &conversation.id)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create conversations directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(conversation)
+            .map_err(|e| format!("Failed to serialize conversation: {}", e))?;
+
+        // Write then rename, so a crash never leaves a half-written file
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, content).map_err(|e| format!("Failed to write conversation: {}", e))?;
+        fs::rename(&tmp, &path).map_err(|e| format!("Failed to write conversation: {}", e))
+    }
+
+    /// Every conversation of the workspace. Files that fail to parse are skipped.
+    fn load_all(&self, workspace: &str) -> Result<Vec<Conversation>, String> {
+        let dir = self.workspace_dir(workspace);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read conversations: {}", e))?;
+        let mut conversations: Vec<Conversation> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .collect();
+        conversations.sort_by_key(|c| std::cmp::Reverse(c.updated_at));
+        Ok(conversations)
+    }
+
+    /// Summaries, most recently updated first.
+    pub fn list(&self, workspace: &str) -> Result<Vec<ConversationSummary>, String> {
+        Ok(self.load_all(workspace)?.iter().map(Conversation::summary).collect())
+    }
+
+    pub fn create(&self, workspace: &str, title: Option<String>) -> Result<Conversation, String> {
+        let now = now_millis();
+        let conversation = Conversation {
+            id: uuid::Uuid::new_v4().to_string(),
+            workspace: workspace.to_string(),
+            title: title.map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).unwrap_or_else(|| DEFAULT_TITLE.to_string()),
+            created_at: now,
+            updated_at: now,
+            forked_from: None,
+            messages: Vec::new(),
+        };
+        self.save(&conversation)?;
+        Ok(conversation)
+    }
+
+    pub fn rename(&self, workspace: &str, id: &str, title: &str) -> Result<ConversationSummary, String> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err("Title cannot be empty".to_string());
+        }
+        let mut conversation = self.get(workspace, id)?;
+        conversation.title = title.to_string();
+        conversation.updated_at = now_millis();
+        self.save(&conversation)?;
+        Ok(conversation.summary())
+    }
+
+    pub fn delete(&self, workspace: &str, id: &str) -> Result<(), String> {
+        let path = self.conversation_path(workspace, id)?;
+        if !path.exists() {
+            return Err(format!("Conversation '{}' not found", id));
+        }
+        fs::remove_file(path).map_err(|e| format!("Failed to delete conversation: {}", e))
+    }
+
+    /// Appends `messages` and returns them as stored. A conversation still
+    /// under the default title is named after its first user message.
+    pub fn append(&self, workspace: &str, id: &str, messages: Vec<NewMessage>) -> Result<Vec<StoredMessage>, String> {
+        let mut conversation = self.get(workspace, id)?;
+        let now = now_millis();
+        let stored: Vec<StoredMessage> = messages
+            .into_iter()
+            .map(|m| StoredMessage {
+                id: uuid::Uuid::new_v4().to_string(),
+                role: m.role,
+                content: m.content,
+                model: m.model,
+                provider: m.provider,
+                usage: m.usage,
+                created_at: now,
+            })
+            .collect();
+
+        if conversation.title == DEFAULT_TITLE {
+            let first_user = conversation.messages.iter().chain(&stored).find(|m| m.role == Role::User);
+            if let Some(title) = first_user.and_then(|m| title_from(&m.content.text())) {
+                conversation.title = title;
+            }
+        }
+        conversation.messages.extend(stored.iter().cloned());
+        conversation.updated_at = now;
+        self.save(&conversation)?;
+        Ok(stored)
+    }
+
+    /// New conversation holding a copy of the messages of `id` up to and
+    /// including `message_id`, so the thread can continue differently.
+    pub fn fork(&self, workspace: &str, id: &str, message_id: &str, title: Option<String>) -> Result<Conversation, String> {
+        let source = self.get(workspace, id)?;
+        let end = source
+            .messages
+            .iter()
+            .position(|m| m.id == message_id)
+            .ok_or_else(|| format!("Message '{}' not found in conversation", message_id))?;
+
+        let now = now_millis();
+        let fork = Conversation {
+            id: uuid::Uuid::new_v4().to_string(),
+            workspace: workspace.to_string(),
+            title: title
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| format!("{} (fork)", source.title)),
+            created_at: now,
+            updated_at: now,
+            forked_from: Some(ForkOrigin { conversation_id: source.id.clone(), message_id: message_id.to_string() }),
+            messages: source.messages[..=end].to_vec(),
+        };
+        self.save(&fork)?;
+        Ok(fork)
+    }
+
+    /// Case-insensitive search over titles and message text, one hit per
+    /// matching message, most recent conversations first.
+    pub fn search(&self, workspace: &str, query: &str) -> Result<Vec<ConversationSearchHit>, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut hits = Vec::new();
+        for conversation in self.load_all(workspace)? {
+            let hit = |message_id: Option<String>, snippet: String| ConversationSearchHit {
+                conversation_id: conversation.id.clone(),
+                title: conversation.title.clone(),
+                message_id,
+                snippet,
+            };
+            let message_hits: Vec<ConversationSearchHit> = conversation
+                .messages
+                .iter()
+                .filter_map(|m| {
+                    let text = searchable_text(&m.content);
+                    find_ignore_case(&text, query).map(|at| hit(Some(m.id.clone()), snippet(&text, at)))
+                })
+                .collect();
+            if message_hits.is_empty() && find_ignore_case(&conversation.title, query).is_some() {
+                hits.push(hit(None, conversation.title.clone()));
+            }
+            hits.extend(message_hits);
+        }
+        Ok(hits)
+    }
+}
+
+impl Default for ConversationStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// First line of `text`, shortened to fit a title.
+fn title_from(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    if line.chars().count() <= TITLE_MAX_CHARS {
+        return Some(line.to_string());
+    }
+    let short: String = line.chars().take(TITLE_MAX_CHARS - 1).collect();
+    Some(format!("{}…", short.trim_end()))
+}
+
+/// Text parts plus tool results, which is what a user would search for.
+fn searchable_text(content: &MessageContent) -> String {
+    content
+        .parts()
+        .iter()
+        .filter_map(|part| match part {
+            ContentPart::Text { text } => Some(text.as_str()),
+            ContentPart::ToolResult { content, .. } => Some(content.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Byte offset of the first case-insensitive match of `query` in `text`.
+fn find_ignore_case(text: &str, query: &str) -> Option<usize> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    text.char_indices().map(|(i, _)| i).find(|&i| {
+        let mut rest = text[i..].chars().flat_map(char::to_lowercase);
+        query.iter().all(|q| rest.next() == Some(*q))
+    })
+}
+
+/// One line of text around byte offset `at`.
+fn snippet(text: &str, at: usize) -> String {
+    let before: Vec<char> = text[..at].chars().collect();
+    let start = before.len().saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let start_cut = start > 0;
+    let before: String = before[start..].iter().collect();
+    let after: Vec<char> = text[at..].chars().take(SNIPPET_CONTEXT_CHARS * 2 + 1).collect();
+    let end_cut = after.len() > SNIPPET_CONTEXT_CHARS * 2;
+    let after: String = after.into_iter().take(SNIPPET_CONTEXT_CHARS * 2).collect();
+
+    let mut snippet = format!("{}{}", before, after).split_whitespace().collect::<Vec<_>>().join(" ");
+    if start_cut {
+        snippet.insert(0, '…');
+    }
+    if end_cut {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::types::Usage;
+
+    fn message(role: Role, text: &str) -> NewMessage {
+        NewMessage { role, content: MessageContent::Text(text.to_string()), model: None, provider: None, usage: None }
+    }
+
+    #[test]
+    fn test_create_append_list_and_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ConversationStore::with_root(dir.path().to_path_buf());
+
+        let conversation = store.create("/work/a", None).unwrap();
+        assert_eq!(conversation.title, DEFAULT_TITLE);
+        let mut reply = message(Role::Assistant, "Use a HashMap.");
+        reply.model = Some("gpt-4o".into());
+        reply.provider = Some("openai".into());
+        reply.usage = Some(Usage { input_tokens: 12, output_tokens: 5 });
+        store
+            .append("/work/a", &conversation.id, vec![message(Role::User, "\n  How do I count words?\nIn Rust"), reply])
+            .unwrap();
+
+        let list = store.list("/work/a").unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].title, "How do I count words?");
+        assert_eq!(list[0].message_count, 2);
+        assert_eq!(list[0].usage, Usage { input_tokens: 12, output_tokens: 5 });
+        // Other workspaces keep their own history
+        assert!(store.list("/work/b").unwrap().is_empty());
+
+        let renamed = store.rename("/work/a", &conversation.id, " Word counts ").unwrap();
+        assert_eq!(renamed.title, "Word counts");
+        let saved = store.get("/work/a", &conversation.id).unwrap();
+        assert_eq!(saved.messages[1].model.as_deref(), Some("gpt-4o"));
+
+        store.delete("/work/a", &conversation.id).unwrap();
+        assert!(store.list("/work/a").unwrap().is_empty());
+        assert!(store.get("/work/a", "../../etc/passwd").is_err());
+    }
+
+    #[test]
+    fn test_fork_and_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ConversationStore::with_root(dir.path().to_path_buf());
+        let original = store.create("/work", Some("Parsing".into())).unwrap();
+        let stored = store
+            .append("/work", &original.id, vec![
+                message(Role::User, "Should I use nom or pest?"),
+                message(Role::Assistant, "Pest, if you like grammars."),
+                message(Role::User, "And for binary formats?"),
+            ])
+            .unwrap();
+
+        let fork = store.fork("/work", &original.id, &stored[1].id, None).unwrap();
+        assert_eq!(fork.title, "Parsing (fork)");
+        assert_eq!(fork.messages, stored[..2].to_vec());
+        assert_eq!(fork.forked_from.as_ref().unwrap().conversation_id, original.id);
+        assert!(store.fork("/work", &original.id, "missing", None).is_err());
+
+        let hits = store.search("/work", "PEST").unwrap();
+        assert_eq!(hits.len(), 4);
+        assert!(hits.iter().all(|h| h.snippet.to_lowercase().contains("pest")));
+        let title_only = store.search("/work", "parsing (fork").unwrap();
+        assert_eq!(title_only.len(), 1);
+        assert_eq!(title_only[0].message_id, None);
+    }
+
+    #[test]
+    fn test_snippet_trims_long_text() {
+        let text = format!("{}needle{}", "a ".repeat(50), "b ".repeat(100));
+        let at = find_ignore_case(&text, "NEEDLE").unwrap();
+        let snippet = snippet(&text, at);
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+        assert_eq!(find_ignore_case("ÄPFEL", "äpfel"), Some(0));
+    }
+}
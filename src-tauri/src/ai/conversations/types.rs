@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ai::types::{ChatMessage, MessageContent, Role, Usage};
+
+/// A message as stored, with who produced it and when.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredMessage {
+    pub id: String,
+    pub role: Role,
+    pub content: MessageContent,
+    /// Model and provider that wrote an assistant message.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+    /// Unix milliseconds.
+    pub created_at: i64,
+}
+
+/// A message to append; the store assigns its id and timestamp.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewMessage {
+    pub role: Role,
+    pub content: MessageContent,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// Where a forked conversation was copied from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkOrigin {
+    pub conversation_id: String,
+    /// Last message copied into the fork.
+    pub message_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conversation {
+    pub id: String,
+    pub workspace: String,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    #[serde(default)]
+    pub forked_from: Option<ForkOrigin>,
+    pub messages: Vec<StoredMessage>,
+}
+
+impl Conversation {
+    /// The messages in the form the chat commands take.
+    pub fn chat_messages(&self) -> Vec<ChatMessage> {
+        self.messages
+            .iter()
+            .map(|m| ChatMessage { role: m.role, content: m.content.clone() })
+            .collect()
+    }
+
+    /// Token usage summed over all messages.
+    pub fn total_usage(&self) -> Usage {
+        self.messages.iter().filter_map(|m| m.usage).fold(Usage::default(), |mut total, usage| {
+            total.input_tokens += usage.input_tokens;
+            total.output_tokens += usage.output_tokens;
+            total
+        })
+    }
+
+    pub fn summary(&self) -> ConversationSummary {
+        ConversationSummary {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            message_count: self.messages.len(),
+            forked_from: self.forked_from.clone(),
+            usage: self.total_usage(),
+        }
+    }
+}
+
+/// A conversation without its messages, for lists.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub message_count: usize,
+    pub forked_from: Option<ForkOrigin>,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationSearchHit {
+    pub conversation_id: String,
+    pub title: String,
+    /// The matching message; `None` when only the title matched.
+    pub message_id: Option<String>,
+    /// Text around the match.
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
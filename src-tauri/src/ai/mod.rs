@@ -0,0 +1,27 @@
+mod agent;
+mod catalog;
+mod client;
+mod commands;
+mod context;
+mod conversations;
+mod provider;
+mod providers;
+mod registry;
+mod requests;
+mod stream;
+mod tokens;
+mod types;
+mod usage;
+
+// Re-export public API
+pub use agent::*;
+pub use catalog::{ModelSpec, Pricing};
+pub use client::AiError;
+pub use commands::*;
+pub use context::ContextFit;
+pub use conversations::*;
+pub use provider::{LlmProvider, StreamFormat};
+pub use registry::ProviderRegistry;
+pub use tokens::TokenEstimate;
+pub use types::*;
+pub use usage::{DailyUsage, UsageRecord, UsageSummary, UsageTotal};
@@ -0,0 +1,82 @@
+use futures_util::future::BoxFuture;
+use reqwest::{Client, RequestBuilder};
+
+use super::client::AiError;
+use super::stream::StreamFrame;
+use super::types::{ChatRequest, ChatResponse, ProviderInfo, StreamEvent};
+
+/// How a provider frames its streaming responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// Server-sent events (`data: {...}` lines).
+    Sse,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+/// A chat backend. Implementations only translate between the common
+/// message model and their wire format; sending, streaming and error
+/// handling live in `client`.
+pub trait LlmProvider: Send + Sync {
+    fn info(&self) -> ProviderInfo;
+
+    fn stream_format(&self) -> StreamFormat {
+        StreamFormat::Sse
+    }
+
+    /// Streamed `Delta` events carry all the text so far rather than just
+    /// the new part; the client turns them into increments.
+    fn streams_full_text(&self) -> bool {
+        false
+    }
+
+    /// The credential `build_request` receives, called before every request.
+    /// Providers whose stored key must first be exchanged for an access
+    /// token do that here; by default the key is passed through.
+    fn authorize<'a>(&'a self, _http: &'a Client, api_key: Option<&'a str>) -> BoxFuture<'a, Result<Option<String>, AiError>> {
+        Box::pin(async move { Ok(api_key.map(str::to_string)) })
+    }
+
+    /// HTTP request for `request`, asking for a stream when `stream` is set.
+    fn build_request(
+        &self,
+        http: &Client,
+        api_key: Option<&str>,
+        request: &ChatRequest,
+        stream: bool,
+    ) -> Result<RequestBuilder, AiError>;
+
+    /// Parses a complete (non-streaming) response body.
+    fn parse_response(&self, body: &str) -> Result<ChatResponse, AiError>;
+
+    /// Events carried by one frame of a streaming response.
+    fn parse_stream_frame(&self, frame: &StreamFrame) -> Result<Vec<StreamEvent>, AiError>;
+
+    /// HTTP request listing the models the provider serves, for providers
+    /// that can list them.
+    fn models_request(&self, _http: &Client, _api_key: Option<&str>) -> Option<Result<RequestBuilder, AiError>> {
+        None
+    }
+
+    /// Model ids from the response to `models_request`.
+    fn parse_models(&self, _body: &str) -> Result<Vec<String>, AiError> {
+        Ok(Vec::new())
+    }
+}
+
+pub(crate) fn require_key<'a>(provider: &str, api_key: Option<&'a str>) -> Result<&'a str, AiError> {
+    api_key
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| AiError::MissingApiKey(provider.to_string()))
+}
+
+pub(crate) fn parse_json<T: serde::de::DeserializeOwned>(body: &str) -> Result<T, AiError> {
+    serde_json::from_str(body).map_err(|e| AiError::InvalidResponse(format!("{}: {}", e, truncate(body, 200))))
+}
+
+fn truncate(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => &text[..idx],
+        None => text,
+    }
+}
@@ -0,0 +1,333 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::ai::client::AiError;
+use crate::ai::provider::{parse_json, require_key, LlmProvider};
+use crate::ai::stream::StreamFrame;
+use crate::ai::types::*;
+
+const API_URL: &str = "https://api.anthropic.com/v1/messages";
+const API_VERSION: &str = "2023-06-01";
+/// The Messages API requires `max_tokens`.
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+pub struct Anthropic {
+    endpoint: String,
+}
+
+impl Default for Anthropic {
+    fn default() -> Self {
+        Self { endpoint: API_URL.to_string() }
+    }
+}
+
+impl Anthropic {
+    fn body(&self, request: &ChatRequest, stream: bool) -> Value {
+        let messages: Vec<Value> = request.messages
+            .iter()
+            .filter(|m| m.role != Role::System)
+            .map(|message| {
+                let content = if message.content.is_text_only() {
+                    json!(message.content.text())
+                } else {
+                    let blocks: Vec<Value> = message.content.parts().into_iter().map(|part| match part {
+                        ContentPart::Text { text } => json!({ "type": "text", "text": text }),
+                        ContentPart::Image { media_type, data } => json!({
+                            "type": "image",
+                            "source": { "type": "base64", "media_type": media_type, "data": data },
+                        }),
+                        ContentPart::ToolCall(call) => json!({
+                            "type": "tool_use",
+                            "id": call.id,
+                            "name": call.name,
+                            "input": call.arguments,
+                        }),
+                        ContentPart::ToolResult { tool_call_id, content, is_error, .. } => json!({
+                            "type": "tool_result",
+                            "tool_use_id": tool_call_id,
+                            "content": content,
+                            "is_error": is_error,
+                        }),
+                    }).collect();
+                    json!(blocks)
+                };
+                // Tool results travel in user turns
+                let role = if message.role == Role::Tool { "user" } else { message.role.as_str() };
+                json!({ "role": role, "content": content })
+            })
+            .collect();
+
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "max_tokens": request.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS),
+            "stream": stream,
+        });
+        if let Some(system) = request.system_prompt() {
+            body["system"] = json!(system);
+        }
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if !request.tools.is_empty() {
+            let tools: Vec<Value> = request.tools
+                .iter()
+                .map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "input_schema": tool.parameters,
+                }))
+                .collect();
+            body["tools"] = json!(tools);
+            if let Some(choice) = &request.tool_choice {
+                body["tool_choice"] = match choice {
+                    ToolChoice::Auto => json!({ "type": "auto" }),
+                    ToolChoice::None => json!({ "type": "none" }),
+                    ToolChoice::Required => json!({ "type": "any" }),
+                    ToolChoice::Tool { name } => json!({ "type": "tool", "name": name }),
+                };
+            }
+        }
+        body
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct WireUsage {
+    #[serde(default)]
+    input_tokens: u32,
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+impl From<WireUsage> for Usage {
+    fn from(usage: WireUsage) -> Self {
+        Usage { input_tokens: usage.input_tokens, output_tokens: usage.output_tokens }
+    }
+}
+
+#[derive(Deserialize)]
+struct WireBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    input: Value,
+}
+
+#[derive(Deserialize)]
+struct WireResponse {
+    #[serde(default)]
+    model: String,
+    content: Vec<WireBlock>,
+    stop_reason: Option<String>,
+    usage: Option<WireUsage>,
+}
+
+#[derive(Deserialize)]
+struct WireStreamDelta {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    partial_json: String,
+    stop_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WireStreamMessage {
+    usage: Option<WireUsage>,
+}
+
+#[derive(Deserialize)]
+struct WireError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct WireStreamEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    index: usize,
+    content_block: Option<WireBlock>,
+    delta: Option<WireStreamDelta>,
+    message: Option<WireStreamMessage>,
+    usage: Option<WireUsage>,
+    error: Option<WireError>,
+}
+
+impl LlmProvider for Anthropic {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo { id: "anthropic".to_string(), name: "Anthropic".to_string(), requires_api_key: true }
+    }
+
+    fn build_request(&self, http: &Client, api_key: Option<&str>, request: &ChatRequest, stream: bool) -> Result<RequestBuilder, AiError> {
+        let api_key = require_key("anthropic", api_key)?;
+        Ok(http.post(&self.endpoint)
+            .header("x-api-key", api_key)
+            .header("anthropic-version", API_VERSION)
+            .json(&self.body(request, stream)))
+    }
+
+    fn parse_response(&self, body: &str) -> Result<ChatResponse, AiError> {
+        let response: WireResponse = parse_json(body)?;
+        let mut content = String::new();
+        let mut tool_calls = Vec::new();
+        for block in response.content {
+            match block.kind.as_str() {
+                "text" => content.push_str(&block.text),
+                "tool_use" => tool_calls.push(ToolCall { id: block.id, name: block.name, arguments: block.input }),
+                _ => {}
+            }
+        }
+        Ok(ChatResponse {
+            content,
+            model: response.model,
+            finish_reason: response.stop_reason,
+            usage: response.usage.map(Usage::from),
+            tool_calls,
+        })
+    }
+
+    fn parse_stream_frame(&self, frame: &StreamFrame) -> Result<Vec<StreamEvent>, AiError> {
+        let event: WireStreamEvent = parse_json(&frame.data)?;
+        let mut events = Vec::new();
+        match event.kind.as_str() {
+            "message_start" => {
+                if let Some(usage) = event.message.and_then(|m| m.usage) {
+                    events.push(StreamEvent::Usage(usage.into()));
+                }
+            }
+            "content_block_start" => {
+                if let Some(block) = event.content_block.filter(|b| b.kind == "tool_use") {
+                    // The input arrives as `input_json_delta` fragments; the start block's is always empty
+                    events.push(StreamEvent::ToolCallStart { index: event.index, id: block.id, name: block.name });
+                }
+            }
+            "content_block_delta" => match event.delta {
+                Some(delta) if delta.kind == "text_delta" && !delta.text.is_empty() => {
+                    events.push(StreamEvent::Delta(delta.text));
+                }
+                Some(delta) if delta.kind == "input_json_delta" && !delta.partial_json.is_empty() => {
+                    events.push(StreamEvent::ToolCallDelta { index: event.index, arguments: delta.partial_json });
+                }
+                _ => {}
+            },
+            "message_delta" => {
+                if let Some(usage) = event.usage {
+                    events.push(StreamEvent::Usage(usage.into()));
+                }
+                if let Some(reason) = event.delta.and_then(|d| d.stop_reason) {
+                    events.push(StreamEvent::Done { finish_reason: Some(reason) });
+                }
+            }
+            "error" => {
+                let message = event.error.map(|e| e.message).unwrap_or_else(|| frame.data.clone());
+                return Err(AiError::StreamError(message));
+            }
+            _ => {}
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(data: &str) -> StreamFrame {
+        StreamFrame { event: None, data: data.to_string() }
+    }
+
+    #[test]
+    fn test_system_prompt_moves_out_of_messages() {
+        let request = ChatRequest {
+            model: "claude".into(),
+            messages: vec![ChatMessage::new(Role::System, "be brief"), ChatMessage::new(Role::User, "hi")],
+            ..Default::default()
+        };
+        let body = Anthropic::default().body(&request, false);
+        assert_eq!(body["system"], "be brief");
+        assert_eq!(body["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(body["max_tokens"], DEFAULT_MAX_TOKENS);
+    }
+
+    #[test]
+    fn test_stream_events() {
+        let provider = Anthropic::default();
+        let start = provider.parse_stream_frame(&frame(r#"{"type":"message_start","message":{"usage":{"input_tokens":12,"output_tokens":1}}}"#)).unwrap();
+        assert_eq!(start, vec![StreamEvent::Usage(Usage { input_tokens: 12, output_tokens: 1 })]);
+        let delta = provider.parse_stream_frame(&frame(r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}"#)).unwrap();
+        assert_eq!(delta, vec![StreamEvent::Delta("Hi".into())]);
+        let end = provider.parse_stream_frame(&frame(r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":7}}"#)).unwrap();
+        assert_eq!(end[1], StreamEvent::Done { finish_reason: Some("end_turn".into()) });
+        assert!(provider.parse_stream_frame(&frame(r#"{"type":"ping"}"#)).unwrap().is_empty());
+        assert!(provider.parse_stream_frame(&frame(r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#)).is_err());
+    }
+
+    #[test]
+    fn test_parse_response_joins_text_blocks() {
+        let response = Anthropic::default().parse_response(
+            r#"{"model":"claude","content":[{"type":"text","text":"a"},{"type":"text","text":"b"}],"stop_reason":"end_turn","usage":{"input_tokens":3,"output_tokens":2}}"#,
+        ).unwrap();
+        assert_eq!(response.content, "ab");
+        assert_eq!(response.usage, Some(Usage { input_tokens: 3, output_tokens: 2 }));
+    }
+
+    #[test]
+    fn test_tool_use() {
+        let request = ChatRequest {
+            model: "claude".into(),
+            messages: vec![
+                ChatMessage {
+                    role: Role::Assistant,
+                    content: MessageContent::Parts(vec![ContentPart::ToolCall(ToolCall {
+                        id: "toolu_1".into(),
+                        name: "get_outline".into(),
+                        arguments: json!({ "path": "a.rs" }),
+                    })]),
+                },
+                ChatMessage {
+                    role: Role::Tool,
+                    content: MessageContent::Parts(vec![ContentPart::ToolResult {
+                        tool_call_id: "toolu_1".into(),
+                        name: "get_outline".into(),
+                        content: "fn main".into(),
+                        is_error: false,
+                    }]),
+                },
+            ],
+            tools: vec![ToolDefinition { name: "get_outline".into(), description: String::new(), parameters: json!({ "type": "object" }) }],
+            tool_choice: Some(ToolChoice::Required),
+            ..Default::default()
+        };
+        let body = Anthropic::default().body(&request, false);
+        assert_eq!(body["messages"][0]["content"][0]["input"]["path"], "a.rs");
+        assert_eq!(body["messages"][1]["role"], "user");
+        assert_eq!(body["messages"][1]["content"][0]["tool_use_id"], "toolu_1");
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(body["tool_choice"]["type"], "any");
+
+        let provider = Anthropic::default();
+        let start = provider.parse_stream_frame(&frame(r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"get_outline","input":{}}}"#)).unwrap();
+        assert_eq!(start, vec![StreamEvent::ToolCallStart { index: 1, id: "toolu_1".into(), name: "get_outline".into() }]);
+        let delta = provider.parse_stream_frame(&frame(r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"path\":"}}"#)).unwrap();
+        assert_eq!(delta, vec![StreamEvent::ToolCallDelta { index: 1, arguments: "{\"path\":".into() }]);
+
+        let response = provider.parse_response(
+            r#"{"model":"claude","content":[{"type":"tool_use","id":"toolu_2","name":"git_diff","input":{"staged":true}}],"stop_reason":"tool_use"}"#,
+        ).unwrap();
+        assert_eq!(response.tool_calls[0].arguments["staged"], true);
+    }
+}
@@ -0,0 +1,383 @@
+use futures_util::future::BoxFuture;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::ai::client::AiError;
+use crate::ai::provider::{parse_json, require_key, LlmProvider};
+use crate::ai::stream::StreamFrame;
+use crate::ai::types::*;
+
+const AUTH_URL: &str = "https://ngw.devices.sberbank.ru:9443/api/v2/oauth";
+const API_URL: &str = "https://gigachat.devices.sberbank.ru/api/v1/chat/completions";
+const DEFAULT_SCOPE: &str = "GIGACHAT_API_PERS";
+/// Tokens this close to expiring are replaced before use.
+const EXPIRY_MARGIN_MS: i64 = 60_000;
+
+/// Sber's GigaChat. The stored key is the authorization key from the
+/// developer console, optionally prefixed with its scope
+/// (`GIGACHAT_API_CORP:<key>`); it is exchanged for an access token that is
+/// cached until shortly before it expires.
+///
+/// Both hosts use certificates from the Russian Trusted Root CA, which has
+/// to be in the system trust store.
+pub struct GigaChat {
+    auth_url: String,
+    endpoint: String,
+    /// Access token and its expiry in epoch milliseconds, by stored key.
+    tokens: Mutex<HashMap<String, (String, i64)>>,
+}
+
+impl Default for GigaChat {
+    fn default() -> Self {
+        Self::new(AUTH_URL.to_string(), API_URL.to_string())
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// Epoch milliseconds.
+    expires_at: i64,
+}
+
+impl GigaChat {
+    fn new(auth_url: String, endpoint: String) -> Self {
+        Self { auth_url, endpoint, tokens: Mutex::new(HashMap::new()) }
+    }
+
+    fn cached_token(&self, key: &str) -> Option<String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let tokens = self.tokens.lock().ok()?;
+        tokens
+            .get(key)
+            .filter(|(_, expires_at)| *expires_at - EXPIRY_MARGIN_MS > now)
+            .map(|(token, _)| token.clone())
+    }
+
+    async fn fetch_token(&self, http: &Client, key: &str) -> Result<String, AiError> {
+        let (scope, authorization_key) = match key.split_once(':') {
+            Some((scope, authorization_key)) => (scope.trim(), authorization_key.trim()),
+            None => (DEFAULT_SCOPE, key.trim()),
+        };
+        let response = http
+            .post(&self.auth_url)
+            .header("Authorization", format!("Basic {}", authorization_key))
+            .header("RqUID", uuid::Uuid::new_v4().to_string())
+            .header("Accept", "application/json")
+            .form(&[("scope", scope)])
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(AiError::ApiError { status: status.as_u16(), message: body });
+        }
+        let token: TokenResponse = parse_json(&body)?;
+        if let Ok(mut tokens) = self.tokens.lock() {
+            tokens.insert(key.to_string(), (token.access_token.clone(), token.expires_at));
+        }
+        Ok(token.access_token)
+    }
+
+    fn body(&self, request: &ChatRequest, stream: bool) -> Value {
+        let messages: Vec<Value> = request.messages.iter().flat_map(message_json).collect();
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "stream": stream,
+        });
+        if let Some(max_tokens) = request.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if !request.tools.is_empty() {
+            let functions: Vec<Value> = request.tools
+                .iter()
+                .map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.parameters,
+                }))
+                .collect();
+            body["functions"] = json!(functions);
+            match &request.tool_choice {
+                Some(ToolChoice::None) => body["function_call"] = json!("none"),
+                Some(ToolChoice::Tool { name }) => body["function_call"] = json!({ "name": name }),
+                // There is no way to require some call without naming it
+                Some(ToolChoice::Auto) | Some(ToolChoice::Required) | None => body["function_call"] = json!("auto"),
+            }
+        }
+        body
+    }
+}
+
+/// GigaChat uses the older OpenAI `functions` protocol: arguments are
+/// objects, results are `function` messages whose content must be JSON.
+fn message_json(message: &ChatMessage) -> Vec<Value> {
+    let parts = message.content.parts();
+    if message.role == Role::Tool {
+        return parts
+            .into_iter()
+            .filter_map(|part| match part {
+                ContentPart::ToolResult { name, content, .. } => Some(json!({
+                    "role": "function",
+                    "name": name,
+                    "content": json!({ "result": content }).to_string(),
+                })),
+                _ => None,
+            })
+            .collect();
+    }
+
+    let mut value = json!({ "role": message.role.as_str(), "content": message.content.text() });
+    // Only one call per assistant turn is supported
+    if let Some(call) = message.content.tool_calls().first() {
+        value["function_call"] = json!({ "name": call.name, "arguments": call.arguments });
+    }
+    vec![value]
+}
+
+#[derive(Deserialize)]
+struct WireUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+impl From<WireUsage> for Usage {
+    fn from(usage: WireUsage) -> Self {
+        Usage { input_tokens: usage.prompt_tokens, output_tokens: usage.completion_tokens }
+    }
+}
+
+#[derive(Deserialize)]
+struct WireFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+impl WireFunctionCall {
+    /// Name and arguments, with missing arguments as an empty object.
+    fn into_parts(self) -> (String, Value) {
+        let arguments = match self.arguments {
+            Value::Null => json!({}),
+            arguments => arguments,
+        };
+        (self.name, arguments)
+    }
+}
+
+#[derive(Deserialize)]
+struct WireMessage {
+    content: Option<String>,
+    function_call: Option<WireFunctionCall>,
+}
+
+#[derive(Deserialize)]
+struct WireChoice {
+    message: WireMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WireResponse {
+    #[serde(default)]
+    model: String,
+    choices: Vec<WireChoice>,
+    usage: Option<WireUsage>,
+}
+
+#[derive(Deserialize)]
+struct WireStreamChoice {
+    delta: Option<WireMessage>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WireStreamChunk {
+    #[serde(default)]
+    choices: Vec<WireStreamChoice>,
+    usage: Option<WireUsage>,
+}
+
+impl LlmProvider for GigaChat {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo { id: "gigachat".to_string(), name: "GigaChat".to_string(), requires_api_key: true }
+    }
+
+    fn authorize<'a>(&'a self, http: &'a Client, api_key: Option<&'a str>) -> BoxFuture<'a, Result<Option<String>, AiError>> {
+        Box::pin(async move {
+            let key = require_key("gigachat", api_key)?;
+            match self.cached_token(key) {
+                Some(token) => Ok(Some(token)),
+                None => self.fetch_token(http, key).await.map(Some),
+            }
+        })
+    }
+
+    fn build_request(&self, http: &Client, api_key: Option<&str>, request: &ChatRequest, stream: bool) -> Result<RequestBuilder, AiError> {
+        let token = require_key("gigachat", api_key)?;
+        Ok(http.post(&self.endpoint).bearer_auth(token).json(&self.body(request, stream)))
+    }
+
+    fn parse_response(&self, body: &str) -> Result<ChatResponse, AiError> {
+        let response: WireResponse = parse_json(body)?;
+        let choice = response.choices.into_iter().next()
+            .ok_or_else(|| AiError::InvalidResponse("No choices in response".to_string()))?;
+        let tool_calls = choice.message.function_call
+            .map(|call| {
+                let (name, arguments) = call.into_parts();
+                ToolCall { id: "call_0".to_string(), name, arguments }
+            })
+            .into_iter()
+            .collect();
+        Ok(ChatResponse {
+            content: choice.message.content.unwrap_or_default(),
+            model: response.model,
+            finish_reason: choice.finish_reason,
+            usage: response.usage.map(Usage::from),
+            tool_calls,
+        })
+    }
+
+    fn parse_stream_frame(&self, frame: &StreamFrame) -> Result<Vec<StreamEvent>, AiError> {
+        if frame.is_done_marker() {
+            return Ok(Vec::new());
+        }
+        let chunk: WireStreamChunk = parse_json(&frame.data)?;
+        let mut events = Vec::new();
+        for choice in chunk.choices {
+            if let Some(delta) = choice.delta {
+                if let Some(text) = delta.content.filter(|t| !t.is_empty()) {
+                    events.push(StreamEvent::Delta(text));
+                }
+                // Function calls are not split across chunks
+                if let Some(call) = delta.function_call {
+                    let (name, arguments) = call.into_parts();
+                    events.push(StreamEvent::ToolCallStart { index: 0, id: String::new(), name });
+                    events.push(StreamEvent::ToolCallDelta { index: 0, arguments: arguments.to_string() });
+                }
+            }
+            if let Some(reason) = choice.finish_reason {
+                events.push(StreamEvent::Done { finish_reason: Some(reason) });
+            }
+        }
+        if let Some(usage) = chunk.usage {
+            events.push(StreamEvent::Usage(usage.into()));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::client::tests::mock_server;
+    use crate::ai::client::{chat, chat_stream, http_client};
+
+    fn token_server(expires_at: i64) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        let body = json!({ "access_token": "access-1", "expires_at": expires_at }).to_string();
+        let (base, recorded) = mock_server(200, "application/json", body);
+        (format!("{}/api/v2/oauth", base), recorded)
+    }
+
+    #[tokio::test]
+    async fn test_token_exchange_is_cached() {
+        let (auth_url, auth_requests) = token_server(chrono::Utc::now().timestamp_millis() + 30 * 60_000);
+        let reply = r#"{"model":"GigaChat:1.0","choices":[{"message":{"role":"assistant","content":"Привет"},"finish_reason":"stop"}],"usage":{"prompt_tokens":7,"completion_tokens":2}}"#;
+        let (base, chat_requests) = mock_server(200, "application/json", reply.to_string());
+        let provider = GigaChat::new(auth_url, format!("{}/api/v1/chat/completions", base));
+        let request = ChatRequest { model: "GigaChat".into(), messages: vec![ChatMessage::new(Role::User, "hi")], ..Default::default() };
+
+        let response = chat(&http_client(), &provider, Some("GIGACHAT_API_CORP:c2VjcmV0"), &request).await.unwrap();
+        assert_eq!(response.content, "Привет");
+        assert_eq!(response.usage, Some(Usage { input_tokens: 7, output_tokens: 2 }));
+        chat(&http_client(), &provider, Some("GIGACHAT_API_CORP:c2VjcmV0"), &request).await.unwrap();
+
+        let auth_requests = auth_requests.lock().unwrap();
+        assert_eq!(auth_requests.len(), 1);
+        let exchange = auth_requests[0].to_lowercase();
+        assert!(exchange.contains("authorization: basic c2vjcmv0"));
+        assert!(exchange.contains("rquid: "));
+        assert!(exchange.ends_with("scope=gigachat_api_corp"));
+        let chat_requests = chat_requests.lock().unwrap();
+        assert_eq!(chat_requests.len(), 2);
+        assert!(chat_requests.iter().all(|r| r.to_lowercase().contains("authorization: bearer access-1")));
+    }
+
+    #[tokio::test]
+    async fn test_expiring_token_is_replaced() {
+        // Already inside the expiry margin, so every request exchanges again
+        let (auth_url, auth_requests) = token_server(chrono::Utc::now().timestamp_millis() + 1000);
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"\",\"role\":\"assistant\",\"function_call\":{\"name\":\"read_file\",\"arguments\":{\"path\":\"a.rs\"}}},\"index\":0}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"\"},\"index\":0,\"finish_reason\":\"function_call\"}],\"usage\":{\"prompt_tokens\":20,\"completion_tokens\":9}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let (base, _) = mock_server(200, "text/event-stream", body.to_string());
+        let provider = GigaChat::new(auth_url, base);
+        let request = ChatRequest { model: "GigaChat-Pro".into(), ..Default::default() };
+
+        let response = chat_stream(&http_client(), &provider, Some("c2VjcmV0"), &request, |_| {}).await.unwrap();
+        chat_stream(&http_client(), &provider, Some("c2VjcmV0"), &request, |_| {}).await.unwrap();
+
+        assert_eq!(response.tool_calls, vec![ToolCall { id: "call_0".into(), name: "read_file".into(), arguments: json!({ "path": "a.rs" }) }]);
+        assert_eq!(response.finish_reason.as_deref(), Some("function_call"));
+        assert_eq!(response.usage, Some(Usage { input_tokens: 20, output_tokens: 9 }));
+        let auth_requests = auth_requests.lock().unwrap();
+        assert_eq!(auth_requests.len(), 2);
+        assert!(auth_requests[0].ends_with("scope=GIGACHAT_API_PERS"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_key_reports_auth_error() {
+        let (base, _) = mock_server(401, "application/json", "{\"message\":\"Authorization error\"}".to_string());
+        let provider = GigaChat::new(base.clone(), base);
+        let request = ChatRequest { model: "GigaChat".into(), ..Default::default() };
+        match chat(&http_client(), &provider, Some("bad"), &request).await {
+            Err(AiError::ApiError { status, message }) => {
+                assert_eq!(status, 401);
+                assert!(message.contains("Authorization error"));
+            }
+            other => panic!("unexpected {:?}", other.map(|r| r.content)),
+        }
+    }
+
+    #[test]
+    fn test_function_messages() {
+        let call = ToolCall { id: "call_0".into(), name: "read_file".into(), arguments: json!({ "path": "a.rs" }) };
+        let request = ChatRequest {
+            model: "GigaChat".into(),
+            messages: vec![
+                ChatMessage { role: Role::Assistant, content: MessageContent::Parts(vec![ContentPart::ToolCall(call)]) },
+                ChatMessage {
+                    role: Role::Tool,
+                    content: MessageContent::Parts(vec![ContentPart::ToolResult {
+                        tool_call_id: "call_0".into(),
+                        name: "read_file".into(),
+                        content: "fn main() {}".into(),
+                        is_error: false,
+                    }]),
+                },
+            ],
+            tools: vec![ToolDefinition { name: "read_file".into(), description: "Read".into(), parameters: json!({ "type": "object" }) }],
+            tool_choice: Some(ToolChoice::Required),
+            ..Default::default()
+        };
+        let body = GigaChat::default().body(&request, false);
+        assert_eq!(body["messages"][0]["function_call"], json!({ "name": "read_file", "arguments": { "path": "a.rs" } }));
+        assert_eq!(body["messages"][1], json!({ "role": "function", "name": "read_file", "content": "{\"result\":\"fn main() {}\"}" }));
+        assert_eq!(body["functions"][0]["name"], "read_file");
+        assert_eq!(body["function_call"], "auto");
+    }
+}
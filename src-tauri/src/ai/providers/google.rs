@@ -0,0 +1,310 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::ai::client::AiError;
+use crate::ai::provider::{parse_json, require_key, LlmProvider};
+use crate::ai::stream::StreamFrame;
+use crate::ai::types::*;
+
+const API_BASE: &str = "https://generativelanguage.googleapis.com/v1beta";
+const SAFETY_CATEGORIES: [&str; 4] = [
+    "HARM_CATEGORY_HARASSMENT",
+    "HARM_CATEGORY_HATE_SPEECH",
+    "HARM_CATEGORY_SEXUALLY_EXPLICIT",
+    "HARM_CATEGORY_DANGEROUS_CONTENT",
+];
+
+/// Gemini through the Generative Language API.
+pub struct Google {
+    base_url: String,
+}
+
+impl Default for Google {
+    fn default() -> Self {
+        Self { base_url: API_BASE.to_string() }
+    }
+}
+
+impl Google {
+    fn body(&self, request: &ChatRequest) -> Value {
+        let contents: Vec<Value> = request.messages
+            .iter()
+            .filter(|m| m.role != Role::System)
+            .map(|message| {
+                let parts: Vec<Value> = message.content.parts().into_iter().map(|part| match part {
+                    ContentPart::Text { text } => json!({ "text": text }),
+                    ContentPart::Image { media_type, data } => json!({
+                        "inlineData": { "mimeType": media_type, "data": data },
+                    }),
+                    ContentPart::ToolCall(call) => json!({
+                        "functionCall": { "name": call.name, "args": call.arguments },
+                    }),
+                    ContentPart::ToolResult { name, content, is_error, .. } => {
+                        let key = if is_error { "error" } else { "content" };
+                        json!({ "functionResponse": { "name": name, "response": { key: content } } })
+                    }
+                }).collect();
+                // Gemini calls the assistant "model"
+                let role = if message.role == Role::Assistant { "model" } else { "user" };
+                json!({ "role": role, "parts": parts })
+            })
+            .collect();
+
+        let mut generation_config = json!({});
+        if let Some(max_tokens) = request.max_tokens {
+            generation_config["maxOutputTokens"] = json!(max_tokens);
+        }
+        if let Some(temperature) = request.temperature {
+            generation_config["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = request.top_p {
+            generation_config["topP"] = json!(top_p);
+        }
+        let safety_settings: Vec<Value> = SAFETY_CATEGORIES
+            .iter()
+            .map(|category| json!({ "category": category, "threshold": "BLOCK_NONE" }))
+            .collect();
+
+        let mut body = json!({
+            "contents": contents,
+            "generationConfig": generation_config,
+            "safetySettings": safety_settings,
+        });
+        if let Some(system) = request.system_prompt() {
+            body["systemInstruction"] = json!({ "parts": [{ "text": system }] });
+        }
+        if !request.tools.is_empty() {
+            let declarations: Vec<Value> = request.tools
+                .iter()
+                .map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.parameters,
+                }))
+                .collect();
+            body["tools"] = json!([{ "functionDeclarations": declarations }]);
+            if let Some(choice) = &request.tool_choice {
+                let config = match choice {
+                    ToolChoice::Auto => json!({ "mode": "AUTO" }),
+                    ToolChoice::None => json!({ "mode": "NONE" }),
+                    ToolChoice::Required => json!({ "mode": "ANY" }),
+                    ToolChoice::Tool { name } => json!({ "mode": "ANY", "allowedFunctionNames": [name] }),
+                };
+                body["toolConfig"] = json!({ "functionCallingConfig": config });
+            }
+        }
+        body
+    }
+}
+
+#[derive(Deserialize)]
+struct WireFunctionCall {
+    #[serde(default)]
+    id: String,
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WirePart {
+    #[serde(default)]
+    text: String,
+    function_call: Option<WireFunctionCall>,
+}
+
+#[derive(Deserialize)]
+struct WireContent {
+    #[serde(default)]
+    parts: Vec<WirePart>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireCandidate {
+    content: Option<WireContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireUsage {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+}
+
+impl From<WireUsage> for Usage {
+    fn from(usage: WireUsage) -> Self {
+        Usage { input_tokens: usage.prompt_token_count, output_tokens: usage.candidates_token_count }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireResponse {
+    #[serde(default)]
+    candidates: Vec<WireCandidate>,
+    usage_metadata: Option<WireUsage>,
+    #[serde(default)]
+    model_version: String,
+}
+
+impl WireResponse {
+    fn text(&self) -> String {
+        self.candidates
+            .first()
+            .and_then(|c| c.content.as_ref())
+            .map(|content| content.parts.iter().map(|p| p.text.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Gemini sends each call whole, in a single part.
+    fn function_calls(&self) -> Vec<&WireFunctionCall> {
+        self.candidates
+            .first()
+            .and_then(|c| c.content.as_ref())
+            .map(|content| content.parts.iter().filter_map(|p| p.function_call.as_ref()).collect())
+            .unwrap_or_default()
+    }
+
+    fn finish_reason(&self) -> Option<String> {
+        self.candidates.first().and_then(|c| c.finish_reason.clone())
+    }
+}
+
+impl LlmProvider for Google {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo { id: "google".to_string(), name: "Google Gemini".to_string(), requires_api_key: true }
+    }
+
+    fn build_request(&self, http: &Client, api_key: Option<&str>, request: &ChatRequest, stream: bool) -> Result<RequestBuilder, AiError> {
+        let api_key = require_key("google", api_key)?;
+        let url = if stream {
+            format!("{}/models/{}:streamGenerateContent?alt=sse", self.base_url, request.model)
+        } else {
+            format!("{}/models/{}:generateContent", self.base_url, request.model)
+        };
+        // Header rather than `?key=` so the key stays out of URLs and logs
+        Ok(http.post(url).header("x-goog-api-key", api_key).json(&self.body(request)))
+    }
+
+    fn parse_response(&self, body: &str) -> Result<ChatResponse, AiError> {
+        let response: WireResponse = parse_json(body)?;
+        if response.candidates.is_empty() {
+            return Err(AiError::InvalidResponse("No candidates in response".to_string()));
+        }
+        let tool_calls = response.function_calls()
+            .into_iter()
+            .enumerate()
+            .map(|(i, call)| ToolCall {
+                id: if call.id.is_empty() { format!("call_{}", i) } else { call.id.clone() },
+                name: call.name.clone(),
+                arguments: if call.args.is_null() { json!({}) } else { call.args.clone() },
+            })
+            .collect();
+        Ok(ChatResponse {
+            content: response.text(),
+            tool_calls,
+            finish_reason: response.finish_reason(),
+            model: response.model_version,
+            usage: response.usage_metadata.map(Usage::from),
+        })
+    }
+
+    fn parse_stream_frame(&self, frame: &StreamFrame) -> Result<Vec<StreamEvent>, AiError> {
+        let chunk: WireResponse = parse_json(&frame.data)?;
+        let mut events = Vec::new();
+        let text = chunk.text();
+        if !text.is_empty() {
+            events.push(StreamEvent::Delta(text));
+        }
+        for (index, call) in chunk.function_calls().into_iter().enumerate() {
+            events.push(StreamEvent::ToolCallStart { index, id: call.id.clone(), name: call.name.clone() });
+            if !call.args.is_null() {
+                events.push(StreamEvent::ToolCallDelta { index, arguments: call.args.to_string() });
+            }
+        }
+        if let Some(reason) = chunk.finish_reason() {
+            events.push(StreamEvent::Done { finish_reason: Some(reason) });
+        }
+        if let Some(usage) = chunk.usage_metadata {
+            events.push(StreamEvent::Usage(usage.into()));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_uses_model_role_and_system_instruction() {
+        let request = ChatRequest {
+            model: "gemini".into(),
+            messages: vec![
+                ChatMessage::new(Role::System, "be brief"),
+                ChatMessage::new(Role::User, "hi"),
+                ChatMessage::new(Role::Assistant, "hello"),
+            ],
+            temperature: Some(0.5),
+            ..Default::default()
+        };
+        let body = Google::default().body(&request);
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "be brief");
+        assert_eq!(body["contents"].as_array().unwrap().len(), 2);
+        assert_eq!(body["contents"][1]["role"], "model");
+        assert_eq!(body["generationConfig"]["temperature"], 0.5);
+    }
+
+    #[test]
+    fn test_stream_frame() {
+        let frame = StreamFrame {
+            event: None,
+            data: r#"{"candidates":[{"content":{"parts":[{"text":"Hi"}],"role":"model"},"finishReason":"STOP"}],"usageMetadata":{"promptTokenCount":5,"candidatesTokenCount":1}}"#.into(),
+        };
+        let events = Google::default().parse_stream_frame(&frame).unwrap();
+        assert_eq!(events, vec![
+            StreamEvent::Delta("Hi".into()),
+            StreamEvent::Done { finish_reason: Some("STOP".into()) },
+            StreamEvent::Usage(Usage { input_tokens: 5, output_tokens: 1 }),
+        ]);
+    }
+
+    #[test]
+    fn test_function_calls() {
+        let request = ChatRequest {
+            model: "gemini".into(),
+            messages: vec![ChatMessage {
+                role: Role::Tool,
+                content: MessageContent::Parts(vec![ContentPart::ToolResult {
+                    tool_call_id: "call_0".into(),
+                    name: "search_in_files".into(),
+                    content: "no matches".into(),
+                    is_error: false,
+                }]),
+            }],
+            tools: vec![ToolDefinition { name: "search_in_files".into(), description: String::new(), parameters: json!({ "type": "object" }) }],
+            tool_choice: Some(ToolChoice::Tool { name: "search_in_files".into() }),
+            ..Default::default()
+        };
+        let body = Google::default().body(&request);
+        assert_eq!(body["contents"][0]["role"], "user");
+        assert_eq!(body["contents"][0]["parts"][0]["functionResponse"]["response"]["content"], "no matches");
+        assert_eq!(body["tools"][0]["functionDeclarations"][0]["name"], "search_in_files");
+        assert_eq!(body["toolConfig"]["functionCallingConfig"]["allowedFunctionNames"][0], "search_in_files");
+
+        let frame = StreamFrame {
+            event: None,
+            data: r#"{"candidates":[{"content":{"parts":[{"functionCall":{"name":"search_in_files","args":{"query":"fn"}}}],"role":"model"}}]}"#.into(),
+        };
+        assert_eq!(Google::default().parse_stream_frame(&frame).unwrap(), vec![
+            StreamEvent::ToolCallStart { index: 0, id: String::new(), name: "search_in_files".into() },
+            StreamEvent::ToolCallDelta { index: 0, arguments: r#"{"query":"fn"}"#.into() },
+        ]);
+    }
+}
@@ -0,0 +1,13 @@
+mod anthropic;
+mod gigachat;
+mod google;
+mod ollama;
+mod openai;
+mod yandex;
+
+pub use anthropic::Anthropic;
+pub use gigachat::GigaChat;
+pub use google::Google;
+pub use ollama::Ollama;
+pub use openai::OpenAiCompatible;
+pub use yandex::Yandex;
@@ -0,0 +1,266 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::ai::client::AiError;
+use crate::ai::provider::{parse_json, LlmProvider, StreamFormat};
+use super::openai::tools_json;
+use crate::ai::stream::StreamFrame;
+use crate::ai::types::*;
+
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// A local Ollama server; no API key, newline-delimited JSON streams.
+pub struct Ollama {
+    base_url: String,
+}
+
+impl Default for Ollama {
+    fn default() -> Self {
+        Self { base_url: DEFAULT_BASE_URL.to_string() }
+    }
+}
+
+impl Ollama {
+    fn body(&self, request: &ChatRequest, stream: bool) -> Value {
+        let messages: Vec<Value> = request.messages.iter().flat_map(message_json).collect();
+
+        let mut options = json!({});
+        if let Some(max_tokens) = request.max_tokens {
+            options["num_predict"] = json!(max_tokens);
+        }
+        if let Some(temperature) = request.temperature {
+            options["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = request.top_p {
+            options["top_p"] = json!(top_p);
+        }
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "stream": stream,
+            "options": options,
+        });
+        // Ollama has no tool_choice; `none` is honoured by not offering tools
+        let tools = request.offered_tools();
+        if !tools.is_empty() {
+            body["tools"] = tools_json(tools);
+        }
+        body
+    }
+}
+
+/// One message of the common model; tool messages become one `tool`
+/// message per result.
+fn message_json(message: &ChatMessage) -> Vec<Value> {
+    let parts = message.content.parts();
+    if message.role == Role::Tool {
+        return parts
+            .into_iter()
+            .filter_map(|part| match part {
+                ContentPart::ToolResult { name, content, .. } => Some(json!({
+                    "role": "tool",
+                    "tool_name": name,
+                    "content": content,
+                })),
+                _ => None,
+            })
+            .collect();
+    }
+
+    let mut value = json!({ "role": message.role.as_str(), "content": message.content.text() });
+    let images: Vec<String> = parts.iter().filter_map(|part| match part {
+        ContentPart::Image { data, .. } => Some(data.clone()),
+        _ => None,
+    }).collect();
+    if !images.is_empty() {
+        value["images"] = json!(images);
+    }
+    let tool_calls: Vec<Value> = message.content.tool_calls()
+        .into_iter()
+        .map(|call| json!({ "function": { "name": call.name, "arguments": call.arguments } }))
+        .collect();
+    if !tool_calls.is_empty() {
+        value["tool_calls"] = json!(tool_calls);
+    }
+    vec![value]
+}
+
+#[derive(Deserialize)]
+struct WireFunction {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+struct WireToolCall {
+    function: WireFunction,
+}
+
+#[derive(Deserialize)]
+struct WireMessage {
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    tool_calls: Vec<WireToolCall>,
+}
+
+#[derive(Deserialize)]
+struct WireResponse {
+    #[serde(default)]
+    model: String,
+    message: Option<WireMessage>,
+    #[serde(default)]
+    done: bool,
+    done_reason: Option<String>,
+    prompt_eval_count: Option<u32>,
+    eval_count: Option<u32>,
+    error: Option<String>,
+}
+
+impl WireResponse {
+    fn usage(&self) -> Option<Usage> {
+        if self.prompt_eval_count.is_none() && self.eval_count.is_none() {
+            return None;
+        }
+        Some(Usage {
+            input_tokens: self.prompt_eval_count.unwrap_or(0),
+            output_tokens: self.eval_count.unwrap_or(0),
+        })
+    }
+}
+
+impl LlmProvider for Ollama {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo { id: "ollama".to_string(), name: "Ollama".to_string(), requires_api_key: false }
+    }
+
+    fn stream_format(&self) -> StreamFormat {
+        StreamFormat::Ndjson
+    }
+
+    fn build_request(&self, http: &Client, _api_key: Option<&str>, request: &ChatRequest, stream: bool) -> Result<RequestBuilder, AiError> {
+        Ok(http.post(format!("{}/api/chat", self.base_url)).json(&self.body(request, stream)))
+    }
+
+    fn parse_response(&self, body: &str) -> Result<ChatResponse, AiError> {
+        let response: WireResponse = parse_json(body)?;
+        let usage = response.usage();
+        let (content, calls) = response.message.map(|m| (m.content, m.tool_calls)).unwrap_or_default();
+        // Ollama does not assign call ids
+        let tool_calls = calls
+            .into_iter()
+            .enumerate()
+            .map(|(i, call)| ToolCall {
+                id: format!("call_{}", i),
+                name: call.function.name,
+                arguments: if call.function.arguments.is_null() { json!({}) } else { call.function.arguments },
+            })
+            .collect();
+        Ok(ChatResponse {
+            content,
+            model: response.model,
+            finish_reason: response.done_reason,
+            usage,
+            tool_calls,
+        })
+    }
+
+    fn parse_stream_frame(&self, frame: &StreamFrame) -> Result<Vec<StreamEvent>, AiError> {
+        let chunk: WireResponse = parse_json(&frame.data)?;
+        if let Some(error) = chunk.error {
+            return Err(AiError::StreamError(error));
+        }
+        let mut events = Vec::new();
+        if let Some(message) = &chunk.message {
+            if !message.content.is_empty() {
+                events.push(StreamEvent::Delta(message.content.clone()));
+            }
+            // Calls arrive whole, one line each
+            for (index, call) in message.tool_calls.iter().enumerate() {
+                events.push(StreamEvent::ToolCallStart { index, id: String::new(), name: call.function.name.clone() });
+                if !call.function.arguments.is_null() {
+                    events.push(StreamEvent::ToolCallDelta { index, arguments: call.function.arguments.to_string() });
+                }
+            }
+        }
+        if chunk.done {
+            if let Some(usage) = chunk.usage() {
+                events.push(StreamEvent::Usage(usage));
+            }
+            events.push(StreamEvent::Done { finish_reason: chunk.done_reason.or_else(|| Some("stop".to_string())) });
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_maps_options_and_images() {
+        let request = ChatRequest {
+            model: "llama".into(),
+            messages: vec![ChatMessage {
+                role: Role::User,
+                content: MessageContent::Parts(vec![
+                    ContentPart::Text { text: "look".into() },
+                    ContentPart::Image { media_type: "image/png".into(), data: "AAAA".into() },
+                ]),
+            }],
+            max_tokens: Some(64),
+            ..Default::default()
+        };
+        let body = Ollama::default().body(&request, true);
+        assert_eq!(body["messages"][0]["images"][0], "AAAA");
+        assert_eq!(body["messages"][0]["content"], "look");
+        assert_eq!(body["options"]["num_predict"], 64);
+    }
+
+    #[test]
+    fn test_final_line_reports_usage() {
+        let provider = Ollama::default();
+        let line = |data: &str| StreamFrame { event: None, data: data.to_string() };
+        assert_eq!(
+            provider.parse_stream_frame(&line(r#"{"message":{"role":"assistant","content":"Hi"},"done":false}"#)).unwrap(),
+            vec![StreamEvent::Delta("Hi".into())]
+        );
+        let last = provider.parse_stream_frame(&line(r#"{"message":{"role":"assistant","content":""},"done":true,"done_reason":"stop","prompt_eval_count":9,"eval_count":3}"#)).unwrap();
+        assert_eq!(last, vec![
+            StreamEvent::Usage(Usage { input_tokens: 9, output_tokens: 3 }),
+            StreamEvent::Done { finish_reason: Some("stop".into()) },
+        ]);
+        assert!(provider.parse_stream_frame(&line(r#"{"error":"model not found"}"#)).is_err());
+    }
+
+    #[test]
+    fn test_tools() {
+        let tool = ToolDefinition { name: "get_problems".into(), description: String::new(), parameters: json!({ "type": "object" }) };
+        let mut request = ChatRequest {
+            model: "llama".into(),
+            messages: vec![ChatMessage {
+                role: Role::Tool,
+                content: MessageContent::Parts(vec![ContentPart::ToolResult {
+                    tool_call_id: "call_0".into(),
+                    name: "get_problems".into(),
+                    content: "[]".into(),
+                    is_error: false,
+                }]),
+            }],
+            tools: vec![tool],
+            ..Default::default()
+        };
+        let body = Ollama::default().body(&request, false);
+        assert_eq!(body["messages"][0], json!({ "role": "tool", "tool_name": "get_problems", "content": "[]" }));
+        assert_eq!(body["tools"][0]["function"]["name"], "get_problems");
+        request.tool_choice = Some(ToolChoice::None);
+        assert!(Ollama::default().body(&request, false).get("tools").is_none());
+
+        let response = Ollama::default().parse_response(
+            r#"{"model":"llama","message":{"role":"assistant","content":"","tool_calls":[{"function":{"name":"get_problems","arguments":{"path":"."}}}]},"done":true}"#,
+        ).unwrap();
+        assert_eq!(response.tool_calls, vec![ToolCall { id: "call_0".into(), name: "get_problems".into(), arguments: json!({ "path": "." }) }]);
+    }
+}
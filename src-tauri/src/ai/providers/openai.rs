@@ -0,0 +1,471 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use crate::ai::client::AiError;
+use crate::ai::provider::{parse_json, require_key, LlmProvider};
+use crate::ai::stream::StreamFrame;
+use crate::ai::types::*;
+use crate::settings::{EndpointAuth, OpenAiEndpoint};
+
+/// Any backend speaking the OpenAI chat completions API (OpenAI, xAI,
+/// AgentRouter, local servers, ...).
+pub struct OpenAiCompatible {
+    id: String,
+    name: String,
+    /// API root; `/chat/completions` and `/models` live under it.
+    base_url: String,
+    headers: BTreeMap<String, String>,
+    auth: EndpointAuth,
+    /// Ask for a final usage chunk with `stream_options.include_usage`.
+    stream_usage: bool,
+}
+
+impl OpenAiCompatible {
+    pub fn new(id: &str, name: &str, base_url: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            headers: BTreeMap::new(),
+            auth: EndpointAuth::Bearer,
+            stream_usage: true,
+        }
+    }
+
+    /// A provider for an endpoint configured in settings.
+    pub fn from_endpoint(endpoint: &OpenAiEndpoint) -> Self {
+        Self {
+            headers: endpoint.headers.clone(),
+            auth: endpoint.auth.clone(),
+            ..Self::new(&endpoint.id, &endpoint.name, &endpoint.base_url)
+        }
+    }
+
+    /// For gateways that reject unknown request fields.
+    pub fn without_stream_usage(mut self) -> Self {
+        self.stream_usage = false;
+        self
+    }
+
+    fn body(&self, request: &ChatRequest, stream: bool) -> Value {
+        let messages: Vec<Value> = request.messages.iter().flat_map(message_json).collect();
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "stream": stream,
+        });
+        if let Some(max_tokens) = request.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = request.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if !request.tools.is_empty() {
+            body["tools"] = tools_json(&request.tools);
+            if let Some(choice) = &request.tool_choice {
+                body["tool_choice"] = tool_choice_json(choice);
+            }
+        }
+        if stream && self.stream_usage {
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        body
+    }
+
+    /// `builder` with the key placed as configured and the extra headers added.
+    fn request(&self, builder: RequestBuilder, api_key: Option<&str>) -> Result<RequestBuilder, AiError> {
+        let mut builder = match &self.auth {
+            EndpointAuth::None => builder,
+            EndpointAuth::Bearer => builder.bearer_auth(require_key(&self.id, api_key)?),
+            EndpointAuth::Header { name } => builder.header(name.as_str(), require_key(&self.id, api_key)?),
+        };
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        Ok(builder)
+    }
+}
+
+/// Tool definitions in the `tools` format shared by OpenAI-style APIs and Ollama.
+pub(crate) fn tools_json(tools: &[ToolDefinition]) -> Value {
+    let tools: Vec<Value> = tools
+        .iter()
+        .map(|tool| json!({
+            "type": "function",
+            "function": {
+                "name": tool.name,
+                "description": tool.description,
+                "parameters": tool.parameters,
+            },
+        }))
+        .collect();
+    json!(tools)
+}
+
+fn tool_choice_json(choice: &ToolChoice) -> Value {
+    match choice {
+        ToolChoice::Auto => json!("auto"),
+        ToolChoice::None => json!("none"),
+        ToolChoice::Required => json!("required"),
+        ToolChoice::Tool { name } => json!({ "type": "function", "function": { "name": name } }),
+    }
+}
+
+/// One message of the common model; tool messages become one `tool`
+/// message per result.
+fn message_json(message: &ChatMessage) -> Vec<Value> {
+    let parts = message.content.parts();
+    if message.role == Role::Tool {
+        return parts
+            .into_iter()
+            .filter_map(|part| match part {
+                ContentPart::ToolResult { tool_call_id, content, .. } => Some(json!({
+                    "role": "tool",
+                    "tool_call_id": tool_call_id,
+                    "content": content,
+                })),
+                _ => None,
+            })
+            .collect();
+    }
+
+    let content = if message.content.has_images() {
+        let parts: Vec<Value> = parts.into_iter().filter_map(|part| match part {
+            ContentPart::Text { text } => Some(json!({ "type": "text", "text": text })),
+            ContentPart::Image { media_type, data } => Some(json!({
+                "type": "image_url",
+                "image_url": { "url": format!("data:{};base64,{}", media_type, data) },
+            })),
+            _ => None,
+        }).collect();
+        json!(parts)
+    } else {
+        json!(message.content.text())
+    };
+    let mut value = json!({ "role": message.role.as_str(), "content": content });
+
+    let tool_calls = message.content.tool_calls();
+    if !tool_calls.is_empty() {
+        let calls: Vec<Value> = tool_calls
+            .iter()
+            .map(|call| json!({
+                "id": call.id,
+                "type": "function",
+                "function": { "name": call.name, "arguments": call.arguments.to_string() },
+            }))
+            .collect();
+        value["tool_calls"] = json!(calls);
+        if value["content"] == json!("") {
+            value["content"] = Value::Null;
+        }
+    }
+    vec![value]
+}
+
+#[derive(Deserialize)]
+struct WireUsage {
+    #[serde(default)]
+    prompt_tokens: u32,
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+impl From<WireUsage> for Usage {
+    fn from(usage: WireUsage) -> Self {
+        Usage { input_tokens: usage.prompt_tokens, output_tokens: usage.completion_tokens }
+    }
+}
+
+#[derive(Deserialize)]
+struct WireFunction {
+    name: String,
+    #[serde(default)]
+    arguments: String,
+}
+
+#[derive(Deserialize)]
+struct WireToolCall {
+    id: String,
+    function: WireFunction,
+}
+
+#[derive(Deserialize)]
+struct WireMessage {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<WireToolCall>,
+}
+
+#[derive(Deserialize)]
+struct WireChoice {
+    message: WireMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WireResponse {
+    #[serde(default)]
+    model: String,
+    choices: Vec<WireChoice>,
+    usage: Option<WireUsage>,
+}
+
+#[derive(Deserialize)]
+struct WireFunctionDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WireToolCallDelta {
+    index: usize,
+    id: Option<String>,
+    function: Option<WireFunctionDelta>,
+}
+
+#[derive(Deserialize)]
+struct WireDelta {
+    content: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<WireToolCallDelta>,
+}
+
+#[derive(Deserialize)]
+struct WireStreamChoice {
+    delta: Option<WireDelta>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WireStreamChunk {
+    #[serde(default)]
+    choices: Vec<WireStreamChoice>,
+    usage: Option<WireUsage>,
+}
+
+#[derive(Deserialize)]
+struct WireModel {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct WireModelList {
+    data: Vec<WireModel>,
+}
+
+impl LlmProvider for OpenAiCompatible {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            requires_api_key: self.auth != EndpointAuth::None,
+        }
+    }
+
+    fn build_request(&self, http: &Client, api_key: Option<&str>, request: &ChatRequest, stream: bool) -> Result<RequestBuilder, AiError> {
+        let builder = http.post(format!("{}/chat/completions", self.base_url));
+        Ok(self.request(builder, api_key)?.json(&self.body(request, stream)))
+    }
+
+    fn models_request(&self, http: &Client, api_key: Option<&str>) -> Option<Result<RequestBuilder, AiError>> {
+        Some(self.request(http.get(format!("{}/models", self.base_url)), api_key))
+    }
+
+    fn parse_models(&self, body: &str) -> Result<Vec<String>, AiError> {
+        let list: WireModelList = parse_json(body)?;
+        let mut models: Vec<String> = list.data.into_iter().map(|model| model.id).collect();
+        models.sort();
+        models.dedup();
+        Ok(models)
+    }
+
+    fn parse_response(&self, body: &str) -> Result<ChatResponse, AiError> {
+        let response: WireResponse = parse_json(body)?;
+        let choice = response.choices.into_iter().next()
+            .ok_or_else(|| AiError::InvalidResponse("No choices in response".to_string()))?;
+        let tool_calls = choice.message.tool_calls
+            .into_iter()
+            .map(|call| {
+                let arguments = if call.function.arguments.trim().is_empty() {
+                    json!({})
+                } else {
+                    parse_json(&call.function.arguments)?
+                };
+                Ok(ToolCall { id: call.id, name: call.function.name, arguments })
+            })
+            .collect::<Result<Vec<_>, AiError>>()?;
+        Ok(ChatResponse {
+            content: choice.message.content.unwrap_or_default(),
+            model: response.model,
+            finish_reason: choice.finish_reason,
+            usage: response.usage.map(Usage::from),
+            tool_calls,
+        })
+    }
+
+    fn parse_stream_frame(&self, frame: &StreamFrame) -> Result<Vec<StreamEvent>, AiError> {
+        if frame.is_done_marker() {
+            return Ok(Vec::new());
+        }
+        let chunk: WireStreamChunk = parse_json(&frame.data)?;
+        let mut events = Vec::new();
+        for choice in chunk.choices {
+            let delta = choice.delta.unwrap_or(WireDelta { content: None, tool_calls: Vec::new() });
+            if let Some(text) = delta.content.filter(|t| !t.is_empty()) {
+                events.push(StreamEvent::Delta(text));
+            }
+            for call in delta.tool_calls {
+                let function = call.function.unwrap_or(WireFunctionDelta { name: None, arguments: None });
+                // Only the first delta of a call names the function
+                if let Some(name) = function.name.filter(|n| !n.is_empty()) {
+                    events.push(StreamEvent::ToolCallStart {
+                        index: call.index,
+                        id: call.id.unwrap_or_default(),
+                        name,
+                    });
+                }
+                if let Some(arguments) = function.arguments.filter(|a| !a.is_empty()) {
+                    events.push(StreamEvent::ToolCallDelta { index: call.index, arguments });
+                }
+            }
+            if let Some(reason) = choice.finish_reason {
+                events.push(StreamEvent::Done { finish_reason: Some(reason) });
+            }
+        }
+        if let Some(usage) = chunk.usage {
+            events.push(StreamEvent::Usage(usage.into()));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> OpenAiCompatible {
+        OpenAiCompatible::new("openai", "OpenAI", "http://localhost/v1")
+    }
+
+    #[test]
+    fn test_body_translates_messages() {
+        let request = ChatRequest {
+            model: "gpt".into(),
+            messages: vec![
+                ChatMessage::new(Role::System, "be brief"),
+                ChatMessage {
+                    role: Role::User,
+                    content: MessageContent::Parts(vec![
+                        ContentPart::Text { text: "what is this".into() },
+                        ContentPart::Image { media_type: "image/png".into(), data: "AAAA".into() },
+                    ]),
+                },
+            ],
+            max_tokens: Some(10),
+            ..Default::default()
+        };
+        let body = provider().body(&request, true);
+        assert_eq!(body["messages"][0], json!({ "role": "system", "content": "be brief" }));
+        assert_eq!(body["messages"][1]["content"][1]["image_url"]["url"], "data:image/png;base64,AAAA");
+        assert_eq!(body["max_tokens"], 10);
+        assert_eq!(body["stream_options"]["include_usage"], true);
+        assert!(body.get("temperature").is_none());
+        assert!(provider().without_stream_usage().body(&request, true).get("stream_options").is_none());
+    }
+
+    #[test]
+    fn test_parse_response_and_stream() {
+        let response = provider().parse_response(
+            r#"{"model":"gpt","choices":[{"message":{"role":"assistant","content":"hi"},"finish_reason":"stop"}],"usage":{"prompt_tokens":4,"completion_tokens":1}}"#,
+        ).unwrap();
+        assert_eq!(response.content, "hi");
+        assert_eq!(response.usage, Some(Usage { input_tokens: 4, output_tokens: 1 }));
+
+        let frame = StreamFrame { event: None, data: r#"{"choices":[{"delta":{"content":"x"},"finish_reason":null}]}"#.into() };
+        assert_eq!(provider().parse_stream_frame(&frame).unwrap(), vec![StreamEvent::Delta("x".into())]);
+        let done = StreamFrame { event: None, data: "[DONE]".into() };
+        assert!(provider().parse_stream_frame(&done).unwrap().is_empty());
+        let garbage = StreamFrame { event: None, data: "{oops".into() };
+        assert!(provider().parse_stream_frame(&garbage).is_err());
+    }
+
+    #[test]
+    fn test_tool_round_trip() {
+        let call = ToolCall { id: "call_1".into(), name: "read_file".into(), arguments: json!({ "path": "a.rs" }) };
+        let request = ChatRequest {
+            model: "gpt".into(),
+            messages: vec![
+                ChatMessage { role: Role::Assistant, content: MessageContent::Parts(vec![ContentPart::ToolCall(call)]) },
+                ChatMessage {
+                    role: Role::Tool,
+                    content: MessageContent::Parts(vec![ContentPart::ToolResult {
+                        tool_call_id: "call_1".into(),
+                        name: "read_file".into(),
+                        content: "fn main() {}".into(),
+                        is_error: false,
+                    }]),
+                },
+            ],
+            tools: vec![ToolDefinition { name: "read_file".into(), description: "Read".into(), parameters: json!({ "type": "object" }) }],
+            tool_choice: Some(ToolChoice::Tool { name: "read_file".into() }),
+            ..Default::default()
+        };
+        let body = provider().body(&request, false);
+        assert_eq!(body["messages"][0]["content"], Value::Null);
+        assert_eq!(body["messages"][0]["tool_calls"][0]["function"]["arguments"], r#"{"path":"a.rs"}"#);
+        assert_eq!(body["messages"][1], json!({ "role": "tool", "tool_call_id": "call_1", "content": "fn main() {}" }));
+        assert_eq!(body["tools"][0]["function"]["name"], "read_file");
+        assert_eq!(body["tool_choice"]["function"]["name"], "read_file");
+
+        let start = StreamFrame {
+            event: None,
+            data: r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","type":"function","function":{"name":"read_file","arguments":""}}]}}]}"#.into(),
+        };
+        let more = StreamFrame {
+            event: None,
+            data: r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"{\"pa"}}]}}]}"#.into(),
+        };
+        assert_eq!(provider().parse_stream_frame(&start).unwrap(), vec![
+            StreamEvent::ToolCallStart { index: 0, id: "call_1".into(), name: "read_file".into() },
+        ]);
+        assert_eq!(provider().parse_stream_frame(&more).unwrap(), vec![
+            StreamEvent::ToolCallDelta { index: 0, arguments: "{\"pa".into() },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_auth_and_model_discovery() {
+        use crate::ai::client::tests::mock_server;
+        use crate::ai::client::{http_client, list_models};
+
+        let models = r#"{"object":"list","data":[{"id":"qwen2.5-coder","object":"model"},{"id":"llama-3.1-8b","object":"model"}]}"#;
+        let (base, recorded) = mock_server(200, "application/json", models.to_string());
+        let endpoint = OpenAiEndpoint {
+            id: "gateway".into(),
+            name: "Gateway".into(),
+            base_url: format!("{}/v1/", base),
+            headers: BTreeMap::from([("X-Team".to_string(), "editor".to_string())]),
+            auth: EndpointAuth::Header { name: "api-key".into() },
+        };
+        let provider = OpenAiCompatible::from_endpoint(&endpoint);
+
+        let listed = list_models(&http_client(), &provider, Some("secret")).await.unwrap();
+        assert_eq!(listed, ["llama-3.1-8b", "qwen2.5-coder"]);
+        let sent = recorded.lock().unwrap()[0].to_lowercase();
+        assert!(sent.starts_with("get /v1/models "));
+        assert!(sent.contains("api-key: secret"));
+        assert!(sent.contains("x-team: editor"));
+        assert!(!sent.contains("authorization:"));
+        assert!(list_models(&http_client(), &provider, None).await.is_err());
+
+        let local = OpenAiCompatible::from_endpoint(&OpenAiEndpoint { auth: EndpointAuth::None, ..endpoint });
+        assert!(!local.info().requires_api_key);
+        let request = ChatRequest { model: "m".into(), ..Default::default() };
+        assert!(local.build_request(&http_client(), None, &request, false).is_ok());
+    }
+}
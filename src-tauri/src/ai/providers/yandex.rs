@@ -0,0 +1,396 @@
+use reqwest::{Client, RequestBuilder};
+use serde::{Deserialize, Deserializer};
+use serde_json::{json, Value};
+
+use crate::ai::client::AiError;
+use crate::ai::provider::{parse_json, require_key, LlmProvider, StreamFormat};
+use crate::ai::stream::StreamFrame;
+use crate::ai::types::*;
+
+const API_URL: &str = "https://llm.api.cloud.yandex.net/foundationModels/v1/completion";
+/// Prefix of IAM tokens; anything else is taken for an API key.
+const IAM_TOKEN_PREFIX: &str = "t1.";
+
+/// YandexGPT through the Foundation Models API. The stored key is
+/// `<folder id>:<API key or IAM token>`, since every request is billed to a
+/// cloud folder.
+pub struct Yandex {
+    endpoint: String,
+}
+
+impl Default for Yandex {
+    fn default() -> Self {
+        Self { endpoint: API_URL.to_string() }
+    }
+}
+
+/// A stored key split into its folder and secret.
+struct Credentials<'a> {
+    folder_id: &'a str,
+    secret: &'a str,
+}
+
+impl<'a> Credentials<'a> {
+    fn parse(key: &'a str) -> Result<Self, AiError> {
+        match key.split_once(':') {
+            Some((folder_id, secret)) if !folder_id.trim().is_empty() && !secret.trim().is_empty() => {
+                Ok(Self { folder_id: folder_id.trim(), secret: secret.trim() })
+            }
+            _ => Err(AiError::InvalidApiKey("Yandex keys have the form '<folder id>:<API key or IAM token>'".to_string())),
+        }
+    }
+
+    fn authorization(&self) -> String {
+        if self.secret.starts_with(IAM_TOKEN_PREFIX) {
+            format!("Bearer {}", self.secret)
+        } else {
+            format!("Api-Key {}", self.secret)
+        }
+    }
+
+    /// Model names like `yandexgpt/latest` become `gpt://<folder>/yandexgpt/latest`;
+    /// full URIs are used as given.
+    fn model_uri(&self, model: &str) -> String {
+        if model.contains("://") {
+            model.to_string()
+        } else {
+            format!("gpt://{}/{}", self.folder_id, model)
+        }
+    }
+}
+
+impl Yandex {
+    fn body(&self, credentials: &Credentials, request: &ChatRequest, stream: bool) -> Value {
+        let messages: Vec<Value> = request.messages.iter().map(message_json).collect();
+        let mut options = json!({ "stream": stream });
+        if let Some(temperature) = request.temperature {
+            options["temperature"] = json!(temperature);
+        }
+        // int64 fields are strings in the API's JSON mapping
+        if let Some(max_tokens) = request.max_tokens {
+            options["maxTokens"] = json!(max_tokens.to_string());
+        }
+        let mut body = json!({
+            "modelUri": credentials.model_uri(&request.model),
+            "completionOptions": options,
+            "messages": messages,
+        });
+        if !request.tools.is_empty() && request.tool_choice != Some(ToolChoice::None) {
+            let tools: Vec<Value> = request.tools
+                .iter()
+                .map(|tool| json!({
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.parameters,
+                    },
+                }))
+                .collect();
+            body["tools"] = json!(tools);
+        }
+        body
+    }
+}
+
+/// Tool calls and results travel as lists in place of text; results are
+/// matched to calls by function name.
+fn message_json(message: &ChatMessage) -> Value {
+    if message.role == Role::Tool {
+        let results: Vec<Value> = message.content
+            .parts()
+            .into_iter()
+            .filter_map(|part| match part {
+                ContentPart::ToolResult { name, content, .. } => Some(json!({
+                    "functionResult": { "name": name, "content": content },
+                })),
+                _ => None,
+            })
+            .collect();
+        return json!({ "role": "user", "toolResultList": { "toolResults": results } });
+    }
+
+    let tool_calls = message.content.tool_calls();
+    if !tool_calls.is_empty() {
+        let calls: Vec<Value> = tool_calls
+            .iter()
+            .map(|call| json!({ "functionCall": { "name": call.name, "arguments": call.arguments } }))
+            .collect();
+        return json!({ "role": "assistant", "toolCallList": { "toolCalls": calls } });
+    }
+    json!({ "role": message.role.as_str(), "text": message.content.text() })
+}
+
+/// Token counts arrive as strings.
+fn count<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Count {
+        Number(u32),
+        Text(String),
+    }
+    match Count::deserialize(deserializer)? {
+        Count::Number(n) => Ok(n),
+        Count::Text(text) => text.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireUsage {
+    #[serde(default, deserialize_with = "count")]
+    input_text_tokens: u32,
+    #[serde(default, deserialize_with = "count")]
+    completion_tokens: u32,
+}
+
+impl From<WireUsage> for Usage {
+    fn from(usage: WireUsage) -> Self {
+        Usage { input_tokens: usage.input_text_tokens, output_tokens: usage.completion_tokens }
+    }
+}
+
+#[derive(Deserialize)]
+struct WireFunctionCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireToolCall {
+    function_call: WireFunctionCall,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireToolCallList {
+    #[serde(default)]
+    tool_calls: Vec<WireToolCall>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireMessage {
+    #[serde(default)]
+    text: String,
+    tool_call_list: Option<WireToolCallList>,
+}
+
+#[derive(Deserialize)]
+struct WireAlternative {
+    message: WireMessage,
+    #[serde(default)]
+    status: String,
+}
+
+impl WireAlternative {
+    /// Partial alternatives of a stream have no finish reason yet.
+    fn finish_reason(&self) -> Option<String> {
+        match self.status.as_str() {
+            "" | "ALTERNATIVE_STATUS_PARTIAL" => None,
+            status => Some(status.to_string()),
+        }
+    }
+
+    fn tool_calls(&mut self) -> Vec<(String, Value)> {
+        self.message
+            .tool_call_list
+            .take()
+            .map(|list| list.tool_calls)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| {
+                let arguments = match call.function_call.arguments {
+                    Value::Null => json!({}),
+                    arguments => arguments,
+                };
+                (call.function_call.name, arguments)
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WireResult {
+    #[serde(default)]
+    alternatives: Vec<WireAlternative>,
+    usage: Option<WireUsage>,
+}
+
+#[derive(Deserialize)]
+struct WireError {
+    #[serde(default)]
+    message: String,
+}
+
+/// Every response and stream line wraps a result, or an error on failure.
+#[derive(Deserialize)]
+struct WireResponse {
+    result: Option<WireResult>,
+    error: Option<WireError>,
+}
+
+impl WireResponse {
+    fn into_result(self) -> Result<WireResult, AiError> {
+        match (self.result, self.error) {
+            (Some(result), _) => Ok(result),
+            (None, Some(error)) => Err(AiError::StreamError(error.message)),
+            (None, None) => Err(AiError::InvalidResponse("No result in response".to_string())),
+        }
+    }
+}
+
+impl LlmProvider for Yandex {
+    fn info(&self) -> ProviderInfo {
+        ProviderInfo { id: "yandex".to_string(), name: "Yandex".to_string(), requires_api_key: true }
+    }
+
+    fn stream_format(&self) -> StreamFormat {
+        StreamFormat::Ndjson
+    }
+
+    fn streams_full_text(&self) -> bool {
+        true
+    }
+
+    fn build_request(&self, http: &Client, api_key: Option<&str>, request: &ChatRequest, stream: bool) -> Result<RequestBuilder, AiError> {
+        let credentials = Credentials::parse(require_key("yandex", api_key)?)?;
+        Ok(http
+            .post(&self.endpoint)
+            .header("Authorization", credentials.authorization())
+            .header("x-folder-id", credentials.folder_id)
+            .json(&self.body(&credentials, request, stream)))
+    }
+
+    fn parse_response(&self, body: &str) -> Result<ChatResponse, AiError> {
+        let response: WireResponse = parse_json(body)?;
+        let result = response.into_result()?;
+        let mut alternative = result.alternatives.into_iter().next()
+            .ok_or_else(|| AiError::InvalidResponse("No alternatives in response".to_string()))?;
+        let tool_calls = alternative
+            .tool_calls()
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, arguments))| ToolCall { id: format!("call_{}", index), name, arguments })
+            .collect();
+        Ok(ChatResponse {
+            finish_reason: alternative.finish_reason(),
+            content: alternative.message.text,
+            // Only a model version comes back; `chat` fills in the requested model
+            model: String::new(),
+            usage: result.usage.map(Usage::from),
+            tool_calls,
+        })
+    }
+
+    fn parse_stream_frame(&self, frame: &StreamFrame) -> Result<Vec<StreamEvent>, AiError> {
+        let response: WireResponse = parse_json(&frame.data)?;
+        let result = response.into_result()?;
+        let mut events = Vec::new();
+        if let Some(mut alternative) = result.alternatives.into_iter().next() {
+            // Each line repeats the whole text so far
+            events.push(StreamEvent::Delta(std::mem::take(&mut alternative.message.text)));
+            // Tool calls only come whole, with the final line
+            for (index, (name, arguments)) in alternative.tool_calls().into_iter().enumerate() {
+                events.push(StreamEvent::ToolCallStart { index, id: String::new(), name });
+                events.push(StreamEvent::ToolCallDelta { index, arguments: arguments.to_string() });
+            }
+            if let Some(reason) = alternative.finish_reason() {
+                events.push(StreamEvent::Done { finish_reason: Some(reason) });
+            }
+        }
+        if let Some(usage) = result.usage {
+            events.push(StreamEvent::Usage(usage.into()));
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::client::tests::mock_server;
+    use crate::ai::client::{chat, chat_stream, http_client};
+
+    #[test]
+    fn test_body_and_auth() {
+        let credentials = Credentials::parse("b1gfolder:t1.iam-token").unwrap();
+        assert_eq!(credentials.authorization(), "Bearer t1.iam-token");
+        assert_eq!(Credentials::parse("b1gfolder:AQVNkey").unwrap().authorization(), "Api-Key AQVNkey");
+        assert!(Credentials::parse("AQVNkey").is_err());
+
+        let call = ToolCall { id: "call_0".into(), name: "read_file".into(), arguments: json!({ "path": "a.rs" }) };
+        let request = ChatRequest {
+            model: "yandexgpt/latest".into(),
+            messages: vec![
+                ChatMessage::new(Role::System, "be brief"),
+                ChatMessage { role: Role::Assistant, content: MessageContent::Parts(vec![ContentPart::ToolCall(call)]) },
+                ChatMessage {
+                    role: Role::Tool,
+                    content: MessageContent::Parts(vec![ContentPart::ToolResult {
+                        tool_call_id: "call_0".into(),
+                        name: "read_file".into(),
+                        content: "fn main() {}".into(),
+                        is_error: false,
+                    }]),
+                },
+            ],
+            max_tokens: Some(100),
+            ..Default::default()
+        };
+        let body = Yandex::default().body(&credentials, &request, false);
+        assert_eq!(body["modelUri"], "gpt://b1gfolder/yandexgpt/latest");
+        assert_eq!(body["completionOptions"], json!({ "stream": false, "maxTokens": "100" }));
+        assert_eq!(body["messages"][0], json!({ "role": "system", "text": "be brief" }));
+        assert_eq!(body["messages"][1]["toolCallList"]["toolCalls"][0]["functionCall"]["arguments"]["path"], "a.rs");
+        assert_eq!(body["messages"][2]["toolResultList"]["toolResults"][0]["functionResult"]["content"], "fn main() {}");
+    }
+
+    #[tokio::test]
+    async fn test_chat_against_mock_server() {
+        let reply = r#"{"result":{"alternatives":[{"message":{"role":"assistant","toolCallList":{"toolCalls":[{"functionCall":{"name":"get_problems","arguments":{}}}]}},"status":"ALTERNATIVE_STATUS_TOOL_CALLS"}],"usage":{"inputTextTokens":"12","completionTokens":"4","totalTokens":"16"},"modelVersion":"23.10.2024"}}"#;
+        let (base, recorded) = mock_server(200, "application/json", reply.to_string());
+        let provider = Yandex { endpoint: format!("{}/foundationModels/v1/completion", base) };
+        let request = ChatRequest { model: "yandexgpt/latest".into(), messages: vec![ChatMessage::new(Role::User, "hi")], ..Default::default() };
+
+        let response = chat(&http_client(), &provider, Some("b1gfolder:AQVNkey"), &request).await.unwrap();
+        assert_eq!(response.tool_calls, vec![ToolCall { id: "call_0".into(), name: "get_problems".into(), arguments: json!({}) }]);
+        assert_eq!(response.finish_reason.as_deref(), Some("ALTERNATIVE_STATUS_TOOL_CALLS"));
+        assert_eq!(response.usage, Some(Usage { input_tokens: 12, output_tokens: 4 }));
+
+        let sent = recorded.lock().unwrap()[0].to_lowercase();
+        assert!(sent.contains("authorization: api-key aqvnkey"));
+        assert!(sent.contains("x-folder-id: b1gfolder"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_turns_full_text_into_deltas() {
+        let body = concat!(
+            "{\"result\":{\"alternatives\":[{\"message\":{\"role\":\"assistant\",\"text\":\"Hel\"},\"status\":\"ALTERNATIVE_STATUS_PARTIAL\"}],\"usage\":{\"inputTextTokens\":\"5\",\"completionTokens\":\"1\"}}}\n",
+            "{\"result\":{\"alternatives\":[{\"message\":{\"role\":\"assistant\",\"text\":\"Hello\"},\"status\":\"ALTERNATIVE_STATUS_PARTIAL\"}],\"usage\":{\"inputTextTokens\":\"5\",\"completionTokens\":\"2\"}}}\n",
+            "{\"result\":{\"alternatives\":[{\"message\":{\"role\":\"assistant\",\"text\":\"Hello!\"},\"status\":\"ALTERNATIVE_STATUS_FINAL\"}],\"usage\":{\"inputTextTokens\":\"5\",\"completionTokens\":\"3\"}}}\n",
+        );
+        let (base, recorded) = mock_server(200, "application/json", body.to_string());
+        let provider = Yandex { endpoint: base };
+        let request = ChatRequest { model: "gpt://other/yandexgpt-lite/latest".into(), ..Default::default() };
+
+        let mut deltas = Vec::new();
+        let response = chat_stream(&http_client(), &provider, Some("b1gfolder:t1.token"), &request, |event| {
+            if let StreamEvent::Delta(text) = event {
+                deltas.push(text.clone());
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(deltas, ["Hel", "lo", "!"]);
+        assert_eq!(response.content, "Hello!");
+        assert_eq!(response.usage, Some(Usage { input_tokens: 5, output_tokens: 3 }));
+        assert_eq!(response.finish_reason.as_deref(), Some("ALTERNATIVE_STATUS_FINAL"));
+        let sent = recorded.lock().unwrap()[0].clone();
+        assert!(sent.contains("\"modelUri\":\"gpt://other/yandexgpt-lite/latest\""));
+        assert!(sent.to_lowercase().contains("authorization: bearer t1.token"));
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::settings::OpenAiEndpoint;
+
+use super::client::AiError;
+use super::provider::LlmProvider;
+use super::providers::{Anthropic, GigaChat, Google, Ollama, OpenAiCompatible, Yandex};
+use super::types::ProviderInfo;
+
+/// Providers by id, in registration order.
+pub struct ProviderRegistry {
+    providers: HashMap<String, Arc<dyn LlmProvider>>,
+    order: Vec<String>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self { providers: HashMap::new(), order: Vec::new() }
+    }
+
+    /// Every built-in provider.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register(OpenAiCompatible::new("openai", "OpenAI", "https://api.openai.com/v1"));
+        registry.register(Anthropic::default());
+        registry.register(Google::default());
+        registry.register(OpenAiCompatible::new("xai", "xAI", "https://api.x.ai/v1"));
+        registry.register(
+            OpenAiCompatible::new("zhipu", "Zhipu AI", "https://open.bigmodel.cn/api/paas/v4")
+                .without_stream_usage(),
+        );
+        registry.register(Yandex::default());
+        registry.register(GigaChat::default());
+        registry.register(Ollama::default());
+        registry.register(
+            OpenAiCompatible::new("agentrouter", "AgentRouter", "https://agentrouter.org/v1")
+                .without_stream_usage(),
+        );
+        registry
+    }
+
+    /// The built-in providers plus the endpoints configured in settings,
+    /// which replace built-ins of the same id.
+    pub fn with_endpoints(endpoints: &[OpenAiEndpoint]) -> Self {
+        let mut registry = Self::with_defaults();
+        for endpoint in endpoints {
+            registry.register(OpenAiCompatible::from_endpoint(endpoint));
+        }
+        registry
+    }
+
+    /// Adds a provider, replacing any registered under the same id.
+    pub fn register(&mut self, provider: impl LlmProvider + 'static) {
+        let id = provider.info().id;
+        if !self.providers.contains_key(&id) {
+            self.order.push(id.clone());
+        }
+        self.providers.insert(id, Arc::new(provider));
+    }
+
+    pub fn get(&self, id: &str) -> Result<Arc<dyn LlmProvider>, AiError> {
+        self.providers.get(id).cloned().ok_or_else(|| AiError::UnknownProvider(id.to_string()))
+    }
+
+    pub fn list(&self) -> Vec<ProviderInfo> {
+        self.order.iter().filter_map(|id| self.providers.get(id)).map(|p| p.info()).collect()
+    }
+}
+
+impl Default for ProviderRegistry {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::EndpointAuth;
+
+    #[test]
+    fn test_defaults_are_listed_in_order() {
+        let registry = ProviderRegistry::with_defaults();
+        let ids: Vec<String> = registry.list().into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, ["openai", "anthropic", "google", "xai", "zhipu", "yandex", "gigachat", "ollama", "agentrouter"]);
+        assert!(!registry.get("ollama").unwrap().info().requires_api_key);
+    }
+
+    #[test]
+    fn test_register_replaces_same_id() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(OpenAiCompatible::new("x", "First", "http://a"));
+        registry.register(OpenAiCompatible::new("x", "Second", "http://b"));
+        let list = registry.list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].name, "Second");
+    }
+
+    #[test]
+    fn test_endpoints_extend_and_replace_defaults() {
+        let endpoint = |id: &str, auth| OpenAiEndpoint {
+            id: id.into(),
+            name: id.to_uppercase(),
+            base_url: "http://localhost:1234/v1".into(),
+            headers: Default::default(),
+            auth,
+        };
+        let registry = ProviderRegistry::with_endpoints(&[
+            endpoint("openai", EndpointAuth::Bearer),
+            endpoint("lmstudio", EndpointAuth::None),
+        ]);
+        let list = registry.list();
+        assert_eq!(list[0].name, "OPENAI");
+        assert_eq!(list.last().unwrap().id, "lmstudio");
+        assert!(!list.last().unwrap().requires_api_key);
+        assert_eq!(list.len(), ProviderRegistry::with_defaults().list().len() + 1);
+    }
+}
@@ -0,0 +1,73 @@
+use futures_util::future::{AbortHandle, AbortRegistration};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::client::AiError;
+
+/// Abort handles of the streams currently running, by request id.
+#[derive(Default, Clone)]
+pub struct RunningRequests {
+    running: Arc<Mutex<HashMap<String, AbortHandle>>>,
+}
+
+impl RunningRequests {
+    /// Registers `request_id`; the stream wrapped with the returned
+    /// registration stops at its next poll once `cancel` is called.
+    pub fn start(&self, request_id: &str) -> Result<(AbortRegistration, RunningGuard), AiError> {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.contains_key(request_id) {
+            return Err(AiError::RequestInProgress(request_id.to_string()));
+        }
+        let (handle, registration) = AbortHandle::new_pair();
+        running.insert(request_id.to_string(), handle);
+        let guard = RunningGuard { running: self.running.clone(), request_id: request_id.to_string() };
+        Ok((registration, guard))
+    }
+
+    /// Aborts `request_id`, returning whether it was running.
+    pub fn cancel(&self, request_id: &str) -> bool {
+        let running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        match running.get(request_id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Removes the request from the running set however the stream ends.
+pub struct RunningGuard {
+    running: Arc<Mutex<HashMap<String, AbortHandle>>>,
+    request_id: String,
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(&self.request_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::{pending, Abortable};
+
+    #[tokio::test]
+    async fn test_cancel_aborts_and_guard_unregisters() {
+        let requests = RunningRequests::default();
+        let (registration, guard) = requests.start("a").unwrap();
+        assert!(matches!(requests.start("a"), Err(AiError::RequestInProgress(_))));
+
+        let task = tokio::spawn(Abortable::new(pending::<()>(), registration));
+        assert!(requests.cancel("a"));
+        assert!(task.await.unwrap().is_err());
+
+        drop(guard);
+        assert!(!requests.cancel("a"));
+        assert!(requests.start("a").is_ok());
+    }
+}
@@ -0,0 +1,247 @@
+//! Framing for streamed responses, shared by every provider.
+
+use super::provider::StreamFormat;
+
+/// One logical message of a stream: an SSE event or an NDJSON line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamFrame {
+    /// SSE `event:` field, if the server sent one.
+    pub event: Option<String>,
+    pub data: String,
+}
+
+impl StreamFrame {
+    /// The OpenAI-style end-of-stream sentinel.
+    pub fn is_done_marker(&self) -> bool {
+        self.data.trim() == "[DONE]"
+    }
+}
+
+/// Splits incoming chunks into frames. Bytes are buffered until a whole line
+/// has arrived, so frames and multi-byte characters split across network
+/// chunks come out intact.
+///
+/// SSE follows the HTML event-stream rules: `\n`, `\r\n` and lone `\r` end a
+/// line, lines starting with `:` are comments, `data:` lines accumulate until
+/// a blank line dispatches them, and `id:`/`retry:` are ignored.
+pub struct StreamDecoder {
+    format: StreamFormat,
+    buffer: Vec<u8>,
+    /// The previous chunk ended in `\r`; a `\n` starting the next one belongs to it.
+    after_cr: bool,
+    /// Still looking for a byte order mark at the start of the stream.
+    at_start: bool,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl StreamDecoder {
+    pub fn new(format: StreamFormat) -> Self {
+        Self {
+            format,
+            buffer: Vec::new(),
+            after_cr: false,
+            at_start: true,
+            event: None,
+            data: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, mut chunk: &[u8]) -> Vec<StreamFrame> {
+        if self.after_cr && !chunk.is_empty() {
+            self.after_cr = false;
+            if chunk[0] == b'\n' {
+                chunk = &chunk[1..];
+            }
+        }
+        self.buffer.extend_from_slice(chunk);
+        if self.at_start {
+            if self.buffer.len() < 3 && b"\xEF\xBB\xBF".starts_with(&self.buffer) {
+                return Vec::new();
+            }
+            if self.buffer.starts_with(b"\xEF\xBB\xBF") {
+                self.buffer.drain(..3);
+            }
+            self.at_start = false;
+        }
+
+        let mut frames = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+        while i < self.buffer.len() {
+            match self.buffer[i] {
+                b'\n' => {
+                    self.line(start, i, &mut frames);
+                    start = i + 1;
+                }
+                b'\r' => {
+                    self.line(start, i, &mut frames);
+                    if i + 1 == self.buffer.len() {
+                        self.after_cr = true;
+                    } else if self.buffer[i + 1] == b'\n' {
+                        i += 1;
+                    }
+                    start = i + 1;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        self.buffer.drain(..start);
+        frames
+    }
+
+    /// Flushes whatever is left once the body ends. An unterminated last
+    /// line or event is still delivered, since some servers omit the final
+    /// blank line.
+    pub fn finish(&mut self) -> Vec<StreamFrame> {
+        let mut frames = Vec::new();
+        if !self.buffer.is_empty() {
+            self.line(0, self.buffer.len(), &mut frames);
+            self.buffer.clear();
+        }
+        self.dispatch(&mut frames);
+        frames
+    }
+
+    fn line(&mut self, start: usize, end: usize, frames: &mut Vec<StreamFrame>) {
+        // Only complete lines are decoded, so a character is never cut in half
+        let line = String::from_utf8_lossy(&self.buffer[start..end]).into_owned();
+        match self.format {
+            StreamFormat::Ndjson => {
+                if !line.trim().is_empty() {
+                    frames.push(StreamFrame { event: None, data: line });
+                }
+            }
+            StreamFormat::Sse => {
+                if line.is_empty() {
+                    self.dispatch(frames);
+                    return;
+                }
+                if line.starts_with(':') {
+                    return;
+                }
+                let (field, value) = match line.split_once(':') {
+                    Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                    None => (line.as_str(), ""),
+                };
+                match field {
+                    "data" => self.data.push(value.to_string()),
+                    "event" => self.event = Some(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn dispatch(&mut self, frames: &mut Vec<StreamFrame>) {
+        let event = self.event.take().filter(|e| !e.is_empty());
+        let data = std::mem::take(&mut self.data).join("\n");
+        if data.is_empty() {
+            return;
+        }
+        frames.push(StreamFrame { event, data });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Recorded OpenAI chat completion stream.
+    const OPENAI_STREAM: &str = concat!(
+        "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\",\"content\":\"\"}}]}\n\n",
+        "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Привет\"}}]}\n\n",
+        "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\", мир 👋\"}}]}\n\n",
+        "data: {\"id\":\"c1\",\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+        "data: [DONE]\n\n",
+    );
+
+    /// Recorded Anthropic Messages stream, with CRLF line endings, a comment
+    /// and an `id:` field mixed in.
+    const ANTHROPIC_STREAM: &str = concat!(
+        ": keep-alive\r\n\r\n",
+        "event: message_start\r\n",
+        "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":10,\"output_tokens\":1}}}\r\n\r\n",
+        "event: ping\r\n",
+        "data: {\"type\": \"ping\"}\r\n\r\n",
+        "id: 7\r\n",
+        "event: content_block_delta\r\n",
+        "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"日本語\"}}\r\n\r\n",
+        "event: message_delta\r\n",
+        "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":4}}\r\n\r\n",
+        "event: message_stop\r\n",
+        "data: {\"type\":\"message_stop\"}\r\n\r\n",
+    );
+
+    /// Recorded Ollama NDJSON stream.
+    const OLLAMA_STREAM: &str = concat!(
+        "{\"model\":\"llama\",\"message\":{\"role\":\"assistant\",\"content\":\"Grüß\"},\"done\":false}\n",
+        "{\"model\":\"llama\",\"message\":{\"role\":\"assistant\",\"content\":\" dich\"},\"done\":false}\n",
+        "{\"model\":\"llama\",\"message\":{\"role\":\"assistant\",\"content\":\"\"},\"done\":true,\"done_reason\":\"stop\"}\n",
+    );
+
+    fn decode(format: StreamFormat, chunks: &[&[u8]]) -> Vec<StreamFrame> {
+        let mut decoder = StreamDecoder::new(format);
+        let mut frames: Vec<StreamFrame> = chunks.iter().flat_map(|c| decoder.push(c)).collect();
+        frames.extend(decoder.finish());
+        frames
+    }
+
+    /// Decodes `stream` split at every byte offset, and byte by byte, and
+    /// checks each run yields the same frames as the whole stream at once.
+    fn assert_split_invariant(format: StreamFormat, stream: &str) -> Vec<StreamFrame> {
+        let bytes = stream.as_bytes();
+        let whole = decode(format, &[bytes]);
+        for offset in 0..=bytes.len() {
+            let (head, tail) = bytes.split_at(offset);
+            assert_eq!(decode(format, &[head, tail]), whole, "split at byte {}", offset);
+        }
+        let single: Vec<&[u8]> = bytes.chunks(1).collect();
+        assert_eq!(decode(format, &single), whole, "byte by byte");
+        whole
+    }
+
+    #[test]
+    fn test_openai_stream_split_anywhere() {
+        let frames = assert_split_invariant(StreamFormat::Sse, OPENAI_STREAM);
+        assert_eq!(frames.len(), 5);
+        assert!(frames[1].data.contains("Привет"));
+        assert!(frames[2].data.contains("мир 👋"));
+        assert!(frames[4].is_done_marker());
+    }
+
+    #[test]
+    fn test_anthropic_stream_split_anywhere() {
+        let frames = assert_split_invariant(StreamFormat::Sse, ANTHROPIC_STREAM);
+        let events: Vec<&str> = frames.iter().filter_map(|f| f.event.as_deref()).collect();
+        assert_eq!(events, ["message_start", "ping", "content_block_delta", "message_delta", "message_stop"]);
+        assert!(frames[2].data.contains("日本語"));
+    }
+
+    #[test]
+    fn test_ndjson_stream_split_anywhere() {
+        let frames = assert_split_invariant(StreamFormat::Ndjson, OLLAMA_STREAM);
+        assert_eq!(frames.len(), 3);
+        assert!(frames[0].data.contains("Grüß"));
+    }
+
+    #[test]
+    fn test_sse_field_rules() {
+        let stream = concat!(
+            "\u{FEFF}data: first\r",
+            "data:second\r",
+            "data\r",
+            "\r",
+            "event: only-event\n\n",
+            "data:\n\n",
+            "event: last\n",
+            "data: unterminated",
+        );
+        let frames = assert_split_invariant(StreamFormat::Sse, stream);
+        assert_eq!(frames, vec![
+            StreamFrame { event: None, data: "first\nsecond\n".into() },
+            StreamFrame { event: Some("last".into()), data: "unterminated".into() },
+        ]);
+    }
+}
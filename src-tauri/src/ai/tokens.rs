@@ -0,0 +1,100 @@
+//! Local token estimates, for checking a request against the context window
+//! before it is sent. Tokenizers differ by provider, so these are
+//! deliberately rough and err on the high side.
+
+use serde::Serialize;
+
+use super::types::{ChatMessage, ChatRequest, ContentPart};
+
+/// Images cost about this much with most providers at typical sizes.
+const IMAGE_TOKENS: u32 = 1_000;
+/// Role markers and separators around each message.
+const MESSAGE_OVERHEAD: u32 = 4;
+
+/// Tokens in `text`. English and code run about four characters per
+/// token, other alphabetic scripts about two, and CJK about one.
+pub fn estimate_text(text: &str) -> u32 {
+    let quarters: u64 = text
+        .chars()
+        .map(|c| match c as u32 {
+            0..=0x7F => 1,
+            // Latin extensions, Greek, Cyrillic, Hebrew, Arabic, ...
+            0x80..=0x2FFF => 2,
+            _ => 4,
+        })
+        .sum();
+    quarters.div_ceil(4) as u32
+}
+
+pub fn estimate_message(message: &ChatMessage) -> u32 {
+    let content: u32 = message
+        .content
+        .parts()
+        .iter()
+        .map(|part| match part {
+            ContentPart::Text { text } => estimate_text(text),
+            ContentPart::Image { .. } => IMAGE_TOKENS,
+            ContentPart::ToolCall(call) => estimate_text(&call.name) + estimate_text(&call.arguments.to_string()),
+            ContentPart::ToolResult { content, .. } => estimate_text(content),
+        })
+        .sum();
+    content + MESSAGE_OVERHEAD
+}
+
+/// Tokens the tool definitions add to every request.
+pub fn estimate_tools(request: &ChatRequest) -> u32 {
+    request
+        .offered_tools()
+        .iter()
+        .map(|tool| serde_json::to_string(tool).map(|json| estimate_text(&json)).unwrap_or(0))
+        .sum()
+}
+
+/// Input tokens of the whole request.
+pub fn estimate_request(request: &ChatRequest) -> u32 {
+    request.messages.iter().map(estimate_message).sum::<u32>() + estimate_tools(request)
+}
+
+/// A request measured against its model, for display before sending.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenEstimate {
+    pub input_tokens: u32,
+    /// `None` for models missing from the catalog.
+    pub context_window: Option<u32>,
+    /// Room left for the reply; `None` when the window is unknown.
+    pub available_output_tokens: Option<u32>,
+    /// Cost of the input alone in USD, if the model is priced.
+    pub input_cost: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::types::{MessageContent, Role, ToolDefinition};
+
+    #[test]
+    fn test_estimates_scale_with_script() {
+        assert_eq!(estimate_text(""), 0);
+        assert_eq!(estimate_text("fn main() {}"), 3);
+        // Same number of characters, more tokens
+        assert!(estimate_text("привет мир") > estimate_text("hello wrld"));
+        assert_eq!(estimate_text("你好世界"), 4);
+
+        let request = ChatRequest {
+            model: "m".into(),
+            messages: vec![
+                ChatMessage::new(Role::User, "hello"),
+                ChatMessage {
+                    role: Role::User,
+                    content: MessageContent::Parts(vec![ContentPart::Image { media_type: "image/png".into(), data: "AAAA".into() }]),
+                },
+            ],
+            tools: vec![ToolDefinition { name: "t".into(), description: String::new(), parameters: serde_json::json!({}) }],
+            ..Default::default()
+        };
+        let without_tools = ChatRequest { tools: Vec::new(), ..request.clone() };
+        assert_eq!(estimate_request(&without_tools), 2 + IMAGE_TOKENS + 2 * MESSAGE_OVERHEAD);
+        assert!(estimate_request(&request) > estimate_request(&without_tools));
+    }
+}
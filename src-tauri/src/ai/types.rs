@@ -0,0 +1,293 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::context::ContextFit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+    /// Results of the tool calls in the preceding assistant message.
+    Tool,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::Tool => "tool",
+        }
+    }
+}
+
+/// One piece of a multi-part message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text { text: String },
+    /// Base64 encoded image data.
+    Image { media_type: String, data: String },
+    /// A call the assistant made; only in assistant messages.
+    ToolCall(ToolCall),
+    /// The outcome of a call; only in tool messages. `name` repeats the
+    /// called tool because Gemini matches results by name.
+    ToolResult {
+        tool_call_id: String,
+        name: String,
+        content: String,
+        #[serde(default)]
+        is_error: bool,
+    },
+}
+
+/// Plain text, or a list of parts when the message carries images.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+impl MessageContent {
+    /// All text parts joined together.
+    pub fn text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    pub fn parts(&self) -> Vec<ContentPart> {
+        match self {
+            MessageContent::Text(text) => vec![ContentPart::Text { text: text.clone() }],
+            MessageContent::Parts(parts) => parts.clone(),
+        }
+    }
+
+    pub fn has_images(&self) -> bool {
+        matches!(self, MessageContent::Parts(parts) if parts.iter().any(|p| matches!(p, ContentPart::Image { .. })))
+    }
+
+    /// Whether the content is only text, so it can be sent as a plain string.
+    pub fn is_text_only(&self) -> bool {
+        match self {
+            MessageContent::Text(_) => true,
+            MessageContent::Parts(parts) => parts.iter().all(|p| matches!(p, ContentPart::Text { .. })),
+        }
+    }
+
+    pub fn tool_calls(&self) -> Vec<ToolCall> {
+        self.parts()
+            .into_iter()
+            .filter_map(|part| match part {
+                ContentPart::ToolCall(call) => Some(call),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: MessageContent,
+}
+
+impl ChatMessage {
+    pub fn new(role: Role, text: impl Into<String>) -> Self {
+        Self { role, content: MessageContent::Text(text.into()) }
+    }
+}
+
+/// A tool the model may call. `parameters` is a JSON Schema object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub parameters: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides.
+    Auto,
+    /// Tools are not offered.
+    None,
+    /// The model must call some tool.
+    Required,
+    /// The model must call this tool.
+    Tool { name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// Parsed JSON arguments.
+    pub arguments: Value,
+}
+
+/// Provider-neutral chat request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    #[serde(default)]
+    pub tools: Vec<ToolDefinition>,
+    #[serde(default)]
+    pub tool_choice: Option<ToolChoice>,
+}
+
+impl ChatRequest {
+    /// System messages joined, for providers that take the system prompt separately.
+    pub fn system_prompt(&self) -> Option<String> {
+        let system: Vec<String> = self.messages
+            .iter()
+            .filter(|m| m.role == Role::System)
+            .map(|m| m.content.text())
+            .collect();
+        (!system.is_empty()).then(|| system.join("\n\n"))
+    }
+
+    /// Tools to send; none when the caller opted out with `ToolChoice::None`
+    /// and the provider has no way to say so.
+    pub fn offered_tools(&self) -> &[ToolDefinition] {
+        if self.tool_choice == Some(ToolChoice::None) {
+            &[]
+        } else {
+            &self.tools
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+impl Usage {
+    /// Providers report usage piecemeal while streaming; later non-zero counts win.
+    pub fn merge(&mut self, other: Usage) {
+        if other.input_tokens > 0 {
+            self.input_tokens = other.input_tokens;
+        }
+        if other.output_tokens > 0 {
+            self.output_tokens = other.output_tokens;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatResponse {
+    pub content: String,
+    pub model: String,
+    pub finish_reason: Option<String>,
+    pub usage: Option<Usage>,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// What a provider extracted from one stream frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    Delta(String),
+    Usage(Usage),
+    Done { finish_reason: Option<String> },
+    /// A tool call begins. `index` identifies it within the response; a
+    /// start for an index already seen begins another call. `id` may be
+    /// empty for providers that do not assign ids.
+    ToolCallStart { index: usize, id: String, name: String },
+    /// A fragment of the JSON arguments of the call at `index`.
+    ToolCallDelta { index: usize, arguments: String },
+    /// A call with its arguments fully received and parsed; produced by the
+    /// client once the stream ends, never by providers.
+    ToolCall(ToolCall),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub id: String,
+    pub name: String,
+    pub requires_api_key: bool,
+}
+
+/// Payload of the `ai-stream` event, scoped to one request.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiStreamEvent {
+    pub request_id: String,
+    pub provider: String,
+    #[serde(flatten)]
+    pub payload: AiStreamPayload,
+}
+
+/// Every stream emits any number of `delta`/`usage`/tool call events
+/// followed by exactly one `done` or `error`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum AiStreamPayload {
+    Delta { content: String },
+    /// Usage so far, already merged across events.
+    Usage { usage: Usage },
+    /// `finish_reason` is `"cancelled"` when stopped with `ai_cancel`.
+    Done { finish_reason: Option<String>, usage: Option<Usage> },
+    Error { message: String },
+    ToolCallStart { index: usize, id: String, name: String },
+    /// A fragment of the call's JSON arguments.
+    ToolCallDelta { index: usize, arguments: String },
+    /// Sent for each call, complete, just before `done`.
+    ToolCall { tool_call: ToolCall },
+    /// Sent before the first delta when older messages were dropped or
+    /// summarized to fit the context window.
+    ContextFitted { fit: ContextFit },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_event_shape() {
+        let event = AiStreamEvent {
+            request_id: "r1".into(),
+            provider: "openai".into(),
+            payload: AiStreamPayload::Done {
+                finish_reason: Some("stop".into()),
+                usage: Some(Usage { input_tokens: 3, output_tokens: 2 }),
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "requestId": "r1",
+                "provider": "openai",
+                "type": "done",
+                "finishReason": "stop",
+                "usage": { "inputTokens": 3, "outputTokens": 2 },
+            })
+        );
+    }
+}
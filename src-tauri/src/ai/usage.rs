@@ -0,0 +1,182 @@
+//! Token usage of every request, kept for cost totals.
+
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRecord {
+    /// Unix millis.
+    pub timestamp: i64,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// USD; `None` for models without a known price.
+    pub cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageTotal {
+    pub requests: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Sum over the priced requests only.
+    pub cost: f64,
+    /// Requests whose cost is unknown and missing from `cost`.
+    pub unpriced_requests: u32,
+}
+
+impl UsageTotal {
+    fn add(&mut self, record: &UsageRecord) {
+        self.requests += 1;
+        self.input_tokens += record.input_tokens as u64;
+        self.output_tokens += record.output_tokens as u64;
+        match record.cost {
+            Some(cost) => self.cost += cost,
+            None => self.unpriced_requests += 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// Local date, `YYYY-MM-DD`.
+    pub date: String,
+    pub provider: String,
+    pub total: UsageTotal,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageSummary {
+    /// Oldest day first, providers by id within a day.
+    pub days: Vec<DailyUsage>,
+    pub providers: BTreeMap<String, UsageTotal>,
+    pub total: UsageTotal,
+}
+
+/// Append-only JSON lines file in the app config directory.
+pub struct UsageLedger {
+    path: PathBuf,
+}
+
+impl UsageLedger {
+    pub fn new() -> Self {
+        let config_dir = dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("colbex");
+        Self::with_path(config_dir.join("usage.jsonl"))
+    }
+
+    pub fn with_path(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn record(&self, record: &UsageRecord) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create usage directory: {}", e))?;
+        }
+        let line = serde_json::to_string(record).map_err(|e| format!("Failed to serialize usage: {}", e))?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open usage log: {}", e))?;
+        writeln!(file, "{}", line).map_err(|e| format!("Failed to write usage log: {}", e))
+    }
+
+    /// Every record, oldest first. Lines that fail to parse, such as one cut
+    /// short by a crash, are skipped.
+    pub fn records(&self) -> Result<Vec<UsageRecord>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let content = fs::read_to_string(&self.path).map_err(|e| format!("Failed to read usage log: {}", e))?;
+        Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+    }
+
+    /// Totals of the records from `from` up to, not including, `to` (Unix millis).
+    pub fn summary(&self, from: Option<i64>, to: Option<i64>) -> Result<UsageSummary, String> {
+        let mut days: BTreeMap<(String, String), UsageTotal> = BTreeMap::new();
+        let mut summary = UsageSummary::default();
+        let in_range = |t: i64| from.is_none_or(|from| t >= from) && to.is_none_or(|to| t < to);
+
+        for record in self.records()?.iter().filter(|r| in_range(r.timestamp)) {
+            let date = Local
+                .timestamp_millis_opt(record.timestamp)
+                .single()
+                .map(|time| time.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            days.entry((date, record.provider.clone())).or_default().add(record);
+            summary.providers.entry(record.provider.clone()).or_default().add(record);
+            summary.total.add(record);
+        }
+        summary.days = days
+            .into_iter()
+            .map(|((date, provider), total)| DailyUsage { date, provider, total })
+            .collect();
+        Ok(summary)
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to clear usage log: {}", e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: i64, provider: &str, cost: Option<f64>) -> UsageRecord {
+        UsageRecord {
+            timestamp,
+            provider: provider.into(),
+            model: "m".into(),
+            input_tokens: 100,
+            output_tokens: 10,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_summary_by_day_and_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = UsageLedger::with_path(dir.path().join("usage.jsonl"));
+        assert_eq!(ledger.summary(None, None).unwrap(), UsageSummary::default());
+
+        let day = 24 * 60 * 60 * 1000;
+        let noon = Local.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap().timestamp_millis();
+        ledger.record(&record(noon, "openai", Some(0.5))).unwrap();
+        ledger.record(&record(noon + 1000, "openai", Some(0.25))).unwrap();
+        ledger.record(&record(noon + 2000, "yandex", None)).unwrap();
+        ledger.record(&record(noon + day, "openai", Some(1.0))).unwrap();
+
+        let summary = ledger.summary(None, None).unwrap();
+        assert_eq!(summary.total.requests, 4);
+        assert_eq!(summary.total.input_tokens, 400);
+        assert_eq!(summary.total.unpriced_requests, 1);
+        assert!((summary.total.cost - 1.75).abs() < 1e-9);
+        assert_eq!(summary.providers["openai"].requests, 3);
+
+        let days: Vec<_> = summary.days.iter().map(|d| (d.date.as_str(), d.provider.as_str(), d.total.requests)).collect();
+        assert_eq!(days, vec![("2025-03-01", "openai", 2), ("2025-03-01", "yandex", 1), ("2025-03-02", "openai", 1)]);
+
+        let second_day = ledger.summary(Some(noon + day), None).unwrap();
+        assert_eq!(second_day.total.requests, 1);
+        assert_eq!(ledger.summary(None, Some(noon + 1000)).unwrap().total.requests, 1);
+
+        ledger.clear().unwrap();
+        assert!(ledger.records().unwrap().is_empty());
+        ledger.clear().unwrap();
+    }
+}
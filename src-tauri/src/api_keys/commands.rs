@@ -0,0 +1,128 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::ai::AiState;
+
+use super::store::{open_vault, ApiKeyStorageStatus, ApiKeyStore};
+use super::vault::VaultKey;
+
+/// Key status of one provider, as shown in settings.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyInfo {
+    pub provider: String,
+    pub name: String,
+    /// A global key is stored.
+    pub global: bool,
+    /// The workspace has its own key overriding the global one.
+    pub workspace: bool,
+}
+
+/// The key for `provider` in `workspace`, falling back to the global key.
+pub fn get_api_key(
+    state: &State<'_, Mutex<ApiKeyStore>>,
+    provider: &str,
+    workspace: Option<&str>,
+) -> Result<String, String> {
+    let store = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    store.get_key(provider, workspace).ok_or_else(|| format!("{} API key not configured", provider))
+}
+
+/// Store a key globally, or as an override for `workspace`. An empty key removes it.
+#[tauri::command]
+pub fn set_api_key(
+    state: State<'_, Mutex<ApiKeyStore>>,
+    provider: String,
+    key: String,
+    workspace: Option<String>,
+) -> Result<(), String> {
+    let mut store = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    store.set_key(&provider, &key, workspace.as_deref())
+}
+
+/// Whether each registered provider that needs a key has one for `workspace`
+#[tauri::command]
+pub fn get_api_keys(
+    state: State<'_, Mutex<ApiKeyStore>>,
+    ai: State<'_, AiState>,
+    workspace: Option<String>,
+) -> Result<HashMap<String, bool>, String> {
+    let store = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    Ok(ai
+        .providers()
+        .into_iter()
+        .filter(|info| info.requires_api_key)
+        .map(|info| {
+            let configured = store.has_key(&info.id, workspace.as_deref());
+            (info.id, configured)
+        })
+        .collect())
+}
+
+/// Global and workspace key status of each registered provider that needs a key
+#[tauri::command]
+pub fn api_keys_list(
+    state: State<'_, Mutex<ApiKeyStore>>,
+    ai: State<'_, AiState>,
+    workspace: Option<String>,
+) -> Result<Vec<ApiKeyInfo>, String> {
+    let store = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    Ok(ai
+        .providers()
+        .into_iter()
+        .filter(|info| info.requires_api_key)
+        .map(|info| ApiKeyInfo {
+            global: store.has_key(&info.id, None),
+            workspace: workspace.as_deref().is_some_and(|w| store.has_override(&info.id, w)),
+            provider: info.id,
+            name: info.name,
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn api_keys_status(state: State<'_, Mutex<ApiKeyStore>>) -> Result<ApiKeyStorageStatus, String> {
+    let store = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    Ok(store.status())
+}
+
+/// Unlock the encrypted key file, creating it with this passphrase on first
+/// use, or retry a keyring that failed to load. The key derivation runs on a
+/// blocking thread without holding the store lock.
+#[tauri::command]
+pub async fn api_keys_unlock(state: State<'_, Mutex<ApiKeyStore>>, passphrase: String) -> Result<ApiKeyStorageStatus, String> {
+    let vault_path = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?.vault_path();
+    let unlocked = match vault_path {
+        Some(path) => Some(
+            tokio::task::spawn_blocking(move || open_vault(&path, &passphrase))
+                .await
+                .map_err(|e| format!("Unlock task failed: {}", e))??,
+        ),
+        None => None,
+    };
+
+    let mut store = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    match unlocked {
+        Some((key, keys)) => store.set_unlocked(key, keys),
+        None => store.retry_load()?,
+    }
+    Ok(store.status())
+}
+
+#[tauri::command]
+pub fn api_keys_lock(state: State<'_, Mutex<ApiKeyStore>>) -> Result<ApiKeyStorageStatus, String> {
+    let mut store = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    store.lock();
+    Ok(store.status())
+}
+
+#[tauri::command]
+pub async fn api_keys_change_passphrase(state: State<'_, Mutex<ApiKeyStore>>, passphrase: String) -> Result<(), String> {
+    let key = tokio::task::spawn_blocking(move || VaultKey::create(&passphrase))
+        .await
+        .map_err(|e| format!("Passphrase task failed: {}", e))??;
+    let mut store = state.lock().map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    store.set_vault_key(key)
+}
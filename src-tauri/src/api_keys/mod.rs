@@ -0,0 +1,8 @@
+mod commands;
+mod os_keyring;
+mod store;
+mod vault;
+
+// Re-export public API
+pub use commands::*;
+pub use store::{ApiKeyStorageStatus, ApiKeyStore};
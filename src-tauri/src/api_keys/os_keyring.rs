@@ -0,0 +1,111 @@
+//! Keys stored in the OS keyring: Keychain, Windows Credential Manager or the
+//! Secret Service. One entry per key, plus a plain index of which entries
+//! exist, since keyrings cannot be listed.
+
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::store::KeyMap;
+
+const SERVICE: &str = "colbex";
+
+/// Provider ids with a stored key, per scope. Holds no secrets.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KeyringIndex {
+    #[serde(default)]
+    global: Vec<String>,
+    #[serde(default)]
+    workspaces: BTreeMap<String, Vec<String>>,
+}
+
+pub struct KeyringBackend {
+    index_path: PathBuf,
+}
+
+impl KeyringBackend {
+    pub fn new(index_path: PathBuf) -> Self {
+        Self { index_path }
+    }
+
+    /// Whether the platform has a keyring we can reach, e.g. not a Linux
+    /// session without a Secret Service.
+    pub fn available() -> bool {
+        match Entry::new(SERVICE, "availability-probe").and_then(|entry| entry.get_password()) {
+            Ok(_) | Err(keyring::Error::NoEntry) => true,
+            Err(_) => false,
+        }
+    }
+
+    fn entry(provider: &str, workspace: Option<&str>) -> Result<Entry, String> {
+        let user = match workspace {
+            Some(workspace) => format!("{}@{}", provider, workspace),
+            None => provider.to_string(),
+        };
+        Entry::new(SERVICE, &user).map_err(|e| e.to_string())
+    }
+
+    fn read_index(&self) -> KeyringIndex {
+        fs::read_to_string(&self.index_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Every key in the index. Entries removed from the keyring behind our
+    /// back are skipped.
+    pub fn load(&self) -> Result<KeyMap, String> {
+        let index = self.read_index();
+        let mut keys = KeyMap::default();
+        let scopes = index
+            .global
+            .iter()
+            .map(|provider| (None, provider))
+            .chain(index.workspaces.iter().flat_map(|(workspace, providers)| {
+                providers.iter().map(move |provider| (Some(workspace.as_str()), provider))
+            }));
+        for (workspace, provider) in scopes {
+            match Self::entry(provider, workspace)?.get_password() {
+                Ok(key) => keys.insert(provider, &key, workspace),
+                Err(keyring::Error::NoEntry) => {}
+                Err(e) => return Err(format!("Failed to read {} API key from keyring: {}", provider, e)),
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Writes the entries that changed from `old` to `new` and deletes the
+    /// ones that are gone.
+    pub fn save(&self, old: &KeyMap, new: &KeyMap) -> Result<(), String> {
+        for (workspace, provider, key) in new.entries() {
+            if old.get(provider, workspace) != Some(key) {
+                Self::entry(provider, workspace)?
+                    .set_password(key)
+                    .map_err(|e| format!("Failed to store {} API key in keyring: {}", provider, e))?;
+            }
+        }
+        for (workspace, provider, _) in old.entries() {
+            if new.get(provider, workspace).is_none() {
+                match Self::entry(provider, workspace)?.delete_credential() {
+                    Ok(()) | Err(keyring::Error::NoEntry) => {}
+                    Err(e) => return Err(format!("Failed to remove {} API key from keyring: {}", provider, e)),
+                }
+            }
+        }
+
+        let mut index = KeyringIndex::default();
+        for (workspace, provider, _) in new.entries() {
+            match workspace {
+                Some(workspace) => index.workspaces.entry(workspace.to_string()).or_default().push(provider.to_string()),
+                None => index.global.push(provider.to_string()),
+            }
+        }
+        if let Some(parent) = self.index_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&index).map_err(|e| e.to_string())?;
+        fs::write(&self.index_path, content).map_err(|e| format!("Failed to write API key index: {}", e))
+    }
+}
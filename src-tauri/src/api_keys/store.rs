@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::os_keyring::KeyringBackend;
+use super::vault::{VaultFile, VaultKey};
+
+/// Global keys by provider id, and per-workspace overrides by workspace path.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KeyMap {
+    #[serde(default)]
+    global: BTreeMap<String, String>,
+    #[serde(default)]
+    workspaces: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl KeyMap {
+    pub fn get(&self, provider: &str, workspace: Option<&str>) -> Option<&str> {
+        match workspace {
+            Some(workspace) => self.workspaces.get(workspace)?.get(provider),
+            None => self.global.get(provider),
+        }
+        .map(String::as_str)
+    }
+
+    pub fn insert(&mut self, provider: &str, key: &str, workspace: Option<&str>) {
+        let scope = match workspace {
+            Some(workspace) => self.workspaces.entry(workspace.to_string()).or_default(),
+            None => &mut self.global,
+        };
+        scope.insert(provider.to_string(), key.to_string());
+    }
+
+    pub fn remove(&mut self, provider: &str, workspace: Option<&str>) {
+        match workspace {
+            Some(workspace) => {
+                if let Some(scope) = self.workspaces.get_mut(workspace) {
+                    scope.remove(provider);
+                    if scope.is_empty() {
+                        self.workspaces.remove(workspace);
+                    }
+                }
+            }
+            None => {
+                self.global.remove(provider);
+            }
+        }
+    }
+
+    /// `(workspace, provider, key)` for every key, global ones first.
+    pub fn entries(&self) -> impl Iterator<Item = (Option<&str>, &str, &str)> {
+        let global = self.global.iter().map(|(provider, key)| (None, provider.as_str(), key.as_str()));
+        let workspaces = self.workspaces.iter().flat_map(|(workspace, keys)| {
+            keys.iter().map(move |(provider, key)| (Some(workspace.as_str()), provider.as_str(), key.as_str()))
+        });
+        global.chain(workspaces)
+    }
+}
+
+/// Where keys are persisted.
+enum Backend {
+    /// Nothing is written; keys last until the app quits.
+    Memory,
+    /// `load_error` is set while the stored keys could not be read; saving
+    /// then would rewrite the index without them.
+    Keyring { keyring: KeyringBackend, load_error: Option<String> },
+    /// Encrypted file; `key` is set while unlocked.
+    Vault { path: PathBuf, key: Option<VaultKey> },
+}
+
+/// How keys are stored, for the settings UI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyStorageStatus {
+    /// "keyring", "file" or "memory"
+    pub backend: String,
+    /// Keys are unavailable until `api_keys_unlock` is called: with the master
+    /// passphrase for the file, or to retry a keyring that failed to load.
+    pub locked: bool,
+    /// A passphrase has been set; when false, unlocking creates the vault.
+    pub initialized: bool,
+    /// Why the stored keys could not be loaded; nothing is saved until they are.
+    pub error: Option<String>,
+}
+
+pub struct ApiKeyStore {
+    keys: KeyMap,
+    backend: Backend,
+}
+
+impl ApiKeyStore {
+    /// A store that keeps keys in memory only.
+    pub fn new() -> Self {
+        Self { keys: KeyMap::default(), backend: Backend::Memory }
+    }
+
+    /// Keys persisted in the OS keyring, or when there is none, in an
+    /// encrypted file in the config directory that starts out locked.
+    pub fn load() -> Self {
+        let config_dir = dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("colbex");
+        if KeyringBackend::available() {
+            Self::with_keyring(KeyringBackend::new(config_dir.join("api_keys.json")))
+        } else {
+            Self::with_vault(config_dir.join("api_keys.vault"))
+        }
+    }
+
+    fn with_keyring(keyring: KeyringBackend) -> Self {
+        let loaded = keyring.load();
+        Self::from_keyring(keyring, loaded)
+    }
+
+    fn from_keyring(keyring: KeyringBackend, loaded: Result<KeyMap, String>) -> Self {
+        let (keys, load_error) = match loaded {
+            Ok(keys) => (keys, None),
+            Err(e) => {
+                eprintln!("Failed to load API keys: {}", e);
+                (KeyMap::default(), Some(e))
+            }
+        };
+        Self { keys, backend: Backend::Keyring { keyring, load_error } }
+    }
+
+    pub fn with_vault(path: PathBuf) -> Self {
+        Self { keys: KeyMap::default(), backend: Backend::Vault { path, key: None } }
+    }
+
+    pub fn status(&self) -> ApiKeyStorageStatus {
+        let (backend, locked, initialized, error) = match &self.backend {
+            Backend::Memory => ("memory", false, true, None),
+            Backend::Keyring { load_error, .. } => ("keyring", load_error.is_some(), true, load_error.clone()),
+            Backend::Vault { path, key } => ("file", key.is_none(), path.exists(), None),
+        };
+        ApiKeyStorageStatus { backend: backend.to_string(), locked, initialized, error }
+    }
+
+    /// The key to use in `workspace`: its override if it has one, else the global key.
+    pub fn get_key(&self, provider: &str, workspace: Option<&str>) -> Option<String> {
+        workspace
+            .and_then(|workspace| self.keys.get(provider, Some(workspace)))
+            .or_else(|| self.keys.get(provider, None))
+            .map(str::to_string)
+    }
+
+    pub fn has_key(&self, provider: &str, workspace: Option<&str>) -> bool {
+        self.get_key(provider, workspace).is_some()
+    }
+
+    /// Whether `workspace` overrides the global key, as opposed to inheriting it.
+    pub fn has_override(&self, provider: &str, workspace: &str) -> bool {
+        self.keys.get(provider, Some(workspace)).is_some()
+    }
+
+    /// `(provider, key)` for every global key.
+    pub fn global_keys(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.keys.entries().filter_map(|(workspace, provider, key)| workspace.is_none().then_some((provider, key)))
+    }
+
+    /// Sets the global key, or the override for `workspace`. An empty key removes it.
+    pub fn set_key(&mut self, provider: &str, key: &str, workspace: Option<&str>) -> Result<(), String> {
+        let mut keys = self.keys.clone();
+        match key.trim() {
+            "" => keys.remove(provider, workspace),
+            key => keys.insert(provider, key, workspace),
+        }
+        self.persist(keys)
+    }
+
+    pub fn remove_key(&mut self, provider: &str, workspace: Option<&str>) -> Result<(), String> {
+        self.set_key(provider, "", workspace)
+    }
+
+    /// Saves `keys` and only then makes them current, so a failed write
+    /// leaves memory matching what is on disk.
+    fn persist(&mut self, keys: KeyMap) -> Result<(), String> {
+        match &self.backend {
+            Backend::Memory => {}
+            Backend::Keyring { load_error: Some(e), .. } => {
+                return Err(format!("API keys could not be loaded, so none are saved until they are: {}", e))
+            }
+            Backend::Keyring { keyring, load_error: None } => keyring.save(&self.keys, &keys)?,
+            Backend::Vault { key: None, .. } => return Err("API key storage is locked".to_string()),
+            Backend::Vault { path, key: Some(key) } => write_vault(path, key, &keys)?,
+        }
+        self.keys = keys;
+        Ok(())
+    }
+
+    /// Opens the vault with the master passphrase, creating it on first use,
+    /// or retries loading a keyring that failed to load.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<(), String> {
+        match self.vault_path() {
+            Some(path) => {
+                let (key, keys) = open_vault(&path, passphrase)?;
+                self.set_unlocked(key, keys);
+                Ok(())
+            }
+            None => self.retry_load(),
+        }
+    }
+
+    /// The encrypted file, when keys are kept in one.
+    pub fn vault_path(&self) -> Option<PathBuf> {
+        match &self.backend {
+            Backend::Vault { path, .. } => Some(path.clone()),
+            _ => None,
+        }
+    }
+
+    /// Installs what `open_vault` returned.
+    pub fn set_unlocked(&mut self, unlocked: VaultKey, keys: KeyMap) {
+        if let Backend::Vault { key, .. } = &mut self.backend {
+            *key = Some(unlocked);
+            self.keys = keys;
+        }
+    }
+
+    /// Reads the keyring again after a failed load.
+    pub fn retry_load(&mut self) -> Result<(), String> {
+        let Backend::Keyring { keyring, load_error } = &mut self.backend else {
+            return Ok(());
+        };
+        if load_error.is_none() {
+            return Ok(());
+        }
+        let keys = keyring.load().inspect_err(|e| *load_error = Some(e.clone()))?;
+        *load_error = None;
+        self.keys = keys;
+        Ok(())
+    }
+
+    /// Forgets the keys and the derived key until the next unlock.
+    pub fn lock(&mut self) {
+        if let Backend::Vault { key, .. } = &mut self.backend {
+            *key = None;
+            self.keys = KeyMap::default();
+        }
+    }
+
+    /// Re-encrypts the vault under a new passphrase. Must be unlocked.
+    pub fn change_passphrase(&mut self, passphrase: &str) -> Result<(), String> {
+        self.set_vault_key(VaultKey::create(passphrase)?)
+    }
+
+    /// Re-encrypts the vault with `new_key`, derived beforehand with
+    /// `VaultKey::create`. Must be unlocked.
+    pub fn set_vault_key(&mut self, new_key: VaultKey) -> Result<(), String> {
+        let Backend::Vault { path, key } = &mut self.backend else {
+            return Err("API keys are not protected by a passphrase".to_string());
+        };
+        if key.is_none() {
+            return Err("API key storage is locked".to_string());
+        }
+        write_vault(path, &new_key, &self.keys)?;
+        *key = Some(new_key);
+        Ok(())
+    }
+}
+
+impl Default for ApiKeyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Derives the vault key from `passphrase` and decrypts the keys, creating an
+/// empty vault if there is none yet. Slow by design, so it takes no lock.
+pub fn open_vault(path: &Path, passphrase: &str) -> Result<(VaultKey, KeyMap), String> {
+    let Some(file) = read_vault(path)? else {
+        let key = VaultKey::create(passphrase)?;
+        write_vault(path, &key, &KeyMap::default())?;
+        return Ok((key, KeyMap::default()));
+    };
+    let (key, plaintext) = VaultKey::unlock(passphrase, &file)?;
+    let keys = serde_json::from_slice(&plaintext).map_err(|e| format!("Corrupt key vault: {}", e))?;
+    Ok((key, keys))
+}
+
+fn read_vault(path: &Path) -> Result<Option<VaultFile>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read key vault: {}", e))?;
+    serde_json::from_str(&content).map(Some).map_err(|e| format!("Corrupt key vault: {}", e))
+}
+
+fn write_vault(path: &Path, key: &VaultKey, keys: &KeyMap) -> Result<(), String> {
+    let plaintext = serde_json::to_vec(keys).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(&key.seal(&plaintext)?).map_err(|e| e.to_string())?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    // Write then rename, so a crash never leaves a half-written vault
+    let tmp = path.with_extension("vault.tmp");
+    fs::write(&tmp, content).map_err(|e| format!("Failed to write key vault: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("Failed to write key vault: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspace_overrides_fall_back_to_global() {
+        let mut store = ApiKeyStore::new();
+        store.set_key("openai", " sk-global ", None).unwrap();
+        store.set_key("openai", "sk-work", Some("/work")).unwrap();
+
+        assert_eq!(store.get_key("openai", None).as_deref(), Some("sk-global"));
+        assert_eq!(store.get_key("openai", Some("/work")).as_deref(), Some("sk-work"));
+        assert_eq!(store.get_key("openai", Some("/other")).as_deref(), Some("sk-global"));
+        assert!(store.has_override("openai", "/work"));
+        assert!(!store.has_override("openai", "/other"));
+
+        store.remove_key("openai", Some("/work")).unwrap();
+        assert_eq!(store.get_key("openai", Some("/work")).as_deref(), Some("sk-global"));
+        assert_eq!(store.keys.workspaces.len(), 0);
+    }
+
+    #[test]
+    fn test_vault_persists_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api_keys.vault");
+
+        let mut store = ApiKeyStore::with_vault(path.clone());
+        assert_eq!(
+            store.status(),
+            ApiKeyStorageStatus { backend: "file".into(), locked: true, initialized: false, error: None }
+        );
+        assert!(store.set_key("anthropic", "sk-ant-1", None).is_err());
+
+        store.unlock("hunter2").unwrap();
+        store.set_key("anthropic", "sk-ant-1", None).unwrap();
+        store.set_key("anthropic", "sk-ant-2", Some("/work")).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("sk-ant"));
+
+        // A fresh start sees nothing until unlocked with the right passphrase
+        let mut reopened = ApiKeyStore::with_vault(path.clone());
+        assert!(reopened.status().initialized);
+        assert_eq!(reopened.get_key("anthropic", None), None);
+        assert_eq!(reopened.unlock("wrong").unwrap_err(), "Wrong passphrase");
+        reopened.unlock("hunter2").unwrap();
+        assert_eq!(reopened.get_key("anthropic", Some("/work")).as_deref(), Some("sk-ant-2"));
+
+        reopened.change_passphrase("correct horse").unwrap();
+        reopened.lock();
+        assert_eq!(reopened.get_key("anthropic", None), None);
+        assert!(reopened.unlock("hunter2").is_err());
+        reopened.unlock("correct horse").unwrap();
+        assert_eq!(reopened.get_key("anthropic", None).as_deref(), Some("sk-ant-1"));
+    }
+
+    #[test]
+    fn test_failed_keyring_load_is_never_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = dir.path().join("api_keys.json");
+        fs::write(&index, r#"{"global":["openai","anthropic"]}"#).unwrap();
+
+        let mut store = ApiKeyStore::from_keyring(KeyringBackend::new(index.clone()), Err("keyring is locked".into()));
+        let status = store.status();
+        assert!(status.locked);
+        assert_eq!(status.error.as_deref(), Some("keyring is locked"));
+
+        // Saving now would rewrite the index with only the new key
+        assert!(store.set_key("openai", "sk-new", None).unwrap_err().contains("keyring is locked"));
+        assert_eq!(store.get_key("openai", None), None);
+        assert_eq!(fs::read_to_string(&index).unwrap(), r#"{"global":["openai","anthropic"]}"#);
+    }
+}
@@ -0,0 +1,132 @@
+//! Passphrase-encrypted file, used for keys when no OS keyring is available.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Argon2id cost, stored with each file so it can be raised later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KdfParams {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // The real cost would make every test that touches a vault take seconds
+        if cfg!(test) {
+            Self { memory_kib: 1024, iterations: 1, parallelism: 1 }
+        } else {
+            Self { memory_kib: 64 * 1024, iterations: 3, parallelism: 1 }
+        }
+    }
+}
+
+/// On-disk form: everything but the ciphertext is public.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Key derived from the master passphrase, kept in memory while unlocked
+/// so changes can be saved without asking again.
+pub struct VaultKey {
+    key: [u8; 32],
+    salt: [u8; SALT_LEN],
+    kdf: KdfParams,
+}
+
+impl VaultKey {
+    /// Key for a new vault, with a fresh salt.
+    pub fn create(passphrase: &str) -> Result<Self, String> {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        Self::derive(passphrase, salt, KdfParams::default())
+    }
+
+    /// Key for an existing vault. Fails if the passphrase is wrong.
+    pub fn unlock(passphrase: &str, file: &VaultFile) -> Result<(Self, Vec<u8>), String> {
+        let salt: [u8; SALT_LEN] = decode(&file.salt)?
+            .try_into()
+            .map_err(|_| "Corrupt key vault: bad salt".to_string())?;
+        let key = Self::derive(passphrase, salt, file.kdf)?;
+        let plaintext = key.open(file)?;
+        Ok((key, plaintext))
+    }
+
+    fn derive(passphrase: &str, salt: [u8; SALT_LEN], kdf: KdfParams) -> Result<Self, String> {
+        if passphrase.is_empty() {
+            return Err("Passphrase cannot be empty".to_string());
+        }
+        let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32)).map_err(|e| e.to_string())?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| e.to_string())?;
+        Ok(Self { key, salt, kdf })
+    }
+
+    /// Encrypts `plaintext` under a new random nonce.
+    pub fn seal(&self, plaintext: &[u8]) -> Result<VaultFile, String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = Aes256Gcm::new(&self.key.into())
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| "Failed to encrypt key vault".to_string())?;
+        Ok(VaultFile {
+            version: 1,
+            kdf: self.kdf,
+            salt: STANDARD.encode(self.salt),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    fn open(&self, file: &VaultFile) -> Result<Vec<u8>, String> {
+        let nonce = decode(&file.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err("Corrupt key vault: bad nonce".to_string());
+        }
+        // GCM authenticates the data, so a wrong key and a tampered file fail alike
+        Aes256Gcm::new(&self.key.into())
+            .decrypt(Nonce::from_slice(&nonce), decode(&file.ciphertext)?.as_slice())
+            .map_err(|_| "Wrong passphrase".to_string())
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, String> {
+    STANDARD.decode(value).map_err(|e| format!("Corrupt key vault: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_unlock() {
+        let key = VaultKey::create("correct horse").unwrap();
+        let file = key.seal(b"{\"openai\":\"sk-1\"}").unwrap();
+        assert!(!file.ciphertext.contains("sk-1"));
+
+        let (_, plaintext) = VaultKey::unlock("correct horse", &file).unwrap();
+        assert_eq!(plaintext, b"{\"openai\":\"sk-1\"}");
+        assert_eq!(VaultKey::unlock("battery staple", &file).err().unwrap(), "Wrong passphrase");
+        assert!(VaultKey::create("").is_err());
+
+        // Every save gets its own nonce
+        assert_ne!(key.seal(b"x").unwrap().nonce, key.seal(b"x").unwrap().nonce);
+    }
+}
@@ -0,0 +1,877 @@
+use reqwest::{Client, Method, RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use thiserror::Error;
+
+use super::types::*;
+
+const PER_PAGE: u32 = 50;
+
+#[derive(Error, Debug)]
+pub enum ForgeError {
+    #[error("HTTP request failed: {0}")]
+    RequestError(#[from] reqwest::Error),
+    #[error("Forge API returned {status}: {message}")]
+    ApiError { status: u16, message: String },
+    #[error("Invalid response format: {0}")]
+    InvalidResponse(String),
+    #[error("Invalid API URL: {0}")]
+    InvalidUrl(String),
+}
+
+// ==================== Wire formats ====================
+
+#[derive(Deserialize)]
+struct HubUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct HubBranch {
+    #[serde(rename = "ref")]
+    name: String,
+    sha: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct HubPull {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    /// Gitea reports this directly; GitHub only sets `merged_at`.
+    #[serde(default)]
+    merged: bool,
+    merged_at: Option<String>,
+    user: Option<HubUser>,
+    head: HubBranch,
+    base: HubBranch,
+    html_url: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Deserialize)]
+struct HubLabel {
+    #[serde(default)]
+    id: u64,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct HubIssue {
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: String,
+    user: Option<HubUser>,
+    #[serde(default)]
+    labels: Vec<HubLabel>,
+    #[serde(default)]
+    comments: u32,
+    html_url: String,
+    created_at: String,
+    updated_at: String,
+    /// Present when the "issue" is really a pull request (GitHub lists both).
+    pull_request: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct HubReviewComment {
+    id: u64,
+    user: Option<HubUser>,
+    body: String,
+    path: String,
+    line: Option<u32>,
+    original_line: Option<u32>,
+    side: Option<String>,
+    commit_id: Option<String>,
+    diff_hunk: Option<String>,
+    in_reply_to_id: Option<u64>,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaReview {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct GiteaReviewComment {
+    id: u64,
+    user: Option<HubUser>,
+    body: String,
+    path: String,
+    /// Line in the new file, 0 when the comment is on the old side
+    #[serde(default)]
+    position: u32,
+    #[serde(default)]
+    original_position: u32,
+    commit_id: Option<String>,
+    diff_hunk: Option<String>,
+    created_at: String,
+}
+
+#[derive(Deserialize)]
+struct LabUser {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct LabMergeRequest {
+    iid: u64,
+    title: String,
+    description: Option<String>,
+    state: String,
+    #[serde(default)]
+    draft: bool,
+    author: Option<LabUser>,
+    source_branch: String,
+    target_branch: String,
+    sha: Option<String>,
+    web_url: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Deserialize)]
+struct LabIssue {
+    iid: u64,
+    title: String,
+    description: Option<String>,
+    state: String,
+    author: Option<LabUser>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    user_notes_count: u32,
+    web_url: String,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Deserialize)]
+struct LabPosition {
+    new_path: Option<String>,
+    old_path: Option<String>,
+    new_line: Option<u32>,
+    old_line: Option<u32>,
+    head_sha: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LabNote {
+    id: u64,
+    body: String,
+    author: Option<LabUser>,
+    created_at: String,
+    position: Option<LabPosition>,
+}
+
+#[derive(Deserialize)]
+struct LabDiscussion {
+    notes: Vec<LabNote>,
+}
+
+fn login(user: Option<HubUser>) -> String {
+    user.map(|u| u.login).unwrap_or_default()
+}
+
+fn username(user: Option<LabUser>) -> String {
+    user.map(|u| u.username).unwrap_or_default()
+}
+
+impl From<HubPull> for ForgePullRequest {
+    fn from(pr: HubPull) -> Self {
+        let state = if pr.merged || pr.merged_at.is_some() { "merged".to_string() } else { pr.state };
+        ForgePullRequest {
+            number: pr.number,
+            title: pr.title,
+            body: pr.body.unwrap_or_default(),
+            state,
+            draft: pr.draft,
+            author: login(pr.user),
+            head_branch: pr.head.name,
+            head_sha: pr.head.sha,
+            base_branch: pr.base.name,
+            url: pr.html_url,
+            created_at: pr.created_at,
+            updated_at: pr.updated_at,
+        }
+    }
+}
+
+impl From<LabMergeRequest> for ForgePullRequest {
+    fn from(mr: LabMergeRequest) -> Self {
+        let state = match mr.state.as_str() {
+            "opened" => "open",
+            "merged" => "merged",
+            _ => "closed",
+        };
+        ForgePullRequest {
+            number: mr.iid,
+            title: mr.title,
+            body: mr.description.unwrap_or_default(),
+            state: state.to_string(),
+            draft: mr.draft,
+            author: username(mr.author),
+            head_branch: mr.source_branch,
+            head_sha: mr.sha,
+            base_branch: mr.target_branch,
+            url: mr.web_url,
+            created_at: mr.created_at,
+            updated_at: mr.updated_at,
+        }
+    }
+}
+
+impl From<HubIssue> for ForgeIssue {
+    fn from(issue: HubIssue) -> Self {
+        ForgeIssue {
+            number: issue.number,
+            title: issue.title,
+            body: issue.body.unwrap_or_default(),
+            state: issue.state,
+            author: login(issue.user),
+            labels: issue.labels.into_iter().map(|l| l.name).collect(),
+            comments: issue.comments,
+            url: issue.html_url,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+        }
+    }
+}
+
+impl From<LabIssue> for ForgeIssue {
+    fn from(issue: LabIssue) -> Self {
+        ForgeIssue {
+            number: issue.iid,
+            title: issue.title,
+            body: issue.description.unwrap_or_default(),
+            state: if issue.state == "opened" { "open".to_string() } else { issue.state },
+            author: username(issue.author),
+            labels: issue.labels,
+            comments: issue.user_notes_count,
+            url: issue.web_url,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+        }
+    }
+}
+
+impl From<HubReviewComment> for ForgeReviewComment {
+    fn from(c: HubReviewComment) -> Self {
+        ForgeReviewComment {
+            id: c.id,
+            author: login(c.user),
+            body: c.body,
+            path: c.path,
+            outdated: c.line.is_none(),
+            line: c.line,
+            original_line: c.original_line,
+            side: c.side.unwrap_or_else(|| "RIGHT".to_string()),
+            commit_id: c.commit_id,
+            diff_hunk: c.diff_hunk,
+            in_reply_to: c.in_reply_to_id,
+            created_at: c.created_at,
+        }
+    }
+}
+
+impl From<GiteaReviewComment> for ForgeReviewComment {
+    fn from(c: GiteaReviewComment) -> Self {
+        let (line, side) = if c.position > 0 {
+            (Some(c.position), "RIGHT")
+        } else {
+            (Some(c.original_position).filter(|l| *l > 0), "LEFT")
+        };
+        ForgeReviewComment {
+            id: c.id,
+            author: login(c.user),
+            body: c.body,
+            path: c.path,
+            line,
+            original_line: Some(c.original_position).filter(|l| *l > 0),
+            side: side.to_string(),
+            commit_id: c.commit_id,
+            diff_hunk: c.diff_hunk,
+            in_reply_to: None,
+            outdated: false,
+            created_at: c.created_at,
+        }
+    }
+}
+
+/// Diff notes of GitLab discussions; replies point at the thread's first note.
+fn lab_review_comments(discussions: Vec<LabDiscussion>) -> Vec<ForgeReviewComment> {
+    let mut comments = Vec::new();
+    for discussion in discussions {
+        let thread = discussion.notes.first().map(|n| n.id);
+        for note in discussion.notes {
+            let Some(position) = note.position else {
+                continue;
+            };
+            let (line, side) = match (position.new_line, position.old_line) {
+                (Some(new), _) => (Some(new), "RIGHT"),
+                (None, old) => (old, "LEFT"),
+            };
+            comments.push(ForgeReviewComment {
+                in_reply_to: thread.filter(|id| *id != note.id),
+                id: note.id,
+                author: username(note.author),
+                body: note.body,
+                path: position.new_path.or(position.old_path).unwrap_or_default(),
+                line,
+                original_line: position.old_line,
+                side: side.to_string(),
+                commit_id: position.head_sha,
+                diff_hunk: None,
+                outdated: line.is_none(),
+                created_at: note.created_at,
+            });
+        }
+    }
+    comments
+}
+
+// ==================== Client ====================
+
+/// REST client for one forge API root. The base URL is taken as given, so the
+/// same client talks to github.com, an Enterprise or Gitea instance, GitLab or
+/// a local mock server.
+pub struct ForgeClient {
+    client: Client,
+    kind: ForgeKind,
+    base_url: Url,
+    token: Option<String>,
+}
+
+impl ForgeClient {
+    pub fn new(kind: ForgeKind, api_url: &str, token: Option<String>) -> Result<Self, ForgeError> {
+        let base_url = Url::parse(api_url).map_err(|e| ForgeError::InvalidUrl(format!("{}: {}", api_url, e)))?;
+        if base_url.cannot_be_a_base() {
+            return Err(ForgeError::InvalidUrl(api_url.to_string()));
+        }
+
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(concat!("colbex/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+
+        Ok(Self { client, kind, base_url, token })
+    }
+
+    fn url(&self, repo: &ForgeRepo, path: &[&str]) -> Url {
+        let mut url = self.base_url.clone();
+        {
+            let mut segments = url.path_segments_mut().expect("checked in ForgeClient::new");
+            segments.pop_if_empty();
+            match self.kind {
+                ForgeKind::Github | ForgeKind::Gitea => {
+                    segments.extend(["repos", &repo.owner, &repo.name]);
+                }
+                // The project path is a single, percent-encoded segment
+                ForgeKind::Gitlab => {
+                    segments.extend(["projects", &format!("{}/{}", repo.owner, repo.name)]);
+                }
+            }
+            segments.extend(path);
+        }
+        url
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        let request = match self.kind {
+            ForgeKind::Github => request.header("Accept", "application/vnd.github+json"),
+            _ => request.header("Accept", "application/json"),
+        };
+        match (&self.token, self.kind) {
+            (None, _) => request,
+            (Some(token), ForgeKind::Github) => request.bearer_auth(token),
+            (Some(token), ForgeKind::Gitea) => request.header("Authorization", format!("token {}", token)),
+            (Some(token), ForgeKind::Gitlab) => request.header("PRIVATE-TOKEN", token),
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ForgeError> {
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            // All three APIs put a human-readable reason in "message" (GitLab sometimes in "error")
+            let message = serde_json::from_str::<Value>(&text)
+                .ok()
+                .and_then(|v| v.get("message").or_else(|| v.get("error")).cloned())
+                .map(|m| m.as_str().map(|s| s.to_string()).unwrap_or_else(|| m.to_string()))
+                .unwrap_or_else(|| if text.is_empty() { status.to_string() } else { text });
+            return Err(ForgeError::ApiError { status: status.as_u16(), message });
+        }
+        response.json().await.map_err(|e| ForgeError::InvalidResponse(e.to_string()))
+    }
+
+    fn paged(&self, request: RequestBuilder, page: u32) -> RequestBuilder {
+        let page = page.max(1).to_string();
+        match self.kind {
+            ForgeKind::Gitea => request.query(&[("limit", PER_PAGE.to_string()), ("page", page)]),
+            _ => request.query(&[("per_page", PER_PAGE.to_string()), ("page", page)]),
+        }
+    }
+
+    /// Every page of a list, requested until one comes back short.
+    async fn send_all<T: DeserializeOwned>(&self, request: impl Fn() -> RequestBuilder) -> Result<Vec<T>, ForgeError> {
+        let mut items = Vec::new();
+        let mut page = 1;
+        loop {
+            let batch: Vec<T> = self.send(self.paged(request(), page)).await?;
+            let last = batch.len() < PER_PAGE as usize;
+            items.extend(batch);
+            if last {
+                return Ok(items);
+            }
+            page += 1;
+        }
+    }
+
+    /// `state` is "open", "closed", "merged" or "all".
+    pub async fn list_pull_requests(&self, repo: &ForgeRepo, state: &str, page: u32) -> Result<Vec<ForgePullRequest>, ForgeError> {
+        match self.kind {
+            ForgeKind::Github | ForgeKind::Gitea => {
+                let wire_state = if state == "merged" { "closed" } else { state };
+                let request = self.request(Method::GET, self.url(repo, &["pulls"])).query(&[("state", wire_state)]);
+                let pulls: Vec<HubPull> = self.send(self.paged(request, page)).await?;
+                Ok(pulls.into_iter()
+                    .map(ForgePullRequest::from)
+                    .filter(|pr| state != "merged" || pr.state == "merged")
+                    .collect())
+            }
+            ForgeKind::Gitlab => {
+                let wire_state = if state == "open" { "opened" } else { state };
+                let request = self.request(Method::GET, self.url(repo, &["merge_requests"])).query(&[("state", wire_state)]);
+                let requests: Vec<LabMergeRequest> = self.send(self.paged(request, page)).await?;
+                Ok(requests.into_iter().map(ForgePullRequest::from).collect())
+            }
+        }
+    }
+
+    pub async fn get_pull_request(&self, repo: &ForgeRepo, number: u64) -> Result<ForgePullRequest, ForgeError> {
+        let number = number.to_string();
+        match self.kind {
+            ForgeKind::Github | ForgeKind::Gitea => {
+                let pull: HubPull = self.send(self.request(Method::GET, self.url(repo, &["pulls", &number]))).await?;
+                Ok(pull.into())
+            }
+            ForgeKind::Gitlab => {
+                let request: LabMergeRequest = self.send(self.request(Method::GET, self.url(repo, &["merge_requests", &number]))).await?;
+                Ok(request.into())
+            }
+        }
+    }
+
+    pub async fn default_branch(&self, repo: &ForgeRepo) -> Result<String, ForgeError> {
+        #[derive(Deserialize)]
+        struct RepoInfo {
+            default_branch: Option<String>,
+        }
+
+        let url = self.url(repo, &[]);
+        let info: RepoInfo = self.send(self.request(Method::GET, url)).await?;
+        info.default_branch.ok_or_else(|| ForgeError::InvalidResponse("repository has no default branch".to_string()))
+    }
+
+    /// `head` and `base` must already be resolved.
+    pub async fn create_pull_request(
+        &self,
+        repo: &ForgeRepo,
+        title: &str,
+        body: &str,
+        head: &str,
+        base: &str,
+        draft: bool,
+    ) -> Result<ForgePullRequest, ForgeError> {
+        match self.kind {
+            ForgeKind::Github => {
+                let payload = json!({ "title": title, "body": body, "head": head, "base": base, "draft": draft });
+                let pull: HubPull = self.send(self.request(Method::POST, self.url(repo, &["pulls"])).json(&payload)).await?;
+                Ok(pull.into())
+            }
+            ForgeKind::Gitea => {
+                // Gitea marks drafts by title prefix
+                let title = if draft { format!("WIP: {}", title) } else { title.to_string() };
+                let payload = json!({ "title": title, "body": body, "head": head, "base": base });
+                let pull: HubPull = self.send(self.request(Method::POST, self.url(repo, &["pulls"])).json(&payload)).await?;
+                Ok(pull.into())
+            }
+            ForgeKind::Gitlab => {
+                let title = if draft { format!("Draft: {}", title) } else { title.to_string() };
+                let payload = json!({
+                    "title": title,
+                    "description": body,
+                    "source_branch": head,
+                    "target_branch": base,
+                });
+                let request: LabMergeRequest = self.send(self.request(Method::POST, self.url(repo, &["merge_requests"])).json(&payload)).await?;
+                Ok(request.into())
+            }
+        }
+    }
+
+    pub async fn list_review_comments(&self, repo: &ForgeRepo, number: u64) -> Result<Vec<ForgeReviewComment>, ForgeError> {
+        let number = number.to_string();
+        match self.kind {
+            ForgeKind::Github => {
+                let url = self.url(repo, &["pulls", &number, "comments"]);
+                let comments: Vec<HubReviewComment> = self.send_all(|| self.request(Method::GET, url.clone())).await?;
+                Ok(comments.into_iter().map(ForgeReviewComment::from).collect())
+            }
+            // Gitea only exposes comments per review
+            ForgeKind::Gitea => {
+                let url = self.url(repo, &["pulls", &number, "reviews"]);
+                let reviews: Vec<GiteaReview> = self.send_all(|| self.request(Method::GET, url.clone())).await?;
+                let mut comments = Vec::new();
+                for review in reviews {
+                    let id = review.id.to_string();
+                    let url = self.url(repo, &["pulls", &number, "reviews", &id, "comments"]);
+                    let batch: Vec<GiteaReviewComment> = self.send(self.request(Method::GET, url)).await?;
+                    comments.extend(batch.into_iter().map(ForgeReviewComment::from));
+                }
+                Ok(comments)
+            }
+            ForgeKind::Gitlab => {
+                let url = self.url(repo, &["merge_requests", &number, "discussions"]);
+                let discussions: Vec<LabDiscussion> = self.send_all(|| self.request(Method::GET, url.clone())).await?;
+                Ok(lab_review_comments(discussions))
+            }
+        }
+    }
+
+    /// `state` is "open", "closed" or "all". Pull requests are left out.
+    pub async fn list_issues(&self, repo: &ForgeRepo, state: &str, page: u32) -> Result<Vec<ForgeIssue>, ForgeError> {
+        match self.kind {
+            ForgeKind::Github | ForgeKind::Gitea => {
+                let mut request = self.request(Method::GET, self.url(repo, &["issues"])).query(&[("state", state)]);
+                if self.kind == ForgeKind::Gitea {
+                    request = request.query(&[("type", "issues")]);
+                }
+                let issues: Vec<HubIssue> = self.send(self.paged(request, page)).await?;
+                Ok(issues.into_iter()
+                    .filter(|issue| issue.pull_request.is_none())
+                    .map(ForgeIssue::from)
+                    .collect())
+            }
+            ForgeKind::Gitlab => {
+                let wire_state = if state == "open" { "opened" } else { state };
+                let request = self.request(Method::GET, self.url(repo, &["issues"])).query(&[("state", wire_state)]);
+                let issues: Vec<LabIssue> = self.send(self.paged(request, page)).await?;
+                Ok(issues.into_iter().map(ForgeIssue::from).collect())
+            }
+        }
+    }
+
+    pub async fn get_issue(&self, repo: &ForgeRepo, number: u64) -> Result<ForgeIssue, ForgeError> {
+        let number = number.to_string();
+        match self.kind {
+            ForgeKind::Github | ForgeKind::Gitea => {
+                let issue: HubIssue = self.send(self.request(Method::GET, self.url(repo, &["issues", &number]))).await?;
+                Ok(issue.into())
+            }
+            ForgeKind::Gitlab => {
+                let issue: LabIssue = self.send(self.request(Method::GET, self.url(repo, &["issues", &number]))).await?;
+                Ok(issue.into())
+            }
+        }
+    }
+
+    pub async fn create_issue(&self, repo: &ForgeRepo, issue: &NewIssue) -> Result<ForgeIssue, ForgeError> {
+        let body = issue.body.clone().unwrap_or_default();
+        match self.kind {
+            ForgeKind::Github => {
+                let payload = json!({ "title": issue.title, "body": body, "labels": issue.labels });
+                let created: HubIssue = self.send(self.request(Method::POST, self.url(repo, &["issues"])).json(&payload)).await?;
+                Ok(created.into())
+            }
+            ForgeKind::Gitea => {
+                // Gitea takes label ids rather than names
+                let labels: Vec<u64> = if issue.labels.is_empty() {
+                    Vec::new()
+                } else {
+                    let known: Vec<HubLabel> = self.send(self.request(Method::GET, self.url(repo, &["labels"]))).await?;
+                    known.into_iter().filter(|l| issue.labels.contains(&l.name)).map(|l| l.id).collect()
+                };
+                let payload = json!({ "title": issue.title, "body": body, "labels": labels });
+                let created: HubIssue = self.send(self.request(Method::POST, self.url(repo, &["issues"])).json(&payload)).await?;
+                Ok(created.into())
+            }
+            ForgeKind::Gitlab => {
+                let payload = json!({ "title": issue.title, "description": body, "labels": issue.labels.join(",") });
+                let created: LabIssue = self.send(self.request(Method::POST, self.url(repo, &["issues"])).json(&payload)).await?;
+                Ok(created.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone)]
+    struct Recorded {
+        method: String,
+        target: String,
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl Recorded {
+        fn header(&self, name: &str) -> Option<&str> {
+            self.headers.iter().find(|(k, _)| k.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+        }
+    }
+
+    /// Minimal HTTP/1.1 server answering `(method, path) -> (status, body)`;
+    /// the query string is ignored for matching unless the route has one,
+    /// and is always recorded.
+    fn mock_server(routes: Vec<(&'static str, &'static str, u16, String)>) -> (String, Arc<Mutex<Vec<Recorded>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let log = recorded.clone();
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap_or("").to_string();
+                let target = parts.next().unwrap_or("").to_string();
+
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((k, v)) = line.split_once(':') {
+                        headers.push((k.trim().to_string(), v.trim().to_string()));
+                    }
+                }
+                let length = headers.iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                    .and_then(|(_, v)| v.parse().ok())
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let path = target.split('?').next().unwrap_or("").to_string();
+                let (status, response) = routes.iter()
+                    .find(|(m, p, _, _)| *m == method && (*p == path || *p == target))
+                    .map(|(_, _, s, b)| (*s, b.clone()))
+                    .unwrap_or((404, r#"{"message":"Not Found"}"#.to_string()));
+                log.lock().unwrap().push(Recorded { method, target, headers, body: String::from_utf8_lossy(&body).to_string() });
+
+                let reply = format!(
+                    "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status, response.len(), response
+                );
+                let _ = stream.write_all(reply.as_bytes());
+            }
+        });
+
+        (base, recorded)
+    }
+
+    fn repo(kind: ForgeKind, owner: &str) -> ForgeRepo {
+        ForgeRepo {
+            host: "example.com".to_string(),
+            owner: owner.to_string(),
+            name: "app".to_string(),
+            kind,
+            api_url: String::new(),
+            remote: "origin".to_string(),
+        }
+    }
+
+    fn hub_pull(number: u64, merged_at: Option<&str>) -> Value {
+        json!({
+            "number": number,
+            "title": format!("PR {}", number),
+            "body": null,
+            "state": if merged_at.is_some() { "closed" } else { "open" },
+            "draft": number == 2,
+            "merged_at": merged_at,
+            "user": { "login": "alice" },
+            "head": { "ref": "feature", "sha": "abc123" },
+            "base": { "ref": "main", "sha": "def456" },
+            "html_url": format!("https://example.com/me/app/pull/{}", number),
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-02T00:00:00Z",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_github_pull_requests_and_auth() {
+        let pulls = json!([hub_pull(1, None), hub_pull(2, None), hub_pull(3, Some("2024-01-03T00:00:00Z"))]);
+        let (base, recorded) = mock_server(vec![("GET", "/api/repos/me/app/pulls", 200, pulls.to_string())]);
+        let client = ForgeClient::new(ForgeKind::Github, &format!("{}/api/", base), Some("secret".to_string())).unwrap();
+
+        let merged = client.list_pull_requests(&repo(ForgeKind::Github, "me"), "merged", 2).await.unwrap();
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].number, 3);
+        assert_eq!(merged[0].state, "merged");
+
+        let request = recorded.lock().unwrap()[0].clone();
+        assert_eq!(request.header("authorization"), Some("Bearer secret"));
+        assert!(request.target.contains("state=closed"));
+        assert!(request.target.contains("page=2"));
+
+        let all = client.list_pull_requests(&repo(ForgeKind::Github, "me"), "all", 1).await.unwrap();
+        assert!(all[1].draft);
+        assert_eq!(all[0].body, "");
+        assert_eq!(all[0].head_branch, "feature");
+    }
+
+    #[tokio::test]
+    async fn test_gitlab_project_path_and_discussions() {
+        let discussions = json!([
+            {
+                "id": "d1",
+                "notes": [
+                    {
+                        "id": 10, "body": "Typo here", "author": { "username": "bob" },
+                        "created_at": "2024-01-01T00:00:00Z",
+                        "position": { "new_path": "src/lib.rs", "old_path": "src/lib.rs", "new_line": 12, "old_line": null, "head_sha": "abc" }
+                    },
+                    {
+                        "id": 11, "body": "Fixed", "author": { "username": "alice" },
+                        "created_at": "2024-01-02T00:00:00Z",
+                        "position": { "new_path": "src/lib.rs", "old_path": "src/lib.rs", "new_line": 12, "old_line": null, "head_sha": "abc" }
+                    }
+                ]
+            },
+            { "id": "d2", "notes": [{ "id": 20, "body": "General remark", "author": null, "created_at": "2024-01-01T00:00:00Z", "position": null }] },
+            {
+                "id": "d3",
+                "notes": [{
+                    "id": 30, "body": "Why removed?", "author": { "username": "bob" },
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "position": { "new_path": null, "old_path": "old.rs", "new_line": null, "old_line": 4, "head_sha": "abc" }
+                }]
+            }
+        ]);
+        let (base, recorded) = mock_server(vec![(
+            "GET",
+            "/api/v4/projects/group%2Fsub%2Fapp/merge_requests/7/discussions",
+            200,
+            discussions.to_string(),
+        )]);
+        let client = ForgeClient::new(ForgeKind::Gitlab, &format!("{}/api/v4", base), Some("glpat".to_string())).unwrap();
+
+        let comments = client.list_review_comments(&repo(ForgeKind::Gitlab, "group/sub"), 7).await.unwrap();
+        // Matched only if the whole project path went out as one encoded segment
+        assert_eq!(recorded.lock().unwrap()[0].header("private-token"), Some("glpat"));
+
+        assert_eq!(comments.len(), 3);
+        assert_eq!((comments[0].path.as_str(), comments[0].line, comments[0].side.as_str()), ("src/lib.rs", Some(12), "RIGHT"));
+        assert_eq!(comments[0].in_reply_to, None);
+        assert_eq!(comments[1].in_reply_to, Some(10));
+        assert_eq!((comments[2].path.as_str(), comments[2].line, comments[2].side.as_str()), ("old.rs", Some(4), "LEFT"));
+    }
+
+    #[tokio::test]
+    async fn test_review_comments_follow_pages() {
+        let comment = |id: u64| json!({
+            "id": id, "user": { "login": "bob" }, "body": format!("Note {}", id), "path": "src/lib.rs",
+            "line": 3, "original_line": 3, "side": "RIGHT", "commit_id": "abc", "diff_hunk": null,
+            "in_reply_to_id": null, "created_at": "2024-01-01T00:00:00Z",
+        });
+        let first: Vec<Value> = (1..=u64::from(PER_PAGE)).map(comment).collect();
+        let (base, recorded) = mock_server(vec![
+            ("GET", "/repos/me/app/pulls/3/comments?per_page=50&page=1", 200, json!(first).to_string()),
+            ("GET", "/repos/me/app/pulls/3/comments?per_page=50&page=2", 200, json!([comment(51)]).to_string()),
+        ]);
+        let client = ForgeClient::new(ForgeKind::Github, &base, None).unwrap();
+
+        let comments = client.list_review_comments(&repo(ForgeKind::Github, "me"), 3).await.unwrap();
+        assert_eq!(comments.len(), 51);
+        assert_eq!(comments[50].body, "Note 51");
+        // A short page is the last one
+        assert_eq!(recorded.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_create_pull_request_payloads() {
+        let created = hub_pull(5, None).to_string();
+        let (base, recorded) = mock_server(vec![
+            ("POST", "/repos/me/app/pulls", 201, created.clone()),
+            ("POST", "/api/v1/repos/me/app/pulls", 201, created),
+        ]);
+
+        let github = ForgeClient::new(ForgeKind::Github, &base, None).unwrap();
+        let pr = github.create_pull_request(&repo(ForgeKind::Github, "me"), "Add feature", "Body", "feature", "main", true).await.unwrap();
+        assert_eq!(pr.number, 5);
+
+        let gitea = ForgeClient::new(ForgeKind::Gitea, &format!("{}/api/v1", base), Some("t".to_string())).unwrap();
+        gitea.create_pull_request(&repo(ForgeKind::Gitea, "me"), "Add feature", "", "feature", "main", true).await.unwrap();
+
+        let requests = recorded.lock().unwrap().clone();
+        let github_body: Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(github_body["draft"], json!(true));
+        assert_eq!(github_body["head"], json!("feature"));
+        assert!(requests[0].header("authorization").is_none());
+
+        let gitea_body: Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(gitea_body["title"], json!("WIP: Add feature"));
+        assert_eq!(requests[1].header("authorization"), Some("token t"));
+    }
+
+    #[tokio::test]
+    async fn test_issues_skip_pull_requests_and_errors_carry_message() {
+        let issues = json!([
+            {
+                "number": 1, "title": "Bug", "body": "It breaks", "state": "open",
+                "user": { "login": "carol" }, "labels": [{ "id": 3, "name": "bug" }], "comments": 2,
+                "html_url": "https://example.com/1", "created_at": "c", "updated_at": "u"
+            },
+            {
+                "number": 2, "title": "A PR", "body": null, "state": "open", "user": null, "labels": [],
+                "html_url": "https://example.com/2", "created_at": "c", "updated_at": "u",
+                "pull_request": { "url": "https://example.com/pulls/2" }
+            }
+        ]);
+        let (base, _) = mock_server(vec![
+            ("GET", "/repos/me/app/issues", 200, issues.to_string()),
+            ("GET", "/repos/me/app/issues/9", 404, r#"{"message":"Not Found","documentation_url":"x"}"#.to_string()),
+        ]);
+        let client = ForgeClient::new(ForgeKind::Github, &base, None).unwrap();
+
+        let issues = client.list_issues(&repo(ForgeKind::Github, "me"), "open", 1).await.unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].labels, vec!["bug".to_string()]);
+        assert_eq!(issues[0].comments, 2);
+
+        match client.get_issue(&repo(ForgeKind::Github, "me"), 9).await {
+            Err(ForgeError::ApiError { status, message }) => {
+                assert_eq!(status, 404);
+                assert_eq!(message, "Not Found");
+            }
+            other => panic!("expected an API error, got {:?}", other.map(|i| i.number)),
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_base_url() {
+        assert!(matches!(ForgeClient::new(ForgeKind::Github, "not a url", None), Err(ForgeError::InvalidUrl(_))));
+    }
+}
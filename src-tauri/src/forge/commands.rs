@@ -0,0 +1,434 @@
+use git2::build::CheckoutBuilder;
+use git2::Repository;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+use super::client::ForgeClient;
+use super::types::*;
+use crate::api_keys::ApiKeyStore;
+use crate::git::credentials::{self, CredentialSession};
+use crate::git::operations::ensure_clean_state;
+use crate::git::remote::{current_branch_ref, default_remote, fetch_remote};
+use crate::git::service::GitService;
+use crate::settings::{self, SettingsState};
+
+/// Where a remote URL points: host for lookups, web root for deriving the API.
+#[derive(Debug, PartialEq)]
+struct RemoteLocation {
+    host: String,
+    web_root: String,
+    owner: String,
+    name: String,
+}
+
+/// Splits HTTPS, `ssh://` and scp-style (`git@host:owner/repo.git`) remote URLs.
+fn parse_remote_url(url: &str) -> Option<RemoteLocation> {
+    let url = url.trim();
+    let (scheme, authority, path) = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            let (authority, path) = rest.split_once('/')?;
+            (scheme.to_lowercase(), authority, path)
+        }
+        None => {
+            let (authority, path) = url.split_once(':')?;
+            ("ssh".to_string(), authority, path)
+        }
+    };
+
+    let host_port = authority.rsplit_once('@').map(|(_, h)| h).unwrap_or(authority);
+    let host = host_port.split(':').next()?.to_lowercase();
+    if host.is_empty() {
+        return None;
+    }
+    // An HTTP port belongs to the web server; an SSH port does not
+    let web_root = match scheme.as_str() {
+        "http" | "https" => format!("{}://{}", scheme, host_port.to_lowercase()),
+        _ => format!("https://{}", host),
+    };
+
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, name) = path.rsplit_once('/')?;
+    if owner.is_empty() || name.is_empty() {
+        return None;
+    }
+
+    Some(RemoteLocation {
+        host,
+        web_root,
+        owner: owner.to_string(),
+        name: name.to_string(),
+    })
+}
+
+fn detect_kind(host: &str) -> ForgeKind {
+    if host.contains("github") {
+        ForgeKind::Github
+    } else if host.contains("gitlab") {
+        ForgeKind::Gitlab
+    } else {
+        // Codeberg, Forgejo and most self-hosted instances
+        ForgeKind::Gitea
+    }
+}
+
+fn default_api_url(kind: ForgeKind, location: &RemoteLocation) -> String {
+    match kind {
+        ForgeKind::Github if location.host == "github.com" => "https://api.github.com".to_string(),
+        ForgeKind::Github => format!("{}/api/v3", location.web_root),
+        ForgeKind::Gitea => format!("{}/api/v1", location.web_root),
+        ForgeKind::Gitlab => format!("{}/api/v4", location.web_root),
+    }
+}
+
+/// Hosts configured in the user's git settings; anything else is detected
+/// from its name.
+fn configured_hosts(settings: &SettingsState) -> Result<Vec<ForgeHostConfig>, String> {
+    let store = settings.store.lock().map_err(|e| e.to_string())?;
+    Ok(store.get_settings().git.forge_hosts)
+}
+
+fn forge_repo(hosts: &[ForgeHostConfig], repo: &Repository, remote_name: Option<String>) -> Result<ForgeRepo, String> {
+    let remote_name = remote_name.unwrap_or_else(|| default_remote(repo));
+    let remote = repo.find_remote(&remote_name)
+        .map_err(|e| format!("Remote '{}' not found: {}", remote_name, e))?;
+    let url = remote.url().ok_or_else(|| format!("Remote '{}' has no URL", remote_name))?;
+    let location = parse_remote_url(url)
+        .ok_or_else(|| format!("Cannot tell which forge repository '{}' points at", url))?;
+
+    let configured = hosts.iter().find(|c| c.host.trim().eq_ignore_ascii_case(&location.host)).cloned();
+    let kind = configured.as_ref().map(|c| c.kind).unwrap_or_else(|| detect_kind(&location.host));
+    let api_url = configured
+        .and_then(|c| c.api_url)
+        .filter(|u| !u.trim().is_empty())
+        .unwrap_or_else(|| default_api_url(kind, &location));
+
+    Ok(ForgeRepo {
+        host: location.host,
+        owner: location.owner,
+        name: location.name,
+        kind,
+        api_url: api_url.trim_end_matches('/').to_string(),
+        remote: remote_name,
+    })
+}
+
+fn resolve(
+    service: &GitService,
+    settings: &SettingsState,
+    repo_path: &str,
+    remote_name: Option<String>,
+) -> Result<ForgeRepo, String> {
+    let repo = service.open(repo_path)?;
+    forge_repo(&configured_hosts(settings)?, &repo, remote_name)
+}
+
+fn client_for(repo: &ForgeRepo, keys: &Mutex<ApiKeyStore>) -> Result<ForgeClient, String> {
+    ForgeClient::new(repo.kind, &repo.api_url, credentials::api_token(keys, &repo.host)).map_err(|e| e.to_string())
+}
+
+// ==================== Hosts ====================
+
+#[tauri::command]
+pub fn forge_list_hosts(settings: State<'_, SettingsState>) -> Result<Vec<ForgeHostConfig>, String> {
+    let mut hosts = configured_hosts(&settings)?;
+    hosts.sort_by(|a, b| a.host.cmp(&b.host));
+    Ok(hosts)
+}
+
+/// Saves the user's host list in their git settings.
+fn save_hosts(app_handle: AppHandle, settings: State<'_, SettingsState>, hosts: Vec<ForgeHostConfig>) -> Result<(), String> {
+    let value = serde_json::to_value(&hosts).map_err(|e| e.to_string())?;
+    settings::settings_update_value(app_handle, settings, "git".to_string(), "forgeHosts".to_string(), value, "user".to_string())
+}
+
+/// Sets the API flavour and root for a host, e.g. a GitHub Enterprise server.
+#[tauri::command]
+pub fn forge_set_host(app_handle: AppHandle, settings: State<'_, SettingsState>, config: ForgeHostConfig) -> Result<(), String> {
+    let host = config.host.trim().to_lowercase();
+    if host.is_empty() {
+        return Err("Host must not be empty".to_string());
+    }
+    let mut hosts = configured_hosts(&settings)?;
+    hosts.retain(|c| !c.host.trim().eq_ignore_ascii_case(&host));
+    hosts.push(ForgeHostConfig { host, ..config });
+    save_hosts(app_handle, settings, hosts)
+}
+
+#[tauri::command]
+pub fn forge_remove_host(app_handle: AppHandle, settings: State<'_, SettingsState>, host: String) -> Result<(), String> {
+    let host = host.trim().to_lowercase();
+    let mut hosts = configured_hosts(&settings)?;
+    hosts.retain(|c| !c.host.trim().eq_ignore_ascii_case(&host));
+    save_hosts(app_handle, settings, hosts)
+}
+
+/// The forge repository behind `remote_name` (default: the current branch's remote).
+#[tauri::command]
+pub fn forge_repo_info(
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    repo_path: String,
+    remote_name: Option<String>,
+) -> Result<ForgeRepo, String> {
+    resolve(&service, &settings, &repo_path, remote_name)
+}
+
+// ==================== Pull requests ====================
+
+/// `state`: "open" (default), "closed", "merged" or "all".
+#[tauri::command]
+pub async fn forge_list_pull_requests(
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    repo_path: String,
+    remote_name: Option<String>,
+    state: Option<String>,
+    page: Option<u32>,
+) -> Result<Vec<ForgePullRequest>, String> {
+    let repo = resolve(&service, &settings, &repo_path, remote_name)?;
+    let client = client_for(&repo, &keys)?;
+    let state = state.unwrap_or_else(|| "open".to_string());
+    client.list_pull_requests(&repo, &state, page.unwrap_or(1)).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn forge_get_pull_request(
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    repo_path: String,
+    remote_name: Option<String>,
+    number: u64,
+) -> Result<ForgePullRequest, String> {
+    let repo = resolve(&service, &settings, &repo_path, remote_name)?;
+    let client = client_for(&repo, &keys)?;
+    client.get_pull_request(&repo, number).await.map_err(|e| e.to_string())
+}
+
+/// Opens a pull request from an already pushed branch.
+#[tauri::command]
+pub async fn forge_create_pull_request(
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    repo_path: String,
+    remote_name: Option<String>,
+    pull_request: NewPullRequest,
+) -> Result<ForgePullRequest, String> {
+    if pull_request.title.trim().is_empty() {
+        return Err("Pull request title must not be empty".to_string());
+    }
+
+    let (repo, head) = {
+        let git_repo = service.open(&repo_path)?;
+        let repo = forge_repo(&configured_hosts(&settings)?, &git_repo, remote_name)?;
+        let head = match pull_request.head.clone().filter(|h| !h.trim().is_empty()) {
+            Some(head) => head,
+            None => current_branch_ref(&git_repo)?.trim_start_matches("refs/heads/").to_string(),
+        };
+        (repo, head)
+    };
+
+    let client = client_for(&repo, &keys)?;
+    let base = match pull_request.base.clone().filter(|b| !b.trim().is_empty()) {
+        Some(base) => base,
+        None => client.default_branch(&repo).await.map_err(|e| e.to_string())?,
+    };
+    if base == head {
+        return Err(format!("Cannot open a pull request from '{}' into itself", head));
+    }
+
+    client.create_pull_request(
+        &repo,
+        pull_request.title.trim(),
+        pull_request.body.as_deref().unwrap_or(""),
+        &head,
+        &base,
+        pull_request.draft,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Review comments of a pull request, each anchored to a file and line.
+#[tauri::command]
+pub async fn forge_list_review_comments(
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    repo_path: String,
+    remote_name: Option<String>,
+    number: u64,
+) -> Result<Vec<ForgeReviewComment>, String> {
+    let repo = resolve(&service, &settings, &repo_path, remote_name)?;
+    let client = client_for(&repo, &keys)?;
+    client.list_review_comments(&repo, number).await.map_err(|e| e.to_string())
+}
+
+fn checkout_pull_request(
+    repo: &Repository,
+    session: CredentialSession,
+    remote: &str,
+    kind: ForgeKind,
+    number: u64,
+    branch: &str,
+) -> Result<String, String> {
+    ensure_clean_state(repo)?;
+
+    let source = match kind {
+        ForgeKind::Gitlab => format!("refs/merge-requests/{}/head", number),
+        ForgeKind::Github | ForgeKind::Gitea => format!("refs/pull/{}/head", number),
+    };
+    let tracking = format!("refs/remotes/{}/pr/{}", remote, number);
+    fetch_remote(repo, session, remote, &[&format!("+{}:{}", source, tracking)], false)
+        .map_err(|e| format!("Cannot fetch pull request #{}: {}", number, e))?;
+
+    let commit = repo.find_reference(&tracking)
+        .and_then(|r| r.peel_to_commit())
+        .map_err(|e| e.to_string())?;
+
+    // Re-checking out a PR fast-forwards its branch; local commits are never dropped
+    let refname = format!("refs/heads/{}", branch);
+    if let Some(existing) = repo.find_reference(&refname).ok().and_then(|r| r.target()) {
+        let fast_forward = existing == commit.id()
+            || repo.graph_descendant_of(commit.id(), existing).map_err(|e| e.to_string())?;
+        if !fast_forward {
+            return Err(format!(
+                "Local branch '{}' has diverged from pull request #{}; rename or delete it first",
+                branch, number
+            ));
+        }
+    }
+
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))
+        .map_err(|e| format!("Cannot check out pull request #{}: {}", number, e))?;
+    repo.reference(&refname, commit.id(), true, &format!("forge: checkout pull request #{}", number))
+        .map_err(|e| e.to_string())?;
+    repo.set_head(&refname).map_err(|e| e.to_string())?;
+
+    Ok(branch.to_string())
+}
+
+/// Fetches a pull request's head into a local branch (default `pr-<number>`)
+/// and checks it out. Returns the branch name.
+#[tauri::command]
+pub async fn forge_checkout_pull_request(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    repo_path: String,
+    remote_name: Option<String>,
+    number: u64,
+    branch_name: Option<String>,
+) -> Result<String, String> {
+    let repo = service.open(&repo_path)?;
+    let remote = remote_name.unwrap_or_else(|| default_remote(&repo));
+    // Only the ref layout matters here, so mirrors and local remotes work too
+    let kind = forge_repo(&configured_hosts(&settings)?, &repo, Some(remote.clone()))
+        .map(|r| r.kind)
+        .unwrap_or(ForgeKind::Github);
+    let branch = branch_name
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+        .unwrap_or_else(|| format!("pr-{}", number));
+    let session = credentials::session_for(&app_handle);
+
+    tokio::task::spawn_blocking(move || checkout_pull_request(&repo, session, &remote, kind, number, &branch))
+        .await
+        .map_err(|e| format!("Checkout task failed: {}", e))?
+}
+
+// ==================== Issues ====================
+
+/// `state`: "open" (default), "closed" or "all".
+#[tauri::command]
+pub async fn forge_list_issues(
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    repo_path: String,
+    remote_name: Option<String>,
+    state: Option<String>,
+    page: Option<u32>,
+) -> Result<Vec<ForgeIssue>, String> {
+    let repo = resolve(&service, &settings, &repo_path, remote_name)?;
+    let client = client_for(&repo, &keys)?;
+    let state = state.unwrap_or_else(|| "open".to_string());
+    client.list_issues(&repo, &state, page.unwrap_or(1)).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn forge_get_issue(
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    repo_path: String,
+    remote_name: Option<String>,
+    number: u64,
+) -> Result<ForgeIssue, String> {
+    let repo = resolve(&service, &settings, &repo_path, remote_name)?;
+    let client = client_for(&repo, &keys)?;
+    client.get_issue(&repo, number).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn forge_create_issue(
+    service: State<'_, GitService>,
+    settings: State<'_, SettingsState>,
+    keys: State<'_, Mutex<ApiKeyStore>>,
+    repo_path: String,
+    remote_name: Option<String>,
+    issue: NewIssue,
+) -> Result<ForgeIssue, String> {
+    if issue.title.trim().is_empty() {
+        return Err("Issue title must not be empty".to_string());
+    }
+    let repo = resolve(&service, &settings, &repo_path, remote_name)?;
+    let client = client_for(&repo, &keys)?;
+    client.create_issue(&repo, &issue).await.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_remote_url() {
+        let https = parse_remote_url("https://github.com/owner/repo.git").unwrap();
+        assert_eq!((https.host.as_str(), https.owner.as_str(), https.name.as_str()), ("github.com", "owner", "repo"));
+
+        let scp = parse_remote_url("git@gitlab.com:group/sub/project.git").unwrap();
+        assert_eq!((scp.owner.as_str(), scp.name.as_str()), ("group/sub", "project"));
+        assert_eq!(scp.web_root, "https://gitlab.com");
+
+        // HTTP ports are kept for the API, SSH ports are not
+        let local = parse_remote_url("http://user@localhost:3000/me/app").unwrap();
+        assert_eq!(local.web_root, "http://localhost:3000");
+        let ssh = parse_remote_url("ssh://git@git.example.com:2222/me/app.git").unwrap();
+        assert_eq!(ssh.web_root, "https://git.example.com");
+
+        assert!(parse_remote_url("/srv/git/repo.git").is_none());
+    }
+
+    #[test]
+    fn test_configured_host_overrides_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.remote("origin", "https://code.example.com/team/tool.git").unwrap();
+
+        let detected = forge_repo(&[], &repo, None).unwrap();
+        assert_eq!(detected.kind, ForgeKind::Gitea);
+        assert_eq!(detected.api_url, "https://code.example.com/api/v1");
+
+        let hosts = vec![ForgeHostConfig {
+            host: "code.example.com".to_string(),
+            kind: ForgeKind::Github,
+            api_url: Some("http://127.0.0.1:9999/".to_string()),
+        }];
+        let configured = forge_repo(&hosts, &repo, Some("origin".to_string())).unwrap();
+        assert_eq!(configured.kind, ForgeKind::Github);
+        assert_eq!(configured.api_url, "http://127.0.0.1:9999");
+    }
+}
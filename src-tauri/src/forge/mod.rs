@@ -0,0 +1,8 @@
+mod client;
+mod commands;
+mod types;
+
+// Re-export public API
+pub use client::{ForgeClient, ForgeError};
+pub use commands::*;
+pub use types::*;
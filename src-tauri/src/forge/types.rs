@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+
+pub use crate::settings::{ForgeHostConfig, ForgeKind};
+
+/// The forge repository behind a git remote.
+#[derive(Debug, Clone, Serialize)]
+pub struct ForgeRepo {
+    pub host: String,
+    /// Owner or namespace; nested GitLab groups are joined with `/`.
+    pub owner: String,
+    pub name: String,
+    pub kind: ForgeKind,
+    pub api_url: String,
+    pub remote: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForgePullRequest {
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    /// "open", "closed" or "merged"
+    pub state: String,
+    pub draft: bool,
+    pub author: String,
+    pub head_branch: String,
+    pub head_sha: Option<String>,
+    pub base_branch: String,
+    pub url: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewPullRequest {
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Source branch; defaults to the current branch.
+    #[serde(default)]
+    pub head: Option<String>,
+    /// Target branch; defaults to the remote's default branch.
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+/// A review comment anchored to a line of the pull request diff.
+#[derive(Debug, Clone, Serialize)]
+pub struct ForgeReviewComment {
+    pub id: u64,
+    pub author: String,
+    pub body: String,
+    pub path: String,
+    /// Line in the file on `side`; `None` once the code it pointed at changed.
+    pub line: Option<u32>,
+    pub original_line: Option<u32>,
+    /// "RIGHT" for the new version of the file, "LEFT" for the old one.
+    pub side: String,
+    pub commit_id: Option<String>,
+    pub diff_hunk: Option<String>,
+    /// First comment of the thread this one answers.
+    pub in_reply_to: Option<u64>,
+    pub outdated: bool,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForgeIssue {
+    pub number: u64,
+    pub title: String,
+    pub body: String,
+    /// "open" or "closed"
+    pub state: String,
+    pub author: String,
+    pub labels: Vec<String>,
+    pub comments: u32,
+    pub url: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NewIssue {
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
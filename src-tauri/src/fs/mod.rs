@@ -5,3 +5,4 @@ mod commands;
 
 pub use commands::*;
 pub use commands::{FileWatcherState, AudioCache};
+pub use types::{SearchOptions, SearchResult};
@@ -0,0 +1,501 @@
+//! Author avatars served from a local cache.
+//!
+//! Every author gets a deterministic generated image (initials, or an
+//! identicon when the name has no letters) written next to the cache, so the
+//! history view renders offline without sending emails anywhere. When network
+//! fetches are enabled in the git settings a background worker looks the
+//! author up on GitHub and Gravatar, keeps the result for the configured TTL
+//! and emits `git-avatar-updated` so the UI can swap the image in.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::settings::SettingsState;
+
+/// Extensions a fetched avatar may be stored under.
+const REMOTE_EXTENSIONS: [&str; 4] = ["png", "jpg", "gif", "webp"];
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the fetch worker is allowed to do, read from the settings per job.
+#[derive(Debug, Clone, Copy)]
+pub struct AvatarPolicy {
+    pub network: bool,
+    pub ttl: Duration,
+}
+
+/// Payload of the `git-avatar-updated` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct AvatarUpdate {
+    pub key: String,
+    /// URL handed out before the fetch, still present in rendered commits.
+    pub previous: String,
+    pub url: String,
+}
+
+struct FetchJob {
+    key: String,
+    candidates: Vec<String>,
+}
+
+pub struct AvatarService {
+    dir: PathBuf,
+    /// Keys already queued this session, so a long log does not refetch.
+    requested: Arc<Mutex<HashSet<String>>>,
+    fetcher: Mutex<Option<mpsc::Sender<FetchJob>>>,
+}
+
+impl Default for AvatarService {
+    fn default() -> Self {
+        let base = dirs::cache_dir().unwrap_or_else(std::env::temp_dir);
+        Self::new(base.join("colbex").join("avatars"))
+    }
+}
+
+impl AvatarService {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            requested: Arc::new(Mutex::new(HashSet::new())),
+            fetcher: Mutex::new(None),
+        }
+    }
+
+    /// Starts the background worker. Without it only generated avatars are served.
+    pub fn start<P, N>(&self, policy: P, notify: N)
+    where
+        P: Fn() -> AvatarPolicy + Send + 'static,
+        N: Fn(&AvatarUpdate) + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel::<FetchJob>();
+        let worker = Fetcher {
+            dir: self.dir.clone(),
+            requested: self.requested.clone(),
+            policy: Box::new(policy),
+            notify: Box::new(notify),
+        };
+        std::thread::spawn(move || {
+            let client = match reqwest::blocking::Client::builder()
+                .timeout(FETCH_TIMEOUT)
+                .user_agent("colbex")
+                .build()
+            {
+                Ok(client) => client,
+                Err(_) => return,
+            };
+            for job in rx {
+                worker.process(&client, job);
+            }
+        });
+        *self.fetcher.lock().unwrap() = Some(tx);
+    }
+
+    /// Asset URL of the best avatar available right now. `github_username`
+    /// is only passed for the local user, whose account is known from origin.
+    pub fn url_for(&self, email: &str, name: &str, github_username: Option<&str>) -> String {
+        let key = avatar_key(email, name);
+        let url = match cached_remote(&self.dir, &key) {
+            Some(path) => asset_url(&path),
+            None => self.generated_url(&key, name),
+        };
+        self.request(&key, email, github_username);
+        url
+    }
+
+    /// Drops every cached and generated image.
+    pub fn clear(&self) -> Result<(), String> {
+        self.requested.lock().unwrap().clear();
+        match fs::remove_dir_all(&self.dir) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn generated_url(&self, key: &str, name: &str) -> String {
+        let path = self.dir.join(format!("{}.svg", key));
+        if path.exists() {
+            return asset_url(&path);
+        }
+        let svg = avatar_svg(key, name);
+        match fs::create_dir_all(&self.dir).and_then(|_| fs::write(&path, &svg)) {
+            Ok(()) => asset_url(&path),
+            // An unwritable cache still gets a picture
+            Err(_) => format!(
+                "data:image/svg+xml;base64,{}",
+                base64::engine::general_purpose::STANDARD.encode(svg)
+            ),
+        }
+    }
+
+    fn request(&self, key: &str, email: &str, github_username: Option<&str>) {
+        let fetcher = self.fetcher.lock().unwrap();
+        let Some(tx) = fetcher.as_ref() else {
+            return;
+        };
+        if !self.requested.lock().unwrap().insert(key.to_string()) {
+            return;
+        }
+        let candidates = remote_candidates(email, github_username);
+        if candidates.is_empty() {
+            return;
+        }
+        let _ = tx.send(FetchJob { key: key.to_string(), candidates });
+    }
+}
+
+/// Wires the worker to the git settings and the frontend event bus.
+pub fn start_fetcher(app_handle: &AppHandle) {
+    let settings_handle = app_handle.clone();
+    let emit_handle = app_handle.clone();
+    app_handle.state::<AvatarService>().start(
+        move || {
+            let state = settings_handle.state::<SettingsState>();
+            let git = state.store.lock().unwrap().get_settings().git;
+            AvatarPolicy {
+                network: git.avatar_network_fetch,
+                ttl: Duration::from_secs(u64::from(git.avatar_cache_ttl_hours) * 3600),
+            }
+        },
+        move |update| {
+            let _ = emit_handle.emit("git-avatar-updated", update);
+        },
+    );
+}
+
+#[tauri::command]
+pub fn git_avatar_clear_cache(avatars: State<'_, AvatarService>) -> Result<(), String> {
+    avatars.clear()
+}
+
+enum FetchOutcome {
+    Image(Vec<u8>, &'static str),
+    /// Every candidate answered 404; remembered so it is not asked again until the TTL expires.
+    Missing,
+    /// Network trouble, retried next session.
+    Failed,
+}
+
+struct Fetcher {
+    dir: PathBuf,
+    requested: Arc<Mutex<HashSet<String>>>,
+    policy: Box<dyn Fn() -> AvatarPolicy + Send>,
+    notify: Box<dyn Fn(&AvatarUpdate) + Send>,
+}
+
+impl Fetcher {
+    fn process(&self, client: &reqwest::blocking::Client, job: FetchJob) {
+        let policy = (self.policy)();
+        if !policy.network {
+            // Allow a retry once the setting is switched back on
+            self.requested.lock().unwrap().remove(&job.key);
+            return;
+        }
+        if is_fresh(&self.dir, &job.key, policy.ttl) {
+            return;
+        }
+
+        match fetch_first(client, &job.candidates) {
+            FetchOutcome::Image(bytes, ext) => {
+                let previous = cached_remote(&self.dir, &job.key)
+                    .unwrap_or_else(|| self.dir.join(format!("{}.svg", job.key)));
+                if let Ok(path) = store_remote(&self.dir, &job.key, &bytes, ext) {
+                    (self.notify)(&AvatarUpdate {
+                        key: job.key,
+                        previous: asset_url(&previous),
+                        url: asset_url(&path),
+                    });
+                }
+            }
+            FetchOutcome::Missing => {
+                let _ = fs::create_dir_all(&self.dir)
+                    .and_then(|_| fs::write(self.dir.join(format!("{}.miss", job.key)), b""));
+            }
+            FetchOutcome::Failed => {}
+        }
+    }
+}
+
+fn fetch_first(client: &reqwest::blocking::Client, candidates: &[String]) -> FetchOutcome {
+    let mut all_missing = true;
+    for url in candidates {
+        let response = match client.get(url).send() {
+            Ok(response) => response,
+            Err(_) => {
+                all_missing = false;
+                continue;
+            }
+        };
+        let status = response.status();
+        if status.is_success() {
+            let ext = response.headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(image_extension);
+            if let (Some(ext), Ok(bytes)) = (ext, response.bytes()) {
+                return FetchOutcome::Image(bytes.to_vec(), ext);
+            }
+        } else if status != reqwest::StatusCode::NOT_FOUND {
+            all_missing = false;
+        }
+    }
+    if all_missing { FetchOutcome::Missing } else { FetchOutcome::Failed }
+}
+
+fn image_extension(content_type: &str) -> Option<&'static str> {
+    match content_type.split(';').next()?.trim() {
+        "image/png" => Some("png"),
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+fn store_remote(dir: &Path, key: &str, bytes: &[u8], ext: &str) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let tmp = dir.join(format!("{}.tmp", key));
+    fs::write(&tmp, bytes)?;
+    for other in REMOTE_EXTENSIONS.iter().filter(|e| **e != ext) {
+        let _ = fs::remove_file(dir.join(format!("{}.{}", key, other)));
+    }
+    let _ = fs::remove_file(dir.join(format!("{}.miss", key)));
+    let path = dir.join(format!("{}.{}", key, ext));
+    fs::rename(&tmp, &path)?;
+    Ok(path)
+}
+
+fn cached_remote(dir: &Path, key: &str) -> Option<PathBuf> {
+    REMOTE_EXTENSIONS.iter()
+        .map(|ext| dir.join(format!("{}.{}", key, ext)))
+        .find(|path| path.is_file())
+}
+
+/// A fetched image or a recorded miss younger than `ttl`.
+fn is_fresh(dir: &Path, key: &str, ttl: Duration) -> bool {
+    let miss = dir.join(format!("{}.miss", key));
+    cached_remote(dir, key).into_iter().chain(Some(miss)).any(|path| {
+        fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .is_some_and(|age| age < ttl)
+    })
+}
+
+/// File name stem for an author. Hashed so no email ends up on disk.
+fn avatar_key(email: &str, name: &str) -> String {
+    let email = email.trim().to_lowercase();
+    let source = if email.is_empty() { format!("name:{}", name.trim()) } else { email };
+    format!("{:x}", Sha256::digest(source.as_bytes()))
+}
+
+/// Where to look the author up, most specific first.
+/// Author names are not used: a name that happens to match a GitHub account
+/// would show a stranger's picture.
+fn remote_candidates(email: &str, github_username: Option<&str>) -> Vec<String> {
+    let mut candidates = Vec::new();
+    let email = email.trim().to_lowercase();
+
+    if email.ends_with("@users.noreply.github.com") {
+        let local = email.split('@').next().unwrap_or("");
+        let username = local.split_once('+').map(|(_, user)| user).unwrap_or(local);
+        if !username.is_empty() {
+            candidates.push(github_avatar(username));
+        }
+    }
+    if let Some(username) = github_username {
+        candidates.push(github_avatar(username));
+    }
+    if !email.is_empty() {
+        candidates.push(format!("https://www.gravatar.com/avatar/{}?s=80&d=404", md5_hash(&email)));
+    }
+
+    candidates.dedup();
+    candidates
+}
+
+fn github_avatar(username: &str) -> String {
+    format!("https://github.com/{}.png?size=80", username)
+}
+
+fn md5_hash(input: &str) -> String {
+    use md5::{Md5, Digest};
+    let mut hasher = Md5::new();
+    hasher.update(input.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Same URL the frontend's `convertFileSrc` builds for the asset protocol.
+fn asset_url(path: &Path) -> String {
+    let encoded = encode_uri_component(&path.to_string_lossy());
+    if cfg!(windows) {
+        format!("http://asset.localhost/{}", encoded)
+    } else {
+        format!("asset://localhost/{}", encoded)
+    }
+}
+
+fn encode_uri_component(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9'
+            | b'-' | b'_' | b'.' | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Initials on a colour derived from the key, or a mirrored 5x5 identicon.
+fn avatar_svg(key: &str, name: &str) -> String {
+    let hue = u32::from_str_radix(&key[..4], 16).unwrap_or(0) % 360;
+    let initials: String = name
+        .split_whitespace()
+        .filter_map(|word| word.chars().find(|c| c.is_alphabetic()))
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect();
+
+    let body = if initials.is_empty() {
+        let bits = u32::from_str_radix(&key[4..8], 16).unwrap_or(0);
+        let mut cells = String::new();
+        for row in 0..5 {
+            for col in 0..3 {
+                if bits & (1 << (row * 3 + col)) == 0 {
+                    continue;
+                }
+                for x in [col, 4 - col] {
+                    cells.push_str(&format!(r#"<rect x="{}" y="{}" width="14" height="14"/>"#, 5 + x * 14, 5 + row * 14));
+                    if x == 2 {
+                        break;
+                    }
+                }
+            }
+        }
+        format!(
+            r#"<rect width="80" height="80" fill="hsl({hue},30%,92%)"/><g fill="hsl({hue},55%,45%)">{cells}</g>"#
+        )
+    } else {
+        format!(
+            r##"<rect width="80" height="80" fill="hsl({hue},55%,45%)"/><text x="40" y="40" dy=".35em" text-anchor="middle" fill="#fff" font-family="system-ui,sans-serif" font-size="32">{initials}</text>"##
+        )
+    };
+    format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="80" height="80" viewBox="0 0 80 80">{}</svg>"#, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_key_hides_email() {
+        let key = avatar_key(" Alice@Example.com ", "Alice");
+        assert_eq!(key, avatar_key("alice@example.com", "Someone else"));
+        assert_eq!(key.len(), 64);
+        assert!(!key.contains("alice"));
+        assert_ne!(avatar_key("", "Alice"), avatar_key("", "Bob"));
+    }
+
+    #[test]
+    fn test_generated_avatars_are_deterministic() {
+        let key = avatar_key("alice@example.com", "");
+        assert_eq!(avatar_svg(&key, "alice smith"), avatar_svg(&key, "alice smith"));
+        assert!(avatar_svg(&key, "alice smith").contains(">AS</text>"));
+        let identicon = avatar_svg(&key, "1234");
+        assert!(!identicon.contains("<text"));
+        assert_ne!(identicon, avatar_svg(&avatar_key("bob@example.com", ""), "1234"));
+    }
+
+    #[test]
+    fn test_asset_url_is_encoded() {
+        let url = asset_url(Path::new("/tmp/my cache/a#b.svg"));
+        assert!(url.ends_with("%2Ftmp%2Fmy%20cache%2Fa%23b.svg"));
+    }
+
+    #[test]
+    fn test_candidates_prefer_github_handles() {
+        let candidates = remote_candidates("12345+octocat@users.noreply.github.com", None);
+        assert_eq!(candidates[0], "https://github.com/octocat.png?size=80");
+        assert!(candidates[1].starts_with("https://www.gravatar.com/avatar/"));
+        assert!(candidates[1].ends_with("d=404"));
+        assert_eq!(remote_candidates("", None), Vec::<String>::new());
+        assert_eq!(remote_candidates("", Some("octocat")), vec!["https://github.com/octocat.png?size=80"]);
+    }
+
+    #[test]
+    fn test_url_for_serves_generated_then_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let service = AvatarService::new(dir.path().to_path_buf());
+        let first = service.url_for("alice@example.com", "Alice", None);
+        assert!(first.ends_with(".svg"));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let key = avatar_key("alice@example.com", "Alice");
+        store_remote(dir.path(), &key, b"png", "png").unwrap();
+        assert!(service.url_for("alice@example.com", "Alice", None).ends_with(".png"));
+        assert!(is_fresh(dir.path(), &key, Duration::from_secs(60)));
+        assert!(!is_fresh(dir.path(), &key, Duration::ZERO));
+
+        service.clear().unwrap();
+        assert!(!dir.path().exists());
+    }
+
+    fn serve(responses: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for (stream, response) in listener.incoming().zip(responses) {
+                let mut stream = stream.unwrap();
+                let mut buf = [0u8; 2048];
+                let _ = stream.read(&mut buf);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}/avatar", addr)
+    }
+
+    #[test]
+    fn test_fetch_classifies_responses() {
+        let client = reqwest::blocking::Client::new();
+        let not_found = "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        let image = "HTTP/1.1 200 OK\r\nContent-Type: image/jpeg\r\nContent-Length: 3\r\nConnection: close\r\n\r\njpg";
+
+        let url = serve(vec![not_found, image]);
+        match fetch_first(&client, &[url.clone(), url]) {
+            FetchOutcome::Image(bytes, ext) => assert_eq!((bytes.as_slice(), ext), (&b"jpg"[..], "jpg")),
+            _ => panic!("expected an image"),
+        }
+
+        let url = serve(vec![not_found]);
+        assert!(matches!(fetch_first(&client, &[url]), FetchOutcome::Missing));
+    }
+
+    #[test]
+    fn test_worker_respects_network_setting() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let fetcher = Fetcher {
+            dir: dir.path().to_path_buf(),
+            requested: Arc::new(Mutex::new(HashSet::from(["k".to_string()]))),
+            policy: Box::new(|| AvatarPolicy { network: false, ttl: Duration::from_secs(60) }),
+            notify: Box::new(move |update| tx.send(update.clone()).unwrap()),
+        };
+        let client = reqwest::blocking::Client::new();
+        let url = serve(vec!["HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: 3\r\n\r\npng"]);
+        fetcher.process(&client, FetchJob { key: "k".to_string(), candidates: vec![url] });
+        assert!(rx.try_recv().is_err());
+        assert!(fetcher.requested.lock().unwrap().is_empty());
+        assert!(cached_remote(dir.path(), "k").is_none());
+    }
+}
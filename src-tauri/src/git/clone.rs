@@ -0,0 +1,451 @@
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{CheckoutNotificationType, Direction, FetchOptions, RemoteCallbacks, Repository, SubmoduleUpdateOptions};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, State};
+
+use super::credentials::{self, CredentialSession};
+
+/// Cancellation flags of the clones currently running, by clone id.
+#[derive(Default)]
+pub struct GitCloneState {
+    running: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+}
+
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct GitCloneOptions {
+    /// Branch to check out instead of the remote's default.
+    pub branch: Option<String>,
+    /// Shallow clone with this many commits of history.
+    pub depth: Option<u32>,
+    /// Fetch only the checked-out branch.
+    pub single_branch: bool,
+    /// Initialise and update submodules recursively after checkout.
+    pub recurse_submodules: bool,
+}
+
+/// Payload of the `git-clone-progress` event.
+#[derive(Clone, Serialize, Default, Debug)]
+pub struct GitCloneProgress {
+    pub clone_id: String,
+    /// "receiving", "resolving", "checkout" or "submodule"
+    pub stage: String,
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub indexed_deltas: usize,
+    pub total_deltas: usize,
+    pub received_bytes: usize,
+    pub checkout_completed: usize,
+    pub checkout_total: usize,
+    /// Submodule being cloned, relative to the top-level repository.
+    pub submodule: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitCloneResult {
+    pub clone_id: String,
+    pub path: String,
+    pub branch: Option<String>,
+    pub submodules: Vec<String>,
+}
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Emits progress, throttled so a fast transfer doesn't flood the frontend.
+/// Stage changes are always sent.
+struct ProgressReporter<'a> {
+    on_progress: Box<dyn FnMut(&GitCloneProgress) + 'a>,
+    current: GitCloneProgress,
+    last_emit: Option<Instant>,
+}
+
+impl ProgressReporter<'_> {
+    fn update(&mut self, apply: impl FnOnce(&mut GitCloneProgress)) {
+        let stage = self.current.stage.clone();
+        apply(&mut self.current);
+
+        let due = self.last_emit.map(|t| t.elapsed() >= PROGRESS_INTERVAL).unwrap_or(true);
+        if due || stage != self.current.stage {
+            (self.on_progress)(&self.current);
+            self.last_emit = Some(Instant::now());
+        }
+    }
+
+    fn finish_stage(&mut self) {
+        (self.on_progress)(&self.current);
+    }
+}
+
+/// Removes the clone from the running set however the clone ends.
+struct RunningGuard {
+    running: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    clone_id: String,
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(&self.clone_id);
+        }
+    }
+}
+
+fn fetch_options<'a>(
+    mut session: CredentialSession,
+    reporter: &'a RefCell<ProgressReporter<'_>>,
+    cancelled: &'a AtomicBool,
+    depth: Option<u32>,
+) -> FetchOptions<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| session.callback(url, username, allowed));
+    callbacks.transfer_progress(move |stats| {
+        reporter.borrow_mut().update(|p| {
+            p.stage = if stats.received_objects() < stats.total_objects() { "receiving" } else { "resolving" }.to_string();
+            p.received_objects = stats.received_objects();
+            p.total_objects = stats.total_objects();
+            p.indexed_deltas = stats.indexed_deltas();
+            p.total_deltas = stats.total_deltas();
+            p.received_bytes = stats.received_bytes();
+        });
+        // false aborts the transfer
+        !cancelled.load(Ordering::Relaxed)
+    });
+
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(callbacks);
+    if let Some(depth) = depth.filter(|d| *d > 0) {
+        options.depth(depth as i32);
+    }
+    options
+}
+
+fn checkout_builder<'a>(reporter: &'a RefCell<ProgressReporter<'_>>, cancelled: &'a AtomicBool) -> CheckoutBuilder<'a> {
+    let mut checkout = CheckoutBuilder::new();
+    // The progress callback cannot abort; notify runs for every file before
+    // it is written and false stops the checkout.
+    checkout.notify_on(CheckoutNotificationType::UPDATED);
+    checkout.notify(move |_why, _path, _baseline, _target, _workdir| !cancelled.load(Ordering::Relaxed));
+    checkout.progress(move |_path, completed, total| {
+        reporter.borrow_mut().update(|p| {
+            p.stage = "checkout".to_string();
+            p.checkout_completed = completed;
+            p.checkout_total = total;
+        });
+    });
+    checkout
+}
+
+/// Asks the remote for its default branch; needed to narrow the refspec of
+/// a single-branch clone when no branch was given.
+fn remote_default_branch(mut session: CredentialSession, url: &str) -> Result<String, String> {
+    let mut remote = git2::Remote::create_detached(url).map_err(|e| e.to_string())?;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| session.callback(url, username, allowed));
+    let connection = remote.connect_auth(Direction::Fetch, Some(callbacks), None)
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    let head = connection.default_branch().map_err(|e| format!("Cannot determine default branch: {}", e))?;
+    let head = head.as_str().ok_or("Default branch name is not valid UTF-8")?;
+    Ok(head.strip_prefix("refs/heads/").unwrap_or(head).to_string())
+}
+
+fn update_submodules(
+    new_session: &dyn Fn() -> CredentialSession,
+    repo: &Repository,
+    prefix: &Path,
+    reporter: &RefCell<ProgressReporter<'_>>,
+    cancelled: &AtomicBool,
+    updated: &mut Vec<String>,
+) -> Result<(), String> {
+    for mut submodule in repo.submodules().map_err(|e| e.to_string())? {
+        if cancelled.load(Ordering::Relaxed) {
+            return Err("Clone cancelled".to_string());
+        }
+
+        let path = prefix.join(submodule.path());
+        let display = path.to_string_lossy().replace('\\', "/");
+        reporter.borrow_mut().update(|p| {
+            *p = GitCloneProgress {
+                clone_id: p.clone_id.clone(),
+                stage: "submodule".to_string(),
+                submodule: Some(display.clone()),
+                ..Default::default()
+            };
+        });
+
+        let mut options = SubmoduleUpdateOptions::new();
+        options.fetch(fetch_options(new_session(), reporter, cancelled, None));
+        options.checkout(checkout_builder(reporter, cancelled));
+        submodule.update(true, Some(&mut options)).map_err(|e| {
+            if cancelled.load(Ordering::Relaxed) {
+                "Clone cancelled".to_string()
+            } else {
+                format!("Failed to update submodule '{}': {}", display, e)
+            }
+        })?;
+        updated.push(display);
+
+        let nested = submodule.open().map_err(|e| e.to_string())?;
+        update_submodules(new_session, &nested, &path, reporter, cancelled, updated)?;
+    }
+    Ok(())
+}
+
+fn remove_partial_clone(target: &Path, existed: bool) -> std::io::Result<()> {
+    if !existed {
+        return match std::fs::remove_dir_all(target) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            other => other,
+        };
+    }
+    for entry in std::fs::read_dir(target)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Clones and, when cancelled, removes whatever was written: the whole
+/// directory if the clone created it, otherwise its contents. Only a missing
+/// or empty destination is accepted, so cleanup never touches other files.
+fn clone_repository(
+    clone_id: &str,
+    url: &str,
+    path: &str,
+    options: GitCloneOptions,
+    cancelled: &AtomicBool,
+    new_session: &dyn Fn() -> CredentialSession,
+    on_progress: impl FnMut(&GitCloneProgress),
+) -> Result<GitCloneResult, String> {
+    let target = Path::new(path);
+    let existed = target.exists();
+    if existed {
+        let empty = std::fs::read_dir(target)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if !empty {
+            return Err(format!("Destination '{}' already exists and is not an empty directory", path));
+        }
+    }
+    let result = clone_into(clone_id, url, path, options, cancelled, new_session, on_progress);
+    if result.is_err() && cancelled.load(Ordering::Relaxed) {
+        if let Err(e) = remove_partial_clone(target, existed) {
+            eprintln!("Failed to clean up cancelled clone at {}: {}", path, e);
+        }
+    }
+    result
+}
+
+fn clone_into(
+    clone_id: &str,
+    url: &str,
+    path: &str,
+    options: GitCloneOptions,
+    cancelled: &AtomicBool,
+    new_session: &dyn Fn() -> CredentialSession,
+    on_progress: impl FnMut(&GitCloneProgress),
+) -> Result<GitCloneResult, String> {
+    let reporter = RefCell::new(ProgressReporter {
+        on_progress: Box::new(on_progress),
+        current: GitCloneProgress { clone_id: clone_id.to_string(), ..Default::default() },
+        last_emit: None,
+    });
+
+    let branch = match options.branch.filter(|b| !b.trim().is_empty()) {
+        Some(branch) => Some(branch.trim().to_string()),
+        None if options.single_branch => Some(remote_default_branch(new_session(), url)?),
+        None => None,
+    };
+
+    let mut builder = RepoBuilder::new();
+    builder.fetch_options(fetch_options(new_session(), &reporter, cancelled, options.depth));
+    builder.with_checkout(checkout_builder(&reporter, cancelled));
+    if let Some(branch) = &branch {
+        builder.branch(branch);
+        if options.single_branch {
+            let refspec = format!("+refs/heads/{0}:refs/remotes/origin/{0}", branch);
+            builder.remote_create(move |repo, name, url| repo.remote_with_fetch(name, url, &refspec));
+        }
+    }
+
+    let repo = builder.clone(url, Path::new(path)).map_err(|e| {
+        if cancelled.load(Ordering::Relaxed) {
+            "Clone cancelled".to_string()
+        } else {
+            e.to_string()
+        }
+    })?;
+    // Cancelled while the last files were written
+    if cancelled.load(Ordering::Relaxed) {
+        return Err("Clone cancelled".to_string());
+    }
+    reporter.borrow_mut().finish_stage();
+
+    let mut submodules = Vec::new();
+    if options.recurse_submodules {
+        update_submodules(new_session, &repo, Path::new(""), &reporter, cancelled, &mut submodules)?;
+        reporter.borrow_mut().finish_stage();
+    }
+
+    let checked_out = repo.head().ok()
+        .and_then(|head| head.shorthand().map(|s| s.to_string()));
+
+    Ok(GitCloneResult {
+        clone_id: clone_id.to_string(),
+        path: path.to_string(),
+        branch: checked_out.or(branch),
+        submodules,
+    })
+}
+
+/// Clones `url` into `path`, streaming `git-clone-progress` events. Pass a
+/// `clone_id` to be able to cancel with `git_cancel_clone`; otherwise one is
+/// generated and reported in the progress events.
+#[tauri::command]
+pub async fn git_clone(
+    app_handle: AppHandle,
+    state: State<'_, GitCloneState>,
+    url: String,
+    path: String,
+    options: Option<GitCloneOptions>,
+    clone_id: Option<String>,
+) -> Result<GitCloneResult, String> {
+    let clone_id = clone_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let cancelled = Arc::new(AtomicBool::new(false));
+    {
+        let mut running = state.running.lock().map_err(|e| e.to_string())?;
+        if running.contains_key(&clone_id) {
+            return Err(format!("Clone '{}' is already running", clone_id));
+        }
+        running.insert(clone_id.clone(), cancelled.clone());
+    }
+    let guard = RunningGuard { running: state.running.clone(), clone_id: clone_id.clone() };
+
+    let result = tokio::task::spawn_blocking(move || {
+        let new_session = || credentials::session_for(&app_handle);
+        let on_progress = |progress: &GitCloneProgress| {
+            let _ = app_handle.emit("git-clone-progress", progress);
+        };
+        clone_repository(&clone_id, &url, &path, options.unwrap_or_default(), &cancelled, &new_session, on_progress)
+    })
+    .await
+    .map_err(|e| format!("Clone task failed: {}", e))?;
+
+    drop(guard);
+    result
+}
+
+#[tauri::command]
+pub fn git_cancel_clone(state: State<'_, GitCloneState>, clone_id: String) -> Result<(), String> {
+    let running = state.running.lock().map_err(|e| e.to_string())?;
+    match running.get(&clone_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            Ok(())
+        }
+        None => Err(format!("No running clone '{}'", clone_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Repository with a few committed files and its `file://` URL.
+    fn source_repo(dir: &Path) -> String {
+        let repo = Repository::init(dir).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(dir.join(name), name).unwrap();
+        }
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[]).unwrap();
+        format!("file://{}", dir.display())
+    }
+
+    #[test]
+    fn test_local_clone_reports_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = source_repo(&dir.path().join("src"));
+        let out = dir.path().join("out");
+        let cancelled = AtomicBool::new(false);
+        let mut events = Vec::new();
+
+        let result = clone_repository(
+            "c1",
+            &url,
+            &out.to_string_lossy(),
+            GitCloneOptions::default(),
+            &cancelled,
+            &credentials::test_session,
+            |progress| events.push(progress.clone()),
+        )
+        .unwrap();
+
+        assert!(out.join("c.txt").exists());
+        assert!(result.branch.is_some());
+        assert!(events.iter().all(|e| e.clone_id == "c1"));
+        let checkout = events.iter().rev().find(|e| e.stage == "checkout").unwrap();
+        assert_eq!(checkout.checkout_completed, checkout.checkout_total);
+        assert_eq!(checkout.checkout_total, 3);
+    }
+
+    #[test]
+    fn test_cancelled_clone_removes_what_it_wrote() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = source_repo(&dir.path().join("src"));
+        let cancelled = AtomicBool::new(false);
+        let cancel = |_: &GitCloneProgress| cancelled.store(true, Ordering::Relaxed);
+
+        let out = dir.path().join("out");
+        let err = clone_repository("c1", &url, &out.to_string_lossy(), GitCloneOptions::default(), &cancelled, &credentials::test_session, cancel)
+            .unwrap_err();
+        assert_eq!(err, "Clone cancelled");
+        assert!(!out.exists());
+
+        // A directory picked by the user is kept, but emptied
+        cancelled.store(false, Ordering::Relaxed);
+        let existing = dir.path().join("existing");
+        std::fs::create_dir(&existing).unwrap();
+        let err = clone_repository("c2", &url, &existing.to_string_lossy(), GitCloneOptions::default(), &cancelled, &credentials::test_session, cancel)
+            .unwrap_err();
+        assert_eq!(err, "Clone cancelled");
+        assert_eq!(std::fs::read_dir(&existing).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_clone_into_populated_directory_keeps_its_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = source_repo(&dir.path().join("src"));
+        let populated = dir.path().join("populated");
+        std::fs::create_dir_all(populated.join("nested")).unwrap();
+        std::fs::write(populated.join("notes.txt"), "mine").unwrap();
+        std::fs::write(populated.join("nested/data.txt"), "mine too").unwrap();
+
+        // Cancelled before the clone could even start
+        let cancelled = AtomicBool::new(true);
+        let err = clone_repository("c1", &url, &populated.to_string_lossy(), GitCloneOptions::default(), &cancelled, &credentials::test_session, |_| {})
+            .unwrap_err();
+        assert!(err.contains("not an empty directory"), "{}", err);
+        assert_eq!(std::fs::read_to_string(populated.join("notes.txt")).unwrap(), "mine");
+        assert_eq!(std::fs::read_to_string(populated.join("nested/data.txt")).unwrap(), "mine too");
+
+        // A file in the way is refused the same way
+        let file = dir.path().join("file");
+        std::fs::write(&file, "x").unwrap();
+        assert!(clone_repository("c2", &url, &file.to_string_lossy(), GitCloneOptions::default(), &cancelled, &credentials::test_session, |_| {}).is_err());
+        assert!(file.exists());
+    }
+}
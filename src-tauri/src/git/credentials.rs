@@ -0,0 +1,491 @@
+use base64::Engine;
+use git2::{Cred, CredentialType, Error};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::api_keys::ApiKeyStore;
+use crate::settings::{self, SettingsState};
+
+use super::expand_home;
+
+pub use crate::settings::{CredentialSource, GitCredentialConfig};
+
+/// Key store ids of host tokens are `git:<host>`.
+const TOKEN_PREFIX: &str = "git:";
+
+#[derive(Clone, Debug)]
+struct HostToken {
+    username: String,
+    token: String,
+}
+
+/// Stored token as listed to the frontend; the secret itself never leaves the backend.
+#[derive(Serialize, Clone, Debug)]
+pub struct StoredTokenInfo {
+    pub host: String,
+    pub username: String,
+}
+
+/// Payload of `git-credential-prompt`. Answer with `git_credentials_respond`.
+#[derive(Serialize, Clone, Debug)]
+pub struct CredentialPrompt {
+    pub prompt_id: String,
+    /// Currently always "ssh_passphrase"
+    pub kind: String,
+    pub url: String,
+    pub key_path: Option<String>,
+}
+
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+type PendingPrompts = Arc<Mutex<HashMap<String, mpsc::Sender<Option<String>>>>>;
+
+/// Passphrase prompts waiting on the frontend. The chain lives in the git
+/// settings and host tokens in the API key store, so both survive restarts.
+#[derive(Default)]
+pub struct GitCredentialState {
+    pending: PendingPrompts,
+}
+
+impl GitCredentialState {
+    fn session(&self, app_handle: &AppHandle) -> CredentialSession {
+        let config = app_handle.state::<SettingsState>().store.lock()
+            .map(|store| store.get_settings().git.credentials)
+            .unwrap_or_default();
+        let tokens = host_tokens(&app_handle.state::<Mutex<ApiKeyStore>>());
+        let prompter = Prompter { app_handle: app_handle.clone(), pending: self.pending.clone() };
+        CredentialSession::new(&config, tokens, Some(prompter))
+    }
+}
+
+/// Stored as `<username>:<token>`; user names cannot contain a colon in HTTP Basic auth.
+fn parse_token(value: &str) -> Option<HostToken> {
+    let (username, token) = value.split_once(':')?;
+    Some(HostToken { username: username.to_string(), token: token.to_string() })
+}
+
+/// Every stored token by host.
+fn host_tokens(keys: &Mutex<ApiKeyStore>) -> HashMap<String, HostToken> {
+    let Ok(keys) = keys.lock() else {
+        return HashMap::new();
+    };
+    keys.global_keys()
+        .filter_map(|(id, value)| Some((id.strip_prefix(TOKEN_PREFIX)?.to_string(), parse_token(value)?)))
+        .collect()
+}
+
+/// API token for `host`: the stored one, else the GitHub CLI's login.
+pub(crate) fn api_token(keys: &Mutex<ApiKeyStore>, host: &str) -> Option<String> {
+    let stored = keys.lock().ok()
+        .and_then(|keys| keys.get_key(&format!("{}{}", TOKEN_PREFIX, host), None))
+        .and_then(|value| parse_token(&value))
+        .map(|token| token.token);
+    stored.or_else(|| gh_cli_token(host))
+}
+
+/// `gh auth token` for `host`, if the GitHub CLI is installed and logged in there.
+pub(crate) fn gh_cli_token(host: &str) -> Option<String> {
+    let output = std::process::Command::new("gh")
+        .args(["auth", "token", "--hostname", host])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!token.is_empty()).then_some(token)
+}
+
+/// New credential session for one remote operation (clone, fetch, pull, push).
+pub(crate) fn session_for(app_handle: &AppHandle) -> CredentialSession {
+    app_handle.state::<GitCredentialState>().session(app_handle)
+}
+
+/// Asks the frontend for a secret and blocks until it answers or times out.
+struct Prompter {
+    app_handle: AppHandle,
+    pending: PendingPrompts,
+}
+
+impl Prompter {
+    fn ask(&self, url: &str, key_path: &Path) -> Option<String> {
+        let prompt_id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = mpsc::channel();
+        self.pending.lock().ok()?.insert(prompt_id.clone(), tx);
+
+        let prompt = CredentialPrompt {
+            prompt_id: prompt_id.clone(),
+            kind: "ssh_passphrase".to_string(),
+            url: url.to_string(),
+            key_path: Some(key_path.to_string_lossy().to_string()),
+        };
+        let _ = self.app_handle.emit("git-credential-prompt", &prompt);
+
+        let answer = rx.recv_timeout(PROMPT_TIMEOUT).ok().flatten();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&prompt_id);
+        }
+        answer
+    }
+}
+
+enum Candidate {
+    StoredToken,
+    GithubCli,
+    SshAgent,
+    SshKey(PathBuf),
+    CredentialHelper,
+    SystemDefault,
+}
+
+impl Candidate {
+    fn credential_type(&self) -> CredentialType {
+        match self {
+            Candidate::StoredToken | Candidate::GithubCli | Candidate::CredentialHelper => CredentialType::USER_PASS_PLAINTEXT,
+            Candidate::SshAgent | Candidate::SshKey(_) => CredentialType::SSH_KEY,
+            Candidate::SystemDefault => CredentialType::DEFAULT,
+        }
+    }
+}
+
+/// Walks the configured chain across repeated libgit2 credential callbacks.
+/// libgit2 calls back again whenever the remote rejects what it was given, so
+/// every call moves on to the next candidate; once the chain or the attempt
+/// budget runs out the operation fails instead of looping forever.
+pub(crate) struct CredentialSession {
+    candidates: Vec<Candidate>,
+    tokens: HashMap<String, HostToken>,
+    prompter: Option<Prompter>,
+    next: usize,
+    attempts: usize,
+    max_attempts: usize,
+}
+
+impl CredentialSession {
+    fn new(config: &GitCredentialConfig, tokens: HashMap<String, HostToken>, prompter: Option<Prompter>) -> Self {
+        let mut candidates = Vec::new();
+        for source in &config.chain {
+            match source {
+                CredentialSource::StoredToken => candidates.push(Candidate::StoredToken),
+                CredentialSource::GithubCli => candidates.push(Candidate::GithubCli),
+                CredentialSource::SshAgent => candidates.push(Candidate::SshAgent),
+                CredentialSource::SshKeyFile => {
+                    let paths: Vec<PathBuf> = if config.ssh_key_paths.is_empty() {
+                        ["~/.ssh/id_ed25519", "~/.ssh/id_ecdsa", "~/.ssh/id_rsa"].iter().map(|p| expand_home(p)).collect()
+                    } else {
+                        config.ssh_key_paths.iter().map(|p| expand_home(p)).collect()
+                    };
+                    candidates.extend(paths.into_iter().map(Candidate::SshKey));
+                }
+                CredentialSource::CredentialHelper => candidates.push(Candidate::CredentialHelper),
+                CredentialSource::SystemDefault => candidates.push(Candidate::SystemDefault),
+            }
+        }
+
+        Self {
+            candidates,
+            tokens,
+            prompter,
+            next: 0,
+            attempts: 0,
+            max_attempts: config.max_attempts.max(1),
+        }
+    }
+
+    /// Signature of `RemoteCallbacks::credentials`.
+    pub(crate) fn callback(&mut self, url: &str, username_from_url: Option<&str>, allowed: CredentialType) -> Result<Cred, Error> {
+        // SSH asks for the user name on its own before any key
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username_from_url.unwrap_or("git"));
+        }
+
+        while self.next < self.candidates.len() && self.attempts < self.max_attempts {
+            let index = self.next;
+            self.next += 1;
+            if !allowed.intersects(self.candidates[index].credential_type()) {
+                continue;
+            }
+            if let Some(cred) = self.try_candidate(index, url, username_from_url) {
+                self.attempts += 1;
+                return Ok(cred);
+            }
+        }
+
+        Err(Error::from_str(&format!(
+            "Authentication failed for {} after {} attempt(s); add a token or SSH key for this host",
+            url, self.attempts
+        )))
+    }
+
+    fn try_candidate(&self, index: usize, url: &str, username_from_url: Option<&str>) -> Option<Cred> {
+        let username = username_from_url.unwrap_or("git");
+        match &self.candidates[index] {
+            Candidate::StoredToken => {
+                let token = self.tokens.get(&url_host(url)?)?;
+                Cred::userpass_plaintext(&token.username, &token.token).ok()
+            }
+            Candidate::GithubCli => {
+                let token = gh_cli_token(&url_host(url)?)?;
+                Cred::userpass_plaintext(username_from_url.unwrap_or("x-access-token"), &token).ok()
+            }
+            Candidate::SshAgent => Cred::ssh_key_from_agent(username).ok(),
+            Candidate::SshKey(path) => {
+                if !path.is_file() {
+                    return None;
+                }
+                let passphrase = if is_encrypted_key(path) {
+                    Some(self.prompter.as_ref()?.ask(url, path)?)
+                } else {
+                    None
+                };
+                let public_key = path.with_extension("pub");
+                let public_key = public_key.is_file().then_some(public_key);
+                Cred::ssh_key(username, public_key.as_deref(), path, passphrase.as_deref()).ok()
+            }
+            Candidate::CredentialHelper => {
+                let config = git2::Config::open_default().ok()?;
+                Cred::credential_helper(&config, url, username_from_url).ok()
+            }
+            Candidate::SystemDefault => Cred::default().ok(),
+        }
+    }
+}
+
+/// Session with the default chain and no way to prompt, for remotes that
+/// need no authentication.
+#[cfg(test)]
+pub(crate) fn test_session() -> CredentialSession {
+    CredentialSession::new(&GitCredentialConfig::default(), HashMap::new(), None)
+}
+
+/// Host part of an HTTPS, `ssh://` or scp-style (`git@host:owner/repo`) URL.
+pub(crate) fn url_host(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let authority = rest.split('/').next()?;
+    let host_port = authority.rsplit_once('@').map(|(_, host)| host).unwrap_or(authority);
+    let host = host_port.split(':').next()?.trim();
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_lowercase())
+    }
+}
+
+/// Whether a private key needs a passphrase (PEM or OpenSSH format).
+fn is_encrypted_key(path: &Path) -> bool {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    if contents.contains("ENCRYPTED") {
+        return true;
+    }
+
+    let body: String = contents.lines().filter(|line| !line.starts_with("-----")).collect();
+    let Ok(bytes) = base64::engine::general_purpose::STANDARD.decode(body.trim()) else {
+        return false;
+    };
+    // "openssh-key-v1\0" followed by a length-prefixed cipher name, "none" when unencrypted
+    const MAGIC: &[u8] = b"openssh-key-v1\0";
+    let Some(rest) = bytes.strip_prefix(MAGIC) else {
+        return false;
+    };
+    if rest.len() < 4 {
+        return false;
+    }
+    let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+    rest.get(4..4 + len).map(|cipher| cipher != b"none").unwrap_or(false)
+}
+
+#[tauri::command]
+pub fn git_credentials_get_config(settings: State<'_, SettingsState>) -> Result<GitCredentialConfig, String> {
+    let store = settings.store.lock().map_err(|e| e.to_string())?;
+    Ok(store.get_settings().git.credentials)
+}
+
+/// Saves the chain in the user's git settings.
+#[tauri::command]
+pub fn git_credentials_set_config(
+    app_handle: AppHandle,
+    settings: State<'_, SettingsState>,
+    config: GitCredentialConfig,
+) -> Result<(), String> {
+    let value = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+    settings::settings_update_value(app_handle, settings, "git".to_string(), "credentials".to_string(), value, "user".to_string())
+}
+
+#[tauri::command]
+pub fn git_credentials_set_token(keys: State<'_, Mutex<ApiKeyStore>>, host: String, username: String, token: String) -> Result<(), String> {
+    let host = url_host(&host).ok_or_else(|| format!("Invalid host '{}'", host))?;
+    if username.contains(':') {
+        return Err("User name cannot contain ':'".to_string());
+    }
+    if token.trim().is_empty() {
+        return Err("Token cannot be empty".to_string());
+    }
+    let mut keys = keys.lock().map_err(|e| e.to_string())?;
+    keys.set_key(&format!("{}{}", TOKEN_PREFIX, host), &format!("{}:{}", username, token.trim()), None)
+}
+
+#[tauri::command]
+pub fn git_credentials_remove_token(keys: State<'_, Mutex<ApiKeyStore>>, host: String) -> Result<(), String> {
+    let host = url_host(&host).ok_or_else(|| format!("Invalid host '{}'", host))?;
+    let mut keys = keys.lock().map_err(|e| e.to_string())?;
+    keys.remove_key(&format!("{}{}", TOKEN_PREFIX, host), None)
+}
+
+#[tauri::command]
+pub fn git_credentials_list_tokens(keys: State<'_, Mutex<ApiKeyStore>>) -> Result<Vec<StoredTokenInfo>, String> {
+    let mut list: Vec<StoredTokenInfo> = host_tokens(&keys).into_iter()
+        .map(|(host, token)| StoredTokenInfo { host, username: token.username })
+        .collect();
+    list.sort_by(|a, b| a.host.cmp(&b.host));
+    Ok(list)
+}
+
+/// Answers a `git-credential-prompt`; `None` skips the key.
+#[tauri::command]
+pub fn git_credentials_respond(state: State<'_, GitCredentialState>, prompt_id: String, secret: Option<String>) -> Result<(), String> {
+    let pending = state.pending.lock().map_err(|e| e.to_string())?;
+    let sender = pending.get(&prompt_id).ok_or_else(|| format!("No pending prompt '{}'", prompt_id))?;
+    sender.send(secret).map_err(|_| "Prompt is no longer waiting".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(chain: Vec<CredentialSource>, tokens: HashMap<String, HostToken>) -> CredentialSession {
+        let config = GitCredentialConfig { chain, ..Default::default() };
+        CredentialSession::new(&config, tokens, None)
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(url_host("https://github.com/owner/repo.git").as_deref(), Some("github.com"));
+        assert_eq!(url_host("https://user@GitLab.example.com:8443/x").as_deref(), Some("gitlab.example.com"));
+        assert_eq!(url_host("git@github.com:owner/repo.git").as_deref(), Some("github.com"));
+        assert_eq!(url_host("ssh://git@host.dev:22/repo").as_deref(), Some("host.dev"));
+        assert_eq!(url_host("github.com").as_deref(), Some("github.com"));
+        assert_eq!(url_host("file:///tmp/repo"), None);
+    }
+
+    #[test]
+    fn test_stored_token_offered_once() {
+        let mut tokens = HashMap::new();
+        tokens.insert("github.com".to_string(), HostToken { username: "me".to_string(), token: "secret".to_string() });
+        let mut session = session(vec![CredentialSource::StoredToken], tokens);
+
+        let url = "https://github.com/owner/repo.git";
+        assert!(session.callback(url, None, CredentialType::USER_PASS_PLAINTEXT).is_ok());
+        // The remote rejected it: the next callback must give up rather than retry
+        assert!(session.callback(url, None, CredentialType::USER_PASS_PLAINTEXT).is_err());
+    }
+
+    #[test]
+    fn test_skips_sources_of_wrong_type() {
+        let mut tokens = HashMap::new();
+        tokens.insert("github.com".to_string(), HostToken { username: "me".to_string(), token: "secret".to_string() });
+        let mut session = session(vec![CredentialSource::StoredToken], tokens);
+
+        let err = session.callback("ssh://git@github.com/owner/repo", None, CredentialType::SSH_KEY).err().unwrap();
+        assert!(err.message().contains("after 0 attempt(s)"));
+    }
+
+    #[test]
+    fn test_attempt_limit() {
+        let config = GitCredentialConfig {
+            chain: vec![CredentialSource::SystemDefault; 5],
+            max_attempts: 2,
+            ..Default::default()
+        };
+        let mut session = CredentialSession::new(&config, HashMap::new(), None);
+        assert!(session.callback("https://example.com/r", None, CredentialType::DEFAULT).is_ok());
+        assert!(session.callback("https://example.com/r", None, CredentialType::DEFAULT).is_ok());
+        assert!(session.callback("https://example.com/r", None, CredentialType::DEFAULT).is_err());
+    }
+
+    /// HTTP server answering everything with 401, counting requests that carried credentials.
+    fn unauthorized_server() -> (String, Arc<Mutex<usize>>) {
+        use std::io::{BufRead, BufReader, Write};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let authorized = Arc::new(Mutex::new(0));
+        let counter = authorized.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    if line.to_lowercase().starts_with("authorization:") {
+                        *counter.lock().unwrap() += 1;
+                    }
+                    line.clear();
+                }
+                let _ = (&stream).write_all(
+                    b"HTTP/1.1 401 Unauthorized\r\nWWW-Authenticate: Basic realm=\"git\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+            }
+        });
+        (base, authorized)
+    }
+
+    #[test]
+    fn test_attempt_limit_across_fetch_callbacks() {
+        let (base, authorized) = unauthorized_server();
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let mut remote = repo.remote_anonymous(&format!("{}/owner/repo.git", base)).unwrap();
+
+        let mut tokens = HashMap::new();
+        tokens.insert("127.0.0.1".to_string(), HostToken { username: "me".to_string(), token: "wrong".to_string() });
+        let config = GitCredentialConfig {
+            chain: vec![CredentialSource::StoredToken; 5],
+            max_attempts: 3,
+            ..Default::default()
+        };
+        let mut session = CredentialSession::new(&config, tokens, None);
+        let mut calls = 0;
+
+        let mut callbacks = git2::RemoteCallbacks::new();
+        callbacks.credentials(|url, username, allowed| {
+            calls += 1;
+            session.callback(url, username, allowed)
+        });
+        let mut options = git2::FetchOptions::new();
+        options.remote_callbacks(callbacks);
+        let err = remote.fetch(&[] as &[&str], Some(&mut options), None).unwrap_err();
+        drop(options);
+
+        // Three rejected tokens, then the session gives up instead of looping
+        assert!(err.message().contains("after 3 attempt(s)"), "{}", err.message());
+        assert_eq!(calls, 4);
+        assert_eq!(*authorized.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_parse_stored_token() {
+        let token = parse_token("me:ghp_abc:def").unwrap();
+        assert_eq!((token.username.as_str(), token.token.as_str()), ("me", "ghp_abc:def"));
+        assert!(parse_token("no-separator").is_none());
+    }
+
+    #[test]
+    #[ignore = "needs ssh-keygen; run with --ignored"]
+    fn test_detects_encrypted_openssh_key() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, passphrase, encrypted) in [("plain", "", false), ("locked", "hunter2", true)] {
+            let key = dir.path().join(name);
+            let status = std::process::Command::new("ssh-keygen")
+                .args(["-q", "-t", "ed25519", "-N", passphrase, "-C", "test", "-f"])
+                .arg(&key)
+                .status()
+                .unwrap();
+            assert!(status.success());
+            assert_eq!(is_encrypted_key(&key), encrypted, "{}", name);
+        }
+    }
+}
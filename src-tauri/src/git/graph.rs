@@ -0,0 +1,251 @@
+use serde::Serialize;
+
+/// One line segment from a row's center down to the next row's center.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct GitGraphEdge {
+    pub from_column: usize,
+    pub to_column: usize,
+    /// Commit the line is heading to (may be below the current page).
+    pub target: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GitGraphRow {
+    pub hash: String,
+    pub column: usize,
+    /// Number of columns in use on this row, for sizing the graph gutter.
+    pub width: usize,
+    pub edges: Vec<GitGraphEdge>,
+}
+
+/// Open lines below the last laid-out row: `lanes[i]` is the commit the
+/// line in column i is waiting for.
+pub type Lanes = Vec<Option<String>>;
+
+/// Assigns every commit a column and computes the connecting edges, like
+/// `git log --graph`. `commits` must be in topological order (children first)
+/// and each entry is `(hash, parent hashes)`.
+pub fn layout(commits: &[(String, Vec<String>)]) -> Vec<GitGraphRow> {
+    layout_from(Vec::new(), commits).0
+}
+
+/// `layout` continuing below the `lanes` a previous page left open. Also
+/// returns the lanes open after the last row, to continue the next page.
+pub fn layout_from(mut lanes: Lanes, commits: &[(String, Vec<String>)]) -> (Vec<GitGraphRow>, Lanes) {
+    let mut rows: Vec<GitGraphRow> = Vec::with_capacity(commits.len());
+
+    for (hash, parents) in commits {
+        let column = column_for(&lanes, hash);
+        if column == lanes.len() {
+            lanes.push(None);
+        }
+
+        // Every line waiting for this commit ends at its node
+        if let Some(prev) = rows.last_mut() {
+            end_lines_at(prev, hash, column);
+        }
+        for lane in lanes.iter_mut() {
+            if lane.as_deref() == Some(hash.as_str()) {
+                *lane = None;
+            }
+        }
+        lanes[column] = None;
+
+        let mut edges: Vec<GitGraphEdge> = lanes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, lane)| lane.as_ref().map(|target| GitGraphEdge {
+                from_column: i,
+                to_column: i,
+                target: target.clone(),
+            }))
+            .collect();
+
+        for (n, parent) in parents.iter().enumerate() {
+            let existing = lanes.iter().position(|l| l.as_ref() == Some(parent));
+            let to_column = match existing {
+                // First parent already has a line further right: pull it into this column
+                Some(existing) if n == 0 && existing > column => {
+                    lanes[existing] = None;
+                    lanes[column] = Some(parent.clone());
+                    for edge in edges.iter_mut().filter(|e| e.from_column == existing) {
+                        edge.to_column = column;
+                    }
+                    column
+                }
+                // Parent already has a line: join it instead of opening a new one
+                Some(existing) => existing,
+                None if n == 0 => {
+                    lanes[column] = Some(parent.clone());
+                    column
+                }
+                None => {
+                    let free = allocate(&mut lanes);
+                    lanes[free] = Some(parent.clone());
+                    free
+                }
+            };
+            edges.push(GitGraphEdge {
+                from_column: column,
+                to_column,
+                target: parent.clone(),
+            });
+        }
+
+        while matches!(lanes.last(), Some(None)) {
+            lanes.pop();
+        }
+
+        let width = edges
+            .iter()
+            .map(|e| e.from_column.max(e.to_column) + 1)
+            .chain(std::iter::once(column + 1))
+            .max()
+            .unwrap_or(1);
+
+        rows.push(GitGraphRow {
+            hash: hash.clone(),
+            column,
+            width,
+            edges,
+        });
+    }
+
+    (rows, lanes)
+}
+
+/// Ends the last row's lines at the column `next`, the first commit of the
+/// following page, will take, so the two pages join up.
+pub fn connect_next(rows: &mut [GitGraphRow], lanes: &Lanes, next: &str) {
+    if let Some(last) = rows.last_mut() {
+        end_lines_at(last, next, column_for(lanes, next));
+    }
+}
+
+fn end_lines_at(row: &mut GitGraphRow, hash: &str, column: usize) {
+    for edge in row.edges.iter_mut().filter(|e| e.target == hash) {
+        edge.to_column = column;
+        row.width = row.width.max(column + 1);
+    }
+}
+
+/// Column of the line waiting for `hash`, else the leftmost free one (which
+/// may be one past the end).
+fn column_for(lanes: &Lanes, hash: &str) -> usize {
+    lanes.iter()
+        .position(|l| l.as_deref() == Some(hash))
+        .or_else(|| lanes.iter().position(|l| l.is_none()))
+        .unwrap_or(lanes.len())
+}
+
+/// `lanes` as part of a page cursor: hashes separated by commas, empty for a free column.
+pub fn encode_lanes(lanes: &Lanes) -> String {
+    lanes.iter().map(|l| l.as_deref().unwrap_or("")).collect::<Vec<_>>().join(",")
+}
+
+pub fn decode_lanes(encoded: &str) -> Result<Lanes, String> {
+    if encoded.is_empty() {
+        return Ok(Vec::new());
+    }
+    encoded
+        .split(',')
+        .map(|hash| match hash {
+            "" => Ok(None),
+            hash if git2::Oid::from_str(hash).is_ok() => Ok(Some(hash.to_string())),
+            _ => Err(format!("Invalid graph lane '{}' in cursor", hash)),
+        })
+        .collect()
+}
+
+/// Index of the leftmost free column, growing the lane list when all are taken.
+fn allocate(lanes: &mut Vec<Option<String>>) -> usize {
+    match lanes.iter().position(|l| l.is_none()) {
+        Some(free) => free,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(hash: &str, parents: &[&str]) -> (String, Vec<String>) {
+        (hash.to_string(), parents.iter().map(|p| p.to_string()).collect())
+    }
+
+    #[test]
+    fn test_linear_history_uses_one_column() {
+        let rows = layout(&[commit("c", &["b"]), commit("b", &["a"]), commit("a", &[])]);
+        assert!(rows.iter().all(|r| r.column == 0 && r.width == 1));
+        assert!(rows[2].edges.is_empty());
+    }
+
+    #[test]
+    fn test_merge_opens_and_closes_a_lane() {
+        // m merges f into b; both sides come from a
+        let rows = layout(&[
+            commit("m", &["b", "f"]),
+            commit("f", &["a"]),
+            commit("b", &["a"]),
+            commit("a", &[]),
+        ]);
+
+        assert_eq!(rows[0].column, 0);
+        assert_eq!(rows[0].edges, vec![
+            GitGraphEdge { from_column: 0, to_column: 0, target: "b".into() },
+            GitGraphEdge { from_column: 0, to_column: 1, target: "f".into() },
+        ]);
+        assert_eq!(rows[1].column, 1);
+        assert_eq!(rows[2].column, 0);
+        // f's line bends back into column 0 where a is drawn
+        assert!(rows[2].edges.iter().any(|e| e.from_column == 1 && e.to_column == 0 && e.target == "a"));
+        assert_eq!(rows[3].column, 0);
+        assert_eq!(rows[3].width, 1);
+    }
+
+    #[test]
+    fn test_pages_join_up_like_a_single_layout() {
+        // Two branches merged twice, so lines are open at every boundary
+        let history = [
+            commit("d2", &["c", "9"]),
+            commit("9", &["f"]),
+            commit("c", &["d1"]),
+            commit("d1", &["b", "f"]),
+            commit("f", &["e"]),
+            commit("b", &["e"]),
+            commit("e", &["a"]),
+            commit("a", &[]),
+        ];
+        let whole = layout(&history);
+
+        for size in 1..history.len() {
+            let mut lanes = Vec::new();
+            let mut paged = Vec::new();
+            for (start, page) in history.chunks(size).enumerate().map(|(i, p)| (i * size, p)) {
+                let encoded = encode_lanes(&lanes);
+                let (mut rows, next_lanes) = layout_from(decode_lanes(&encoded).unwrap(), page);
+                if let Some((next, _)) = history.get(start + page.len()) {
+                    connect_next(&mut rows, &next_lanes, next);
+                }
+                paged.extend(rows);
+                lanes = next_lanes;
+            }
+            for (a, b) in whole.iter().zip(&paged) {
+                assert_eq!((&a.hash, a.column, a.width, &a.edges), (&b.hash, b.column, b.width, &b.edges), "page size {}", size);
+            }
+        }
+        assert!(decode_lanes("zz").is_err());
+    }
+
+    #[test]
+    fn test_unrelated_tips_get_separate_columns() {
+        let rows = layout(&[commit("x", &["a"]), commit("y", &["a"]), commit("a", &[])]);
+        assert_eq!(rows[0].column, 0);
+        assert_eq!(rows[1].column, 1);
+        assert_eq!(rows[1].edges.iter().filter(|e| e.target == "a").count(), 2);
+        assert_eq!(rows[2].column, 0);
+    }
+}
@@ -0,0 +1,142 @@
+use git2::Repository;
+use serde::Serialize;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use tauri::{AppHandle, Emitter};
+
+/// A line printed by a hook, streamed to the UI as `git-hook-output`.
+#[derive(Clone, Serialize, Debug)]
+pub struct HookOutput {
+    pub hook: String,
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub line: String,
+}
+
+/// Sink forwarding hook output to the frontend.
+pub(crate) fn emit_to(app_handle: &AppHandle) -> impl FnMut(HookOutput) + '_ {
+    move |output| {
+        let _ = app_handle.emit("git-hook-output", &output);
+    }
+}
+
+/// `core.hooksPath` (relative to the worktree root, like git) or `.git/hooks`.
+fn hooks_dir(repo: &Repository) -> PathBuf {
+    if let Ok(path) = repo.config().and_then(|c| c.get_path("core.hooksPath")) {
+        if path.is_relative() {
+            if let Some(workdir) = repo.workdir() {
+                return workdir.join(path);
+            }
+        }
+        return path;
+    }
+    repo.path().join("hooks")
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+fn hook_command(path: &Path) -> Command {
+    // Hooks are shell scripts; Windows has no shebang handling so go through Git's sh
+    if cfg!(windows) {
+        let mut command = Command::new("sh");
+        command.arg(path);
+        command
+    } else {
+        Command::new(path)
+    }
+}
+
+fn forward_lines<R: Read + Send + 'static>(reader: R, stream: &'static str, tx: mpsc::Sender<(&'static str, String)>) {
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            if tx.send((stream, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Runs hook `name` if the repository has an executable one. Output is passed
+/// to `on_output` line by line as it is produced; a non-zero exit is an error
+/// carrying the tail of the output.
+pub(crate) fn run_hook(repo: &Repository, name: &str, args: &[&Path], on_output: &mut dyn FnMut(HookOutput)) -> Result<(), String> {
+    let path = hooks_dir(repo).join(name);
+    if !is_executable(&path) {
+        return Ok(());
+    }
+
+    let workdir = repo.workdir().unwrap_or_else(|| repo.path());
+    let mut child = hook_command(&path)
+        .args(args)
+        .current_dir(workdir)
+        .env("GIT_INDEX_FILE", repo.path().join("index"))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {} hook: {}", name, e))?;
+
+    let (tx, rx) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, "stdout", tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, "stderr", tx);
+    } else {
+        drop(tx);
+    }
+
+    let mut tail: Vec<String> = Vec::new();
+    for (stream, line) in rx {
+        tail.push(line.clone());
+        if tail.len() > 20 {
+            tail.remove(0);
+        }
+        on_output(HookOutput {
+            hook: name.to_string(),
+            stream: stream.to_string(),
+            line,
+        });
+    }
+
+    let status = child.wait().map_err(|e| format!("Failed to wait for {} hook: {}", name, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        let code = status.code().map(|c| c.to_string()).unwrap_or_else(|| "signal".to_string());
+        Err(format!("{} hook failed (exit code {})\n{}", name, code, tail.join("\n")))
+    }
+}
+
+/// Runs `pre-commit` and `commit-msg`. Returns the message as left by
+/// `commit-msg`, which is allowed to rewrite it.
+pub(crate) fn run_pre_commit_hooks(repo: &Repository, message: &str, on_output: &mut dyn FnMut(HookOutput)) -> Result<String, String> {
+    run_hook(repo, "pre-commit", &[], on_output)?;
+
+    let message_path = repo.path().join("COMMIT_EDITMSG");
+    std::fs::write(&message_path, message).map_err(|e| e.to_string())?;
+    run_hook(repo, "commit-msg", &[&message_path], on_output)?;
+    std::fs::read_to_string(&message_path).map_err(|e| e.to_string())
+}
+
+/// `post-commit` cannot undo the commit, so its failure is only reported as output.
+pub(crate) fn run_post_commit_hook(repo: &Repository, on_output: &mut dyn FnMut(HookOutput)) {
+    if let Err(e) = run_hook(repo, "post-commit", &[], on_output) {
+        on_output(HookOutput {
+            hook: "post-commit".to_string(),
+            stream: "stderr".to_string(),
+            line: e,
+        });
+    }
+}
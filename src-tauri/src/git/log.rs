@@ -0,0 +1,495 @@
+use git2::{Commit, Delta, DiffFindOptions, Oid, Repository, Revwalk, Sort};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use super::graph::{self, GitGraphRow};
+use super::service::GitService;
+use super::avatar::AvatarService;
+use super::{extract_github_username, GitCommit};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Filters and paging parameters shared by the history commands.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct GitLogQuery {
+    /// Branch, tag, remote ref or commit to walk from. Defaults to HEAD.
+    pub rev: Option<String>,
+    /// Walk every local and remote branch (plus HEAD) instead of a single revision.
+    pub all_refs: bool,
+    /// Opaque cursor returned as `next_cursor` by the previous page.
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    /// Case-insensitive substring matched against the author name and email.
+    pub author: Option<String>,
+    /// Only commits touching this file or directory (relative to the repo root).
+    pub path: Option<String>,
+    /// Case-insensitive regular expression matched against the commit message.
+    pub grep: Option<String>,
+    /// Unix timestamps bounding the commit time, both inclusive.
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    /// Compute files changed / insertions / deletions for every commit on the page.
+    /// When false, use `git_commit_stats` to load them for the visible rows only.
+    pub include_stats: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitLogPage {
+    pub commits: Vec<GitCommit>,
+    /// Lane layout for `commits`, row for row, continuing the previous page.
+    /// Empty when a filter is set, since the commits then skip their parents.
+    pub graph: Vec<GitGraphRow>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitCommitStats {
+    pub hash: String,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitFileHistoryEntry {
+    pub commit: GitCommit,
+    /// Path of the file in this commit (differs from the requested path before a rename).
+    pub path: String,
+    /// "added", "modified", "deleted" or "renamed".
+    pub status: String,
+    pub previous_path: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitFileHistoryPage {
+    pub entries: Vec<GitFileHistoryEntry>,
+    pub next_cursor: Option<String>,
+}
+
+/// Per-repository data needed to turn a `git2::Commit` into a `GitCommit`.
+pub(crate) struct CommitDecorations<'a> {
+    avatars: &'a AvatarService,
+    head_oid: Option<Oid>,
+    branches: HashMap<Oid, Vec<String>>,
+    remote_branches: HashMap<Oid, Vec<String>>,
+    tags: HashMap<Oid, Vec<String>>,
+    local_email: Option<String>,
+    github_username: Option<String>,
+}
+
+impl<'a> CommitDecorations<'a> {
+    pub(crate) fn load(repo: &Repository, avatars: &'a AvatarService) -> Self {
+        let head_oid = repo.head().ok().and_then(|head| head.target());
+
+        let github_username = repo.find_remote("origin")
+            .ok()
+            .and_then(|remote| remote.url().map(|s| s.to_string()))
+            .and_then(|url| extract_github_username(&url));
+
+        let local_email = repo.config().ok().and_then(|c| c.get_string("user.email").ok());
+
+        let mut branches: HashMap<Oid, Vec<String>> = HashMap::new();
+        let mut remote_branches: HashMap<Oid, Vec<String>> = HashMap::new();
+        if let Ok(iter) = repo.branches(None) {
+            for (branch, branch_type) in iter.flatten() {
+                let branch_name = branch.name().ok().flatten().unwrap_or("unknown").to_string();
+                // Skip symbolic refs such as origin/HEAD, they duplicate the branch they point to
+                if branch.get().kind() == Some(git2::ReferenceType::Symbolic) {
+                    continue;
+                }
+                if let Some(oid) = branch.get().target() {
+                    let target = match branch_type {
+                        git2::BranchType::Local => &mut branches,
+                        git2::BranchType::Remote => &mut remote_branches,
+                    };
+                    target.entry(oid).or_default().push(branch_name);
+                }
+            }
+        }
+
+        let mut tags: HashMap<Oid, Vec<String>> = HashMap::new();
+        if let Ok(references) = repo.references_glob("refs/tags/*") {
+            for reference in references.flatten() {
+                let Some(name) = reference.shorthand().map(|s| s.to_string()) else {
+                    continue;
+                };
+                // Annotated tags point at a tag object, peel to the tagged commit
+                if let Ok(commit) = reference.peel_to_commit() {
+                    tags.entry(commit.id()).or_default().push(name);
+                }
+            }
+        }
+
+        Self { avatars, head_oid, branches, remote_branches, tags, local_email, github_username }
+    }
+
+    pub(crate) fn to_git_commit(&self, repo: &Repository, commit: &Commit, include_stats: bool) -> GitCommit {
+        let oid = commit.id();
+        let author = commit.author();
+        let author_name = author.name().unwrap_or("Unknown").to_string();
+        let author_email = author.email().unwrap_or("").to_string();
+
+        let timestamp = commit.time().seconds();
+        let date = chrono::DateTime::from_timestamp(timestamp, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+
+        let is_local = self.local_email.as_ref().map(|le| le == &author_email).unwrap_or(false);
+        let github_username = self.github_username.as_deref().filter(|_| is_local);
+        let author_avatar = Some(self.avatars.url_for(&author_email, &author_name, github_username));
+
+        let (files_changed, insertions, deletions) = if include_stats {
+            commit_stats(repo, commit)
+        } else {
+            (0, 0, 0)
+        };
+
+        let hash = oid.to_string();
+        let short_hash = hash[..7.min(hash.len())].to_string();
+
+        GitCommit {
+            hash,
+            short_hash,
+            message: commit.message().unwrap_or("").trim().to_string(),
+            author_name,
+            author_email,
+            author_avatar,
+            date,
+            timestamp,
+            branches: self.branches.get(&oid).cloned().unwrap_or_default(),
+            remote_branches: self.remote_branches.get(&oid).cloned().unwrap_or_default(),
+            tags: self.tags.get(&oid).cloned().unwrap_or_default(),
+            parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+            is_head: self.head_oid == Some(oid),
+            files_changed,
+            insertions,
+            deletions,
+            has_stats: include_stats,
+        }
+    }
+}
+
+/// Files changed, insertions and deletions of a commit against its first parent.
+pub(crate) fn commit_stats(repo: &Repository, commit: &Commit) -> (usize, usize, usize) {
+    let tree = match commit.tree() {
+        Ok(tree) => tree,
+        Err(_) => return (0, 0, 0),
+    };
+    let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+
+    repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .and_then(|diff| diff.stats())
+        .map(|stats| (stats.files_changed(), stats.insertions(), stats.deletions()))
+        .unwrap_or((0, 0, 0))
+}
+
+/// Revwalk starting at `rev` (or HEAD), newest first.
+fn start_revwalk<'r>(repo: &'r Repository, rev: Option<&str>, all_refs: bool) -> Result<Revwalk<'r>, String> {
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    // Children always come before parents, even when commit timestamps tie.
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME).map_err(|e| e.to_string())?;
+
+    if all_refs {
+        revwalk.push_glob("refs/heads").map_err(|e| e.to_string())?;
+        revwalk.push_glob("refs/remotes").map_err(|e| e.to_string())?;
+        // Detached or unborn HEAD is fine to skip here
+        let _ = revwalk.push_head();
+        return Ok(revwalk);
+    }
+
+    match rev.filter(|r| !r.is_empty()) {
+        Some(rev) => {
+            let commit = repo.revparse_single(rev)
+                .and_then(|obj| obj.peel_to_commit())
+                .map_err(|e| format!("Unknown revision '{}': {}", rev, e))?;
+            revwalk.push(commit.id()).map_err(|e| e.to_string())?;
+        }
+        None => revwalk.push_head().map_err(|e| e.to_string())?,
+    }
+
+    Ok(revwalk)
+}
+
+/// Advances the walk past `cursor`. Walking oids is cheap compared to loading
+/// commits, so re-walking up to the cursor keeps pages stable without server-side state.
+fn skip_past(revwalk: &mut Revwalk, cursor: &str) -> Result<(), String> {
+    let cursor = Oid::from_str(cursor).map_err(|_| format!("Invalid cursor '{}'", cursor))?;
+    for oid in revwalk.by_ref() {
+        if oid.map_err(|e| e.to_string())? == cursor {
+            return Ok(());
+        }
+    }
+    Err("Cursor is not reachable from the selected revision".to_string())
+}
+
+struct LogFilter {
+    author: Option<String>,
+    grep: Option<regex::Regex>,
+    path: Option<PathBuf>,
+    since: Option<i64>,
+    until: Option<i64>,
+}
+
+impl LogFilter {
+    fn from_query(query: &GitLogQuery) -> Result<Self, String> {
+        let grep = match query.grep.as_deref().filter(|g| !g.is_empty()) {
+            Some(pattern) => Some(
+                regex::RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| format!("Invalid message pattern: {}", e))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            author: query.author.as_ref().map(|a| a.trim().to_lowercase()).filter(|a| !a.is_empty()),
+            grep,
+            path: query.path.as_deref().and_then(normalize_path),
+            since: query.since,
+            until: query.until,
+        })
+    }
+
+    fn is_active(&self) -> bool {
+        self.author.is_some() || self.grep.is_some() || self.path.is_some() || self.since.is_some() || self.until.is_some()
+    }
+
+    fn matches(&self, commit: &Commit) -> bool {
+        let time = commit.time().seconds();
+        if self.since.map(|s| time < s).unwrap_or(false) || self.until.map(|u| time > u).unwrap_or(false) {
+            return false;
+        }
+
+        if let Some(author) = &self.author {
+            let signature = commit.author();
+            let name = signature.name().unwrap_or("").to_lowercase();
+            let email = signature.email().unwrap_or("").to_lowercase();
+            if !name.contains(author.as_str()) && !email.contains(author.as_str()) {
+                return false;
+            }
+        }
+
+        if let Some(grep) = &self.grep {
+            if !grep.is_match(commit.message().unwrap_or("")) {
+                return false;
+            }
+        }
+
+        // Path check last: it loads trees, everything above only reads the commit header.
+        match &self.path {
+            Some(path) => touches_path(commit, path),
+            None => true,
+        }
+    }
+}
+
+fn normalize_path(path: &str) -> Option<PathBuf> {
+    let trimmed = path.trim().trim_start_matches("./").trim_end_matches('/');
+    if trimmed.is_empty() || trimmed == "." {
+        None
+    } else {
+        Some(PathBuf::from(trimmed))
+    }
+}
+
+fn entry_id(commit: &Commit, path: &Path) -> Option<Oid> {
+    commit.tree().ok().and_then(|tree| tree.get_path(path).ok()).map(|entry| entry.id())
+}
+
+/// Mirrors git's default history simplification: a commit is kept when the
+/// path differs from every parent (merges identical to one side are skipped).
+fn touches_path(commit: &Commit, path: &Path) -> bool {
+    let current = entry_id(commit, path);
+    if commit.parent_count() == 0 {
+        return current.is_some();
+    }
+    commit.parents().all(|parent| entry_id(&parent, path) != current)
+}
+
+/// How `path` changed in `commit`, and the path it had before if it was renamed.
+fn file_change(repo: &Repository, commit: &Commit, path: &Path) -> Result<Option<(&'static str, Option<PathBuf>)>, String> {
+    if !touches_path(commit, path) {
+        return Ok(None);
+    }
+
+    let current = entry_id(commit, path);
+    let parent = commit.parent(0).ok();
+    let previous = parent.as_ref().and_then(|p| entry_id(p, path));
+
+    match (previous, current) {
+        (Some(_), Some(_)) => Ok(Some(("modified", None))),
+        (Some(_), None) => Ok(Some(("deleted", None))),
+        (None, None) => Ok(None),
+        (None, Some(_)) => {
+            let Some(parent) = parent else {
+                return Ok(Some(("added", None)));
+            };
+            let parent_tree = parent.tree().map_err(|e| e.to_string())?;
+            let tree = commit.tree().map_err(|e| e.to_string())?;
+            let mut diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)
+                .map_err(|e| e.to_string())?;
+            let mut find_opts = DiffFindOptions::new();
+            find_opts.renames(true);
+            diff.find_similar(Some(&mut find_opts)).map_err(|e| e.to_string())?;
+
+            let renamed_from = diff.deltas()
+                .find(|delta| delta.status() == Delta::Renamed && delta.new_file().path() == Some(path))
+                .and_then(|delta| delta.old_file().path().map(|p| p.to_path_buf()));
+
+            match renamed_from {
+                Some(old_path) => Ok(Some(("renamed", Some(old_path)))),
+                None => Ok(Some(("added", None))),
+            }
+        }
+    }
+}
+
+#[tauri::command]
+pub fn git_log(service: State<'_, GitService>, avatars: State<'_, AvatarService>, repo_path: String, max_count: Option<usize>) -> Result<Vec<GitCommit>, String> {
+    let query = GitLogQuery {
+        limit: Some(max_count.unwrap_or(DEFAULT_PAGE_SIZE)),
+        include_stats: true,
+        ..Default::default()
+    };
+    git_log_page(service, avatars, repo_path, Some(query)).map(|page| page.commits)
+}
+
+#[tauri::command]
+pub fn git_log_page(service: State<'_, GitService>, avatars: State<'_, AvatarService>, repo_path: String, query: Option<GitLogQuery>) -> Result<GitLogPage, String> {
+    let query = query.unwrap_or_default();
+    let repo = service.open(&repo_path)?;
+    let filter = LogFilter::from_query(&query)?;
+    let decorations = CommitDecorations::load(&repo, &avatars);
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+
+    let mut revwalk = start_revwalk(&repo, query.rev.as_deref(), query.all_refs)?;
+    // The cursor is "<hash>" or "<hash>|<lanes>", the graph lines still open after that commit
+    let mut lanes = Vec::new();
+    if let Some(cursor) = query.cursor.as_deref() {
+        let (hash, open) = cursor.split_once('|').unwrap_or((cursor, ""));
+        skip_past(&mut revwalk, hash)?;
+        lanes = graph::decode_lanes(open)?;
+    }
+
+    let mut commits: Vec<GitCommit> = Vec::new();
+    let mut next = None;
+
+    for oid in revwalk {
+        let oid = oid.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        if !filter.matches(&commit) {
+            continue;
+        }
+        if commits.len() == limit {
+            next = Some(oid.to_string());
+            break;
+        }
+        commits.push(decorations.to_git_commit(&repo, &commit, query.include_stats));
+    }
+
+    let graph = if filter.is_active() {
+        lanes.clear();
+        Vec::new()
+    } else {
+        let (mut rows, open) = graph::layout_from(
+            lanes,
+            &commits.iter().map(|c| (c.hash.clone(), c.parents.clone())).collect::<Vec<_>>(),
+        );
+        if let Some(next) = &next {
+            graph::connect_next(&mut rows, &open, next);
+        }
+        lanes = open;
+        rows
+    };
+
+    let next_cursor = next.and_then(|_| commits.last()).map(|last| {
+        if lanes.is_empty() {
+            last.hash.clone()
+        } else {
+            format!("{}|{}", last.hash, graph::encode_lanes(&lanes))
+        }
+    });
+
+    Ok(GitLogPage { commits, graph, next_cursor })
+}
+
+/// History of a single file, following it across renames like `git log --follow`.
+#[tauri::command]
+pub fn git_file_history(service: State<'_, GitService>, avatars: State<'_, AvatarService>, repo_path: String, file_path: String, query: Option<GitLogQuery>) -> Result<GitFileHistoryPage, String> {
+    let mut query = query.unwrap_or_default();
+    // The followed path replaces any generic path filter.
+    query.path = None;
+
+    let repo = service.open(&repo_path)?;
+    let filter = LogFilter::from_query(&query)?;
+    let decorations = CommitDecorations::load(&repo, &avatars);
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+
+    let mut current_path = normalize_path(&file_path)
+        .ok_or_else(|| "File path is required".to_string())?;
+
+    let mut revwalk = start_revwalk(&repo, query.rev.as_deref(), query.all_refs)?;
+    // The cursor is "<hash>:<path>" so a page boundary right after a rename resumes on the old name.
+    if let Some(cursor) = query.cursor.as_deref() {
+        let (hash, path) = cursor.split_once(':')
+            .ok_or_else(|| format!("Invalid cursor '{}'", cursor))?;
+        skip_past(&mut revwalk, hash)?;
+        current_path = PathBuf::from(path);
+    }
+
+    let mut entries: Vec<GitFileHistoryEntry> = Vec::new();
+    let mut next_cursor = None;
+    let mut path_after_last = current_path.clone();
+
+    for oid in revwalk {
+        let oid = oid.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+
+        let Some((status, previous_path)) = file_change(&repo, &commit, &current_path)? else {
+            continue;
+        };
+        let entry_path = current_path.clone();
+        if let Some(old_path) = &previous_path {
+            current_path = old_path.clone();
+        }
+
+        if !filter.matches(&commit) {
+            continue;
+        }
+        if entries.len() == limit {
+            next_cursor = entries.last()
+                .map(|e| format!("{}:{}", e.commit.hash, path_after_last.to_string_lossy()));
+            break;
+        }
+
+        entries.push(GitFileHistoryEntry {
+            commit: decorations.to_git_commit(&repo, &commit, query.include_stats),
+            path: entry_path.to_string_lossy().to_string(),
+            status: status.to_string(),
+            previous_path: previous_path.map(|p| p.to_string_lossy().to_string()),
+        });
+        path_after_last = current_path.clone();
+    }
+
+    Ok(GitFileHistoryPage { entries, next_cursor })
+}
+
+/// Loads diff stats for commits fetched without `include_stats`.
+#[tauri::command]
+pub fn git_commit_stats(service: State<'_, GitService>, repo_path: String, hashes: Vec<String>) -> Result<Vec<GitCommitStats>, String> {
+    let repo = service.open(&repo_path)?;
+
+    hashes
+        .into_iter()
+        .map(|hash| {
+            let oid = Oid::from_str(&hash).map_err(|e| e.to_string())?;
+            let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+            let (files_changed, insertions, deletions) = commit_stats(&repo, &commit);
+            Ok(GitCommitStats { hash, files_changed, insertions, deletions })
+        })
+        .collect()
+}
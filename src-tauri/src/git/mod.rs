@@ -1,9 +1,31 @@
+pub mod avatar;
+pub mod clone;
+pub mod credentials;
+mod graph;
+mod hooks;
+pub mod log;
+pub mod operations;
 pub mod push;
-
-use git2::{Repository, StatusOptions, Signature};
+pub mod remote;
+pub mod service;
+pub mod show;
+mod signing;
+pub mod stats;
+pub mod status;
+pub mod submodule;
+pub mod tags;
+pub mod worktree;
+
+#[cfg(test)]
+mod tests;
+
+use avatar::AvatarService;
+use git2::{Repository, Signature};
+use service::GitService;
 use serde::{Serialize, Deserialize};
 use std::path::Path;
 use std::collections::HashMap;
+use tauri::State;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GitContributor {
@@ -20,6 +42,17 @@ pub struct GitFileStatus {
     pub path: String,
     pub status: String,
     pub is_staged: bool,
+    /// Set when the path is a submodule.
+    pub submodule: Option<submodule::GitSubmoduleChange>,
+    /// HEAD vs index: "added", "modified", "deleted", "renamed" or "typechange".
+    pub index_status: Option<String>,
+    /// Index vs working tree: the same values plus "untracked" and "ignored".
+    pub worktree_status: Option<String>,
+    /// Path before a rename.
+    pub original_path: Option<String>,
+    pub is_conflicted: bool,
+    /// "both_modified", "both_added", "deleted_by_us", ... for conflicted paths.
+    pub conflict: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -33,6 +66,11 @@ pub struct GitInfo {
     pub remote_name: Option<String>,
     pub user_name: Option<String>,
     pub user_email: Option<String>,
+    /// `path` is a linked worktree rather than the main checkout.
+    pub is_worktree: bool,
+    /// Submodules with new commits or local changes; only counted when
+    /// `git_info` is asked to include submodules.
+    pub dirty_submodules: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -51,6 +89,11 @@ pub struct FileDiff {
     pub lines: Vec<DiffLine>,
     pub is_new_file: bool,
     pub is_deleted: bool,
+    /// "added", "deleted", "modified", "renamed", "copied", ...
+    pub status: String,
+    /// Source path for renames and copies.
+    pub old_file_path: Option<String>,
+    pub is_binary: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -64,10 +107,15 @@ pub struct GitCommit {
     pub date: String,
     pub timestamp: i64,
     pub branches: Vec<String>,
+    pub remote_branches: Vec<String>,
+    pub tags: Vec<String>,
+    pub parents: Vec<String>,
     pub is_head: bool,
     pub files_changed: usize,
     pub insertions: usize,
     pub deletions: usize,
+    /// False when the commit was listed without stats; see `log::git_commit_stats`.
+    pub has_stats: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -78,76 +126,21 @@ pub struct GitBranch {
 }
 
 #[tauri::command]
-pub fn git_status(path: String) -> Result<Vec<GitFileStatus>, String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    let mut statuses = Vec::new();
-    
-    let mut opts = StatusOptions::new();
-    opts.include_untracked(true);
-    opts.include_ignored(false);
-
-    for entry in repo.statuses(Some(&mut opts)).map_err(|e| e.to_string())?.iter() {
-        let status = entry.status();
-        let is_staged = status.is_index_new() || status.is_index_modified() || status.is_index_deleted();
-        
-        let status_str = if status.is_index_new() {
-            "staged_new"
-        } else if status.is_index_modified() {
-            "staged_modified"
-        } else if status.is_index_deleted() {
-            "staged_deleted"
-        } else if status.is_wt_new() {
-            "untracked"
-        } else if status.is_wt_modified() {
-            "modified"
-        } else if status.is_wt_deleted() {
-            "deleted"
-        } else {
-            continue;
-        };
-
-        if let Some(file_path) = entry.path() {
-            statuses.push(GitFileStatus {
-                path: file_path.to_string(),
-                status: status_str.to_string(),
-                is_staged,
-            });
-        }
-    }
-
-    Ok(statuses)
-}
-
-#[tauri::command]
-pub fn git_info(path: String) -> Result<GitInfo, String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+pub fn git_info(service: State<'_, GitService>, path: String, include_submodules: Option<bool>) -> Result<GitInfo, String> {
+    let repo = service.open(&path)?;
     
     let branch_name = match repo.head() {
         Ok(head) => head.shorthand().unwrap_or("HEAD").to_string(),
         Err(_) => "main".to_string(),
     };
     
-    let mut opts = StatusOptions::new();
-    opts.include_untracked(true);
-    
-    let statuses = repo.statuses(Some(&mut opts)).map_err(|e| e.to_string())?;
-    let mut modified_files = 0;
-    let mut untracked_files = 0;
-    let mut staged_files = 0;
-    
-    for entry in statuses.iter() {
-        let status = entry.status();
-        if status.is_index_new() || status.is_index_modified() || status.is_index_deleted() {
-            staged_files += 1;
-        }
-        if status.is_wt_new() {
-            untracked_files += 1;
-        } else if status.is_wt_modified() || status.is_wt_deleted() {
-            modified_files += 1;
-        }
-    }
-    
-    let is_clean = modified_files == 0 && untracked_files == 0 && staged_files == 0;
+    let options = status::GitStatusOptions {
+        submodules: include_submodules.unwrap_or(false),
+        ..Default::default()
+    };
+    let report = status::collect(&repo, &options)?;
+    let counts = &report.counts;
+    let is_clean = counts.staged == 0 && counts.unstaged == 0 && counts.untracked == 0 && counts.conflicted == 0;
     
     let (has_remote, remote_name) = match repo.find_remote("origin") {
         Ok(remote) => (true, remote.url().map(|s| s.to_string())),
@@ -161,25 +154,21 @@ pub fn git_info(path: String) -> Result<GitInfo, String> {
     Ok(GitInfo {
         branch: branch_name,
         is_clean,
-        modified_files,
-        untracked_files,
-        staged_files,
+        modified_files: counts.unstaged,
+        untracked_files: counts.untracked,
+        staged_files: counts.staged,
         has_remote,
         remote_name,
         user_name,
         user_email,
+        is_worktree: repo.is_worktree(),
+        dirty_submodules: counts.submodules,
     })
 }
 
 #[tauri::command]
-pub fn git_clone(url: String, path: String) -> Result<(), String> {
-    Repository::clone(&url, &path).map_err(|e| e.to_string())?;
-    Ok(())
-}
-
-#[tauri::command]
-pub fn git_stage(repo_path: String, file_path: String) -> Result<(), String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_stage(service: State<'_, GitService>, repo_path: String, file_path: String) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
     let mut index = repo.index().map_err(|e| e.to_string())?;
     index.add_path(std::path::Path::new(&file_path)).map_err(|e| e.to_string())?;
     index.write().map_err(|e| e.to_string())?;
@@ -187,8 +176,8 @@ pub fn git_stage(repo_path: String, file_path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn git_unstage(repo_path: String, file_path: String) -> Result<(), String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_unstage(service: State<'_, GitService>, repo_path: String, file_path: String) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
     let head = repo.head().map_err(|e| e.to_string())?;
     let head_commit = head.peel_to_commit().map_err(|e| e.to_string())?;
     repo.reset_default(Some(&head_commit.into_object()), [std::path::Path::new(&file_path)])
@@ -197,8 +186,8 @@ pub fn git_unstage(repo_path: String, file_path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn git_stage_all(repo_path: String) -> Result<(), String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_stage_all(service: State<'_, GitService>, repo_path: String) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
     let mut index = repo.index().map_err(|e| e.to_string())?;
     index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None).map_err(|e| e.to_string())?;
     index.write().map_err(|e| e.to_string())?;
@@ -206,44 +195,110 @@ pub fn git_stage_all(repo_path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn git_unstage_all(repo_path: String) -> Result<(), String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_unstage_all(service: State<'_, GitService>, repo_path: String) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
     let head = repo.head().map_err(|e| e.to_string())?;
     let head_commit = head.peel_to_commit().map_err(|e| e.to_string())?;
-    repo.reset_default(Some(&head_commit.into_object()), ["."]).map_err(|e| e.to_string())?;
+    // libgit2 pathspecs are globs; "." matches nothing
+    repo.reset_default(Some(&head_commit.into_object()), ["*"]).map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// Commits the index, running the repository's hooks unless `no_verify` is set
+/// (post-commit always runs, as with `git commit --no-verify`). Hook output is
+/// streamed as `git-hook-output` events.
 #[tauri::command]
-pub fn git_commit(repo_path: String, message: String) -> Result<String, String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
-    
-    let mut index = repo.index().map_err(|e| e.to_string())?;
-    let tree_id = index.write_tree().map_err(|e| e.to_string())?;
-    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
-    
+pub async fn git_commit(app_handle: tauri::AppHandle, service: State<'_, GitService>, repo_path: String, message: String, no_verify: Option<bool>) -> Result<String, String> {
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || {
+        let mut on_output = hooks::emit_to(&app_handle);
+
+        let message = if no_verify.unwrap_or(false) {
+            message
+        } else {
+            hooks::run_pre_commit_hooks(&repo, &message, &mut on_output)?
+        };
+
+        let mut index = repo.index().map_err(|e| e.to_string())?;
+        // pre-commit may have restaged files
+        index.read(true).map_err(|e| e.to_string())?;
+        let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+        let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+
+        let sig = default_signature(&repo)?;
+
+        let parent_commit = match repo.head() {
+            Ok(head) => Some(head.peel_to_commit().map_err(|e| e.to_string())?),
+            Err(_) => None,
+        };
+
+        let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
+
+        let commit_id = write_commit(&repo, &sig, &sig, &message, &tree, &parents)?;
+        hooks::run_post_commit_hook(&repo, &mut on_output);
+
+        Ok(commit_id.to_string())
+    })
+    .await
+    .map_err(|e| format!("Commit task failed: {}", e))?
+}
+
+/// Signature built from the repository's `user.name` / `user.email`.
+pub(crate) fn default_signature(repo: &Repository) -> Result<Signature<'static>, String> {
     let config = repo.config().map_err(|e| e.to_string())?;
     let name = config.get_string("user.name").unwrap_or_else(|_| "Unknown".to_string());
     let email = config.get_string("user.email").unwrap_or_else(|_| "unknown@example.com".to_string());
-    
-    let sig = Signature::now(&name, &email).map_err(|e| e.to_string())?;
-    
-    let parent_commit = match repo.head() {
-        Ok(head) => Some(head.peel_to_commit().map_err(|e| e.to_string())?),
-        Err(_) => None,
+    Signature::now(&name, &email).map_err(|e| e.to_string())
+}
+
+/// Creates a commit and moves HEAD (and the branch it points to) onto it,
+/// signing it when `commit.gpgsign` is enabled. Every command that produces
+/// commits goes through here.
+pub(crate) fn write_commit(
+    repo: &Repository,
+    author: &Signature,
+    committer: &Signature,
+    message: &str,
+    tree: &git2::Tree,
+    parents: &[&git2::Commit],
+) -> Result<git2::Oid, String> {
+    let commit_id = match signing::SigningConfig::from_repo(repo, committer)? {
+        Some(signing) => {
+            let buffer = repo.commit_create_buffer(author, committer, message, tree, parents)
+                .map_err(|e| e.to_string())?;
+            let payload = buffer.as_str().ok_or_else(|| "Commit contents are not valid UTF-8".to_string())?;
+            let signature = signing.sign(payload)?;
+            repo.commit_signed(payload, &signature, None).map_err(|e| e.to_string())?
+        }
+        None => repo.commit(None, author, committer, message, tree, parents)
+            .map_err(|e| e.to_string())?,
     };
-    
-    let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
-    
-    let commit_id = repo.commit(Some("HEAD"), &sig, &sig, &message, &tree, &parents)
-        .map_err(|e| e.to_string())?;
-    
-    Ok(commit_id.to_string())
+    update_head(repo, commit_id, message)?;
+    Ok(commit_id)
+}
+
+/// Points HEAD at `commit_id`, going through the branch when HEAD is symbolic.
+/// Unlike `Repository::commit(Some("HEAD"), ..)` this allows replacing HEAD with a
+/// commit that is not its child, which amending needs.
+fn update_head(repo: &Repository, commit_id: git2::Oid, message: &str) -> Result<(), String> {
+    let summary = message.lines().next().unwrap_or("");
+    let log_message = format!("commit: {}", summary);
+
+    let head_ref = repo.find_reference("HEAD").map_err(|e| e.to_string())?;
+    match head_ref.symbolic_target() {
+        Some(branch_ref) => {
+            repo.reference(branch_ref, commit_id, true, &log_message).map_err(|e| e.to_string())?;
+        }
+        None => {
+            repo.set_head_detached(commit_id).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
 }
 
 #[tauri::command]
-pub fn git_discard_changes(repo_path: String, file_path: String) -> Result<(), String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_discard_changes(service: State<'_, GitService>, repo_path: String, file_path: String) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
     let mut checkout_opts = git2::build::CheckoutBuilder::new();
     checkout_opts.path(&file_path);
     checkout_opts.force();
@@ -252,8 +307,8 @@ pub fn git_discard_changes(repo_path: String, file_path: String) -> Result<(), S
 }
 
 #[tauri::command]
-pub fn git_diff(repo_path: String, file_path: String, is_staged: bool) -> Result<FileDiff, String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_diff(service: State<'_, GitService>, repo_path: String, file_path: String, is_staged: bool) -> Result<FileDiff, String> {
+    let repo = service.open(&repo_path)?;
     
     let mut diff_lines: Vec<DiffLine> = Vec::new();
     let mut old_content = String::new();
@@ -357,6 +412,14 @@ pub fn git_diff(repo_path: String, file_path: String, is_staged: bool) -> Result
         }
     }
     
+    let status = if is_new_file {
+        "added"
+    } else if is_deleted {
+        "deleted"
+    } else {
+        "modified"
+    };
+
     Ok(FileDiff {
         file_path,
         old_content,
@@ -364,12 +427,15 @@ pub fn git_diff(repo_path: String, file_path: String, is_staged: bool) -> Result
         lines: diff_lines,
         is_new_file,
         is_deleted,
+        status: status.to_string(),
+        old_file_path: None,
+        is_binary: false,
     })
 }
 
 #[tauri::command]
-pub fn git_contributors(repo_path: String) -> Result<Vec<GitContributor>, String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_contributors(service: State<'_, GitService>, avatars: State<'_, AvatarService>, repo_path: String) -> Result<Vec<GitContributor>, String> {
+    let repo = service.open(&repo_path)?;
     let mut contributors_map: HashMap<String, (String, usize, std::collections::HashSet<String>)> = HashMap::new();
     
     let config = repo.config().ok();
@@ -380,6 +446,9 @@ pub fn git_contributors(repo_path: String) -> Result<Vec<GitContributor>, String
         .and_then(|remote| remote.url().map(|s| s.to_string()))
         .and_then(|url| extract_github_username(&url));
     
+    // Merge aliases listed in .mailmap into one contributor
+    let mailmap = repo.mailmap().ok();
+
     let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
     
     if let Ok(branches) = repo.branches(None) {
@@ -403,9 +472,7 @@ pub fn git_contributors(repo_path: String) -> Result<Vec<GitContributor>, String
     for oid_result in revwalk {
         if let Ok(oid) = oid_result {
             if let Ok(commit) = repo.find_commit(oid) {
-                let author = commit.author();
-                let email = author.email().unwrap_or("unknown").to_string();
-                let name = author.name().unwrap_or("Unknown").to_string();
+                let (name, email) = stats::identity(&commit, mailmap.as_ref());
                 
                 let entry = contributors_map.entry(email.clone())
                     .or_insert_with(|| (name.clone(), 0, std::collections::HashSet::new()));
@@ -418,7 +485,8 @@ pub fn git_contributors(repo_path: String) -> Result<Vec<GitContributor>, String
         .into_iter()
         .map(|(email, (name, commits_count, branches))| {
             let is_local = local_email.as_ref().map(|le| le == &email).unwrap_or(false);
-            let avatar_url = get_avatar_url(&email, &name, is_local, github_username.as_deref());
+            let github_username = github_username.as_deref().filter(|_| is_local);
+            let avatar_url = Some(avatars.url_for(&email, &name, github_username));
             
             GitContributor {
                 name,
@@ -436,152 +504,8 @@ pub fn git_contributors(repo_path: String) -> Result<Vec<GitContributor>, String
 }
 
 #[tauri::command]
-pub fn git_log(repo_path: String, max_count: Option<usize>) -> Result<Vec<GitCommit>, String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
-    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
-    revwalk.push_head().map_err(|e| e.to_string())?;
-    
-    // Get current HEAD commit OID
-    let head_oid = repo.head()
-        .ok()
-        .and_then(|head| head.target());
-    
-    // Get remote URL for avatar generation
-    let github_username = repo.find_remote("origin")
-        .ok()
-        .and_then(|remote| remote.url().map(|s| s.to_string()))
-        .and_then(|url| extract_github_username(&url));
-    
-    // Get local user config
-    let config = repo.config().ok();
-    let local_email = config.as_ref().and_then(|c| c.get_string("user.email").ok());
-    let local_name = config.as_ref().and_then(|c| c.get_string("user.name").ok());
-    
-    // Build commit to branches mapping
-    let mut commit_branches: HashMap<git2::Oid, Vec<String>> = HashMap::new();
-    if let Ok(branches) = repo.branches(None) {
-        for branch_result in branches {
-            if let Ok((branch, branch_type)) = branch_result {
-                let branch_name = branch.name().ok().flatten().unwrap_or("unknown").to_string();
-                let display_name = if branch_type == git2::BranchType::Remote {
-                    branch_name.clone()
-                } else {
-                    branch_name.clone()
-                };
-                
-                if let Ok(reference) = branch.into_reference().resolve() {
-                    if let Some(oid) = reference.target() {
-                        commit_branches.entry(oid).or_insert_with(Vec::new).push(display_name);
-                    }
-                }
-            }
-        }
-    }
-    
-    let max = max_count.unwrap_or(100);
-    let mut commits = Vec::new();
-    
-    for (i, oid_result) in revwalk.enumerate() {
-        if i >= max { break; }
-        if let Ok(oid) = oid_result {
-            if let Ok(commit) = repo.find_commit(oid) {
-                let author = commit.author();
-                let author_name = author.name().unwrap_or("Unknown").to_string();
-                let author_email = author.email().unwrap_or("").to_string();
-                
-                // Check if this is HEAD
-                let is_head = head_oid.map(|h| h == oid).unwrap_or(false);
-                
-                // Get branches for this commit
-                let branches = commit_branches.get(&oid)
-                    .cloned()
-                    .unwrap_or_default();
-                
-                // Get commit time
-                let commit_time = commit.time();
-                let timestamp = commit_time.seconds();
-                
-                // Format date
-                let date = {
-                    use std::time::{SystemTime, UNIX_EPOCH, Duration};
-                    let dt = UNIX_EPOCH + Duration::from_secs(timestamp as u64);
-                    let datetime: chrono::DateTime<chrono::Utc> = dt.into();
-                    datetime.format("%Y-%m-%d %H:%M:%S").to_string()
-                };
-                
-                // Get avatar URL
-                let is_local = local_email.as_ref().map(|le| le == &author_email).unwrap_or(false);
-                let author_avatar = get_avatar_url(&author_email, &author_name, is_local, github_username.as_deref());
-                
-                // Calculate stats (files changed, insertions, deletions)
-                let (files_changed, insertions, deletions) = {
-                    let mut files = 0;
-                    let mut insertions = 0;
-                    let mut deletions = 0;
-                    
-                    if let Ok(parent) = commit.parent(0) {
-                        if let Ok(parent_tree) = parent.tree() {
-                            if let Ok(commit_tree) = commit.tree() {
-                                if let Ok(diff) = repo.diff_tree_to_tree(
-                                    Some(&parent_tree),
-                                    Some(&commit_tree),
-                                    None
-                                ) {
-                                    if let Ok(stats) = diff.stats() {
-                                        files = stats.files_changed();
-                                        insertions = stats.insertions();
-                                        deletions = stats.deletions();
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        // First commit - all files are new
-                        if let Ok(tree) = commit.tree() {
-                            files = tree.len();
-                            if let Ok(diff) = repo.diff_tree_to_tree(None, Some(&tree), None) {
-                                if let Ok(stats) = diff.stats() {
-                                    insertions = stats.insertions();
-                                }
-                            }
-                        }
-                    }
-                    
-                    (files, insertions, deletions)
-                };
-                
-                let hash_str = oid.to_string();
-                let short_hash = if hash_str.len() >= 7 {
-                    hash_str[..7].to_string()
-                } else {
-                    hash_str.clone()
-                };
-                
-                commits.push(GitCommit {
-                    hash: hash_str,
-                    short_hash,
-                    message: commit.message().unwrap_or("").trim().to_string(),
-                    author_name,
-                    author_email,
-                    author_avatar,
-                    date,
-                    timestamp,
-                    branches,
-                    is_head,
-                    files_changed,
-                    insertions,
-                    deletions,
-                });
-            }
-        }
-    }
-    
-    Ok(commits)
-}
-
-#[tauri::command]
-pub fn git_list_branches(repo_path: String) -> Result<Vec<GitBranch>, String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_list_branches(service: State<'_, GitService>, repo_path: String) -> Result<Vec<GitBranch>, String> {
+    let repo = service.open(&repo_path)?;
     let mut branches = Vec::new();
     
     let current_branch = repo.head().ok()
@@ -603,8 +527,8 @@ pub fn git_list_branches(repo_path: String) -> Result<Vec<GitBranch>, String> {
 }
 
 #[tauri::command]
-pub fn git_create_branch(repo_path: String, branch_name: String) -> Result<(), String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_create_branch(service: State<'_, GitService>, repo_path: String, branch_name: String) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
     let head = repo.head().map_err(|e| e.to_string())?;
     let commit = head.peel_to_commit().map_err(|e| e.to_string())?;
     repo.branch(&branch_name, &commit, false).map_err(|e| e.to_string())?;
@@ -612,8 +536,8 @@ pub fn git_create_branch(repo_path: String, branch_name: String) -> Result<(), S
 }
 
 #[tauri::command]
-pub fn git_checkout_branch(repo_path: String, branch_name: String) -> Result<(), String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_checkout_branch(service: State<'_, GitService>, repo_path: String, branch_name: String) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
     let refname = format!("refs/heads/{}", branch_name);
     let obj = repo.revparse_single(&refname).map_err(|e| e.to_string())?;
     repo.checkout_tree(&obj, None).map_err(|e| e.to_string())?;
@@ -622,19 +546,20 @@ pub fn git_checkout_branch(repo_path: String, branch_name: String) -> Result<(),
 }
 
 #[tauri::command]
-pub fn git_delete_branch(repo_path: String, branch_name: String) -> Result<(), String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+pub fn git_delete_branch(service: State<'_, GitService>, repo_path: String, branch_name: String) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
     let mut branch = repo.find_branch(&branch_name, git2::BranchType::Local)
         .map_err(|e| e.to_string())?;
     branch.delete().map_err(|e| e.to_string())?;
     Ok(())
 }
 
-fn md5_hash(input: &str) -> String {
-    use md5::{Md5, Digest};
-    let mut hasher = Md5::new();
-    hasher.update(input.as_bytes());
-    format!("{:x}", hasher.finalize())
+/// Expands a leading `~/` the way git does for paths in its config.
+pub(crate) fn expand_home(path: &str) -> std::path::PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| std::path::PathBuf::from(path)),
+        None => std::path::PathBuf::from(path),
+    }
 }
 
 fn extract_github_username(url: &str) -> Option<String> {
@@ -649,29 +574,6 @@ fn extract_github_username(url: &str) -> Option<String> {
     }
 }
 
-fn get_avatar_url(email: &str, name: &str, is_local: bool, github_username: Option<&str>) -> Option<String> {
-    if email.contains("@users.noreply.github.com") {
-        let username = email.split('+').nth(1)
-            .and_then(|s| s.split('@').next())
-            .or_else(|| email.split('@').next());
-        return username.map(|u| format!("https://github.com/{}.png?size=40", u));
-    }
-    
-    if is_local {
-        if let Some(gh_user) = github_username {
-            return Some(format!("https://github.com/{}.png?size=40", gh_user));
-        }
-    }
-    
-    let name_trimmed = name.trim();
-    if !name_trimmed.contains(' ') && !name_trimmed.is_empty() {
-        return Some(format!("https://github.com/{}.png?size=40", name_trimmed));
-    }
-    
-    let hash = md5_hash(&email.trim().to_lowercase());
-    Some(format!("https://www.gravatar.com/avatar/{}?s=40&d=retro", hash))
-}
-
 #[tauri::command]
 pub fn git_github_auth_status() -> Result<bool, String> {
     // Check if GitHub CLI is authenticated
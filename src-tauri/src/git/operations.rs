@@ -0,0 +1,321 @@
+use git2::{Commit, Index, Repository, RepositoryState, ResetType};
+use serde::Serialize;
+use tauri::State;
+
+use super::hooks;
+use super::service::GitService;
+use super::{default_signature, write_commit};
+
+#[derive(Serialize, Debug)]
+pub struct GitOperationResult {
+    pub success: bool,
+    pub message: String,
+    /// Commits created by the operation, oldest first.
+    pub commits: Vec<String>,
+    /// Paths left in a conflicted state when `success` is false.
+    pub conflicts: Vec<String>,
+}
+
+impl GitOperationResult {
+    pub(crate) fn done(message: String, commits: Vec<String>) -> Self {
+        Self { success: true, message, commits, conflicts: Vec::new() }
+    }
+
+    pub(crate) fn conflicted(message: String, commits: Vec<String>, conflicts: Vec<String>) -> Self {
+        Self { success: false, message, commits, conflicts }
+    }
+}
+
+fn find_commit<'r>(repo: &'r Repository, rev: &str) -> Result<Commit<'r>, String> {
+    repo.revparse_single(rev)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("Unknown commit '{}': {}", rev, e))
+}
+
+fn head_commit(repo: &Repository) -> Result<Commit<'_>, String> {
+    repo.head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| format!("No commit at HEAD: {}", e))
+}
+
+pub(crate) fn ensure_clean_state(repo: &Repository) -> Result<(), String> {
+    match repo.state() {
+        RepositoryState::Clean => Ok(()),
+        state => Err(format!("Another operation is in progress ({:?}); finish or abort it first", state)),
+    }
+}
+
+pub(crate) fn conflicted_paths(index: &Index) -> Result<Vec<String>, String> {
+    let mut paths = Vec::new();
+    for conflict in index.conflicts().map_err(|e| e.to_string())? {
+        let conflict = conflict.map_err(|e| e.to_string())?;
+        let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+        if let Some(entry) = entry {
+            paths.push(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    Ok(paths)
+}
+
+/// Commits the index on top of HEAD and clears any in-progress
+/// revert/cherry-pick state.
+fn commit_index(repo: &Repository, author: &git2::Signature, message: &str) -> Result<String, String> {
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    index.read(true).map_err(|e| e.to_string())?;
+    let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+    let head = head_commit(repo)?;
+    let committer = default_signature(repo)?;
+
+    let commit_id = write_commit(repo, author, &committer, message, &tree, &[&head])?;
+    repo.cleanup_state().map_err(|e| e.to_string())?;
+    Ok(commit_id.to_string())
+}
+
+/// Replaces the last commit. `message: None` keeps the old message;
+/// `include_staged` folds the current index into it. Runs the commit hooks
+/// like `git commit --amend`.
+#[tauri::command]
+pub async fn git_amend_commit(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    repo_path: String,
+    message: Option<String>,
+    include_staged: bool,
+    no_verify: Option<bool>,
+) -> Result<GitOperationResult, String> {
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || amend_commit(&app_handle, &repo, message, include_staged, no_verify.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Amend task failed: {}", e))?
+}
+
+fn amend_commit(
+    app_handle: &tauri::AppHandle,
+    repo: &Repository,
+    message: Option<String>,
+    include_staged: bool,
+    no_verify: bool,
+) -> Result<GitOperationResult, String> {
+    ensure_clean_state(repo)?;
+    let head = head_commit(repo)?;
+
+    let message = message
+        .filter(|m| !m.trim().is_empty())
+        .unwrap_or_else(|| head.message().unwrap_or("").to_string());
+    let mut on_output = hooks::emit_to(app_handle);
+    let message = if no_verify {
+        message
+    } else {
+        hooks::run_pre_commit_hooks(repo, &message, &mut on_output)?
+    };
+
+    let tree = if include_staged {
+        let mut index = repo.index().map_err(|e| e.to_string())?;
+        index.read(true).map_err(|e| e.to_string())?;
+        let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+        repo.find_tree(tree_id).map_err(|e| e.to_string())?
+    } else {
+        head.tree().map_err(|e| e.to_string())?
+    };
+
+    let parents: Vec<Commit> = head.parents().collect();
+    let parent_refs: Vec<&Commit> = parents.iter().collect();
+    let committer = default_signature(repo)?;
+
+    let commit_id = write_commit(repo, &head.author(), &committer, &message, &tree, &parent_refs)?;
+    hooks::run_post_commit_hook(repo, &mut on_output);
+
+    Ok(GitOperationResult::done(
+        format!("Amended {}", &head.id().to_string()[..7]),
+        vec![commit_id.to_string()],
+    ))
+}
+
+/// Commits the index as `fixup! <target summary>` for a later autosquash rebase.
+#[tauri::command]
+pub async fn git_fixup_commit(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    repo_path: String,
+    target: String,
+    no_verify: Option<bool>,
+) -> Result<GitOperationResult, String> {
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || {
+        fixup_commit(&repo, &target, no_verify.unwrap_or(false), &mut hooks::emit_to(&app_handle))
+    })
+    .await
+    .map_err(|e| format!("Fixup task failed: {}", e))?
+}
+
+pub(crate) fn fixup_commit(
+    repo: &Repository,
+    target: &str,
+    no_verify: bool,
+    on_output: &mut dyn FnMut(hooks::HookOutput),
+) -> Result<GitOperationResult, String> {
+    ensure_clean_state(repo)?;
+    let target_commit = find_commit(repo, target)?;
+    let summary = target_commit.summary().unwrap_or("").to_string();
+    // An empty fixup would squash into nothing
+    if index_matches_head(repo)? {
+        return Err("Nothing staged".to_string());
+    }
+
+    let message = format!("fixup! {}", summary);
+    let message = if no_verify {
+        message
+    } else {
+        hooks::run_pre_commit_hooks(repo, &message, on_output)?
+    };
+
+    let author = default_signature(repo)?;
+    let commit_id = commit_index(repo, &author, &message)?;
+    hooks::run_post_commit_hook(repo, on_output);
+
+    Ok(GitOperationResult::done(format!("Created fixup for '{}'", summary), vec![commit_id]))
+}
+
+/// Whether the index holds the same tree as HEAD, i.e. the change is
+/// already applied and committing would create an empty commit.
+fn index_matches_head(repo: &Repository) -> Result<bool, String> {
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    index.read(true).map_err(|e| e.to_string())?;
+    let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+    Ok(head_commit(repo)?.tree_id() == tree_id)
+}
+
+/// Reverts a commit on top of HEAD. `mainline` selects the parent (1-based) when reverting a merge.
+#[tauri::command]
+pub async fn git_revert_commit(
+    service: State<'_, GitService>,
+    repo_path: String,
+    hash: String,
+    mainline: Option<u32>,
+) -> Result<GitOperationResult, String> {
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || revert_commit(&repo, &hash, mainline))
+        .await
+        .map_err(|e| format!("Revert task failed: {}", e))?
+}
+
+fn revert_commit(repo: &Repository, hash: &str, mainline: Option<u32>) -> Result<GitOperationResult, String> {
+    ensure_clean_state(repo)?;
+    let commit = find_commit(repo, hash)?;
+
+    let mut opts = git2::RevertOptions::new();
+    if let Some(mainline) = mainline {
+        opts.mainline(mainline);
+    }
+    repo.revert(&commit, Some(&mut opts)).map_err(|e| e.to_string())?;
+
+    let index = repo.index().map_err(|e| e.to_string())?;
+    if index.has_conflicts() {
+        return Ok(GitOperationResult::conflicted(
+            "Revert stopped with conflicts; resolve them and commit, or reset to abort".to_string(),
+            Vec::new(),
+            conflicted_paths(&index)?,
+        ));
+    }
+
+    let summary = commit.summary().unwrap_or("").to_string();
+    if index_matches_head(repo)? {
+        repo.cleanup_state().map_err(|e| e.to_string())?;
+        return Ok(GitOperationResult::done(
+            format!("Nothing to commit: '{}' is already reverted", summary),
+            Vec::new(),
+        ));
+    }
+
+    let message = format!("Revert \"{}\"\n\nThis reverts commit {}.", summary, commit.id());
+    let author = default_signature(repo)?;
+    let commit_id = commit_index(repo, &author, &message)?;
+
+    Ok(GitOperationResult::done(format!("Reverted '{}'", summary), vec![commit_id]))
+}
+
+/// Applies `hashes` on top of HEAD in order, keeping the original authors.
+/// Commits whose changes are already present are skipped. Stops at the
+/// first conflict, leaving it in the working tree. `mainline` selects the
+/// parent (1-based) of merge commits and is ignored for the others.
+#[tauri::command]
+pub async fn git_cherry_pick(
+    service: State<'_, GitService>,
+    repo_path: String,
+    hashes: Vec<String>,
+    mainline: Option<u32>,
+) -> Result<GitOperationResult, String> {
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || cherry_pick(&repo, &hashes, mainline))
+        .await
+        .map_err(|e| format!("Cherry-pick task failed: {}", e))?
+}
+
+fn cherry_pick(repo: &Repository, hashes: &[String], mainline: Option<u32>) -> Result<GitOperationResult, String> {
+    ensure_clean_state(repo)?;
+
+    let commits = hashes
+        .iter()
+        .map(|hash| find_commit(repo, hash))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut created = Vec::new();
+    let mut skipped = 0;
+    for commit in &commits {
+        let mut opts = git2::CherrypickOptions::new();
+        // Only merges have a parent to choose; `-m 2` would fail on the rest
+        if let Some(mainline) = mainline.filter(|_| commit.parent_count() > 1) {
+            opts.mainline(mainline);
+        }
+        repo.cherrypick(commit, Some(&mut opts)).map_err(|e| e.to_string())?;
+
+        let index = repo.index().map_err(|e| e.to_string())?;
+        if index.has_conflicts() {
+            let short = &commit.id().to_string()[..7];
+            return Ok(GitOperationResult::conflicted(
+                format!("Cherry-pick of {} stopped with conflicts ({} of {} applied)", short, created.len(), commits.len()),
+                created,
+                conflicted_paths(&index)?,
+            ));
+        }
+
+        if index_matches_head(repo)? {
+            repo.cleanup_state().map_err(|e| e.to_string())?;
+            skipped += 1;
+            continue;
+        }
+
+        let message = commit.message().unwrap_or("").to_string();
+        created.push(commit_index(repo, &commit.author(), &message)?);
+    }
+
+    let message = match (created.len(), skipped) {
+        (0, _) => "Nothing to commit: the changes are already applied".to_string(),
+        (n, 0) => format!("Cherry-picked {} commit(s)", n),
+        (n, skipped) => format!("Cherry-picked {} commit(s), skipped {} already applied", n, skipped),
+    };
+    Ok(GitOperationResult::done(message, created))
+}
+
+/// Moves the current branch to `target`. `mode` is "soft", "mixed" or "hard".
+/// Also aborts an in-progress revert or cherry-pick.
+#[tauri::command]
+pub fn git_reset(service: State<'_, GitService>, repo_path: String, target: String, mode: String) -> Result<GitOperationResult, String> {
+    let repo = service.open(&repo_path)?;
+    let reset_type = match mode.as_str() {
+        "soft" => ResetType::Soft,
+        "mixed" => ResetType::Mixed,
+        "hard" => ResetType::Hard,
+        other => return Err(format!("Unknown reset mode '{}'", other)),
+    };
+
+    let commit = find_commit(&repo, &target)?;
+    repo.reset(commit.as_object(), reset_type, None).map_err(|e| e.to_string())?;
+    repo.cleanup_state().map_err(|e| e.to_string())?;
+
+    Ok(GitOperationResult::done(
+        format!("Reset ({}) to {}", mode, &commit.id().to_string()[..7]),
+        Vec::new(),
+    ))
+}
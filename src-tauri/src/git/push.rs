@@ -1,136 +1,256 @@
-use git2::{Repository};
+use git2::{BranchType, Oid, Repository};
+use std::cell::RefCell;
+use tauri::State;
+
+use super::credentials;
+use super::service::GitService;
+
+#[derive(Clone, serde::Serialize)]
+pub struct PushRefUpdate {
+    refname: String,
+    success: bool,
+    /// Причина отказа от сервера, если ссылка не обновлена
+    message: Option<String>,
+}
 
 #[derive(Clone, serde::Serialize)]
 pub struct PushResult {
     success: bool,
     message: String,
     pushed_refs: Vec<String>,
+    ref_updates: Vec<PushRefUpdate>,
+    upstream_set: bool,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+pub struct GitPushOptions {
+    /// `None` — настроить upstream только если у ветки его ещё нет (первый push)
+    pub set_upstream: Option<bool>,
+    /// Перезаписать удалённую ветку, только если она там, где мы её видели последний раз
+    pub force_with_lease: bool,
+    /// Ожидаемый коммит на удалённой стороне; по умолчанию — remote-tracking ветка
+    pub expected_remote_oid: Option<String>,
+    /// Отправить все локальные теги
+    pub push_tags: bool,
+    /// Отдельные теги для отправки
+    pub tags: Vec<String>,
 }
 
 #[tauri::command]
-pub fn git_push(repo_path: String, remote_name: Option<String>, branch_name: Option<String>, force: bool) -> Result<PushResult, String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
-    
-    // Определяем удаленный репозиторий (по умолчанию "origin")
-    let remote_name = remote_name.unwrap_or_else(|| "origin".to_string());
-    
-    // Находим удаленный репозиторий
-    let remote = repo.find_remote(&remote_name)
+pub async fn git_push(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    repo_path: String,
+    remote_name: Option<String>,
+    branch_name: Option<String>,
+    force: bool,
+    options: Option<GitPushOptions>,
+) -> Result<PushResult, String> {
+    // Отдельный поток: запрос пароля от SSH ключа не должен блокировать UI
+    let session = credentials::session_for(&app_handle);
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || push(session, &repo, remote_name, branch_name, force, options.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Push task failed: {}", e))?
+}
+
+fn push(
+    mut session: credentials::CredentialSession,
+    repo: &Repository,
+    remote_name: Option<String>,
+    branch_name: Option<String>,
+    force: bool,
+    options: GitPushOptions,
+) -> Result<PushResult, String> {
+    // Определяем ветку для отправки (по умолчанию текущая ветка).
+    // Имя берём целиком: "feature/login" должна уйти как refs/heads/feature/login
+    let branch_name = match branch_name {
+        Some(name) => name.strip_prefix("refs/heads/").unwrap_or(&name).to_string(),
+        None => {
+            let head = repo.head().map_err(|e| format!("Cannot determine branch to push: {}", e))?;
+            head.name()
+                .and_then(|name| name.strip_prefix("refs/heads/"))
+                .map(|name| name.to_string())
+                .ok_or_else(|| "HEAD is detached; choose a branch to push".to_string())?
+        }
+    };
+    let local_ref = format!("refs/heads/{}", branch_name);
+    repo.find_branch(&branch_name, BranchType::Local)
+        .map_err(|e| format!("Local branch '{}' not found: {}", branch_name, e))?;
+
+    // Удалённый репозиторий: явно заданный, настроенный для ветки, иначе "origin"
+    let configured_remote = repo.branch_upstream_remote(&local_ref)
+        .ok()
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()));
+    let remote_name = remote_name
+        .or_else(|| configured_remote.clone())
+        .unwrap_or_else(|| "origin".to_string());
+
+    let mut remote = repo.find_remote(&remote_name)
         .map_err(|e| format!("Remote '{}' not found: {}", remote_name, e))?;
-    
-    // Определяем ветку для отправки (по умолчанию текущая ветка)
-    let branch_name = branch_name
-        .or_else(|| {
-            repo.head().ok().and_then(|head| {
-                let head_name = head.name()?.to_string();
-                if let Some(local) = head_name.strip_prefix("refs/heads/") {
-                    // Если локальная ветка имеет имя вида "origin/xxx", берём только последнюю часть
-                    // чтобы избежать создания refs/heads/origin/main
-                    let branch = if local.contains('/') {
-                        local.rsplit('/').next().unwrap_or(local)
-                    } else {
-                        local
-                    };
-                    return Some(branch.to_string());
-                }
-                if let Some(remote_ref) = head_name.strip_prefix("refs/remotes/") {
-                    // Для remote refs берём только имя ветки после remote name
-                    return remote_ref.split_once('/').map(|(_, b)| b.to_string());
-                }
-                None
-            })
-        })
-        .or_else(|| {
-            repo.head()
-                .ok()
-                .and_then(|head| head.shorthand().map(|s| s.to_string()))
-                .map(|s| {
-                    // Убираем prefix типа "origin/" если есть
-                    if s.contains('/') {
-                        s.rsplit('/').next().unwrap_or(&s).to_string()
-                    } else {
-                        s
-                    }
-                })
-        })
-        .ok_or_else(|| "Cannot determine branch to push".to_string())?;
 
-    // Получаем refspec для отправки
-    let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch_name);
-    
+    // Если upstream уже настроен на этот remote, пушим в его ветку, иначе в одноимённую
+    let has_upstream = configured_remote.as_deref() == Some(remote_name.as_str());
+    let remote_ref = if has_upstream {
+        repo.branch_upstream_merge(&local_ref)
+            .ok()
+            .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+            .unwrap_or_else(|| local_ref.clone())
+    } else {
+        local_ref.clone()
+    };
+
+    let overwrite = force || options.force_with_lease;
+    let branch_refspec = format!("{}{}:{}", if overwrite { "+" } else { "" }, local_ref, remote_ref);
+    let mut refspecs = vec![branch_refspec];
+
+    let mut tags = options.tags.clone();
+    if options.push_tags {
+        let all_tags = repo.tag_names(None).map_err(|e| e.to_string())?;
+        tags.extend(all_tags.iter().flatten().map(|t| t.to_string()));
+    }
+    tags.sort();
+    tags.dedup();
+    for tag in &tags {
+        refspecs.push(format!("refs/tags/{0}:refs/tags/{0}", tag));
+    }
+
+    // Для force-with-lease запоминаем, где ветка была на remote при последнем fetch
+    let lease = if options.force_with_lease && !force {
+        let expected = match &options.expected_remote_oid {
+            Some(oid) => Oid::from_str(oid).map_err(|e| format!("Invalid expected commit: {}", e))?,
+            None => {
+                let tracking = format!(
+                    "refs/remotes/{}/{}",
+                    remote_name,
+                    remote_ref.strip_prefix("refs/heads/").unwrap_or(&remote_ref)
+                );
+                // Нет remote-tracking ветки — ожидаем, что на remote ветки тоже нет
+                repo.refname_to_id(&tracking).unwrap_or_else(|_| Oid::zero())
+            }
+        };
+        Some(expected)
+    } else {
+        None
+    };
+
+    let ref_updates: RefCell<Vec<PushRefUpdate>> = RefCell::new(Vec::new());
+    let lease_error: RefCell<Option<String>> = RefCell::new(None);
+
     // Настраиваем опции отправки
     let mut push_options = git2::PushOptions::new();
     let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(|_url, username_from_url, _allowed_types| {
-        let username = username_from_url.unwrap_or("git");
-        
-        // Сначала пробуем GitHub CLI token
-        if let Ok(token) = std::process::Command::new("gh")
-            .args(&["auth", "token"])
-            .output()
-        {
-            if token.status.success() {
-                if let Ok(token_str) = String::from_utf8(token.stdout) {
-                    let token_str = token_str.trim();
-                    if !token_str.is_empty() {
-                        if let Ok(cred) = git2::Cred::userpass_plaintext(username, token_str) {
-                            return Ok(cred);
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Пробуем SSH ключи из агента
-        if let Ok(cred) = git2::Cred::ssh_key_from_agent(username) {
-            return Ok(cred);
-        }
-        
-        // Пробуем credential helper (для HTTPS)
-        if let Ok(git_config) = git2::Config::open_default() {
-            if let Ok(cred) = git2::Cred::credential_helper(&git_config, username, None) {
-                return Ok(cred);
+    callbacks.credentials(|url, username, allowed| session.callback(url, username, allowed));
+    callbacks.push_negotiation(|updates| {
+        let Some(expected) = lease else {
+            return Ok(());
+        };
+        for update in updates {
+            // src() — текущее значение ссылки на сервере
+            if update.dst_refname() == Some(remote_ref.as_str()) && update.src() != expected {
+                let message = format!(
+                    "stale info: {} is at {} on {}, expected {}",
+                    remote_ref, update.src(), remote_name, expected
+                );
+                *lease_error.borrow_mut() = Some(message.clone());
+                return Err(git2::Error::from_str(&message));
             }
         }
-        
-        // Возвращаем default credential как последнюю попытку
-        git2::Cred::default()
+        Ok(())
+    });
+    callbacks.push_update_reference(|refname, status| {
+        ref_updates.borrow_mut().push(PushRefUpdate {
+            refname: refname.to_string(),
+            success: status.is_none(),
+            message: status.map(|s| s.to_string()),
+        });
+        Ok(())
     });
     push_options.remote_callbacks(callbacks);
-    
+
     // Выполняем отправку
-    let mut remote = remote;
-    let result = remote.push(&[&refspec], Some(&mut push_options));
-    
+    let refspec_refs: Vec<&str> = refspecs.iter().map(|s| s.as_str()).collect();
+    let result = remote.push(&refspec_refs, Some(&mut push_options));
+    drop(push_options);
+    let ref_updates = ref_updates.into_inner();
+
+    if let Some(message) = lease_error.into_inner() {
+        return Ok(PushResult {
+            success: false,
+            message: format!("Push rejected ({}).\nFetch to see the new remote commits before overwriting them.", message),
+            pushed_refs: vec![],
+            ref_updates,
+            upstream_set: false,
+        });
+    }
+
     match result {
         Ok(_) => {
+            let rejected: Vec<&PushRefUpdate> = ref_updates.iter().filter(|u| !u.success).collect();
+            if !rejected.is_empty() {
+                let details: Vec<String> = rejected.iter()
+                    .map(|u| format!("{}: {}", u.refname, u.message.as_deref().unwrap_or("rejected")))
+                    .collect();
+                return Ok(PushResult {
+                    success: false,
+                    message: format!("Push rejected by {}:\n{}", remote_name, details.join("\n")),
+                    pushed_refs: ref_updates.iter().filter(|u| u.success).map(|u| u.refname.clone()).collect(),
+                    ref_updates,
+                    upstream_set: false,
+                });
+            }
+
+            // --set-upstream: по умолчанию только при первом push ветки.
+            // Push в другой remote (например, fork) не должен переносить существующий upstream
+            let upstream_set = if options.set_upstream.unwrap_or(configured_remote.is_none()) {
+                let mut config = repo.config().map_err(|e| e.to_string())?;
+                config.set_str(&format!("branch.{}.remote", branch_name), &remote_name)
+                    .map_err(|e| e.to_string())?;
+                config.set_str(&format!("branch.{}.merge", branch_name), &remote_ref)
+                    .map_err(|e| e.to_string())?;
+                true
+            } else {
+                false
+            };
+
             Ok(PushResult {
                 success: true,
                 message: format!("Successfully pushed {} to {}", branch_name, remote_name),
-                pushed_refs: vec![refspec],
+                pushed_refs: refspecs,
+                ref_updates,
+                upstream_set,
             })
         }
         Err(e) => {
             let error_msg = e.to_string();
-            
+
             // Проверяем на распространенные ошибки
-            if error_msg.contains("rejected") && error_msg.contains("non-fast-forward") && !force {
+            let non_fast_forward = e.code() == git2::ErrorCode::NotFastForward
+                || (error_msg.contains("rejected") && error_msg.contains("non-fast-forward"));
+            if non_fast_forward && !overwrite {
                 Ok(PushResult {
                     success: false,
                     message: format!("Push rejected: {}.\nTry pulling latest changes or use force push.", error_msg),
                     pushed_refs: vec![],
+                    ref_updates,
+                    upstream_set: false,
                 })
             } else if error_msg.contains("authentication") || error_msg.contains("Auth") {
                 Ok(PushResult {
                     success: false,
                     message: format!("Authentication failed: {}. Please check your credentials.\nFor HTTPS: Make sure you have configured credential helper or personal access token.\nFor SSH: Make sure your SSH keys are properly configured.", error_msg),
                     pushed_refs: vec![],
+                    ref_updates,
+                    upstream_set: false,
                 })
             } else if error_msg.contains("no such file") || error_msg.contains("not found") {
                 Ok(PushResult {
                     success: false,
                     message: format!("Repository not found: {}. Please check if the remote URL is correct.", error_msg),
                     pushed_refs: vec![],
+                    ref_updates,
+                    upstream_set: false,
                 })
             } else {
                 Err(error_msg)
@@ -140,34 +260,84 @@ pub fn git_push(repo_path: String, remote_name: Option<String>, branch_name: Opt
 }
 
 #[tauri::command]
-pub fn git_push_with_force(repo_path: String, remote_name: Option<String>, branch_name: Option<String>) -> Result<PushResult, String> {
-    git_push(repo_path, remote_name, branch_name, true)
+pub async fn git_push_with_force(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    repo_path: String,
+    remote_name: Option<String>,
+    branch_name: Option<String>,
+) -> Result<PushResult, String> {
+    git_push(app_handle, service, repo_path, remote_name, branch_name, true, None).await
 }
 
 #[tauri::command]
-pub fn git_list_remotes(repo_path: String) -> Result<Vec<String>, String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
-    
+pub fn git_list_remotes(service: State<'_, GitService>, repo_path: String) -> Result<Vec<String>, String> {
+    let repo = service.open(&repo_path)?;
+
     let remotes = repo.remotes()
         .map_err(|e| e.to_string())?
         .iter()
         .filter_map(|name| name.map(|s| s.to_string()))
         .collect();
-    
+
     Ok(remotes)
 }
 
 #[tauri::command]
-pub fn git_get_remote_url(repo_path: String, remote_name: String) -> Result<String, String> {
-    let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
-    
+pub fn git_get_remote_url(service: State<'_, GitService>, repo_path: String, remote_name: String) -> Result<String, String> {
+    let repo = service.open(&repo_path)?;
+
     let remote = repo.find_remote(&remote_name)
         .map_err(|e| format!("Remote '{}' not found: {}", remote_name, e))?;
-    
+
     let url = remote.url()
         .or_else(|| remote.pushurl())
         .ok_or_else(|| "No URL configured for remote".to_string())?
         .to_string();
-    
+
     Ok(url)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bare_remote(repo: &Repository, name: &str, dir: &std::path::Path) {
+        let path = dir.join(format!("{}.git", name));
+        Repository::init_bare(&path).unwrap();
+        repo.remote(name, &path.to_string_lossy()).unwrap();
+    }
+
+    fn upstream_remote(repo: &Repository) -> Option<String> {
+        repo.config().unwrap().snapshot().unwrap().get_string("branch.main.remote").ok()
+    }
+
+    #[test]
+    fn test_push_to_second_remote_keeps_upstream() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path().join("work")).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        repo.commit(Some("refs/heads/main"), &sig, &sig, "init", &tree, &[]).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        bare_remote(&repo, "origin", dir.path());
+        bare_remote(&repo, "fork", dir.path());
+
+        // Первый push ветки настраивает upstream
+        let result = push(credentials::test_session(), &repo, None, None, false, GitPushOptions::default()).unwrap();
+        assert!(result.success && result.upstream_set);
+        assert_eq!(upstream_remote(&repo).as_deref(), Some("origin"));
+
+        let result = push(credentials::test_session(), &repo, Some("fork".into()), None, false, GitPushOptions::default()).unwrap();
+        assert!(result.success, "{}", result.message);
+        assert!(!result.upstream_set);
+        assert_eq!(upstream_remote(&repo).as_deref(), Some("origin"));
+        assert!(Repository::open_bare(dir.path().join("fork.git")).unwrap().find_reference("refs/heads/main").is_ok());
+
+        // Явный запрос всё ещё переносит upstream
+        let options = GitPushOptions { set_upstream: Some(true), ..Default::default() };
+        let result = push(credentials::test_session(), &repo, Some("fork".into()), None, false, options).unwrap();
+        assert!(result.upstream_set);
+        assert_eq!(upstream_remote(&repo).as_deref(), Some("fork"));
+    }
+}
@@ -0,0 +1,184 @@
+use git2::build::CheckoutBuilder;
+use git2::{FetchOptions, FetchPrune, RemoteCallbacks, Repository};
+use serde::Serialize;
+use std::cell::RefCell;
+use tauri::State;
+
+use super::credentials::{self, CredentialSession};
+use super::service::GitService;
+use super::operations::{conflicted_paths, ensure_clean_state, GitOperationResult};
+use super::{default_signature, write_commit};
+
+#[derive(Serialize, Debug)]
+pub struct GitFetchResult {
+    pub remote: String,
+    /// Local refs moved by the fetch, e.g. `refs/remotes/origin/main`.
+    pub updated_refs: Vec<String>,
+    pub received_objects: usize,
+}
+
+/// Full name of the branch HEAD points at, even before its first commit.
+pub(crate) fn current_branch_ref(repo: &Repository) -> Result<String, String> {
+    let name = match repo.head() {
+        Ok(head) => head.name().map(|n| n.to_string()),
+        Err(_) => repo.find_reference("HEAD").ok().and_then(|h| h.symbolic_target().map(|n| n.to_string())),
+    };
+    name.filter(|n| n.starts_with("refs/heads/"))
+        .ok_or_else(|| "HEAD is detached; check out a branch first".to_string())
+}
+
+/// The current branch's configured remote, falling back to "origin".
+pub(crate) fn default_remote(repo: &Repository) -> String {
+    current_branch_ref(repo)
+        .ok()
+        .and_then(|branch| repo.branch_upstream_remote(&branch).ok())
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "origin".to_string())
+}
+
+/// Fetches `refspecs`, or the remote's configured refspecs when empty.
+pub(crate) fn fetch_remote(
+    repo: &Repository,
+    mut session: CredentialSession,
+    remote_name: &str,
+    refspecs: &[&str],
+    prune: bool,
+) -> Result<GitFetchResult, String> {
+    let mut remote = repo.find_remote(remote_name)
+        .map_err(|e| format!("Remote '{}' not found: {}", remote_name, e))?;
+
+    let updated = RefCell::new(Vec::new());
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| session.callback(url, username, allowed));
+    callbacks.update_tips(|refname, _old, _new| {
+        updated.borrow_mut().push(refname.to_string());
+        true
+    });
+
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(callbacks);
+    if prune {
+        options.prune(FetchPrune::On);
+    }
+
+    remote.fetch(refspecs, Some(&mut options), None)
+        .map_err(|e| format!("Fetch from {} failed: {}", remote_name, e))?;
+    let received_objects = remote.stats().received_objects();
+    drop(options);
+
+    Ok(GitFetchResult {
+        remote: remote_name.to_string(),
+        updated_refs: updated.into_inner(),
+        received_objects,
+    })
+}
+
+#[tauri::command]
+pub async fn git_fetch(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    repo_path: String,
+    remote_name: Option<String>,
+    prune: Option<bool>,
+) -> Result<GitFetchResult, String> {
+    let session = credentials::session_for(&app_handle);
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || {
+        let remote_name = remote_name.unwrap_or_else(|| default_remote(&repo));
+        fetch_remote(&repo, session, &remote_name, &[], prune.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("Fetch task failed: {}", e))?
+}
+
+/// Fetches and integrates the upstream of the current branch: fast-forward
+/// when possible, otherwise a merge commit. Conflicts are left in the
+/// working tree like `git_cherry_pick` does.
+#[tauri::command]
+pub async fn git_pull(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    repo_path: String,
+    remote_name: Option<String>,
+) -> Result<GitOperationResult, String> {
+    let session = credentials::session_for(&app_handle);
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || pull(session, &repo, remote_name))
+        .await
+        .map_err(|e| format!("Pull task failed: {}", e))?
+}
+
+fn pull(session: CredentialSession, repo: &Repository, remote_name: Option<String>) -> Result<GitOperationResult, String> {
+    ensure_clean_state(repo)?;
+
+    let local_ref = current_branch_ref(repo)?;
+    let branch = local_ref.trim_start_matches("refs/heads/").to_string();
+    let remote_name = remote_name.unwrap_or_else(|| default_remote(repo));
+    let merge_ref = repo.branch_upstream_merge(&local_ref)
+        .ok()
+        .and_then(|buf| buf.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| local_ref.clone());
+    let upstream_branch = merge_ref.trim_start_matches("refs/heads/").to_string();
+
+    fetch_remote(repo, session, &remote_name, &[], false)?;
+
+    let tracking = format!("refs/remotes/{}/{}", remote_name, upstream_branch);
+    let fetched = repo.find_reference(&tracking)
+        .map_err(|_| format!("{} has no branch '{}'", remote_name, upstream_branch))?;
+    let incoming = repo.reference_to_annotated_commit(&fetched).map_err(|e| e.to_string())?;
+    let short = &incoming.id().to_string()[..7];
+
+    let (analysis, _) = repo.merge_analysis(&[&incoming]).map_err(|e| e.to_string())?;
+    if analysis.is_up_to_date() {
+        return Ok(GitOperationResult::done("Already up to date".to_string(), Vec::new()));
+    }
+
+    if analysis.is_fast_forward() || analysis.is_unborn() {
+        let target = repo.find_commit(incoming.id()).map_err(|e| e.to_string())?;
+        // Check out before moving the branch so local edits are compared against the old HEAD
+        repo.checkout_tree(target.as_object(), Some(CheckoutBuilder::new().safe()))
+            .map_err(|e| format!("Cannot fast-forward: {}", e))?;
+
+        let reflog = format!("pull: Fast-forward to {}", short);
+        match repo.find_reference(&local_ref) {
+            Ok(mut reference) => {
+                reference.set_target(target.id(), &reflog).map_err(|e| e.to_string())?;
+            }
+            Err(_) => {
+                repo.reference(&local_ref, target.id(), true, &reflog).map_err(|e| e.to_string())?;
+            }
+        }
+        repo.set_head(&local_ref).map_err(|e| e.to_string())?;
+
+        return Ok(GitOperationResult::done(format!("Fast-forwarded {} to {}", branch, short), Vec::new()));
+    }
+
+    repo.merge(&[&incoming], None, None).map_err(|e| e.to_string())?;
+    let mut index = repo.index().map_err(|e| e.to_string())?;
+    if index.has_conflicts() {
+        return Ok(GitOperationResult::conflicted(
+            format!("Merge of {}/{} stopped with conflicts; resolve them and commit, or reset to abort", remote_name, upstream_branch),
+            Vec::new(),
+            conflicted_paths(&index)?,
+        ));
+    }
+
+    let tree_id = index.write_tree().map_err(|e| e.to_string())?;
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+    let head = repo.head().and_then(|h| h.peel_to_commit()).map_err(|e| e.to_string())?;
+    let theirs = repo.find_commit(incoming.id()).map_err(|e| e.to_string())?;
+
+    let url = repo.find_remote(&remote_name).ok().and_then(|r| r.url().map(|u| u.to_string()));
+    let message = match url {
+        Some(url) => format!("Merge branch '{}' of {}", upstream_branch, url),
+        None => format!("Merge branch '{}' of {}", upstream_branch, remote_name),
+    };
+    let signature = default_signature(repo)?;
+    let commit_id = write_commit(repo, &signature, &signature, &message, &tree, &[&head, &theirs])?;
+    repo.cleanup_state().map_err(|e| e.to_string())?;
+
+    Ok(GitOperationResult::done(
+        format!("Merged {}/{} into {}", remote_name, upstream_branch, branch),
+        vec![commit_id.to_string()],
+    ))
+}
@@ -0,0 +1,201 @@
+use git2::Repository;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const MAX_CACHED_REPOS: usize = 16;
+
+struct CachedRepo {
+    git_dir: PathBuf,
+    /// `None` while a command has the handle checked out.
+    idle: Option<Repository>,
+    last_used: Instant,
+}
+
+/// Shared entry point for opening repositories. Handles are cached per path
+/// and checked out for the duration of a command, so repeated calls skip
+/// repository discovery and config loading. A handle that is already in use
+/// (a long push, a hook run) is never waited for: the caller gets a fresh one.
+#[derive(Clone, Default)]
+pub struct GitService {
+    repos: Arc<Mutex<HashMap<PathBuf, CachedRepo>>>,
+}
+
+fn cache_key(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl GitService {
+    pub fn open(&self, path: &str) -> Result<RepoHandle, String> {
+        let key = cache_key(path);
+
+        let cached = match self.repos.lock() {
+            Ok(mut repos) => match repos.get_mut(&key) {
+                // The repository was deleted or re-created under us
+                Some(entry) if !entry.git_dir.exists() => {
+                    repos.remove(&key);
+                    None
+                }
+                Some(entry) => {
+                    entry.last_used = Instant::now();
+                    entry.idle.take()
+                }
+                None => None,
+            },
+            Err(_) => None,
+        };
+
+        let repo = match cached {
+            Some(repo) => {
+                // Pick up index changes made by other tools since the last command
+                repo.index().and_then(|mut index| index.read(false)).map_err(|e| e.to_string())?;
+                repo
+            }
+            None => Repository::open(path).map_err(|e| e.to_string())?,
+        };
+
+        Ok(RepoHandle {
+            repo: Some(repo),
+            key,
+            repos: self.repos.clone(),
+        })
+    }
+
+    /// Drops the cached handle for `path`, e.g. after deleting a worktree.
+    pub fn forget(&self, path: &str) {
+        if let Ok(mut repos) = self.repos.lock() {
+            repos.remove(&cache_key(path));
+        }
+    }
+
+    fn cached_count(&self) -> usize {
+        self.repos.lock().map(|r| r.len()).unwrap_or(0)
+    }
+}
+
+/// A checked-out repository; returns to the cache when dropped.
+pub struct RepoHandle {
+    repo: Option<Repository>,
+    key: PathBuf,
+    repos: Arc<Mutex<HashMap<PathBuf, CachedRepo>>>,
+}
+
+impl Deref for RepoHandle {
+    type Target = Repository;
+
+    fn deref(&self) -> &Repository {
+        self.repo.as_ref().expect("repository handle used after release")
+    }
+}
+
+impl Drop for RepoHandle {
+    fn drop(&mut self) {
+        let Some(repo) = self.repo.take() else {
+            return;
+        };
+        let Ok(mut repos) = self.repos.lock() else {
+            return;
+        };
+
+        if !repos.contains_key(&self.key) && repos.len() >= MAX_CACHED_REPOS {
+            let oldest = repos.iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                repos.remove(&oldest);
+            }
+        }
+
+        let entry = repos.entry(self.key.clone()).or_insert_with(|| CachedRepo {
+            git_dir: repo.path().to_path_buf(),
+            idle: None,
+            last_used: Instant::now(),
+        });
+        // A second handle opened while this one was busy is simply discarded
+        if entry.idle.is_none() {
+            entry.idle = Some(repo);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_repo() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        Repository::init(dir.path()).unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        (dir, path)
+    }
+
+    #[test]
+    fn test_handle_is_reused() {
+        let (_dir, path) = init_repo();
+        let service = GitService::default();
+
+        drop(service.open(&path).unwrap());
+        assert_eq!(service.cached_count(), 1);
+
+        // Checked out again rather than reopened: the slot is empty while in use
+        let _handle = service.open(&path).unwrap();
+        let repos = service.repos.lock().unwrap();
+        assert!(repos.values().all(|entry| entry.idle.is_none()));
+    }
+
+    #[test]
+    fn test_busy_handle_does_not_block() {
+        let (_dir, path) = init_repo();
+        let service = GitService::default();
+
+        let held = service.open(&path).unwrap();
+        let other = service.open(&path).unwrap();
+        assert_eq!(held.path(), other.path());
+        drop(other);
+        drop(held);
+        assert_eq!(service.cached_count(), 1);
+    }
+
+    #[test]
+    fn test_deleted_repository_is_reopened_or_fails() {
+        let (dir, path) = init_repo();
+        let service = GitService::default();
+        drop(service.open(&path).unwrap());
+
+        std::fs::remove_dir_all(dir.path().join(".git")).unwrap();
+        assert!(service.open(&path).is_err());
+        assert_eq!(service.cached_count(), 0);
+
+        Repository::init(dir.path()).unwrap();
+        assert!(service.open(&path).is_ok());
+    }
+
+    #[test]
+    fn test_sees_index_written_by_another_handle() {
+        let (dir, path) = init_repo();
+        let service = GitService::default();
+        drop(service.open(&path).unwrap());
+
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let outside = Repository::open(&path).unwrap();
+        let mut index = outside.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+
+        let repo = service.open(&path).unwrap();
+        assert!(repo.index().unwrap().get_path(Path::new("a.txt"), 0).is_some());
+    }
+
+    #[test]
+    fn test_cache_is_bounded() {
+        let service = GitService::default();
+        let dirs: Vec<_> = (0..MAX_CACHED_REPOS + 3).map(|_| init_repo()).collect();
+        for (_, path) in &dirs {
+            drop(service.open(path).unwrap());
+        }
+        assert_eq!(service.cached_count(), MAX_CACHED_REPOS);
+    }
+}
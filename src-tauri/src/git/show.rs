@@ -0,0 +1,179 @@
+use git2::{Delta, Diff, DiffFindOptions, Oid, Patch, Repository, Tree};
+use serde::Serialize;
+use std::path::Path;
+use tauri::State;
+
+use super::avatar::AvatarService;
+use super::log::CommitDecorations;
+use super::service::GitService;
+use super::{DiffLine, FileDiff, GitCommit};
+
+#[derive(Serialize, Debug)]
+pub struct GitCommitDetails {
+    pub commit: GitCommit,
+    pub summary: String,
+    pub body: String,
+    pub committer_name: String,
+    pub committer_email: String,
+    pub committer_timestamp: i64,
+    /// Changes against the first parent (or the empty tree for a root commit).
+    pub files: Vec<FileDiff>,
+}
+
+pub(crate) fn diff_line_type(origin: char) -> &'static str {
+    match origin {
+        '+' | '>' => "add",
+        '-' | '<' => "delete",
+        'H' | 'F' => "header",
+        '@' => "hunk",
+        _ => "context",
+    }
+}
+
+fn delta_status(status: Delta) -> &'static str {
+    match status {
+        Delta::Added | Delta::Untracked => "added",
+        Delta::Deleted => "deleted",
+        Delta::Renamed => "renamed",
+        Delta::Copied => "copied",
+        Delta::Typechange => "typechange",
+        Delta::Conflicted => "conflicted",
+        _ => "modified",
+    }
+}
+
+fn blob_text(repo: &Repository, id: Oid) -> String {
+    if id.is_zero() {
+        return String::new();
+    }
+    repo.find_blob(id)
+        .ok()
+        .and_then(|blob| std::str::from_utf8(blob.content()).ok().map(|s| s.to_string()))
+        .unwrap_or_default()
+}
+
+/// Converts every delta of `diff` into a `FileDiff`, detecting renames and copies.
+/// With `path`, only files at or below that path (old or new side) are kept.
+pub(crate) fn collect_file_diffs(repo: &Repository, diff: &mut Diff, path: Option<&Path>) -> Result<Vec<FileDiff>, String> {
+    let mut find_opts = DiffFindOptions::new();
+    find_opts.renames(true);
+    find_opts.copies(true);
+    diff.find_similar(Some(&mut find_opts)).map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+
+    for idx in 0..diff.deltas().len() {
+        let Some(mut patch) = Patch::from_diff(diff, idx).map_err(|e| e.to_string())? else {
+            // libgit2 yields no patch for unchanged entries
+            continue;
+        };
+
+        let delta = patch.delta();
+        let old_path = delta.old_file().path().map(|p| p.to_path_buf());
+        let new_path = delta.new_file().path().map(|p| p.to_path_buf());
+
+        if let Some(filter) = path {
+            let matches = |p: &Option<std::path::PathBuf>| p.as_ref().map(|p| p.starts_with(filter)).unwrap_or(false);
+            if !matches(&old_path) && !matches(&new_path) {
+                continue;
+            }
+        }
+
+        let status = delta.status();
+        let is_binary = delta.flags().is_binary() || delta.old_file().is_binary() || delta.new_file().is_binary();
+        let file_path = new_path.as_ref().or(old_path.as_ref())
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let old_file_path = match status {
+            Delta::Renamed | Delta::Copied => old_path.map(|p| p.to_string_lossy().to_string()),
+            _ => None,
+        };
+
+        let (old_content, new_content) = if is_binary {
+            (String::new(), String::new())
+        } else {
+            (blob_text(repo, delta.old_file().id()), blob_text(repo, delta.new_file().id()))
+        };
+
+        let mut lines = Vec::new();
+        if !is_binary {
+            patch.print(&mut |_delta, _hunk, line| {
+                lines.push(DiffLine {
+                    line_type: diff_line_type(line.origin()).to_string(),
+                    content: String::from_utf8_lossy(line.content()).to_string(),
+                    old_line_no: line.old_lineno(),
+                    new_line_no: line.new_lineno(),
+                });
+                true
+            }).map_err(|e| e.to_string())?;
+        }
+
+        files.push(FileDiff {
+            file_path,
+            old_content,
+            new_content,
+            lines,
+            is_new_file: status == Delta::Added,
+            is_deleted: status == Delta::Deleted,
+            status: delta_status(status).to_string(),
+            old_file_path,
+            is_binary,
+        });
+    }
+
+    Ok(files)
+}
+
+fn resolve_tree<'r>(repo: &'r Repository, rev: &str) -> Result<Tree<'r>, String> {
+    repo.revparse_single(rev)
+        .and_then(|obj| obj.peel_to_tree())
+        .map_err(|e| format!("Unknown revision '{}': {}", rev, e))
+}
+
+#[tauri::command]
+pub fn git_show_commit(service: State<'_, GitService>, avatars: State<'_, AvatarService>, repo_path: String, hash: String) -> Result<GitCommitDetails, String> {
+    let repo = service.open(&repo_path)?;
+    let commit = repo.revparse_single(&hash)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("Unknown commit '{}': {}", hash, e))?;
+
+    let tree = commit.tree().map_err(|e| e.to_string())?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().map_err(|e| e.to_string())?),
+        Err(_) => None,
+    };
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(|e| e.to_string())?;
+    let files = collect_file_diffs(&repo, &mut diff, None)?;
+
+    let committer = commit.committer();
+    let details = GitCommitDetails {
+        summary: commit.summary().unwrap_or("").to_string(),
+        body: commit.body().unwrap_or("").trim().to_string(),
+        committer_name: committer.name().unwrap_or("Unknown").to_string(),
+        committer_email: committer.email().unwrap_or("").to_string(),
+        committer_timestamp: committer.when().seconds(),
+        commit: CommitDecorations::load(&repo, &avatars).to_git_commit(&repo, &commit, true),
+        files,
+    };
+
+    Ok(details)
+}
+
+/// Diff between any two revisions (commits, branches, tags, `HEAD~2`, ...).
+#[tauri::command]
+pub fn git_diff_revisions(service: State<'_, GitService>, repo_path: String, from: String, to: String, path: Option<String>) -> Result<Vec<FileDiff>, String> {
+    let repo = service.open(&repo_path)?;
+    let from_tree = resolve_tree(&repo, &from)?;
+    let to_tree = resolve_tree(&repo, &to)?;
+
+    let mut diff = repo.diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)
+        .map_err(|e| e.to_string())?;
+
+    let path = path.as_deref()
+        .map(|p| p.trim().trim_end_matches('/'))
+        .filter(|p| !p.is_empty() && *p != ".")
+        .map(Path::new);
+
+    collect_file_diffs(&repo, &mut diff, path)
+}
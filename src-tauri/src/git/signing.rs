@@ -0,0 +1,335 @@
+use git2::{Config, Repository, Signature};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use super::expand_home;
+
+#[derive(Debug, Clone, PartialEq)]
+enum SigningFormat {
+    OpenPgp,
+    X509,
+    Ssh,
+}
+
+/// Commit signing setup read from `commit.gpgsign`, `gpg.format`,
+/// `user.signingkey` and the matching `gpg.*.program`.
+#[derive(Debug, Clone)]
+pub(crate) struct SigningConfig {
+    format: SigningFormat,
+    program: String,
+    key: String,
+}
+
+impl SigningConfig {
+    /// `None` when the repository does not ask for signed commits.
+    pub(crate) fn from_repo(repo: &Repository, committer: &Signature) -> Result<Option<Self>, String> {
+        let config = repo.config().map_err(|e| e.to_string())?;
+        Self::from_config(&config, committer)
+    }
+
+    fn from_config(config: &Config, committer: &Signature) -> Result<Option<Self>, String> {
+        if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let format = match config.get_string("gpg.format").unwrap_or_else(|_| "openpgp".to_string()).as_str() {
+            "openpgp" => SigningFormat::OpenPgp,
+            "x509" => SigningFormat::X509,
+            "ssh" => SigningFormat::Ssh,
+            other => return Err(format!("Unsupported gpg.format '{}'", other)),
+        };
+
+        let program = match format {
+            SigningFormat::OpenPgp => config.get_string("gpg.openpgp.program")
+                .or_else(|_| config.get_string("gpg.program"))
+                .unwrap_or_else(|_| "gpg".to_string()),
+            SigningFormat::X509 => config.get_string("gpg.x509.program").unwrap_or_else(|_| "gpgsm".to_string()),
+            SigningFormat::Ssh => config.get_string("gpg.ssh.program").unwrap_or_else(|_| "ssh-keygen".to_string()),
+        };
+
+        let key = match config.get_string("user.signingkey") {
+            Ok(key) if !key.trim().is_empty() => key.trim().to_string(),
+            // gpg picks the key matching the committer identity, ssh has no such fallback
+            _ if format == SigningFormat::Ssh => {
+                return Err("commit.gpgsign is set but user.signingkey is missing for SSH signing".to_string());
+            }
+            _ => format!("{} <{}>", committer.name().unwrap_or(""), committer.email().unwrap_or("")),
+        };
+
+        Ok(Some(Self { format, program, key }))
+    }
+
+    /// Detached, armored signature over the raw commit object.
+    pub(crate) fn sign(&self, payload: &str) -> Result<String, String> {
+        match self.format {
+            SigningFormat::OpenPgp | SigningFormat::X509 => self.sign_gpg(payload),
+            SigningFormat::Ssh => self.sign_ssh(payload),
+        }
+    }
+
+    fn sign_gpg(&self, payload: &str) -> Result<String, String> {
+        let mut child = Command::new(&self.program)
+            .args(["--status-fd=2", "-bsau", &self.key])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", self.program, e))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(payload.as_bytes()).map_err(|e| e.to_string())?;
+        }
+
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        let status = String::from_utf8_lossy(&output.stderr);
+        // Same success check git uses: the exit code alone is not reliable across gpg versions
+        if !output.status.success() || !status.contains("[GNUPG:] SIG_CREATED ") {
+            return Err(format!("Failed to sign commit with {}: {}", self.program, status.trim()));
+        }
+
+        String::from_utf8(output.stdout).map_err(|e| e.to_string())
+    }
+
+    fn sign_ssh(&self, payload: &str) -> Result<String, String> {
+        let mut scratch = TempFiles::new();
+        let payload_path = scratch.path("payload");
+        std::fs::write(&payload_path, payload).map_err(|e| e.to_string())?;
+
+        let mut command = Command::new(&self.program);
+        command.args(["-Y", "sign", "-n", "git", "-f"]);
+
+        // A literal public key means the private half lives in ssh-agent
+        let literal = self.key.strip_prefix("key::").or_else(|| self.key.starts_with("ssh-").then_some(self.key.as_str()));
+        match literal {
+            Some(public_key) => {
+                let key_path = scratch.path("key.pub");
+                std::fs::write(&key_path, public_key).map_err(|e| e.to_string())?;
+                command.arg(&key_path).arg("-U");
+            }
+            None => {
+                command.arg(expand_home(&self.key));
+            }
+        }
+
+        let output = command
+            .arg(&payload_path)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", self.program, e))?;
+
+        if !output.status.success() {
+            return Err(format!("Failed to sign commit with {}: {}", self.program, String::from_utf8_lossy(&output.stderr).trim()));
+        }
+
+        std::fs::read_to_string(scratch.path("payload.sig")).map_err(|e| format!("Failed to read SSH signature: {}", e))
+    }
+}
+
+/// Uniquely named files in the temp dir, removed on drop.
+struct TempFiles {
+    prefix: PathBuf,
+    created: Vec<PathBuf>,
+}
+
+impl TempFiles {
+    fn new() -> Self {
+        Self {
+            prefix: std::env::temp_dir().join(format!("colbex-sign-{}", uuid::Uuid::new_v4())),
+            created: Vec::new(),
+        }
+    }
+
+    fn path(&mut self, suffix: &str) -> PathBuf {
+        let path = PathBuf::from(format!("{}.{}", self.prefix.display(), suffix));
+        self.created.push(path.clone());
+        path
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        for path in &self.created {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::write_commit;
+
+    /// Config holding only `entries`, so the user's global git config
+    /// cannot leak into the result.
+    fn signing_config(entries: &[(&str, &str)]) -> Result<Option<SigningConfig>, String> {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::new().unwrap();
+        config.add_file(&dir.path().join("config"), git2::ConfigLevel::Local, false).unwrap();
+        for (name, value) in entries {
+            config.set_str(name, value).unwrap();
+        }
+        let committer = Signature::now("Test", "test@example.com").unwrap();
+        SigningConfig::from_config(&config, &committer)
+    }
+
+    fn parts(config: SigningConfig) -> (SigningFormat, String, String) {
+        (config.format, config.program, config.key)
+    }
+
+    #[test]
+    fn test_signing_disabled_without_gpgsign() {
+        assert!(signing_config(&[]).unwrap().is_none());
+        assert!(signing_config(&[("commit.gpgsign", "false"), ("gpg.format", "ssh")]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_openpgp_defaults_and_program_fallbacks() {
+        let config = signing_config(&[("commit.gpgsign", "true")]).unwrap().unwrap();
+        assert_eq!(parts(config), (SigningFormat::OpenPgp, "gpg".into(), "Test <test@example.com>".into()));
+
+        let config = signing_config(&[("commit.gpgsign", "true"), ("gpg.program", "gpg2"), ("user.signingkey", " ABCD1234 ")])
+            .unwrap()
+            .unwrap();
+        assert_eq!(parts(config), (SigningFormat::OpenPgp, "gpg2".into(), "ABCD1234".into()));
+
+        // The format-specific program wins over gpg.program
+        let config = signing_config(&[
+            ("commit.gpgsign", "true"),
+            ("gpg.program", "gpg2"),
+            ("gpg.openpgp.program", "/opt/gpg"),
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.program, "/opt/gpg");
+    }
+
+    #[test]
+    fn test_x509_and_ssh_programs() {
+        let config = signing_config(&[("commit.gpgsign", "true"), ("gpg.format", "x509"), ("gpg.program", "gpg2")])
+            .unwrap()
+            .unwrap();
+        assert_eq!(parts(config), (SigningFormat::X509, "gpgsm".into(), "Test <test@example.com>".into()));
+
+        let config = signing_config(&[("commit.gpgsign", "true"), ("gpg.format", "ssh"), ("user.signingkey", "~/.ssh/id_ed25519.pub")])
+            .unwrap()
+            .unwrap();
+        assert_eq!(parts(config), (SigningFormat::Ssh, "ssh-keygen".into(), "~/.ssh/id_ed25519.pub".into()));
+
+        let config = signing_config(&[
+            ("commit.gpgsign", "true"),
+            ("gpg.format", "ssh"),
+            ("gpg.ssh.program", "/usr/local/bin/ssh-keygen"),
+            ("user.signingkey", "key::ssh-ed25519 AAAA"),
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.program, "/usr/local/bin/ssh-keygen");
+    }
+
+    #[test]
+    fn test_ssh_without_key_and_unknown_format_are_errors() {
+        let err = signing_config(&[("commit.gpgsign", "true"), ("gpg.format", "ssh")]).unwrap_err();
+        assert!(err.contains("user.signingkey is missing"), "{}", err);
+        let err = signing_config(&[("commit.gpgsign", "true"), ("gpg.format", "ssh"), ("user.signingkey", "  ")]).unwrap_err();
+        assert!(err.contains("user.signingkey is missing"), "{}", err);
+
+        let err = signing_config(&[("commit.gpgsign", "true"), ("gpg.format", "pgp")]).unwrap_err();
+        assert_eq!(err, "Unsupported gpg.format 'pgp'");
+    }
+
+    #[test]
+    #[ignore = "needs ssh-keygen; run with --ignored"]
+    fn test_ssh_signed_commit_verifies() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = dir.path().join("id_ed25519");
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+            .arg(&key)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let repo = Repository::init(dir.path().join("repo")).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        config.set_bool("commit.gpgsign", true).unwrap();
+        config.set_str("gpg.format", "ssh").unwrap();
+        config.set_str("user.signingkey", key.to_str().unwrap()).unwrap();
+
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let oid = write_commit(&repo, &sig, &sig, "signed", &tree, &[]).unwrap();
+
+        let (signature, signed_data) = repo.extract_signature(&oid, None).unwrap();
+        let signature_path = dir.path().join("commit.sig");
+        std::fs::write(&signature_path, &*signature).unwrap();
+
+        let public_key = std::fs::read_to_string(key.with_extension("pub")).unwrap();
+        let allowed = dir.path().join("allowed_signers");
+        std::fs::write(&allowed, format!("test@example.com {}", public_key)).unwrap();
+
+        let mut verify = Command::new("ssh-keygen")
+            .args(["-Y", "verify", "-n", "git", "-I", "test@example.com", "-f"])
+            .arg(&allowed)
+            .arg("-s")
+            .arg(&signature_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        verify.stdin.take().unwrap().write_all(&signed_data).unwrap();
+        assert!(verify.wait().unwrap().success());
+    }
+
+    /// Runs gpg against a throwaway home so the user's keyring is never touched.
+    #[cfg(unix)]
+    fn gpg(home: &std::path::Path, args: &[&str]) -> std::process::Output {
+        Command::new("gpg").arg("--homedir").arg(home).arg("--batch").args(args).output().unwrap()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[ignore = "needs gpg; run with --ignored"]
+    fn test_gpg_signed_commit_verifies() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("gnupg");
+        std::fs::create_dir(&home).unwrap();
+        std::fs::set_permissions(&home, std::fs::Permissions::from_mode(0o700)).unwrap();
+        let generated = gpg(&home, &["--passphrase", "", "--quick-generate-key", "Test <test@example.com>", "ed25519", "sign", "never"]);
+        assert!(generated.status.success(), "{}", String::from_utf8_lossy(&generated.stderr));
+
+        // gpg.program pointing at a wrapper is how the commit picks up the test home
+        let wrapper = dir.path().join("gpg-test");
+        std::fs::write(&wrapper, format!("#!/bin/sh\nexec gpg --homedir '{}' \"$@\"\n", home.display())).unwrap();
+        std::fs::set_permissions(&wrapper, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let repo = Repository::init(dir.path().join("repo")).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_bool("commit.gpgsign", true).unwrap();
+        config.set_str("gpg.format", "openpgp").unwrap();
+        config.set_str("gpg.program", wrapper.to_str().unwrap()).unwrap();
+
+        // No user.signingkey: gpg picks the key by the committer identity
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let oid = write_commit(&repo, &sig, &sig, "signed", &tree, &[]).unwrap();
+
+        let (signature, signed_data) = repo.extract_signature(&oid, None).unwrap();
+        assert!(signature.as_str().unwrap().starts_with("-----BEGIN PGP SIGNATURE-----"));
+        let signature_path = dir.path().join("commit.asc");
+        let data_path = dir.path().join("commit.txt");
+        std::fs::write(&signature_path, &*signature).unwrap();
+        std::fs::write(&data_path, &*signed_data).unwrap();
+
+        let verified = gpg(&home, &["--verify", signature_path.to_str().unwrap(), data_path.to_str().unwrap()]);
+        let _ = Command::new("gpgconf").arg("--homedir").arg(&home).args(["--kill", "gpg-agent"]).status();
+        assert!(verified.status.success(), "{}", String::from_utf8_lossy(&verified.stderr));
+    }
+}
@@ -0,0 +1,427 @@
+//! Repository statistics: per-author line counts over time, an activity
+//! heatmap, directory ownership and churn hotspots. Identities are merged
+//! through `.mailmap` the way `git shortlog` does.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike};
+use git2::{Commit, Mailmap, Patch, Repository, Sort};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+use super::service::GitService;
+
+const DEFAULT_HOTSPOT_LIMIT: usize = 20;
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsInterval {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct GitStatsQuery {
+    /// Revision to walk from. Defaults to HEAD.
+    pub rev: Option<String>,
+    /// Unix timestamps bounding the commit time, both inclusive.
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    /// Bucket size of the per-author timeline.
+    pub interval: StatsInterval,
+    /// Stop after this many commits, newest first.
+    pub max_commits: Option<usize>,
+    pub hotspot_limit: Option<usize>,
+    /// How many path components make up a directory in the ownership table.
+    pub ownership_depth: Option<usize>,
+    /// Set to false to report raw commit identities.
+    pub use_mailmap: Option<bool>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitRepoStats {
+    pub total_commits: usize,
+    /// The walk stopped at `max_commits`.
+    pub truncated: bool,
+    pub authors: Vec<GitAuthorStats>,
+    /// Commit counts by weekday (0 = Monday) and hour, in each author's own timezone.
+    pub heatmap: Vec<Vec<usize>>,
+    pub ownership: Vec<GitDirectoryOwnership>,
+    pub hotspots: Vec<GitFileChurn>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitAuthorStats {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub first_commit: i64,
+    pub last_commit: i64,
+    /// Oldest bucket first; buckets without commits are omitted.
+    pub timeline: Vec<GitStatsBucket>,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct GitStatsBucket {
+    /// Unix timestamp of the bucket's first day (UTC).
+    pub start: i64,
+    pub commits: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitDirectoryOwnership {
+    /// Directory relative to the repository root, "." for files at the root.
+    pub path: String,
+    /// Most recent toucher first.
+    pub owners: Vec<GitDirectoryOwner>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitDirectoryOwner {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+    pub last_touched: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitFileChurn {
+    pub path: String,
+    pub commits: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub authors: usize,
+    pub last_changed: i64,
+}
+
+#[derive(Default)]
+struct AuthorAcc {
+    name: String,
+    commits: usize,
+    insertions: usize,
+    deletions: usize,
+    first_commit: i64,
+    last_commit: i64,
+    timeline: HashMap<i64, GitStatsBucket>,
+}
+
+#[derive(Default)]
+struct FileAcc {
+    commits: usize,
+    insertions: usize,
+    deletions: usize,
+    authors: HashSet<String>,
+    last_changed: i64,
+}
+
+#[tauri::command]
+pub async fn git_repo_stats(service: State<'_, GitService>, repo_path: String, query: Option<GitStatsQuery>) -> Result<GitRepoStats, String> {
+    let repo = service.open(&repo_path)?;
+    let query = query.unwrap_or_default();
+    tokio::task::spawn_blocking(move || collect_stats(&repo, &query))
+        .await
+        .map_err(|e| format!("Stats task failed: {}", e))?
+}
+
+pub(crate) fn collect_stats(repo: &Repository, query: &GitStatsQuery) -> Result<GitRepoStats, String> {
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME).map_err(|e| e.to_string())?;
+    match query.rev.as_deref().filter(|r| !r.is_empty()) {
+        Some(rev) => {
+            let commit = repo.revparse_single(rev)
+                .and_then(|obj| obj.peel_to_commit())
+                .map_err(|e| format!("Unknown revision '{}': {}", rev, e))?;
+            revwalk.push(commit.id()).map_err(|e| e.to_string())?;
+        }
+        None => revwalk.push_head().map_err(|e| e.to_string())?,
+    }
+
+    let mailmap = if query.use_mailmap.unwrap_or(true) { repo.mailmap().ok() } else { None };
+    let depth = query.ownership_depth.unwrap_or(1).max(1);
+
+    let mut total_commits = 0;
+    let mut truncated = false;
+    let mut heatmap = vec![vec![0usize; 24]; 7];
+    let mut authors: HashMap<String, AuthorAcc> = HashMap::new();
+    let mut files: HashMap<String, FileAcc> = HashMap::new();
+    // directory -> email -> (commits, last touched)
+    let mut directories: HashMap<String, HashMap<String, (usize, i64)>> = HashMap::new();
+
+    for oid in revwalk {
+        let oid = oid.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        let time = commit.time().seconds();
+        if query.until.is_some_and(|until| time > until) {
+            continue;
+        }
+        if query.since.is_some_and(|since| time < since) {
+            continue;
+        }
+        if query.max_commits.is_some_and(|max| total_commits == max) {
+            truncated = true;
+            break;
+        }
+        total_commits += 1;
+
+        let (name, email) = identity(&commit, mailmap.as_ref());
+        let key = email.to_lowercase();
+
+        let author_time = commit.author().when();
+        if let Some(local) = DateTime::from_timestamp(author_time.seconds() + i64::from(author_time.offset_minutes()) * 60, 0) {
+            heatmap[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += 1;
+        }
+
+        // Merges repeat their parents' changes, so like `git log --numstat`
+        // they count as commits but contribute no lines.
+        let changes = if commit.parent_count() <= 1 { file_changes(repo, &commit)? } else { Vec::new() };
+        let (insertions, deletions) = changes.iter().fold((0, 0), |(i, d), (_, ci, cd)| (i + ci, d + cd));
+
+        let author = authors.entry(key.clone()).or_insert_with(|| AuthorAcc {
+            // Newest commit comes first, so this is the most recent spelling
+            name: name.clone(),
+            first_commit: time,
+            last_commit: time,
+            ..Default::default()
+        });
+        author.commits += 1;
+        author.insertions += insertions;
+        author.deletions += deletions;
+        author.first_commit = author.first_commit.min(time);
+        author.last_commit = author.last_commit.max(time);
+        let start = bucket_start(time, query.interval);
+        let bucket = author.timeline.entry(start).or_insert_with(|| GitStatsBucket { start, ..Default::default() });
+        bucket.commits += 1;
+        bucket.insertions += insertions;
+        bucket.deletions += deletions;
+
+        let mut touched_dirs = HashSet::new();
+        for (path, added, removed) in changes {
+            touched_dirs.insert(directory_of(&path, depth));
+            let file = files.entry(path).or_default();
+            file.commits += 1;
+            file.insertions += added;
+            file.deletions += removed;
+            file.authors.insert(key.clone());
+            file.last_changed = file.last_changed.max(time);
+        }
+        for dir in touched_dirs {
+            let owner = directories.entry(dir).or_default().entry(key.clone()).or_insert((0, time));
+            owner.0 += 1;
+            owner.1 = owner.1.max(time);
+        }
+    }
+
+    let author_name = |key: &str| authors.get(key).map(|a| a.name.clone()).unwrap_or_default();
+
+    let mut ownership: Vec<GitDirectoryOwnership> = directories
+        .into_iter()
+        .map(|(path, owners)| {
+            let mut owners: Vec<GitDirectoryOwner> = owners
+                .into_iter()
+                .map(|(email, (commits, last_touched))| GitDirectoryOwner {
+                    name: author_name(&email),
+                    email,
+                    commits,
+                    last_touched,
+                })
+                .collect();
+            owners.sort_by(|a, b| b.last_touched.cmp(&a.last_touched).then_with(|| b.commits.cmp(&a.commits)));
+            GitDirectoryOwnership { path, owners }
+        })
+        .collect();
+    ownership.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut hotspots: Vec<GitFileChurn> = files
+        .into_iter()
+        .map(|(path, file)| GitFileChurn {
+            path,
+            commits: file.commits,
+            insertions: file.insertions,
+            deletions: file.deletions,
+            authors: file.authors.len(),
+            last_changed: file.last_changed,
+        })
+        .collect();
+    hotspots.sort_by(|a, b| {
+        b.commits.cmp(&a.commits)
+            .then_with(|| (b.insertions + b.deletions).cmp(&(a.insertions + a.deletions)))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    hotspots.truncate(query.hotspot_limit.unwrap_or(DEFAULT_HOTSPOT_LIMIT));
+
+    let mut authors: Vec<GitAuthorStats> = authors
+        .into_iter()
+        .map(|(email, author)| {
+            let mut timeline: Vec<GitStatsBucket> = author.timeline.into_values().collect();
+            timeline.sort_by_key(|bucket| bucket.start);
+            GitAuthorStats {
+                name: author.name,
+                email,
+                commits: author.commits,
+                insertions: author.insertions,
+                deletions: author.deletions,
+                first_commit: author.first_commit,
+                last_commit: author.last_commit,
+                timeline,
+            }
+        })
+        .collect();
+    authors.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.email.cmp(&b.email)));
+
+    Ok(GitRepoStats { total_commits, truncated, authors, heatmap, ownership, hotspots })
+}
+
+/// Author name and email after applying the mailmap.
+pub(crate) fn identity(commit: &Commit, mailmap: Option<&Mailmap>) -> (String, String) {
+    let mapped = mailmap.and_then(|m| commit.author_with_mailmap(m).ok());
+    let author = commit.author();
+    let signature = mapped.as_ref().unwrap_or(&author);
+    (
+        signature.name().unwrap_or("Unknown").to_string(),
+        signature.email().unwrap_or("unknown").to_string(),
+    )
+}
+
+/// Lines added and removed per file against the first parent.
+fn file_changes(repo: &Repository, commit: &Commit) -> Result<Vec<(String, usize, usize)>, String> {
+    let tree = commit.tree().map_err(|e| e.to_string())?;
+    let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(|e| e.to_string())?;
+
+    let mut changes = Vec::with_capacity(diff.deltas().len());
+    for (idx, delta) in diff.deltas().enumerate() {
+        let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+            continue;
+        };
+        // Binary files have no patch and count as touched without lines
+        let (added, removed) = Patch::from_diff(&diff, idx)
+            .ok()
+            .flatten()
+            .and_then(|patch| patch.line_stats().ok())
+            .map(|(_, added, removed)| (added, removed))
+            .unwrap_or((0, 0));
+        changes.push((path.to_string_lossy().replace('\\', "/"), added, removed));
+    }
+    Ok(changes)
+}
+
+/// First `depth` directory components of a file path.
+fn directory_of(path: &str, depth: usize) -> String {
+    let components: Vec<&str> = path.split('/').collect();
+    let dirs = &components[..components.len() - 1];
+    if dirs.is_empty() {
+        ".".to_string()
+    } else {
+        dirs[..depth.min(dirs.len())].join("/")
+    }
+}
+
+fn bucket_start(timestamp: i64, interval: StatsInterval) -> i64 {
+    let Some(date) = DateTime::from_timestamp(timestamp, 0).map(|dt| dt.date_naive()) else {
+        return timestamp;
+    };
+    let start = match interval {
+        StatsInterval::Day => date,
+        StatsInterval::Week => date - Duration::days(i64::from(date.weekday().num_days_from_monday())),
+        StatsInterval::Month => NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap_or(date),
+    };
+    start.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc().timestamp()).unwrap_or(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Signature, Time};
+
+    fn commit(repo: &Repository, files: &[(&str, &str)], name: &str, email: &str, time: i64) {
+        let root = repo.workdir().unwrap();
+        for (path, contents) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::new(name, email, &Time::new(time, 0)).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, "change", &tree, &parents).unwrap();
+    }
+
+    // 2024-01-01 was a Monday
+    const MONDAY_NOON: i64 = 1_704_110_400;
+
+    fn fixture() -> (tempfile::TempDir, Repository) {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit(&repo, &[("src/lib.rs", "a\nb\n"), (".mailmap", "Alice <alice@example.com> <alice@old.example>\n")],
+            "alice", "alice@old.example", MONDAY_NOON);
+        commit(&repo, &[("src/lib.rs", "a\nc\nd\n")], "Alice", "alice@example.com", MONDAY_NOON + 86_400);
+        commit(&repo, &[("docs/guide.md", "hi\n"), ("src/lib.rs", "a\n")], "Bob", "bob@example.com", MONDAY_NOON + 8 * 86_400);
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_mailmap_merges_authors() {
+        let (_dir, repo) = fixture();
+        let stats = collect_stats(&repo, &GitStatsQuery::default()).unwrap();
+        assert_eq!(stats.total_commits, 3);
+        assert_eq!(stats.authors.len(), 2);
+
+        let alice = &stats.authors[0];
+        assert_eq!((alice.name.as_str(), alice.email.as_str()), ("Alice", "alice@example.com"));
+        assert_eq!((alice.commits, alice.insertions, alice.deletions), (2, 5, 1));
+        // Both commits fall into the week of 2024-01-01
+        assert_eq!(alice.timeline.len(), 1);
+        assert_eq!(alice.timeline[0].start, MONDAY_NOON - 12 * 3600);
+
+        let raw = collect_stats(&repo, &GitStatsQuery { use_mailmap: Some(false), ..Default::default() }).unwrap();
+        assert_eq!(raw.authors.len(), 3);
+    }
+
+    #[test]
+    fn test_heatmap_ownership_and_hotspots() {
+        let (_dir, repo) = fixture();
+        let stats = collect_stats(&repo, &GitStatsQuery::default()).unwrap();
+        assert_eq!(stats.heatmap[0][12], 1);
+        assert_eq!(stats.heatmap[1][12], 2);
+        assert_eq!(stats.heatmap.iter().flatten().sum::<usize>(), 3);
+
+        let src = stats.ownership.iter().find(|d| d.path == "src").unwrap();
+        assert_eq!(src.owners[0].email, "bob@example.com");
+        assert_eq!(src.owners[1].commits, 2);
+        assert!(stats.ownership.iter().any(|d| d.path == "."));
+
+        assert_eq!(stats.hotspots[0].path, "src/lib.rs");
+        assert_eq!((stats.hotspots[0].commits, stats.hotspots[0].authors), (3, 2));
+    }
+
+    #[test]
+    fn test_limits_and_time_range() {
+        let (_dir, repo) = fixture();
+        let stats = collect_stats(&repo, &GitStatsQuery { max_commits: Some(1), hotspot_limit: Some(1), ..Default::default() }).unwrap();
+        assert!(stats.truncated);
+        assert_eq!(stats.total_commits, 1);
+        assert_eq!(stats.hotspots.len(), 1);
+
+        let stats = collect_stats(&repo, &GitStatsQuery { until: Some(MONDAY_NOON + 86_400), ..Default::default() }).unwrap();
+        assert_eq!(stats.total_commits, 2);
+        assert!(!stats.truncated);
+    }
+
+    #[test]
+    fn test_directory_of() {
+        assert_eq!(directory_of("README.md", 1), ".");
+        assert_eq!(directory_of("src/git/mod.rs", 1), "src");
+        assert_eq!(directory_of("src/git/mod.rs", 3), "src/git");
+    }
+}
@@ -0,0 +1,330 @@
+use git2::{Repository, Status, StatusEntry, StatusOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+use super::service::GitService;
+use super::submodule::{self, GitSubmoduleChange};
+use super::GitFileStatus;
+
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(default)]
+pub struct GitStatusOptions {
+    /// Also report ignored files (directories are collapsed).
+    pub include_ignored: bool,
+    /// List every file inside untracked directories instead of the directory itself.
+    pub recurse_untracked: bool,
+    /// Only report paths matching these pathspecs.
+    pub paths: Vec<String>,
+    /// Pair unstaged deletions with untracked files into renames. Compares
+    /// the contents of every such pair, so it is off unless asked for.
+    pub worktree_renames: bool,
+    /// Open each submodule to see whether it has new commits or local
+    /// changes; fills `submodule` and the submodule count.
+    pub submodules: bool,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct GitStatusCounts {
+    pub staged: usize,
+    pub unstaged: usize,
+    pub untracked: usize,
+    pub conflicted: usize,
+    pub renamed: usize,
+    pub ignored: usize,
+    /// Submodules with new commits or local changes.
+    pub submodules: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GitStatusReport {
+    pub files: Vec<GitFileStatus>,
+    pub counts: GitStatusCounts,
+}
+
+fn index_state(status: Status) -> Option<&'static str> {
+    if status.is_index_new() {
+        Some("added")
+    } else if status.is_index_modified() {
+        Some("modified")
+    } else if status.is_index_deleted() {
+        Some("deleted")
+    } else if status.is_index_renamed() {
+        Some("renamed")
+    } else if status.is_index_typechange() {
+        Some("typechange")
+    } else {
+        None
+    }
+}
+
+fn worktree_state(status: Status) -> Option<&'static str> {
+    if status.is_wt_new() {
+        Some("untracked")
+    } else if status.is_wt_modified() {
+        Some("modified")
+    } else if status.is_wt_deleted() {
+        Some("deleted")
+    } else if status.is_wt_renamed() {
+        Some("renamed")
+    } else if status.is_wt_typechange() {
+        Some("typechange")
+    } else if status.is_ignored() {
+        Some("ignored")
+    } else {
+        None
+    }
+}
+
+/// The single status string `git_status` has always returned; the index wins over the worktree.
+fn legacy_status(status: Status) -> &'static str {
+    if status.is_conflicted() {
+        "conflicted"
+    } else if status.is_index_new() {
+        "staged_new"
+    } else if status.is_index_modified() || status.is_index_typechange() {
+        "staged_modified"
+    } else if status.is_index_deleted() {
+        "staged_deleted"
+    } else if status.is_index_renamed() {
+        "staged_renamed"
+    } else if status.is_wt_new() {
+        "untracked"
+    } else if status.is_wt_modified() || status.is_wt_typechange() {
+        "modified"
+    } else if status.is_wt_deleted() {
+        "deleted"
+    } else if status.is_wt_renamed() {
+        "renamed"
+    } else {
+        "ignored"
+    }
+}
+
+/// `git status` short-format wording for each side of a merge conflict.
+fn conflict_kinds(repo: &Repository) -> Result<HashMap<String, &'static str>, String> {
+    let index = repo.index().map_err(|e| e.to_string())?;
+    let mut kinds = HashMap::new();
+    for conflict in index.conflicts().map_err(|e| e.to_string())? {
+        let conflict = conflict.map_err(|e| e.to_string())?;
+        let kind = match (conflict.ancestor.is_some(), conflict.our.is_some(), conflict.their.is_some()) {
+            (true, true, true) => "both_modified",
+            (false, true, true) => "both_added",
+            (true, false, false) => "both_deleted",
+            (true, false, true) => "deleted_by_us",
+            (true, true, false) => "deleted_by_them",
+            (false, true, false) => "added_by_us",
+            (false, false, true) => "added_by_them",
+            (false, false, false) => continue,
+        };
+        let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+        if let Some(entry) = entry {
+            kinds.insert(String::from_utf8_lossy(&entry.path).to_string(), kind);
+        }
+    }
+    Ok(kinds)
+}
+
+/// Current path and, for renames and copies, where it came from.
+fn entry_paths(entry: &StatusEntry) -> Option<(String, Option<String>)> {
+    let head_to_index = entry.head_to_index();
+    let index_to_workdir = entry.index_to_workdir();
+    let status = entry.status();
+
+    // `StatusEntry::path` is the *old* side of a rename, so read the deltas directly
+    let path = index_to_workdir.as_ref().and_then(|d| d.new_file().path())
+        .or_else(|| head_to_index.as_ref().and_then(|d| d.new_file().path()))
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .or_else(|| entry.path().map(|p| p.to_string()))?;
+
+    let original = if status.is_index_renamed() {
+        head_to_index.and_then(|d| d.old_file().path().map(|p| p.to_string_lossy().replace('\\', "/")))
+    } else if status.is_wt_renamed() {
+        index_to_workdir.and_then(|d| d.old_file().path().map(|p| p.to_string_lossy().replace('\\', "/")))
+    } else {
+        None
+    };
+
+    Some((path, original))
+}
+
+/// One status scan shared by `git_status`, `git_status_report` and `git_info`.
+///
+/// Staged renames are always detected. Copies are not: libgit2's status
+/// has no copy detection, so a copied file is reported as added.
+pub(crate) fn collect(repo: &Repository, options: &GitStatusOptions) -> Result<GitStatusReport, String> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(options.recurse_untracked)
+        .include_ignored(options.include_ignored)
+        .recurse_ignored_dirs(false)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(options.worktree_renames)
+        // Refreshing the on-disk index is a write; leave that to explicit index operations
+        .update_index(false);
+    for path in &options.paths {
+        opts.pathspec(path);
+    }
+
+    let statuses = repo.statuses(Some(&mut opts)).map_err(|e| e.to_string())?;
+
+    let submodules: HashMap<String, GitSubmoduleChange> = if options.submodules {
+        submodule::submodule_changes(repo).into_iter().collect()
+    } else {
+        HashMap::new()
+    };
+    // Conflict sides need another pass over the index; skip it in the common case
+    let has_conflicts = statuses.iter().any(|e| e.status().is_conflicted());
+    let conflicts = if has_conflicts { conflict_kinds(repo)? } else { HashMap::new() };
+
+    let mut files = Vec::with_capacity(statuses.len());
+    let mut counts = GitStatusCounts {
+        submodules: submodules.values()
+            .filter(|c| c.new_commits || c.modified_content || c.untracked_content)
+            .count(),
+        ..Default::default()
+    };
+
+    for entry in statuses.iter() {
+        let status = entry.status();
+        let Some((path, original_path)) = entry_paths(&entry) else {
+            continue;
+        };
+
+        let index_status = index_state(status);
+        let worktree_status = worktree_state(status);
+        let is_conflicted = status.is_conflicted();
+
+        if is_conflicted {
+            counts.conflicted += 1;
+        } else {
+            if index_status.is_some() {
+                counts.staged += 1;
+            }
+            match worktree_status {
+                Some("untracked") => counts.untracked += 1,
+                Some("ignored") => counts.ignored += 1,
+                Some(_) => counts.unstaged += 1,
+                None => {}
+            }
+        }
+        if status.is_index_renamed() || status.is_wt_renamed() {
+            counts.renamed += 1;
+        }
+
+        files.push(GitFileStatus {
+            status: legacy_status(status).to_string(),
+            is_staged: index_status.is_some() && !is_conflicted,
+            submodule: submodules.get(path.trim_end_matches('/')).cloned(),
+            index_status: index_status.map(|s| s.to_string()),
+            worktree_status: worktree_status.map(|s| s.to_string()),
+            original_path,
+            conflict: conflicts.get(&path).map(|k| k.to_string()),
+            is_conflicted,
+            path,
+        });
+    }
+
+    Ok(GitStatusReport { files, counts })
+}
+
+/// Cheap default scan; ask `git_status_report` for worktree renames or submodule changes.
+#[tauri::command]
+pub fn git_status(service: State<'_, GitService>, path: String) -> Result<Vec<GitFileStatus>, String> {
+    let repo = service.open(&path)?;
+    Ok(collect(&repo, &GitStatusOptions::default())?.files)
+}
+
+/// `git_status` plus per-category counts, ignored files and pathspec filtering.
+#[tauri::command]
+pub fn git_status_report(service: State<'_, GitService>, path: String, options: Option<GitStatusOptions>) -> Result<GitStatusReport, String> {
+    let repo = service.open(&path)?;
+    collect(&repo, &options.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn commit_all(repo: &Repository, message: &str) {
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap();
+    }
+
+    #[test]
+    fn test_staged_rename_and_split_states() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let contents = "fn main() {\n    println!(\"hello\");\n}\n".repeat(10);
+        std::fs::write(dir.path().join("old.rs"), &contents).unwrap();
+        std::fs::write(dir.path().join("both.rs"), "one\n").unwrap();
+        commit_all(&repo, "init");
+
+        std::fs::rename(dir.path().join("old.rs"), dir.path().join("new.rs")).unwrap();
+        std::fs::write(dir.path().join("both.rs"), "two\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.remove_path(Path::new("old.rs")).unwrap();
+        index.add_path(Path::new("new.rs")).unwrap();
+        index.add_path(Path::new("both.rs")).unwrap();
+        index.write().unwrap();
+        std::fs::write(dir.path().join("both.rs"), "three\n").unwrap();
+        std::fs::write(dir.path().join("fresh.txt"), "x").unwrap();
+
+        let report = collect(&repo, &GitStatusOptions::default()).unwrap();
+        let file = |p: &str| report.files.iter().find(|f| f.path == p).unwrap();
+
+        let renamed = file("new.rs");
+        assert_eq!(renamed.index_status.as_deref(), Some("renamed"));
+        assert_eq!(renamed.original_path.as_deref(), Some("old.rs"));
+        assert!(report.files.iter().all(|f| f.path != "old.rs"));
+
+        let both = file("both.rs");
+        assert_eq!(both.index_status.as_deref(), Some("modified"));
+        assert_eq!(both.worktree_status.as_deref(), Some("modified"));
+
+        assert_eq!(file("fresh.txt").worktree_status.as_deref(), Some("untracked"));
+        assert_eq!(report.counts.staged, 2);
+        assert_eq!(report.counts.unstaged, 1);
+        assert_eq!(report.counts.untracked, 1);
+        assert_eq!(report.counts.renamed, 1);
+    }
+
+    #[test]
+    fn test_worktree_renames_are_opt_in_and_copies_show_as_added() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let contents = "fn main() {\n    println!(\"hello\");\n}\n".repeat(10);
+        std::fs::write(dir.path().join("old.rs"), &contents).unwrap();
+        std::fs::write(dir.path().join("kept.rs"), contents.replace("hello", "kept")).unwrap();
+        commit_all(&repo, "init");
+
+        std::fs::rename(dir.path().join("old.rs"), dir.path().join("moved.rs")).unwrap();
+        let report = collect(&repo, &GitStatusOptions::default()).unwrap();
+        let states: Vec<_> = report.files.iter().map(|f| (f.path.as_str(), f.worktree_status.as_deref())).collect();
+        assert!(states.contains(&("old.rs", Some("deleted"))));
+        assert!(states.contains(&("moved.rs", Some("untracked"))));
+
+        let options = GitStatusOptions { worktree_renames: true, ..Default::default() };
+        let report = collect(&repo, &options).unwrap();
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.files[0].original_path.as_deref(), Some("old.rs"));
+        assert_eq!(report.counts.renamed, 1);
+
+        // A staged copy is not told apart from a new file
+        std::fs::copy(dir.path().join("kept.rs"), dir.path().join("copy.rs")).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("copy.rs")).unwrap();
+        index.write().unwrap();
+        let report = collect(&repo, &GitStatusOptions::default()).unwrap();
+        let copy = report.files.iter().find(|f| f.path == "copy.rs").unwrap();
+        assert_eq!(copy.index_status.as_deref(), Some("added"));
+        assert_eq!(copy.original_path, None);
+    }
+}
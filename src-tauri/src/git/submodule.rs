@@ -0,0 +1,197 @@
+use git2::{FetchOptions, RemoteCallbacks, Repository, Submodule, SubmoduleIgnore, SubmoduleStatus, SubmoduleUpdateOptions};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use super::credentials;
+use super::service::GitService;
+
+#[derive(Serialize, Debug)]
+pub struct GitSubmodule {
+    pub name: String,
+    pub path: String,
+    pub url: Option<String>,
+    pub branch: Option<String>,
+    /// Commit recorded in the superproject's HEAD.
+    pub recorded_commit: Option<String>,
+    /// Commit staged in the superproject's index.
+    pub index_commit: Option<String>,
+    /// Commit checked out inside the submodule.
+    pub checked_out_commit: Option<String>,
+    pub initialized: bool,
+    /// "clean", "uninitialized", "added", "deleted", "missing", "new_commits" or "modified"
+    pub state: String,
+    pub change: GitSubmoduleChange,
+}
+
+/// How a submodule differs from what the superproject records, as shown in `git status`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GitSubmoduleChange {
+    /// The checked-out commit differs from the one in the superproject's index.
+    pub new_commits: bool,
+    pub modified_content: bool,
+    pub untracked_content: bool,
+}
+
+fn submodule_status(repo: &Repository, submodule: &Submodule) -> Result<SubmoduleStatus, String> {
+    let name = submodule.name().ok_or("Submodule name is not valid UTF-8")?;
+    repo.submodule_status(name, SubmoduleIgnore::None).map_err(|e| e.to_string())
+}
+
+fn change_from(status: SubmoduleStatus) -> GitSubmoduleChange {
+    GitSubmoduleChange {
+        new_commits: status.contains(SubmoduleStatus::WD_MODIFIED),
+        modified_content: status.intersects(SubmoduleStatus::WD_INDEX_MODIFIED | SubmoduleStatus::WD_WD_MODIFIED),
+        untracked_content: status.contains(SubmoduleStatus::WD_UNTRACKED),
+    }
+}
+
+fn state_from(status: SubmoduleStatus) -> &'static str {
+    if status.contains(SubmoduleStatus::INDEX_ADDED) {
+        "added"
+    } else if status.contains(SubmoduleStatus::INDEX_DELETED) {
+        "deleted"
+    } else if status.contains(SubmoduleStatus::WD_UNINITIALIZED) {
+        "uninitialized"
+    } else if status.contains(SubmoduleStatus::WD_DELETED) {
+        "missing"
+    } else if status.contains(SubmoduleStatus::WD_MODIFIED) {
+        "new_commits"
+    } else if status.intersects(SubmoduleStatus::WD_INDEX_MODIFIED | SubmoduleStatus::WD_WD_MODIFIED | SubmoduleStatus::WD_UNTRACKED) {
+        "modified"
+    } else {
+        "clean"
+    }
+}
+
+/// Submodule changes by path, for rolling into the superproject's status.
+pub(crate) fn submodule_changes(repo: &Repository) -> Vec<(String, GitSubmoduleChange)> {
+    let Ok(submodules) = repo.submodules() else {
+        return Vec::new();
+    };
+    submodules.iter()
+        .filter_map(|sm| {
+            let status = submodule_status(repo, sm).ok()?;
+            Some((sm.path().to_string_lossy().replace('\\', "/"), change_from(status)))
+        })
+        .collect()
+}
+
+/// Submodules matching `paths`, or all of them.
+fn selected(repo: &Repository, paths: Option<Vec<String>>) -> Result<Vec<Submodule<'_>>, String> {
+    let submodules = repo.submodules().map_err(|e| e.to_string())?;
+    let Some(paths) = paths.filter(|p| !p.is_empty()) else {
+        return Ok(submodules);
+    };
+
+    let mut result = Vec::new();
+    for path in paths {
+        let wanted = path.trim().trim_end_matches('/').replace('\\', "/");
+        if !submodules.iter().any(|sm| sm.path().to_string_lossy().replace('\\', "/") == wanted) {
+            return Err(format!("'{}' is not a submodule", path));
+        }
+        // Lookup accepts a submodule path as well as its name
+        result.push(repo.find_submodule(&wanted).map_err(|e| e.to_string())?);
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn git_list_submodules(service: State<'_, GitService>, repo_path: String) -> Result<Vec<GitSubmodule>, String> {
+    let repo = service.open(&repo_path)?;
+    let mut result = Vec::new();
+
+    for submodule in repo.submodules().map_err(|e| e.to_string())? {
+        let status = submodule_status(&repo, &submodule)?;
+        result.push(GitSubmodule {
+            name: submodule.name().unwrap_or("").to_string(),
+            path: submodule.path().to_string_lossy().replace('\\', "/"),
+            url: submodule.url().map(|u| u.to_string()),
+            branch: submodule.branch().map(|b| b.to_string()),
+            recorded_commit: submodule.head_id().map(|id| id.to_string()),
+            index_commit: submodule.index_id().map(|id| id.to_string()),
+            checked_out_commit: submodule.workdir_id().map(|id| id.to_string()),
+            initialized: !status.contains(SubmoduleStatus::WD_UNINITIALIZED),
+            state: state_from(status).to_string(),
+            change: change_from(status),
+        });
+    }
+
+    Ok(result)
+}
+
+/// Copies submodule URLs from `.gitmodules` into `.git/config`. Returns the initialised paths.
+#[tauri::command]
+pub fn git_submodule_init(service: State<'_, GitService>, repo_path: String, paths: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    let repo = service.open(&repo_path)?;
+    let mut initialized = Vec::new();
+    for mut submodule in selected(&repo, paths)? {
+        submodule.init(false).map_err(|e| e.to_string())?;
+        initialized.push(submodule.path().to_string_lossy().replace('\\', "/"));
+    }
+    Ok(initialized)
+}
+
+/// Re-applies `.gitmodules` URLs after they changed upstream.
+#[tauri::command]
+pub fn git_submodule_sync(service: State<'_, GitService>, repo_path: String, paths: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    let repo = service.open(&repo_path)?;
+    let mut synced = Vec::new();
+    for mut submodule in selected(&repo, paths)? {
+        submodule.sync().map_err(|e| e.to_string())?;
+        synced.push(submodule.path().to_string_lossy().replace('\\', "/"));
+    }
+    Ok(synced)
+}
+
+fn update_submodules(
+    app_handle: &tauri::AppHandle,
+    submodules: Vec<Submodule<'_>>,
+    prefix: &str,
+    init: bool,
+    recursive: bool,
+    updated: &mut Vec<String>,
+) -> Result<(), String> {
+    for mut submodule in submodules {
+        let path = format!("{}{}", prefix, submodule.path().to_string_lossy().replace('\\', "/"));
+
+        let mut session = credentials::session_for(app_handle);
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.credentials(move |url, username, allowed| session.callback(url, username, allowed));
+        let mut fetch = FetchOptions::new();
+        fetch.remote_callbacks(callbacks);
+        let mut options = SubmoduleUpdateOptions::new();
+        options.fetch(fetch);
+
+        submodule.update(init, Some(&mut options))
+            .map_err(|e| format!("Failed to update submodule '{}': {}", path, e))?;
+        updated.push(path.clone());
+
+        if recursive {
+            let nested = submodule.open().map_err(|e| e.to_string())?;
+            let children = nested.submodules().map_err(|e| e.to_string())?;
+            update_submodules(app_handle, children, &format!("{}/", path), init, recursive, updated)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks out the recorded commit in each submodule, fetching as needed.
+#[tauri::command]
+pub async fn git_submodule_update(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    repo_path: String,
+    paths: Option<Vec<String>>,
+    init: Option<bool>,
+    recursive: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let repo = service.open(&repo_path)?;
+    tokio::task::spawn_blocking(move || {
+        let submodules = selected(&repo, paths)?;
+        let mut updated = Vec::new();
+        update_submodules(&app_handle, submodules, "", init.unwrap_or(true), recursive.unwrap_or(true), &mut updated)?;
+        Ok(updated)
+    })
+    .await
+    .map_err(|e| format!("Submodule update task failed: {}", e))?
+}
@@ -0,0 +1,155 @@
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use tauri::State;
+
+use super::credentials::{self, CredentialSession};
+use super::default_signature;
+use super::service::GitService;
+
+#[derive(Serialize, Debug)]
+pub struct GitTag {
+    pub name: String,
+    pub annotated: bool,
+    /// Commit the tag resolves to, after peeling annotated tags.
+    pub target: String,
+    pub target_summary: Option<String>,
+    /// Annotation message; `None` for lightweight tags.
+    pub message: Option<String>,
+    pub tagger_name: Option<String>,
+    pub tagger_email: Option<String>,
+    /// Tagger time for annotated tags, commit time otherwise.
+    pub timestamp: i64,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct GitCreateTagRequest {
+    pub name: String,
+    /// Revision to tag. Defaults to HEAD.
+    pub target: Option<String>,
+    /// Creates an annotated tag when set and non-empty.
+    pub message: Option<String>,
+    /// Move an existing tag instead of failing.
+    pub force: bool,
+}
+
+/// All tags, newest first.
+#[tauri::command]
+pub fn git_list_tags(service: State<'_, GitService>, repo_path: String) -> Result<Vec<GitTag>, String> {
+    let repo = service.open(&repo_path)?;
+    let names = repo.tag_names(None).map_err(|e| e.to_string())?;
+    let mut tags: Vec<GitTag> = names.iter()
+        .flatten()
+        .filter_map(|name| load_tag(&repo, name).ok())
+        .collect();
+    tags.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| a.name.cmp(&b.name)));
+    Ok(tags)
+}
+
+#[tauri::command]
+pub fn git_create_tag(service: State<'_, GitService>, repo_path: String, request: GitCreateTagRequest) -> Result<GitTag, String> {
+    let repo = service.open(&repo_path)?;
+    let name = request.name.trim();
+    if name.is_empty() || !git2::Reference::is_valid_name(&format!("refs/tags/{}", name)) {
+        return Err(format!("'{}' is not a valid tag name", name));
+    }
+
+    let rev = request.target.as_deref().filter(|t| !t.is_empty()).unwrap_or("HEAD");
+    let target = repo.revparse_single(rev)
+        .and_then(|obj| obj.peel_to_commit())
+        .map_err(|e| format!("Unknown revision '{}': {}", rev, e))?;
+
+    let result = match request.message.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        Some(message) => {
+            let tagger = default_signature(&repo)?;
+            repo.tag(name, target.as_object(), &tagger, message, request.force)
+        }
+        None => repo.tag_lightweight(name, target.as_object(), request.force),
+    };
+    result.map_err(|e| match e.code() {
+        git2::ErrorCode::Exists => format!("Tag '{}' already exists", name),
+        _ => e.to_string(),
+    })?;
+
+    load_tag(&repo, name)
+}
+
+/// Deletes a tag locally and, when `remote` is given, on that remote too.
+/// The remote goes first so a rejected push leaves the local tag to retry with.
+#[tauri::command]
+pub async fn git_delete_tag(
+    app_handle: tauri::AppHandle,
+    service: State<'_, GitService>,
+    repo_path: String,
+    name: String,
+    remote: Option<String>,
+) -> Result<(), String> {
+    let repo = service.open(&repo_path)?;
+    let remote = remote.map(|remote| (remote, credentials::session_for(&app_handle)));
+    tokio::task::spawn_blocking(move || delete_tag(&repo, remote, &name))
+        .await
+        .map_err(|e| format!("Delete tag task failed: {}", e))?
+}
+
+pub(crate) fn delete_tag(repo: &Repository, remote: Option<(String, CredentialSession)>, name: &str) -> Result<(), String> {
+    let local_exists = repo.find_reference(&format!("refs/tags/{}", name)).is_ok();
+    match remote {
+        Some((remote, session)) => {
+            delete_remote_tag(repo, session, &remote, name)?;
+            if local_exists {
+                repo.tag_delete(name).map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        }
+        None if !local_exists => Err(format!("Tag '{}' not found", name)),
+        None => repo.tag_delete(name).map_err(|e| e.to_string()),
+    }
+}
+
+fn load_tag(repo: &Repository, name: &str) -> Result<GitTag, String> {
+    let reference = repo.find_reference(&format!("refs/tags/{}", name))
+        .map_err(|e| e.to_string())?;
+    let commit = reference.peel_to_commit().map_err(|e| e.to_string())?;
+    let annotation = reference.target()
+        .and_then(|oid: Oid| repo.find_tag(oid).ok());
+
+    let tagger = annotation.as_ref().and_then(|tag| tag.tagger());
+    Ok(GitTag {
+        name: name.to_string(),
+        annotated: annotation.is_some(),
+        target: commit.id().to_string(),
+        target_summary: commit.summary().map(|s| s.to_string()),
+        message: annotation.as_ref().and_then(|tag| tag.message()).map(|m| m.trim_end().to_string()),
+        tagger_name: tagger.as_ref().and_then(|t| t.name()).map(|s| s.to_string()),
+        tagger_email: tagger.as_ref().and_then(|t| t.email()).map(|s| s.to_string()),
+        timestamp: tagger.as_ref().map(|t| t.when().seconds()).unwrap_or_else(|| commit.time().seconds()),
+    })
+}
+
+/// `git push <remote> :refs/tags/<name>`
+fn delete_remote_tag(repo: &Repository, mut session: CredentialSession, remote_name: &str, name: &str) -> Result<(), String> {
+    let mut remote = repo.find_remote(remote_name)
+        .map_err(|e| format!("Remote '{}' not found: {}", remote_name, e))?;
+
+    let rejection: RefCell<Option<String>> = RefCell::new(None);
+    let mut callbacks = git2::RemoteCallbacks::new();
+    callbacks.credentials(|url, username, allowed| session.callback(url, username, allowed));
+    callbacks.push_update_reference(|refname, status| {
+        if let Some(status) = status {
+            *rejection.borrow_mut() = Some(format!("{}: {}", refname, status));
+        }
+        Ok(())
+    });
+    let mut options = git2::PushOptions::new();
+    options.remote_callbacks(callbacks);
+
+    remote.push(&[format!(":refs/tags/{}", name)], Some(&mut options))
+        .map_err(|e| format!("Deleting tag on {} failed: {}", remote_name, e))?;
+    drop(options);
+
+    match rejection.into_inner() {
+        Some(reason) => Err(format!("Deleting tag on {} was rejected ({})", remote_name, reason)),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,587 @@
+//! Command-level tests against throwaway repositories, going through the
+//! managed `GitService` the way the frontend does.
+
+use super::avatar::AvatarService;
+use super::credentials;
+use super::log::{git_file_history, git_log_page, GitLogQuery};
+use super::service::GitService;
+use super::show::{git_diff_revisions, git_show_commit};
+use super::status::git_status;
+use super::tags::{delete_tag, git_create_tag, git_list_tags, GitCreateTagRequest};
+use super::*;
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{App, Manager};
+
+struct Fixture {
+    app: App<MockRuntime>,
+    dir: tempfile::TempDir,
+    path: String,
+    _avatars: tempfile::TempDir,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let app = mock_app();
+        app.manage(GitService::default());
+        let avatars = tempfile::tempdir().unwrap();
+        app.manage(AvatarService::new(avatars.path().to_path_buf()));
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        Fixture { app, dir, path, _avatars: avatars }
+    }
+
+    fn service(&self) -> State<'_, GitService> {
+        self.app.state::<GitService>()
+    }
+
+    fn write(&self, name: &str, contents: &str) {
+        std::fs::write(self.dir.path().join(name), contents).unwrap();
+    }
+
+    fn commit(&self, message: &str) {
+        let repo = Repository::open(&self.path).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = repo.signature().unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap();
+    }
+
+    /// Commits every change in the worktree, deletions included, as `author`
+    /// at `time` (Unix seconds). Returns the new commit's hash.
+    fn commit_as(&self, message: &str, author: &str, time: i64) -> String {
+        let repo = Repository::open(&self.path).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let email = format!("{}@example.com", author.to_lowercase());
+        let sig = git2::Signature::new(author, &email, &git2::Time::new(time, 0)).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap().to_string()
+    }
+
+    fn status_of(&self, file: &str) -> Option<String> {
+        git_status(self.service(), self.path.clone()).unwrap()
+            .into_iter()
+            .find(|f| f.path == file)
+            .map(|f| f.status)
+    }
+}
+
+#[test]
+fn test_stage_and_unstage_round_trip() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    fx.write("a.txt", "two\n");
+
+    assert_eq!(fx.status_of("a.txt").as_deref(), Some("modified"));
+    git_stage(fx.service(), fx.path.clone(), "a.txt".to_string()).unwrap();
+    assert_eq!(fx.status_of("a.txt").as_deref(), Some("staged_modified"));
+    git_unstage(fx.service(), fx.path.clone(), "a.txt".to_string()).unwrap();
+    assert_eq!(fx.status_of("a.txt").as_deref(), Some("modified"));
+
+    git_discard_changes(fx.service(), fx.path.clone(), "a.txt".to_string()).unwrap();
+    assert_eq!(fx.status_of("a.txt"), None);
+    assert_eq!(std::fs::read_to_string(fx.dir.path().join("a.txt")).unwrap(), "one\n");
+}
+
+#[test]
+fn test_info_counts_follow_the_index() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    fx.write("a.txt", "two\n");
+    fx.write("b.txt", "new\n");
+
+    let info = git_info(fx.service(), fx.path.clone(), None).unwrap();
+    assert!(!info.is_clean);
+    assert_eq!((info.staged_files, info.modified_files, info.untracked_files), (0, 1, 1));
+
+    git_stage_all(fx.service(), fx.path.clone()).unwrap();
+    let info = git_info(fx.service(), fx.path.clone(), None).unwrap();
+    assert_eq!((info.staged_files, info.modified_files, info.untracked_files), (2, 0, 0));
+    assert_eq!(info.user_name.as_deref(), Some("Test"));
+
+    git_unstage_all(fx.service(), fx.path.clone()).unwrap();
+    let info = git_info(fx.service(), fx.path.clone(), None).unwrap();
+    assert_eq!(info.staged_files, 0);
+}
+
+#[test]
+fn test_cached_handle_sees_outside_commits() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    assert!(git_info(fx.service(), fx.path.clone(), None).unwrap().is_clean);
+
+    // Another tool commits while the service holds a cached handle
+    fx.write("a.txt", "two\n");
+    fx.commit("second");
+    assert!(git_info(fx.service(), fx.path.clone(), None).unwrap().is_clean);
+    assert_eq!(fx.status_of("a.txt"), None);
+}
+
+#[test]
+fn test_branch_lifecycle() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+
+    git_create_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    git_checkout_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    let branches = git_list_branches(fx.service(), fx.path.clone()).unwrap();
+    assert!(branches.iter().any(|b| b.name == "feature" && b.is_current));
+    assert_eq!(git_info(fx.service(), fx.path.clone(), None).unwrap().branch, "feature");
+
+    let default = branches.iter().find(|b| !b.is_current && !b.is_remote).unwrap().name.clone();
+    git_checkout_branch(fx.service(), fx.path.clone(), default).unwrap();
+    git_delete_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    let branches = git_list_branches(fx.service(), fx.path.clone()).unwrap();
+    assert!(branches.iter().all(|b| b.name != "feature"));
+}
+
+#[test]
+fn test_missing_repository_is_an_error() {
+    let app = mock_app();
+    app.manage(GitService::default());
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_string_lossy().to_string();
+    assert!(git_status(app.state::<GitService>(), path).is_err());
+}
+
+fn rev(fx: &Fixture, spec: &str) -> git2::Oid {
+    let repo = Repository::open(&fx.path).unwrap();
+    let id = repo.revparse_single(spec).unwrap().peel_to_commit().unwrap().id();
+    id
+}
+
+fn read(fx: &Fixture, name: &str) -> String {
+    std::fs::read_to_string(fx.dir.path().join(name)).unwrap()
+}
+
+#[tokio::test]
+async fn test_revert_commit_and_nothing_to_revert() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    fx.write("a.txt", "two\n");
+    fx.commit("change");
+    let change = rev(&fx, "HEAD").to_string();
+
+    let result = operations::git_revert_commit(fx.service(), fx.path.clone(), change.clone(), None).await.unwrap();
+    assert!(result.success);
+    assert_eq!(result.commits, vec![rev(&fx, "HEAD").to_string()]);
+    assert_eq!(read(&fx, "a.txt"), "one\n");
+    let repo = Repository::open(&fx.path).unwrap();
+    assert!(repo.head().unwrap().peel_to_commit().unwrap().message().unwrap().starts_with("Revert \"change\""));
+
+    // Reverting again has nothing left to undo and must not add an empty commit
+    let head = rev(&fx, "HEAD");
+    let result = operations::git_revert_commit(fx.service(), fx.path.clone(), change, None).await.unwrap();
+    assert!(result.success);
+    assert!(result.commits.is_empty());
+    assert!(result.message.starts_with("Nothing to commit"));
+    assert_eq!(rev(&fx, "HEAD"), head);
+    assert_eq!(repo.state(), git2::RepositoryState::Clean);
+}
+
+#[tokio::test]
+async fn test_cherry_pick_keeps_author_and_skips_applied() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    let default = git_info(fx.service(), fx.path.clone(), None).unwrap().branch;
+
+    git_create_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    git_checkout_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    fx.write("b.txt", "feature\n");
+    fx.commit("add b");
+    let picked = rev(&fx, "feature").to_string();
+    git_checkout_branch(fx.service(), fx.path.clone(), default).unwrap();
+    assert!(!fx.dir.path().join("b.txt").exists());
+
+    let result = operations::git_cherry_pick(fx.service(), fx.path.clone(), vec![picked.clone()], None).await.unwrap();
+    assert!(result.success);
+    assert_eq!(result.commits.len(), 1);
+    assert_eq!(read(&fx, "b.txt"), "feature\n");
+    let repo = Repository::open(&fx.path).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let original = repo.find_commit(git2::Oid::from_str(&picked).unwrap()).unwrap();
+    assert_eq!(head.message(), Some("add b"));
+    assert_eq!(head.author().when(), original.author().when());
+
+    let result = operations::git_cherry_pick(fx.service(), fx.path.clone(), vec![picked], None).await.unwrap();
+    assert!(result.success);
+    assert!(result.commits.is_empty());
+    assert!(result.message.starts_with("Nothing to commit"));
+    assert_eq!(rev(&fx, "HEAD"), head.id());
+}
+
+#[tokio::test]
+async fn test_cherry_pick_reports_conflicts_and_reset_aborts() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    let default = git_info(fx.service(), fx.path.clone(), None).unwrap().branch;
+
+    git_create_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    git_checkout_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    fx.write("a.txt", "feature\n");
+    fx.commit("feature edit");
+    let picked = rev(&fx, "feature").to_string();
+    git_checkout_branch(fx.service(), fx.path.clone(), default).unwrap();
+    fx.write("a.txt", "main\n");
+    fx.commit("main edit");
+    let head = rev(&fx, "HEAD");
+
+    let result = operations::git_cherry_pick(fx.service(), fx.path.clone(), vec![picked], None).await.unwrap();
+    assert!(!result.success);
+    assert!(result.commits.is_empty());
+    assert_eq!(result.conflicts, vec!["a.txt".to_string()]);
+    let repo = Repository::open(&fx.path).unwrap();
+    assert_eq!(repo.state(), git2::RepositoryState::CherryPick);
+
+    // Another operation is refused until this one is resolved
+    let err = operations::git_revert_commit(fx.service(), fx.path.clone(), head.to_string(), None).await.unwrap_err();
+    assert!(err.contains("in progress"));
+
+    operations::git_reset(fx.service(), fx.path.clone(), "HEAD".to_string(), "hard".to_string()).unwrap();
+    assert_eq!(repo.state(), git2::RepositoryState::Clean);
+    assert_eq!(rev(&fx, "HEAD"), head);
+    assert_eq!(read(&fx, "a.txt"), "main\n");
+}
+
+#[tokio::test]
+async fn test_cherry_pick_applies_mainline_to_merges_only() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    let init = rev(&fx, "HEAD");
+    let default = git_info(fx.service(), fx.path.clone(), None).unwrap().branch;
+
+    git_create_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    git_checkout_branch(fx.service(), fx.path.clone(), "feature".to_string()).unwrap();
+    fx.write("f.txt", "feature\n");
+    fx.commit("feature side");
+    git_checkout_branch(fx.service(), fx.path.clone(), default).unwrap();
+    fx.write("m.txt", "main\n");
+    fx.commit("main side");
+
+    // Merge commit with both sides, then an ordinary commit on top
+    let repo = Repository::open(&fx.path).unwrap();
+    fx.write("f.txt", "feature\n");
+    let mut index = repo.index().unwrap();
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = repo.signature().unwrap();
+    let main = repo.head().unwrap().peel_to_commit().unwrap();
+    let feature = repo.find_commit(rev(&fx, "feature")).unwrap();
+    let merge = repo.commit(Some("HEAD"), &sig, &sig, "merge", &tree, &[&main, &feature]).unwrap();
+    fx.write("c.txt", "after\n");
+    fx.commit("after merge");
+    let after = rev(&fx, "HEAD");
+
+    repo.branch("target", &repo.find_commit(init).unwrap(), false).unwrap();
+    git_checkout_branch(fx.service(), fx.path.clone(), "target".to_string()).unwrap();
+    let hashes = vec![merge.to_string(), after.to_string()];
+    let result = operations::git_cherry_pick(fx.service(), fx.path.clone(), hashes, Some(2)).await.unwrap();
+    assert!(result.success, "{}", result.message);
+    assert_eq!(result.commits.len(), 2);
+    // Relative to the feature side the merge brought in main's file
+    assert_eq!(read(&fx, "m.txt"), "main\n");
+    assert_eq!(read(&fx, "c.txt"), "after\n");
+    assert!(!fx.dir.path().join("f.txt").exists());
+}
+
+#[test]
+fn test_fixup_needs_staged_changes() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    let repo = Repository::open(&fx.path).unwrap();
+
+    let err = operations::fixup_commit(&repo, "HEAD", true, &mut |_| {}).unwrap_err();
+    assert_eq!(err, "Nothing staged");
+    fx.write("a.txt", "two\n");
+    let err = operations::fixup_commit(&repo, "HEAD", true, &mut |_| {}).unwrap_err();
+    assert_eq!(err, "Nothing staged");
+
+    git_stage(fx.service(), fx.path.clone(), "a.txt".to_string()).unwrap();
+    let result = operations::fixup_commit(&repo, "HEAD", true, &mut |_| {}).unwrap();
+    assert_eq!(result.commits.len(), 1);
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message(), Some("fixup! init"));
+}
+
+#[test]
+fn test_reset_modes() {
+    let fx = Fixture::new();
+    fx.write("a.txt", "one\n");
+    fx.commit("init");
+    let first = rev(&fx, "HEAD");
+    fx.write("a.txt", "two\n");
+    fx.commit("second");
+
+    operations::git_reset(fx.service(), fx.path.clone(), "HEAD~1".to_string(), "soft".to_string()).unwrap();
+    assert_eq!(rev(&fx, "HEAD"), first);
+    assert_eq!(fx.status_of("a.txt").as_deref(), Some("staged_modified"));
+
+    operations::git_reset(fx.service(), fx.path.clone(), "HEAD".to_string(), "mixed".to_string()).unwrap();
+    assert_eq!(fx.status_of("a.txt").as_deref(), Some("modified"));
+    assert_eq!(read(&fx, "a.txt"), "two\n");
+
+    operations::git_reset(fx.service(), fx.path.clone(), "HEAD".to_string(), "hard".to_string()).unwrap();
+    assert_eq!(fx.status_of("a.txt"), None);
+    assert_eq!(read(&fx, "a.txt"), "one\n");
+
+    assert!(operations::git_reset(fx.service(), fx.path.clone(), "HEAD".to_string(), "keep".to_string()).is_err());
+}
+
+fn log_query(limit: usize, cursor: Option<String>) -> GitLogQuery {
+    GitLogQuery { limit: Some(limit), cursor, ..Default::default() }
+}
+
+#[test]
+fn test_log_pages_without_gaps_or_duplicates() {
+    let fx = Fixture::new();
+    let mut expected = Vec::new();
+    for i in 0..7 {
+        fx.write("a.txt", &format!("{}\n", i));
+        expected.push(fx.commit_as(&format!("c{}", i), "Alice", 1_700_000_000 + i));
+    }
+    expected.reverse();
+
+    let mut seen = Vec::new();
+    let mut cursor = None;
+    let mut pages = 0;
+    loop {
+        let page = git_log_page(fx.service(), fx.app.state(), fx.path.clone(), Some(log_query(3, cursor))).unwrap();
+        assert_eq!(page.graph.len(), page.commits.len());
+        seen.extend(page.commits.into_iter().map(|c| c.hash));
+        pages += 1;
+        cursor = page.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+    assert_eq!(pages, 3);
+    assert_eq!(seen, expected);
+
+    // A page that ends exactly on the last commit has no further cursor
+    let page = git_log_page(fx.service(), fx.app.state(), fx.path.clone(), Some(log_query(7, None))).unwrap();
+    assert_eq!(page.commits.len(), 7);
+    assert!(page.next_cursor.is_none());
+
+    let bad = git_log_page(fx.service(), fx.app.state(), fx.path.clone(), Some(log_query(3, Some("nope".into()))));
+    assert!(bad.is_err());
+}
+
+#[test]
+fn test_log_filters_by_author_path_and_date() {
+    let fx = Fixture::new();
+    std::fs::create_dir(fx.dir.path().join("src")).unwrap();
+    fx.write("README", "readme\n");
+    fx.commit_as("docs", "Alice", 1_000);
+    fx.write("src/main.rs", "fn main() {}\n");
+    fx.commit_as("code", "Bob", 2_000);
+    fx.write("README", "more\n");
+    fx.commit_as("more docs", "Bob", 3_000);
+    fx.write("src/main.rs", "fn main() { run() }\n");
+    fx.commit_as("more code", "Alice", 4_000);
+
+    let messages = |query: GitLogQuery| -> Vec<String> {
+        let page = git_log_page(fx.service(), fx.app.state(), fx.path.clone(), Some(query)).unwrap();
+        // Filtered commits skip their parents, so no graph is drawn
+        assert!(page.graph.is_empty());
+        page.commits.into_iter().map(|c| c.message.trim().to_string()).collect()
+    };
+
+    let by_author = GitLogQuery { author: Some(" ALICE ".into()), ..Default::default() };
+    assert_eq!(messages(by_author), vec!["more code", "docs"]);
+    let by_email = GitLogQuery { author: Some("bob@example".into()), ..Default::default() };
+    assert_eq!(messages(by_email), vec!["more docs", "code"]);
+
+    let by_path = GitLogQuery { path: Some("./src/".into()), ..Default::default() };
+    assert_eq!(messages(by_path), vec!["more code", "code"]);
+
+    // Both bounds are inclusive
+    let by_date = GitLogQuery { since: Some(2_000), until: Some(3_000), ..Default::default() };
+    assert_eq!(messages(by_date), vec!["more docs", "code"]);
+
+    // Filters combine, and paging skips over commits they reject
+    let combined = GitLogQuery { author: Some("alice".into()), path: Some("src".into()), ..Default::default() };
+    assert_eq!(messages(combined), vec!["more code"]);
+    let first = git_log_page(fx.service(), fx.app.state(), fx.path.clone(), Some(GitLogQuery { author: Some("bob".into()), ..log_query(1, None) })).unwrap();
+    assert_eq!(first.commits[0].message.trim(), "more docs");
+    let second = GitLogQuery { author: Some("bob".into()), ..log_query(1, first.next_cursor) };
+    assert_eq!(messages(second), vec!["code"]);
+}
+
+#[test]
+fn test_file_history_follows_rename_across_pages() {
+    let fx = Fixture::new();
+    let body: String = (0..20).map(|i| format!("line {}\n", i)).collect();
+    fx.write("old.txt", &body);
+    fx.commit_as("add old", "Alice", 1_000);
+    fx.write("old.txt", &format!("{}edit\n", body));
+    fx.commit_as("edit old", "Alice", 2_000);
+    fx.write("other.txt", "unrelated\n");
+    fx.commit_as("unrelated", "Alice", 3_000);
+    std::fs::rename(fx.dir.path().join("old.txt"), fx.dir.path().join("new.txt")).unwrap();
+    fx.commit_as("git mv old new", "Alice", 4_000);
+    fx.write("new.txt", &format!("{}edit\nagain\n", body));
+    fx.commit_as("edit new", "Alice", 5_000);
+
+    let mut entries = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = git_file_history(fx.service(), fx.app.state(), fx.path.clone(), "new.txt".into(), Some(log_query(1, cursor))).unwrap();
+        assert_eq!(page.entries.len(), 1);
+        entries.extend(page.entries);
+        cursor = page.next_cursor;
+        match &cursor {
+            Some(next) if entries.len() == 2 => assert!(next.ends_with(":old.txt"), "{}", next),
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    let rows: Vec<_> = entries
+        .iter()
+        .map(|e| (e.commit.message.trim(), e.path.as_str(), e.status.as_str(), e.previous_path.as_deref()))
+        .collect();
+    assert_eq!(rows, vec![
+        ("edit new", "new.txt", "modified", None),
+        ("git mv old new", "new.txt", "renamed", Some("old.txt")),
+        ("edit old", "old.txt", "modified", None),
+        ("add old", "old.txt", "added", None),
+    ]);
+}
+
+#[test]
+fn test_diff_revisions_with_rename_binary_and_path_filter() {
+    let fx = Fixture::new();
+    let root = fx.dir.path();
+    std::fs::create_dir(root.join("old")).unwrap();
+    let body: String = (0..20).map(|i| format!("line {}\n", i)).collect();
+    fx.write("old/a.txt", &body);
+    std::fs::write(root.join("img.bin"), [0u8, 1, 2, 0, 255, 0]).unwrap();
+    fx.write("keep.txt", "one\n");
+    fx.commit_as("init", "Alice", 1_000);
+
+    std::fs::create_dir(root.join("new")).unwrap();
+    std::fs::remove_file(root.join("old/a.txt")).unwrap();
+    fx.write("new/a.txt", &format!("{}line 20\n", body));
+    std::fs::write(root.join("img.bin"), [0u8, 9, 9, 0, 255, 0]).unwrap();
+    fx.write("keep.txt", "two\n");
+    fx.commit_as("move", "Alice", 2_000);
+
+    let diff = |path: Option<&str>| {
+        git_diff_revisions(fx.service(), fx.path.clone(), "HEAD~1".into(), "HEAD".into(), path.map(String::from)).unwrap()
+    };
+
+    let files = diff(None);
+    let paths: Vec<_> = files.iter().map(|f| (f.file_path.as_str(), f.status.as_str())).collect();
+    assert_eq!(paths, vec![("img.bin", "modified"), ("keep.txt", "modified"), ("new/a.txt", "renamed")]);
+
+    let renamed = &files[2];
+    assert_eq!(renamed.old_file_path.as_deref(), Some("old/a.txt"));
+    assert!(!renamed.is_new_file && !renamed.is_deleted);
+    assert!(renamed.lines.iter().any(|l| l.line_type == "add" && l.content == "line 20\n"));
+
+    let binary = &files[0];
+    assert!(binary.is_binary);
+    assert!(binary.lines.is_empty());
+    assert!(binary.old_content.is_empty() && binary.new_content.is_empty());
+
+    // The filter matches either side of a rename and "." means the whole tree
+    let only = |path: &str| -> Vec<String> { diff(Some(path)).into_iter().map(|f| f.file_path).collect() };
+    assert_eq!(only("old"), vec!["new/a.txt"]);
+    assert_eq!(only("new/"), vec!["new/a.txt"]);
+    assert_eq!(only("keep.txt"), vec!["keep.txt"]);
+    assert_eq!(only("."), vec!["img.bin", "keep.txt", "new/a.txt"]);
+    assert!(only("ol").is_empty());
+
+    let shown = git_show_commit(fx.service(), fx.app.state(), fx.path.clone(), "HEAD".into()).unwrap();
+    assert_eq!(shown.summary, "move");
+    assert_eq!(shown.files.len(), 3);
+}
+
+fn tag_request(name: &str, message: Option<&str>) -> GitCreateTagRequest {
+    GitCreateTagRequest { name: name.to_string(), message: message.map(|m| m.to_string()), ..Default::default() }
+}
+
+#[test]
+fn test_create_and_list_tags() {
+    let fx = Fixture::new();
+    fx.commit("first");
+    let light = git_create_tag(fx.service(), fx.path.clone(), tag_request("v1", None)).unwrap();
+    assert!(!light.annotated);
+    assert_eq!(light.target_summary.as_deref(), Some("first"));
+
+    let annotated = git_create_tag(fx.service(), fx.path.clone(), tag_request("v2", Some("Release 2\n"))).unwrap();
+    assert!(annotated.annotated);
+    assert_eq!(annotated.message.as_deref(), Some("Release 2"));
+    assert_eq!(annotated.tagger_email.as_deref(), Some("test@example.com"));
+    assert_eq!(annotated.target, light.target);
+
+    let tags = git_list_tags(fx.service(), fx.path.clone()).unwrap();
+    let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names.len(), 2);
+    assert!(names.contains(&"v1") && names.contains(&"v2"));
+}
+
+#[test]
+fn test_create_tag_rejects_duplicates_and_bad_names() {
+    let fx = Fixture::new();
+    fx.commit("first");
+    git_create_tag(fx.service(), fx.path.clone(), tag_request("v1", None)).unwrap();
+    let err = git_create_tag(fx.service(), fx.path.clone(), tag_request("v1", None)).unwrap_err();
+    assert!(err.contains("already exists"));
+    assert!(git_create_tag(fx.service(), fx.path.clone(), tag_request("bad..name", None)).is_err());
+
+    let forced = GitCreateTagRequest { force: true, ..tag_request("v1", Some("moved")) };
+    assert!(git_create_tag(fx.service(), fx.path.clone(), forced).unwrap().annotated);
+}
+
+#[test]
+fn test_delete_tag_locally_and_on_remote() {
+    let fx = Fixture::new();
+    fx.commit("first");
+    git_create_tag(fx.service(), fx.path.clone(), tag_request("v1", None)).unwrap();
+    git_create_tag(fx.service(), fx.path.clone(), tag_request("v2", Some("Release 2"))).unwrap();
+
+    let bare_dir = tempfile::tempdir().unwrap();
+    let bare = Repository::init_bare(bare_dir.path()).unwrap();
+    let repo = Repository::open(&fx.path).unwrap();
+    repo.remote("origin", &bare_dir.path().to_string_lossy()).unwrap()
+        .push(&["refs/tags/v1:refs/tags/v1", "refs/tags/v2:refs/tags/v2"], None)
+        .unwrap();
+    let on_remote = |name: &str| bare.find_reference(&format!("refs/tags/{}", name)).is_ok();
+    let local = |name: &str| repo.find_reference(&format!("refs/tags/{}", name)).is_ok();
+    assert!(on_remote("v1") && on_remote("v2"));
+
+    delete_tag(&repo, Some(("origin".to_string(), credentials::test_session())), "v1").unwrap();
+    assert!(!local("v1") && !on_remote("v1"));
+
+    // Without a remote only the local tag goes
+    delete_tag(&repo, None, "v2").unwrap();
+    assert!(!local("v2") && on_remote("v2"));
+    assert!(delete_tag(&repo, None, "v2").unwrap_err().contains("not found"));
+
+    // A tag left only on the remote can still be removed there
+    delete_tag(&repo, Some(("origin".to_string(), credentials::test_session())), "v2").unwrap();
+    assert!(!on_remote("v2"));
+}
@@ -0,0 +1,185 @@
+use git2::{BranchType, Repository, StatusOptions, WorktreeAddOptions, WorktreeLockStatus, WorktreePruneOptions};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use super::service::{GitService, RepoHandle};
+
+#[derive(Serialize, Debug)]
+pub struct GitWorktree {
+    /// `None` for the main working tree.
+    pub name: Option<String>,
+    pub path: String,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub is_main: bool,
+    /// The working tree `repo_path` belongs to.
+    pub is_current: bool,
+    pub is_locked: bool,
+    pub lock_reason: Option<String>,
+    /// The directory was deleted without removing the worktree.
+    pub is_missing: bool,
+}
+
+/// The main repository, also when `repo_path` is a linked worktree.
+fn open_main(service: &GitService, repo_path: &str) -> Result<(RepoHandle, RepoHandle), String> {
+    let repo = service.open(repo_path)?;
+    let main = if repo.is_worktree() {
+        service.open(&repo.commondir().to_string_lossy())?
+    } else {
+        service.open(repo_path)?
+    };
+    Ok((repo, main))
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    canonical(a) == canonical(b)
+}
+
+fn head_info(repo: &Repository) -> (Option<String>, Option<String>) {
+    match repo.head() {
+        Ok(head) => (
+            head.is_branch().then(|| head.shorthand().map(|s| s.to_string())).flatten(),
+            head.target().map(|id| id.to_string()),
+        ),
+        // Unborn branch: HEAD is symbolic but points nowhere yet
+        Err(_) => (
+            repo.find_reference("HEAD").ok()
+                .and_then(|h| h.symbolic_target().map(|t| t.trim_start_matches("refs/heads/").to_string())),
+            None,
+        ),
+    }
+}
+
+fn linked_worktree(main: &Repository, name: &str, current: &Path) -> Result<GitWorktree, String> {
+    let worktree = main.find_worktree(name).map_err(|e| e.to_string())?;
+    let is_missing = worktree.validate().is_err();
+    let (branch, head) = if is_missing {
+        (None, None)
+    } else {
+        Repository::open_from_worktree(&worktree)
+            .map(|repo| head_info(&repo))
+            .unwrap_or((None, None))
+    };
+    let (is_locked, lock_reason) = match worktree.is_locked().map_err(|e| e.to_string())? {
+        WorktreeLockStatus::Unlocked => (false, None),
+        WorktreeLockStatus::Locked(reason) => (true, reason.filter(|r| !r.is_empty())),
+    };
+
+    Ok(GitWorktree {
+        name: Some(name.to_string()),
+        path: worktree.path().to_string_lossy().to_string(),
+        branch,
+        head,
+        is_main: false,
+        is_current: same_path(worktree.path(), current),
+        is_locked,
+        lock_reason,
+        is_missing,
+    })
+}
+
+#[tauri::command]
+pub fn git_list_worktrees(service: State<'_, GitService>, repo_path: String) -> Result<Vec<GitWorktree>, String> {
+    let (repo, main) = open_main(&service, &repo_path)?;
+    let current = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
+
+    let mut worktrees = Vec::new();
+    if let Some(workdir) = main.workdir() {
+        let (branch, head) = head_info(&main);
+        worktrees.push(GitWorktree {
+            name: None,
+            path: workdir.to_string_lossy().to_string(),
+            branch,
+            head,
+            is_main: true,
+            is_current: same_path(workdir, &current),
+            is_locked: false,
+            lock_reason: None,
+            is_missing: false,
+        });
+    }
+
+    for name in main.worktrees().map_err(|e| e.to_string())?.iter().flatten() {
+        worktrees.push(linked_worktree(&main, name, &current)?);
+    }
+
+    Ok(worktrees)
+}
+
+/// Checks out a branch into a new working tree at `path`, like `git worktree add`.
+/// With `new_branch` the branch is created from `start_point` (default HEAD);
+/// without any branch one named after the directory is created.
+#[tauri::command]
+pub fn git_add_worktree(
+    service: State<'_, GitService>,
+    repo_path: String,
+    path: String,
+    branch: Option<String>,
+    new_branch: Option<String>,
+    start_point: Option<String>,
+) -> Result<GitWorktree, String> {
+    let (_, main) = open_main(&service, &repo_path)?;
+    let target = PathBuf::from(&path);
+    if target.exists() && target.read_dir().map(|mut d| d.next().is_some()).unwrap_or(true) {
+        return Err(format!("'{}' already exists and is not empty", path));
+    }
+
+    let name = target.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid worktree path '{}'", path))?
+        .to_string();
+    if main.find_worktree(&name).is_ok() {
+        return Err(format!("A worktree named '{}' already exists", name));
+    }
+
+    let branch = match (branch.filter(|b| !b.trim().is_empty()), new_branch.filter(|b| !b.trim().is_empty())) {
+        (Some(existing), None) => main.find_branch(existing.trim(), BranchType::Local)
+            .map_err(|e| format!("Local branch '{}' not found: {}", existing, e))?,
+        (None, new_branch) => {
+            let new_branch = new_branch.unwrap_or_else(|| name.clone());
+            let start = start_point.as_deref().unwrap_or("HEAD");
+            let commit = main.revparse_single(start)
+                .and_then(|obj| obj.peel_to_commit())
+                .map_err(|e| format!("Unknown start point '{}': {}", start, e))?;
+            main.branch(new_branch.trim(), &commit, false)
+                .map_err(|e| format!("Cannot create branch '{}': {}", new_branch, e))?
+        }
+        (Some(_), Some(_)) => return Err("Pass either an existing branch or a new branch name, not both".to_string()),
+    };
+
+    let mut options = WorktreeAddOptions::new();
+    options.reference(Some(branch.get()));
+    main.worktree(&name, &target, Some(&options)).map_err(|e| e.to_string())?;
+
+    linked_worktree(&main, &name, &target)
+}
+
+/// Deletes a linked worktree and its directory. Without `force`, refuses
+/// when the worktree is locked or has uncommitted changes.
+#[tauri::command]
+pub fn git_remove_worktree(service: State<'_, GitService>, repo_path: String, name: String, force: bool) -> Result<(), String> {
+    let (_, main) = open_main(&service, &repo_path)?;
+    let worktree = main.find_worktree(&name).map_err(|e| format!("Worktree '{}' not found: {}", name, e))?;
+
+    if !force {
+        if let WorktreeLockStatus::Locked(reason) = worktree.is_locked().map_err(|e| e.to_string())? {
+            return Err(format!("Worktree '{}' is locked{}", name, reason.map(|r| format!(": {}", r)).unwrap_or_default()));
+        }
+        if worktree.validate().is_ok() {
+            let repo = Repository::open_from_worktree(&worktree).map_err(|e| e.to_string())?;
+            let mut opts = StatusOptions::new();
+            opts.include_untracked(true);
+            if !repo.statuses(Some(&mut opts)).map_err(|e| e.to_string())?.is_empty() {
+                return Err(format!("Worktree '{}' has uncommitted changes", name));
+            }
+        }
+    }
+
+    let mut options = WorktreePruneOptions::new();
+    options.valid(true).locked(force).working_tree(true);
+    // Before pruning: the cache is keyed by the canonical path, which needs the directory
+    service.forget(&worktree.path().to_string_lossy());
+    worktree.prune(Some(&mut options)).map_err(|e| e.to_string())
+}
@@ -1,5 +1,7 @@
+mod ai;
 mod command_palette;
 mod fs;
+mod forge;
 mod git;
 mod keybindings;
 mod npm;
@@ -11,12 +13,7 @@ mod settings;
 mod timeline;
 mod ollama;
 mod agentrouter;
-mod openai;
-mod anthropic;
-mod google;
-mod xai;
 mod api_keys;
-mod proxy_config;
 
 use tauri::Manager;
 use tauri_plugin_decorum::WebviewWindowExt;
@@ -79,8 +76,15 @@ pub fn run() {
         .manage(AudioCache::new(50, 24)) // 50 files, 24 hours cache
         .manage(ollama::OllamaState::default())
         .manage(agentrouter::AgentRouterState::default())
+        .manage(ai::AiState::default())
+        .manage(ai::AgentState::default())
+        .manage(ai::ConversationState::default())
+        .manage(git::clone::GitCloneState::default())
+        .manage(git::credentials::GitCredentialState::default())
+        .manage(git::service::GitService::default())
+        .manage(git::avatar::AvatarService::default())
         // Wrap ApiKeyStore in a Mutex to match State<'_, Mutex<ApiKeyStore>> in commands
-        .manage(Mutex::new(api_keys::ApiKeyStore::default()))
+        .manage(Mutex::new(api_keys::ApiKeyStore::load()))
         .manage(keybindings::KeybindingsState::new(keybindings::KeybindingsStore::new()))
         .manage(settings::SettingsState::new())
         .manage(session::SessionState::new())
@@ -102,6 +106,9 @@ pub fn run() {
             let asset_protocol_scope = app.asset_protocol_scope();
             asset_protocol_scope.allow_directory("**", true).unwrap();
 
+            git::avatar::start_fetcher(app.handle());
+            settings::apply_ai_settings(app.handle());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -129,28 +136,72 @@ pub fn run() {
             fs::start_file_watcher,
             fs::stop_file_watcher,
             fs::add_watch_path,
-            git::git_status,
+            git::status::git_status,
+            git::status::git_status_report,
             git::git_info,
-            git::git_clone,
+            git::clone::git_clone,
+            git::clone::git_cancel_clone,
             git::git_stage,
             git::git_unstage,
             git::git_stage_all,
             git::git_unstage_all,
             git::git_commit,
+            git::operations::git_amend_commit,
+            git::operations::git_fixup_commit,
+            git::operations::git_revert_commit,
+            git::operations::git_cherry_pick,
+            git::operations::git_reset,
             git::push::git_push,
             git::push::git_push_with_force,
             git::push::git_list_remotes,
             git::push::git_get_remote_url,
+            git::remote::git_fetch,
+            git::remote::git_pull,
+            git::submodule::git_list_submodules,
+            git::submodule::git_submodule_init,
+            git::submodule::git_submodule_update,
+            git::submodule::git_submodule_sync,
+            git::worktree::git_list_worktrees,
+            git::worktree::git_add_worktree,
+            git::worktree::git_remove_worktree,
+            git::credentials::git_credentials_get_config,
+            git::credentials::git_credentials_set_config,
+            git::credentials::git_credentials_set_token,
+            git::credentials::git_credentials_remove_token,
+            git::credentials::git_credentials_list_tokens,
+            git::credentials::git_credentials_respond,
             git::git_discard_changes,
             git::git_diff,
+            git::show::git_show_commit,
+            git::show::git_diff_revisions,
             git::git_contributors,
-            git::git_log,
+            git::log::git_log,
+            git::log::git_log_page,
+            git::log::git_file_history,
+            git::log::git_commit_stats,
             git::git_list_branches,
             git::git_github_auth_status,
             git::git_github_auth_login,
             git::git_create_branch,
             git::git_checkout_branch,
             git::git_delete_branch,
+            git::avatar::git_avatar_clear_cache,
+            git::stats::git_repo_stats,
+            git::tags::git_list_tags,
+            git::tags::git_create_tag,
+            git::tags::git_delete_tag,
+            forge::forge_list_hosts,
+            forge::forge_set_host,
+            forge::forge_remove_host,
+            forge::forge_repo_info,
+            forge::forge_list_pull_requests,
+            forge::forge_get_pull_request,
+            forge::forge_create_pull_request,
+            forge::forge_list_review_comments,
+            forge::forge_checkout_pull_request,
+            forge::forge_list_issues,
+            forge::forge_get_issue,
+            forge::forge_create_issue,
             npm::npm_get_scripts,
             npm::npm_run_script,
             npm::npm_stop_script,
@@ -174,26 +225,39 @@ pub fn run() {
             fs::cache_audio,
             fs::clear_audio_cache,
             fs::get_audio_cache_stats,
-            ollama::ollama_chat,
-            ollama::ollama_chat_stream,
             ollama::ollama_list_models,
             ollama::ollama_pull_model,
             ollama::ollama_generate,
             ollama::ollama_list_local_models,
             agentrouter::agentrouter_configure,
-            agentrouter::agentrouter_chat,
-            agentrouter::agentrouter_chat_stream,
             agentrouter::agentrouter_list_models,
-            openai::openai_chat,
-            openai::openai_chat_stream,
-            anthropic::anthropic_chat,
-            anthropic::anthropic_chat_stream,
-            google::google_chat,
-            google::google_chat_stream,
-            xai::xai_chat,
-            xai::xai_chat_stream,
+            ai::ai_list_providers,
+            ai::ai_list_models,
+            ai::ai_chat,
+            ai::ai_chat_stream,
+            ai::ai_cancel,
+            ai::ai_model_catalog,
+            ai::ai_estimate_tokens,
+            ai::ai_usage_summary,
+            ai::ai_usage_clear,
+            ai::ai_agent_run,
+            ai::ai_agent_approve,
+            ai::ai_conversations_list,
+            ai::ai_conversation_create,
+            ai::ai_conversation_get,
+            ai::ai_conversation_rename,
+            ai::ai_conversation_delete,
+            ai::ai_conversation_append,
+            ai::ai_conversation_fork,
+            ai::ai_conversations_search,
+            ai::ai_conversation_export,
             api_keys::set_api_key,
             api_keys::get_api_keys,
+            api_keys::api_keys_list,
+            api_keys::api_keys_status,
+            api_keys::api_keys_unlock,
+            api_keys::api_keys_lock,
+            api_keys::api_keys_change_passphrase,
             keybindings::keybindings_init,
             keybindings::keybindings_get_all,
             keybindings::keybindings_lookup,
@@ -1,5 +1,7 @@
 use std::sync::Mutex;
-use tauri::{AppHandle, State, Emitter};
+use tauri::{AppHandle, State, Emitter, Manager};
+
+use crate::ai::AiState;
 
 use super::store::SettingsStore;
 use super::types::*;
@@ -26,6 +28,22 @@ impl Default for SettingsState {
     }
 }
 
+/// Apply the AI section of `settings`: custom endpoints and the context strategy
+fn sync_ai_settings(app_handle: &AppHandle, settings: &AppSettings) {
+    app_handle.state::<AiState>().apply_settings(&settings.ai);
+}
+
+/// Load user settings at startup so configured AI endpoints are usable
+/// before the frontend initializes settings
+pub fn apply_ai_settings(app_handle: &AppHandle) {
+    let state = app_handle.state::<SettingsState>();
+    let store = state.store.lock().unwrap();
+    if let Err(e) = store.load_user_settings() {
+        eprintln!("Failed to load settings: {}", e);
+    }
+    sync_ai_settings(app_handle, &store.get_settings());
+}
+
 /// Initialize settings system
 #[tauri::command]
 pub fn settings_init(
@@ -47,13 +65,15 @@ pub fn settings_init(
     
     // Start file watcher
     let mut watcher = state.watcher.lock().unwrap();
-    watcher.start(app_handle, paths)?;
+    watcher.start(app_handle.clone(), paths)?;
     
     drop(watcher);
     
     // Return current settings
     let store = state.store.lock().unwrap();
-    Ok(store.get_settings())
+    let settings = store.get_settings();
+    sync_ai_settings(&app_handle, &settings);
+    Ok(settings)
 }
 
 /// Get all settings (merged user + workspace)
@@ -111,6 +131,11 @@ pub fn settings_update_section(
                 .map_err(|e| format!("Invalid AI settings: {}", e))?;
             store.update_section(&section, ai, source.clone())?;
         }
+        "git" => {
+            let git: GitSettings = serde_json::from_value(value.clone())
+                .map_err(|e| format!("Invalid git settings: {}", e))?;
+            store.update_section(&section, git, source.clone())?;
+        }
         "workspace" => {
             let workspace: WorkspaceSettings = serde_json::from_value(value.clone())
                 .map_err(|e| format!("Invalid workspace settings: {}", e))?;
@@ -118,6 +143,9 @@ pub fn settings_update_section(
         }
         _ => return Err(format!("Unknown section: {}", section)),
     }
+    if section == "ai" {
+        sync_ai_settings(&app_handle, &store.get_settings());
+    }
 
     // Broadcast change to all windows
     let event = SettingsChangeEvent {
@@ -149,6 +177,9 @@ pub fn settings_update_value(
 
     let store = state.store.lock().unwrap();
     store.update_value(&section, &key, value.clone(), source.clone())?;
+    if section == "ai" {
+        sync_ai_settings(&app_handle, &store.get_settings());
+    }
 
     // Broadcast change to all windows
     let event = SettingsChangeEvent {
@@ -181,6 +212,7 @@ pub fn settings_set_workspace(
 
     // Broadcast that settings may have changed
     let store = state.store.lock().unwrap();
+    sync_ai_settings(&app_handle, &store.get_settings());
     let event = SettingsChangeEvent {
         section: "all".to_string(),
         key: None,
@@ -215,6 +247,7 @@ pub fn settings_clear_workspace(
 
     // Broadcast that settings may have changed
     let store = state.store.lock().unwrap();
+    sync_ai_settings(&app_handle, &store.get_settings());
     let event = SettingsChangeEvent {
         section: "all".to_string(),
         key: None,
@@ -234,6 +267,7 @@ pub fn settings_reload(
 ) -> Result<AppSettings, String> {
     let store = state.store.lock().unwrap();
     let event = store.reload()?;
+    sync_ai_settings(&app_handle, &store.get_settings());
     
     // Broadcast reload event
     let _ = app_handle.emit("settings-changed", &event);
@@ -268,6 +302,7 @@ pub fn settings_reset(
             store.update_section("ui", UISettings::default(), SettingsSource::User)?;
             store.update_section("editor", EditorSettings::default(), SettingsSource::User)?;
             store.update_section("ai", AISettings::default(), SettingsSource::User)?;
+            store.update_section("git", GitSettings::default(), SettingsSource::User)?;
         }
         "workspace" => {
             // Clear workspace settings
@@ -278,12 +313,14 @@ pub fn settings_reset(
             store.update_section("ui", UISettings::default(), SettingsSource::User)?;
             store.update_section("editor", EditorSettings::default(), SettingsSource::User)?;
             store.update_section("ai", AISettings::default(), SettingsSource::User)?;
+            store.update_section("git", GitSettings::default(), SettingsSource::User)?;
             store.clear_workspace();
         }
         _ => return Err("Invalid target: must be 'user', 'workspace', or 'all'".to_string()),
     }
 
     let settings = store.get_settings();
+    sync_ai_settings(&app_handle, &settings);
     
     // Broadcast reset event
     let event = SettingsChangeEvent {
@@ -41,6 +41,36 @@ impl Default for AISettings {
             stream_responses: true,
             max_tokens: 4096,
             temperature: 0.7,
+            endpoints: Vec::new(),
+            context_strategy: ContextStrategy::default(),
+        }
+    }
+}
+
+impl Default for GitSettings {
+    fn default() -> Self {
+        Self {
+            avatar_network_fetch: false,
+            avatar_cache_ttl_hours: 168,
+            credentials: GitCredentialConfig::default(),
+            forge_hosts: Vec::new(),
+        }
+    }
+}
+
+impl Default for GitCredentialConfig {
+    fn default() -> Self {
+        Self {
+            chain: vec![
+                CredentialSource::StoredToken,
+                CredentialSource::GithubCli,
+                CredentialSource::SshAgent,
+                CredentialSource::SshKeyFile,
+                CredentialSource::CredentialHelper,
+                CredentialSource::SystemDefault,
+            ],
+            ssh_key_paths: Vec::new(),
+            max_attempts: 6,
         }
     }
 }
@@ -70,6 +100,7 @@ impl Default for AppSettings {
             ui: UISettings::default(),
             editor: EditorSettings::default(),
             ai: AISettings::default(),
+            git: GitSettings::default(),
             workspace: None,
         }
     }
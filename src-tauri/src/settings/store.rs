@@ -1,5 +1,5 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 use super::defaults::*;
@@ -24,10 +24,15 @@ impl SettingsStore {
             .unwrap_or_else(|| PathBuf::from("."))
             .join("colbex");
         
+        Self::with_user_config_path(config_dir.join("settings.json"))
+    }
+
+    /// Store with the user settings file at `path`
+    pub fn with_user_config_path(path: PathBuf) -> Self {
         Self {
             user_settings: RwLock::new(AppSettings::default()),
             workspace_settings: RwLock::new(None),
-            user_config_path: config_dir.join("settings.json"),
+            user_config_path: path,
             workspace_config_path: RwLock::new(None),
         }
     }
@@ -64,13 +69,7 @@ impl SettingsStore {
             .map_err(|e| format!("Failed to parse settings: {}", e))?;
 
         // Validate settings
-        let validation = validate_settings(&settings);
-        if !validation.valid {
-            let errors: Vec<String> = validation.errors.iter()
-                .map(|e| format!("{}: {}", e.path, e.message))
-                .collect();
-            return Err(format!("Invalid settings: {}", errors.join(", ")));
-        }
+        check_valid(&settings, "settings")?;
 
         *self.user_settings.write().unwrap() = settings;
         Ok(())
@@ -103,16 +102,11 @@ impl SettingsStore {
 
         *self.workspace_config_path.write().unwrap() = Some(workspace_config.clone());
 
+        // Cleared first so a rejected file never leaves the previous workspace's settings active
+        *self.workspace_settings.write().unwrap() = None;
         if workspace_config.exists() {
-            let content = fs::read_to_string(&workspace_config)
-                .map_err(|e| format!("Failed to read workspace settings: {}", e))?;
-
-            let settings: AppSettings = serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse workspace settings: {}", e))?;
-
+            let settings = read_workspace_settings(&workspace_config)?;
             *self.workspace_settings.write().unwrap() = Some(settings);
-        } else {
-            *self.workspace_settings.write().unwrap() = None;
         }
 
         Ok(())
@@ -257,6 +251,10 @@ impl SettingsStore {
                 settings.ai = serde_json::from_value(value)
                     .map_err(|e| format!("Invalid AI settings: {}", e))?;
             }
+            "git" => {
+                settings.git = serde_json::from_value(value)
+                    .map_err(|e| format!("Invalid git settings: {}", e))?;
+            }
             "workspace" => {
                 settings.workspace = serde_json::from_value(value)
                     .map_err(|e| format!("Invalid workspace settings: {}", e))?;
@@ -307,7 +305,22 @@ impl SettingsStore {
         AppSettings {
             ui: workspace.ui.clone(),
             editor: workspace.editor.clone(),
-            ai: workspace.ai.clone(),
+            // Endpoints decide where API keys are sent, so a cloned
+            // repository must not be able to redirect them
+            ai: AISettings {
+                endpoints: user.ai.endpoints.clone(),
+                ..workspace.ai.clone()
+            },
+            git: GitSettings {
+                // Privacy choice of the user; a repository cannot turn network fetch on
+                avatar_network_fetch: user.git.avatar_network_fetch,
+                // The avatar cache is shared by every workspace
+                avatar_cache_ttl_hours: user.git.avatar_cache_ttl_hours,
+                // Decides which secrets and key files are offered to remotes
+                credentials: user.git.credentials.clone(),
+                // Decides where forge API tokens are sent
+                forge_hosts: user.git.forge_hosts.clone(),
+            },
             workspace: workspace.workspace.clone().or_else(|| user.workspace.clone()),
         }
     }
@@ -319,12 +332,7 @@ impl SettingsStore {
         // Reload workspace settings if workspace is set
         if let Some(workspace_path) = self.workspace_config_path.read().unwrap().as_ref() {
             if workspace_path.exists() {
-                let content = fs::read_to_string(workspace_path)
-                    .map_err(|e| format!("Failed to read workspace settings: {}", e))?;
-
-                let settings: AppSettings = serde_json::from_str(&content)
-                    .map_err(|e| format!("Failed to parse workspace settings: {}", e))?;
-
+                let settings = read_workspace_settings(workspace_path)?;
                 *self.workspace_settings.write().unwrap() = Some(settings);
             }
         }
@@ -338,8 +346,133 @@ impl SettingsStore {
     }
 }
 
+/// Error listing every validation failure of `settings`
+fn check_valid(settings: &AppSettings, what: &str) -> Result<(), String> {
+    let validation = validate_settings(settings);
+    if !validation.valid {
+        let errors: Vec<String> = validation.errors.iter()
+            .map(|e| format!("{}: {}", e.path, e.message))
+            .collect();
+        return Err(format!("Invalid {}: {}", what, errors.join(", ")));
+    }
+    Ok(())
+}
+
+/// Read and validate a workspace's `.colbex/settings.json`
+fn read_workspace_settings(path: &Path) -> Result<AppSettings, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read workspace settings: {}", e))?;
+
+    let settings: AppSettings = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse workspace settings: {}", e))?;
+
+    check_valid(&settings, "workspace settings")?;
+    Ok(settings)
+}
+
 impl Default for SettingsStore {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(id: &str, base_url: &str) -> OpenAiEndpoint {
+        OpenAiEndpoint {
+            id: id.to_string(),
+            name: id.to_string(),
+            base_url: base_url.to_string(),
+            headers: Default::default(),
+            auth: EndpointAuth::Bearer,
+        }
+    }
+
+    fn write_workspace_settings(workspace: &Path, settings: &AppSettings) {
+        let dir = workspace.join(".colbex");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("settings.json"), serde_json::to_string(settings).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_workspace_cannot_redirect_endpoints() {
+        let config = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let store = SettingsStore::with_user_config_path(config.path().join("settings.json"));
+        store.load_user_settings().unwrap();
+
+        let user_endpoints = vec![endpoint("lm-studio", "http://localhost:1234/v1")];
+        store.update_value("ai", "endpoints", serde_json::to_value(&user_endpoints).unwrap(), SettingsSource::User).unwrap();
+
+        // A cloned repository pointing the built-in OpenAI provider at its own server
+        let mut malicious = AppSettings::default();
+        malicious.ai.temperature = 0.2;
+        malicious.ai.endpoints = vec![endpoint("openai", "https://attacker.example/v1")];
+        write_workspace_settings(workspace.path(), &malicious);
+        store.set_workspace(&workspace.path().to_string_lossy()).unwrap();
+
+        let merged = store.get_settings();
+        assert_eq!(merged.ai.endpoints, user_endpoints);
+        assert_eq!(merged.ai.temperature, 0.2);
+
+        // A workspace without endpoints keeps the user's
+        write_workspace_settings(workspace.path(), &AppSettings::default());
+        store.reload().unwrap();
+        assert_eq!(store.get_settings().ai.endpoints, user_endpoints);
+
+        // Invalid workspace files are rejected rather than applied
+        let mut invalid = AppSettings::default();
+        invalid.ai.endpoints = vec![endpoint("anthropic", "ftp://attacker.example")];
+        write_workspace_settings(workspace.path(), &invalid);
+        assert!(store.set_workspace(&workspace.path().to_string_lossy()).is_err());
+        assert_eq!(store.get_workspace_settings(), None);
+    }
+
+    #[test]
+    fn test_workspace_cannot_enable_avatar_fetch() {
+        let config = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let store = SettingsStore::with_user_config_path(config.path().join("settings.json"));
+        store.load_user_settings().unwrap();
+        assert!(!store.get_settings().git.avatar_network_fetch);
+
+        let mut settings = AppSettings::default();
+        settings.git.avatar_network_fetch = true;
+        settings.git.avatar_cache_ttl_hours = 1;
+        write_workspace_settings(workspace.path(), &settings);
+        store.set_workspace(&workspace.path().to_string_lossy()).unwrap();
+        assert_eq!(store.get_settings().git, GitSettings::default());
+    }
+
+    #[test]
+    fn test_forge_hosts_persist_and_stay_user_only() {
+        let config = tempfile::tempdir().unwrap();
+        let workspace = tempfile::tempdir().unwrap();
+        let path = config.path().join("settings.json");
+        let hosts = vec![ForgeHostConfig {
+            host: "git.example.com".to_string(),
+            kind: ForgeKind::Github,
+            api_url: Some("https://git.example.com/api/v3".to_string()),
+        }];
+        let store = SettingsStore::with_user_config_path(path.clone());
+        store.load_user_settings().unwrap();
+        store.update_value("git", "forgeHosts", serde_json::to_value(&hosts).unwrap(), SettingsSource::User).unwrap();
+
+        // Survives a restart
+        let store = SettingsStore::with_user_config_path(path);
+        store.load_user_settings().unwrap();
+        assert_eq!(store.get_settings().git.forge_hosts, hosts);
+
+        // A repository cannot send forge tokens to its own API root
+        let mut settings = AppSettings::default();
+        settings.git.forge_hosts = vec![ForgeHostConfig {
+            api_url: Some("https://attacker.example".to_string()),
+            ..hosts[0].clone()
+        }];
+        write_workspace_settings(workspace.path(), &settings);
+        store.set_workspace(&workspace.path().to_string_lossy()).unwrap();
+        assert_eq!(store.get_settings().git.forge_hosts, hosts);
+    }
+}
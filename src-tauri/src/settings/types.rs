@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// UI Settings - theme, fonts, layout
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -39,6 +40,58 @@ pub struct AISettings {
     pub stream_responses: bool,
     pub max_tokens: u32,
     pub temperature: f32,
+    /// OpenAI-compatible servers added as providers; one with the id of a
+    /// built-in OpenAI-compatible provider (`openai`, `xai`, ...) replaces it.
+    /// Only taken from user settings; a workspace cannot change them
+    #[serde(default)]
+    pub endpoints: Vec<OpenAiEndpoint>,
+    /// What to do when a conversation outgrows the model's context window
+    #[serde(default)]
+    pub context_strategy: ContextStrategy,
+}
+
+/// How histories too long for the model's context window are shortened
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ContextStrategy {
+    /// Send everything and let the provider reject it
+    Off,
+    /// Drop the oldest exchanges
+    #[default]
+    Truncate,
+    /// Replace the oldest exchanges with a model-written summary
+    Summarize,
+}
+
+/// A user-defined OpenAI-compatible server: LM Studio, vLLM, llama.cpp,
+/// LocalAI, a corporate gateway
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenAiEndpoint {
+    /// Provider id, also the name its API key is stored under
+    pub id: String,
+    pub name: String,
+    /// API root such as `http://localhost:1234/v1`; `/chat/completions`
+    /// and `/models` are appended
+    pub base_url: String,
+    /// Extra headers sent with every request
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub auth: EndpointAuth,
+}
+
+/// How an endpoint's API key is sent
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum EndpointAuth {
+    /// No key, e.g. a local server
+    None,
+    /// `Authorization: Bearer <key>`
+    #[default]
+    Bearer,
+    /// The key as the value of a custom header, e.g. `api-key`
+    Header { name: String },
 }
 
 /// Workspace Settings (per-project)
@@ -50,6 +103,73 @@ pub struct WorkspaceSettings {
     pub file_associations: std::collections::HashMap<String, String>,
 }
 
+/// Git Settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GitSettings {
+    /// Allow fetching author avatars from GitHub and Gravatar; generated
+    /// avatars are used when disabled. Off by default since Gravatar is
+    /// sent a hash of every author's email. Only taken from user settings
+    pub avatar_network_fetch: bool,
+    pub avatar_cache_ttl_hours: u32,
+    /// How clone, fetch, pull and push authenticate. Only taken from user settings
+    pub credentials: GitCredentialConfig,
+    /// API flavour and root of forges the host name does not give away.
+    /// Only taken from user settings
+    pub forge_hosts: Vec<ForgeHostConfig>,
+}
+
+/// One way of answering an authentication request from a git remote
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialSource {
+    /// Token saved with `git_credentials_set_token` for the remote's host
+    StoredToken,
+    /// `gh auth token` for the remote's host
+    GithubCli,
+    SshAgent,
+    /// Private keys from `sshKeyPaths`; encrypted keys prompt for a passphrase
+    SshKeyFile,
+    /// `credential.helper` from the git config
+    CredentialHelper,
+    /// libgit2's default (NTLM/Negotiate) credentials
+    SystemDefault,
+}
+
+/// Git credential chain
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GitCredentialConfig {
+    /// Sources tried in order; each is offered to the remote at most once per operation
+    pub chain: Vec<CredentialSource>,
+    /// Empty means `~/.ssh/id_ed25519`, `~/.ssh/id_ecdsa` and `~/.ssh/id_rsa`
+    pub ssh_key_paths: Vec<String>,
+    /// Hard limit on credentials offered per operation
+    pub max_attempts: usize,
+}
+
+/// API flavour spoken by a forge. Gitea (and Forgejo) mirror most of the
+/// GitHub REST API; GitLab has its own.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ForgeKind {
+    Github,
+    Gitea,
+    Gitlab,
+}
+
+/// Per-host override, e.g. a GitHub Enterprise or self-hosted Gitea instance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ForgeHostConfig {
+    pub host: String,
+    pub kind: ForgeKind,
+    /// REST API root such as `https://git.example.com/api/v1`. Derived from
+    /// the host and kind when omitted.
+    #[serde(default)]
+    pub api_url: Option<String>,
+}
+
 /// All application settings combined
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -58,6 +178,8 @@ pub struct AppSettings {
     pub editor: EditorSettings,
     pub ai: AISettings,
     #[serde(default)]
+    pub git: GitSettings,
+    #[serde(default)]
     pub workspace: Option<WorkspaceSettings>,
 }
 
@@ -1,5 +1,11 @@
+use std::collections::HashSet;
+
 use super::types::*;
 
+/// Built-in providers that do not speak the OpenAI API, so an endpoint
+/// cannot take their place
+const RESERVED_PROVIDER_IDS: [&str; 5] = ["anthropic", "google", "yandex", "gigachat", "ollama"];
+
 /// Validates UI settings
 pub fn validate_ui_settings(settings: &UISettings) -> ValidationResult {
     let mut errors = Vec::new();
@@ -123,6 +129,98 @@ pub fn validate_ai_settings(settings: &AISettings) -> ValidationResult {
         });
     }
 
+    // Validate custom endpoints
+    let mut ids = HashSet::new();
+    for (i, endpoint) in settings.endpoints.iter().enumerate() {
+        let path = format!("ai.endpoints[{}]", i);
+        let valid_id = !endpoint.id.is_empty()
+            && endpoint.id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid_id {
+            errors.push(ValidationError {
+                path: format!("{}.id", path),
+                message: "Endpoint id must be lowercase letters, digits, '-' or '_'".to_string(),
+            });
+        } else if RESERVED_PROVIDER_IDS.contains(&endpoint.id.as_str()) {
+            errors.push(ValidationError {
+                path: format!("{}.id", path),
+                message: format!("'{}' is a built-in provider that is not OpenAI-compatible", endpoint.id),
+            });
+        } else if !ids.insert(endpoint.id.as_str()) {
+            errors.push(ValidationError {
+                path: format!("{}.id", path),
+                message: format!("Duplicate endpoint id '{}'", endpoint.id),
+            });
+        }
+
+        if !endpoint.base_url.starts_with("http://") && !endpoint.base_url.starts_with("https://") {
+            errors.push(ValidationError {
+                path: format!("{}.baseUrl", path),
+                message: "Base URL must start with http:// or https://".to_string(),
+            });
+        }
+
+        let auth_header = match &endpoint.auth {
+            EndpointAuth::Header { name } => Some(name),
+            _ => None,
+        };
+        if endpoint.headers.keys().chain(auth_header).any(|name| name.trim().is_empty()) {
+            errors.push(ValidationError {
+                path: format!("{}.headers", path),
+                message: "Header names cannot be empty".to_string(),
+            });
+        }
+    }
+
+    ValidationResult {
+        valid: errors.is_empty(),
+        errors,
+    }
+}
+
+/// Validates git settings
+pub fn validate_git_settings(settings: &GitSettings) -> ValidationResult {
+    let mut errors = Vec::new();
+
+    // Validate avatar cache TTL (one hour to one year)
+    if settings.avatar_cache_ttl_hours < 1 || settings.avatar_cache_ttl_hours > 8760 {
+        errors.push(ValidationError {
+            path: "git.avatarCacheTtlHours".to_string(),
+            message: "Avatar cache TTL must be between 1 and 8760 hours".to_string(),
+        });
+    }
+
+    // Validate credential chain
+    if settings.credentials.chain.is_empty() {
+        errors.push(ValidationError {
+            path: "git.credentials.chain".to_string(),
+            message: "Credential chain cannot be empty".to_string(),
+        });
+    }
+    if settings.credentials.max_attempts < 1 || settings.credentials.max_attempts > 20 {
+        errors.push(ValidationError {
+            path: "git.credentials.maxAttempts".to_string(),
+            message: "Max attempts must be between 1 and 20".to_string(),
+        });
+    }
+
+    // Validate forge hosts
+    for (i, forge) in settings.forge_hosts.iter().enumerate() {
+        if forge.host.trim().is_empty() {
+            errors.push(ValidationError {
+                path: format!("git.forgeHosts[{}].host", i),
+                message: "Host cannot be empty".to_string(),
+            });
+        }
+        if let Some(url) = forge.api_url.as_deref().filter(|u| !u.trim().is_empty()) {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                errors.push(ValidationError {
+                    path: format!("git.forgeHosts[{}].apiUrl", i),
+                    message: "API URL must start with http:// or https://".to_string(),
+                });
+            }
+        }
+    }
+
     ValidationResult {
         valid: errors.is_empty(),
         errors,
@@ -142,6 +240,9 @@ pub fn validate_settings(settings: &AppSettings) -> ValidationResult {
     let ai_result = validate_ai_settings(&settings.ai);
     all_errors.extend(ai_result.errors);
 
+    let git_result = validate_git_settings(&settings.git);
+    all_errors.extend(git_result.errors);
+
     ValidationResult {
         valid: all_errors.is_empty(),
         errors: all_errors,
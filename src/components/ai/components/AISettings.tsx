@@ -1,6 +1,11 @@
 import { useState } from 'react';
 import { ArrowLeft, X, Save } from 'lucide-react';
 import { useAIStore } from '../../../store/aiStore';
+import { EndpointSettings } from './EndpointSettings';
+import { UsageSettings } from './UsageSettings';
+import { KeyStorageSettings } from './KeyStorageSettings';
+import { useSettings } from '../../../hooks/useSettings';
+import { ContextStrategy } from '../../../lib/settings-api';
 
 interface AISettingsProps {
   onBack: () => void;
@@ -10,6 +15,7 @@ interface AISettingsProps {
 
 export const AISettings: React.FC<AISettingsProps> = ({ onBack, onClose, styles }) => {
   const { apiKeys, setApiKeys, refreshOllamaModels, ollamaLocalModels, availableModels } = useAIStore();
+  const { settings, updateAI } = useSettings();
   const [showKeys, setShowKeys] = useState(false);
   const [isRefreshing, setIsRefreshing] = useState(false);
 
@@ -34,8 +40,8 @@ export const AISettings: React.FC<AISettingsProps> = ({ onBack, onClose, styles
     { id: 'google' as const, name: 'Google AI', description: 'Gemini models' },
     { id: 'xai' as const, name: 'xAI', description: 'Grok models' },
     { id: 'zhipu' as const, name: 'Zhipu AI', description: 'GLM models' },
-    { id: 'yandex' as const, name: 'Yandex', description: 'YandexGPT models' },
-    { id: 'gigachat' as const, name: 'GigaChat', description: 'GigaChat models' },
+    { id: 'yandex' as const, name: 'Yandex', description: 'YandexGPT models. Key as <folder id>:<API key or IAM token>' },
+    { id: 'gigachat' as const, name: 'GigaChat', description: 'GigaChat models. Authorization key, optionally prefixed with <scope>:' },
     { id: 'agentrouter' as const, name: 'AgentRouter', description: 'Unified API for multiple models' }
   ];
 
@@ -62,8 +68,9 @@ export const AISettings: React.FC<AISettingsProps> = ({ onBack, onClose, styles
           <div className={styles.settingsSection}>
             <h3>API Keys</h3>
             <p className={styles.settingsDescription}>
-              Configure API keys for different AI providers. Keys are stored locally.
+              Configure API keys for different AI providers. Keys are stored in the system keyring or an encrypted file.
             </p>
+            <KeyStorageSettings styles={styles} />
             
             {providers.map(provider => (
               <div key={provider.id} className={styles.apiKeyItem}>
@@ -97,6 +104,8 @@ export const AISettings: React.FC<AISettingsProps> = ({ onBack, onClose, styles
             </div>
           </div>
           
+          <EndpointSettings styles={styles} />
+
           <div className={styles.settingsSection}>
             <h3>Ollama Models</h3>
             <p className={styles.settingsDescription}>
@@ -154,7 +163,21 @@ export const AISettings: React.FC<AISettingsProps> = ({ onBack, onClose, styles
               <label>Max Tokens</label>
               <input type="number" defaultValue="2048" className={styles.input} />
             </div>
+            <div className={styles.settingItem}>
+              <label>When a chat outgrows the context window</label>
+              <select
+                className={styles.select}
+                value={settings.ai.contextStrategy ?? 'truncate'}
+                onChange={e => updateAI('contextStrategy', e.target.value as ContextStrategy)}
+              >
+                <option value="truncate">Drop the oldest messages</option>
+                <option value="summarize">Summarize the oldest messages</option>
+                <option value="off">Send everything</option>
+              </select>
+            </div>
           </div>
+
+          <UsageSettings styles={styles} />
         </div>
       </div>
     </div>
@@ -0,0 +1,178 @@
+import { useState } from 'react';
+import { Plus, Trash2 } from 'lucide-react';
+import { useSettings } from '../../../hooks/useSettings';
+import { tauriApi } from '../../../lib/tauri-api';
+import { EndpointAuth, OpenAiEndpoint } from '../../../lib/settings-api';
+
+interface EndpointSettingsProps {
+  styles: any;
+}
+
+const emptyDraft = { name: '', baseUrl: '', authType: 'bearer' as EndpointAuth['type'], authHeader: '', headers: '' };
+
+/** Provider id derived from the display name, e.g. "LM Studio" -> "lm-studio" */
+const toId = (name: string) => name.toLowerCase().trim().replace(/[^a-z0-9_]+/g, '-').replace(/^-+|-+$/g, '');
+
+/** `Name: value` lines to a header map */
+const parseHeaders = (text: string) =>
+  Object.fromEntries(
+    text
+      .split('\n')
+      .map(line => line.split(/:(.*)/s).map(part => part.trim()))
+      .filter(([name, value]) => name && value !== undefined)
+  );
+
+export const EndpointSettings: React.FC<EndpointSettingsProps> = ({ styles }) => {
+  const { settings, updateAI, error } = useSettings();
+  const endpoints = settings.ai.endpoints ?? [];
+  const [draft, setDraft] = useState(emptyDraft);
+  const [models, setModels] = useState<Record<string, string[] | string>>({});
+  const [keys, setKeys] = useState<Record<string, string>>({});
+
+  const saveEndpoints = (next: OpenAiEndpoint[]) => updateAI('endpoints', next);
+
+  const handleAdd = async () => {
+    const auth: EndpointAuth =
+      draft.authType === 'header' ? { type: 'header', name: draft.authHeader.trim() } : { type: draft.authType };
+    const endpoint: OpenAiEndpoint = {
+      id: toId(draft.name),
+      name: draft.name.trim(),
+      baseUrl: draft.baseUrl.trim(),
+      headers: parseHeaders(draft.headers),
+      auth,
+    };
+    await saveEndpoints([...endpoints.filter(e => e.id !== endpoint.id), endpoint]);
+    setDraft(emptyDraft);
+  };
+
+  const handleSaveKey = async (id: string) => {
+    try {
+      await tauriApi.setApiKey(id, keys[id] ?? '');
+      setKeys(prev => ({ ...prev, [id]: '' }));
+    } catch (e) {
+      setModels(prev => ({ ...prev, [id]: String(e) }));
+    }
+  };
+
+  const handleDiscover = async (id: string) => {
+    try {
+      const found = await tauriApi.aiListModels(id);
+      setModels(prev => ({ ...prev, [id]: found }));
+    } catch (e) {
+      setModels(prev => ({ ...prev, [id]: String(e) }));
+    }
+  };
+
+  return (
+    <div className={styles.settingsSection}>
+      <h3>Custom Endpoints</h3>
+      <p className={styles.settingsDescription}>
+        OpenAI-compatible servers such as LM Studio, vLLM, llama.cpp or a company gateway. An endpoint named
+        "openai" or "xai" replaces the built-in one.
+      </p>
+
+      {endpoints.map(endpoint => {
+        const found = models[endpoint.id];
+        return (
+          <div key={endpoint.id} className={styles.apiKeyItem}>
+            <div className={styles.providerInfo}>
+              <div className={styles.providerName}>{endpoint.name} ({endpoint.id})</div>
+              <div className={styles.providerDescription}>
+                {endpoint.baseUrl} · {endpoint.auth.type === 'header' ? `key in ${endpoint.auth.name}` : endpoint.auth.type}
+              </div>
+            </div>
+            {endpoint.auth.type !== 'none' && (
+              <div className={styles.apiKeyInput}>
+                <input
+                  type="password"
+                  placeholder={`Enter ${endpoint.name} API key`}
+                  value={keys[endpoint.id] ?? ''}
+                  onChange={e => setKeys(prev => ({ ...prev, [endpoint.id]: e.target.value }))}
+                  onBlur={() => keys[endpoint.id] && handleSaveKey(endpoint.id)}
+                />
+              </div>
+            )}
+            <div className={styles.settingsActions}>
+              <button className={styles.refreshBtn} onClick={() => handleDiscover(endpoint.id)}>
+                Discover Models
+              </button>
+              <button
+                className={styles.toggleVisibilityBtn}
+                onClick={() => saveEndpoints(endpoints.filter(e => e.id !== endpoint.id))}
+                title="Remove endpoint"
+              >
+                <Trash2 size={14} />
+              </button>
+            </div>
+            {typeof found === 'string' && <p className={styles.noModels}>{found}</p>}
+            {Array.isArray(found) && (
+              <div className={styles.modelsList}>
+                {found.length === 0 && <p className={styles.noModels}>The server lists no models.</p>}
+                {found.map(model => (
+                  <div key={model} className={styles.modelItem}>
+                    <span className={styles.modelName}>{model}</span>
+                  </div>
+                ))}
+              </div>
+            )}
+          </div>
+        );
+      })}
+
+      <div className={styles.settingItem}>
+        <label>Name</label>
+        <input
+          className={styles.input}
+          placeholder="LM Studio"
+          value={draft.name}
+          onChange={e => setDraft({ ...draft, name: e.target.value })}
+        />
+      </div>
+      <div className={styles.settingItem}>
+        <label>Base URL</label>
+        <input
+          className={styles.input}
+          placeholder="http://localhost:1234/v1"
+          value={draft.baseUrl}
+          onChange={e => setDraft({ ...draft, baseUrl: e.target.value })}
+        />
+      </div>
+      <div className={styles.settingItem}>
+        <label>Authentication</label>
+        <select
+          className={styles.select}
+          value={draft.authType}
+          onChange={e => setDraft({ ...draft, authType: e.target.value as EndpointAuth['type'] })}
+        >
+          <option value="bearer">Bearer token</option>
+          <option value="header">Custom header</option>
+          <option value="none">None</option>
+        </select>
+        {draft.authType === 'header' && (
+          <input
+            className={styles.input}
+            placeholder="api-key"
+            value={draft.authHeader}
+            onChange={e => setDraft({ ...draft, authHeader: e.target.value })}
+          />
+        )}
+      </div>
+      <div className={styles.settingItem}>
+        <label>Extra headers (one "Name: value" per line)</label>
+        <textarea
+          className={styles.input}
+          rows={3}
+          value={draft.headers}
+          onChange={e => setDraft({ ...draft, headers: e.target.value })}
+        />
+      </div>
+      {error && <p className={styles.noModels}>{error}</p>}
+      <div className={styles.settingsActions}>
+        <button className={styles.saveBtn} onClick={handleAdd} disabled={!toId(draft.name) || !draft.baseUrl.trim()}>
+          <Plus size={14} />
+          Add Endpoint
+        </button>
+      </div>
+    </div>
+  );
+};
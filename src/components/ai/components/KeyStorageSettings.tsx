@@ -0,0 +1,90 @@
+import { useEffect, useState } from 'react';
+import { useAIStore } from '../../../store/aiStore';
+
+interface KeyStorageSettingsProps {
+  styles: any;
+}
+
+/** Unlock prompt shown while the backend cannot read or store API keys */
+export const KeyStorageSettings: React.FC<KeyStorageSettingsProps> = ({ styles }) => {
+  const { apiKeyStorage, unsavedApiKeys, refreshApiKeyStatus, unlockApiKeys } = useAIStore();
+  const [passphrase, setPassphrase] = useState('');
+  const [confirm, setConfirm] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const [isUnlocking, setIsUnlocking] = useState(false);
+
+  useEffect(() => {
+    refreshApiKeyStatus();
+  }, [refreshApiKeyStatus]);
+
+  if (!apiKeyStorage || !apiKeyStorage.locked) {
+    return null;
+  }
+
+  // A keyring that failed to load only needs a retry, no passphrase
+  const needsPassphrase = apiKeyStorage.backend === 'file';
+  const creating = needsPassphrase && !apiKeyStorage.initialized;
+  const unsavedCount = Object.keys(unsavedApiKeys).length;
+
+  const handleUnlock = async () => {
+    if (creating && passphrase !== confirm) {
+      setError('Passphrases do not match');
+      return;
+    }
+    setIsUnlocking(true);
+    try {
+      await unlockApiKeys(passphrase);
+      setPassphrase('');
+      setConfirm('');
+      setError(null);
+    } catch (e) {
+      setError(String(e));
+    } finally {
+      setIsUnlocking(false);
+    }
+  };
+
+  return (
+    <div className={styles.settingItem}>
+      <p className={styles.settingsDescription}>
+        {!needsPassphrase
+          ? 'The system keyring could not be read, so API keys cannot be saved until it loads.'
+          : creating
+            ? 'No system keyring is available. Choose a master passphrase to keep API keys in an encrypted file.'
+            : 'API keys are in an encrypted file. Enter the master passphrase to use and change them.'}
+        {unsavedCount > 0 && ` ${unsavedCount} key(s) will be saved once storage is unlocked.`}
+      </p>
+      {apiKeyStorage.error && <p className={styles.noModels}>{apiKeyStorage.error}</p>}
+      {needsPassphrase && (
+        <input
+          type="password"
+          placeholder={creating ? 'New master passphrase' : 'Master passphrase'}
+          value={passphrase}
+          onChange={(e) => setPassphrase(e.target.value)}
+          onKeyDown={(e) => e.key === 'Enter' && !creating && handleUnlock()}
+          className={styles.input}
+        />
+      )}
+      {creating && (
+        <input
+          type="password"
+          placeholder="Repeat passphrase"
+          value={confirm}
+          onChange={(e) => setConfirm(e.target.value)}
+          onKeyDown={(e) => e.key === 'Enter' && handleUnlock()}
+          className={styles.input}
+        />
+      )}
+      {error && <p className={styles.noModels}>{error}</p>}
+      <div className={styles.settingsActions}>
+        <button
+          className={styles.saveBtn}
+          onClick={handleUnlock}
+          disabled={isUnlocking || (needsPassphrase && !passphrase)}
+        >
+          {isUnlocking ? 'Unlocking...' : !needsPassphrase ? 'Retry' : creating ? 'Create' : 'Unlock'}
+        </button>
+      </div>
+    </div>
+  );
+};
@@ -0,0 +1,78 @@
+import { useEffect, useState } from 'react';
+import { tauriApi, AiUsageSummary, AiUsageTotal } from '../../../lib/tauri-api';
+
+interface UsageSettingsProps {
+  styles: any;
+}
+
+/** Start of the current month in Unix millis */
+const monthStart = () => {
+  const now = new Date();
+  return new Date(now.getFullYear(), now.getMonth(), 1).getTime();
+};
+
+const formatTotal = (total: AiUsageTotal) => {
+  const tokens = `${total.inputTokens.toLocaleString()} in / ${total.outputTokens.toLocaleString()} out`;
+  const cost = `$${total.cost.toFixed(2)}${total.unpricedRequests > 0 ? ` + ${total.unpricedRequests} unpriced` : ''}`;
+  return `${total.requests} requests · ${tokens} · ${cost}`;
+};
+
+export const UsageSettings: React.FC<UsageSettingsProps> = ({ styles }) => {
+  const [summary, setSummary] = useState<AiUsageSummary | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const load = async () => {
+    try {
+      setSummary(await tauriApi.aiUsageSummary(monthStart()));
+      setError(null);
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  useEffect(() => {
+    load();
+  }, []);
+
+  const handleClear = async () => {
+    try {
+      await tauriApi.aiUsageClear();
+      await load();
+    } catch (e) {
+      setError(String(e));
+    }
+  };
+
+  return (
+    <div className={styles.settingsSection}>
+      <h3>Usage This Month</h3>
+      <p className={styles.settingsDescription}>
+        Costs use list prices and exclude models without a known price.
+      </p>
+      {error && <p className={styles.noModels}>{error}</p>}
+      {summary && summary.total.requests === 0 && <p className={styles.noModels}>No requests yet.</p>}
+      {summary && summary.total.requests > 0 && (
+        <div className={styles.modelsList}>
+          {Object.entries(summary.providers).map(([provider, total]) => (
+            <div key={provider} className={styles.modelItem}>
+              <span className={styles.modelName}>{provider}</span>
+              <span className={styles.modelSize}>{formatTotal(total)}</span>
+            </div>
+          ))}
+          <div className={styles.modelItem}>
+            <span className={styles.modelName}>Total</span>
+            <span className={styles.modelSize}>{formatTotal(summary.total)}</span>
+          </div>
+        </div>
+      )}
+      <div className={styles.settingsActions}>
+        <button className={styles.refreshBtn} onClick={load}>
+          Refresh
+        </button>
+        <button className={styles.toggleVisibilityBtn} onClick={handleClear}>
+          Clear History
+        </button>
+      </div>
+    </div>
+  );
+};
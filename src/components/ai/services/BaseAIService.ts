@@ -1,25 +1,69 @@
 import { AIService, ChatMessage } from './types';
 import { listen } from '@tauri-apps/api/event';
+import { tauriApi, AiChatRequest, AiChatResponse, AiStreamEvent, AiToolCall } from '../../../lib/tauri-api';
 
 /**
  * Configuration for provider-specific stream handling
  */
 export interface StreamConfig {
-  /** Event name to listen for (e.g., 'openai-stream', 'anthropic-stream') */
-  eventName: string;
-  /** Function to call the provider's streaming API */
-  streamFn: (modelId: string, messages: any[]) => Promise<string>;
-  /** Function to call the provider's completion API for title generation */
-  completeFn: (modelId: string, messages: any[]) => Promise<string>;
-  /** Optional message transformer for providers with different formats (e.g., Google) */
-  transformMessages?: (messages: ChatMessage[]) => any[];
+  /** Provider id in the backend registry (e.g., 'openai', 'anthropic') */
+  provider: string;
   /** Provider name for logging */
   providerName: string;
 }
 
+/**
+ * Streams a chat through `ai_chat_stream`, passing text deltas to `onDelta`.
+ * Aborting `signal` cancels the request in the backend.
+ */
+export async function streamAiChat(
+  provider: string,
+  request: AiChatRequest,
+  onDelta: (content: string) => void,
+  signal?: AbortSignal
+): Promise<AiChatResponse> {
+  const requestId = crypto.randomUUID();
+  const cancel = () => {
+    tauriApi.aiCancel(requestId).catch(() => {
+      // Already finished
+    });
+  };
+
+  const unlisten = await listen<AiStreamEvent>('ai-stream', (event) => {
+    if (event.payload.requestId !== requestId) return;
+    if (signal?.aborted) {
+      // The abort may have fired before the backend registered the request
+      cancel();
+      return;
+    }
+    if (event.payload.type === 'delta') {
+      onDelta(event.payload.content);
+    }
+  });
+  signal?.addEventListener('abort', cancel);
+
+  try {
+    return await tauriApi.aiChatStream(provider, request, requestId);
+  } finally {
+    signal?.removeEventListener('abort', cancel);
+    unlisten();
+  }
+}
+
+/**
+ * Lists the tool calls a model asked for without running them. Chat modes
+ * have no approval step or workspace confinement; file changes go through
+ * the agent (`ai_agent_run`), which has both.
+ */
+export function describeToolCalls(toolCalls: AiToolCall[]): string {
+  const lines = toolCalls.map((call) => `- ${call.name} ${JSON.stringify(call.arguments)}`);
+  return `\n\n[Tool calls requested but not run:\n${lines.join('\n')}]\n`;
+}
+
 /**
  * Base class for AI services that handles common streaming logic.
- * Eliminates code duplication across OpenAI, Anthropic, Google, xAI, and Ollama services.
+ * Every provider goes through the backend's `ai_chat`/`ai_chat_stream` commands,
+ * so subclasses only name the provider.
  */
 export abstract class BaseAIService implements AIService {
   protected abstract getStreamConfig(): StreamConfig;
@@ -38,7 +82,6 @@ export abstract class BaseAIService implements AIService {
     signal?: AbortSignal
   ): Promise<void> {
     const config = this.getStreamConfig();
-    let unlisten: (() => void) | undefined;
 
     try {
       // Validate model if needed
@@ -51,45 +94,16 @@ export abstract class BaseAIService implements AIService {
         return;
       }
 
-      // Setup listener for streaming events
-      unlisten = await listen<string>(config.eventName, (event) => {
-        if (signal?.aborted) {
-          if (unlisten) unlisten();
-          return;
-        }
-        onStreamChunk(event.payload);
-      });
-
-      // Setup abort handler
-      const abortHandler = () => {
-        if (unlisten) {
-          unlisten();
-          unlisten = undefined;
-        }
-      };
-      signal?.addEventListener('abort', abortHandler);
-
       // Log model usage
       console.log(`${config.providerName} using model: ${modelId}`);
 
-      // Transform messages if needed (e.g., for Google's format)
-      const transformedMessages = config.transformMessages 
-        ? config.transformMessages(messages)
-        : messages;
-
-      // Call provider's streaming API
-      await config.streamFn(modelId, transformedMessages);
-
-      // Cleanup abort handler
-      signal?.removeEventListener('abort', abortHandler);
+      await streamAiChat(config.provider, { model: modelId, messages }, onStreamChunk, signal);
     } catch (error) {
       if (signal?.aborted) {
         return; // Silently return if aborted
       }
       console.error(`${config.providerName} API error:`, error);
       onStreamChunk(`[Error: ${error instanceof Error ? error.message : String(error)}]`);
-    } finally {
-      if (unlisten) unlisten();
     }
   }
 
@@ -123,13 +137,8 @@ Title:`;
         { role: 'user', content: titlePrompt }
       ];
 
-      // Transform messages if needed
-      const transformedMessages = config.transformMessages 
-        ? config.transformMessages(messages)
-        : messages;
-
-      const response = await config.completeFn(modelId, transformedMessages);
-      return response.trim().replace(/^["']|["']$/g, '').slice(0, 50);
+      const response = await tauriApi.aiChat(config.provider, { model: modelId, messages });
+      return response.content.trim().replace(/^["']|["']$/g, '').slice(0, 50);
     } catch (error) {
       console.error('Error generating title:', error);
       // Fallback to a more meaningful title based on user message
@@ -1,12 +1,9 @@
 import { BaseAIService, StreamConfig } from './BaseAIService';
-import { tauriApi } from '../../../lib/tauri-api';
 
 export class OpenAIService extends BaseAIService {
   protected getStreamConfig(): StreamConfig {
     return {
-      eventName: 'openai-stream',
-      streamFn: (modelId, messages) => tauriApi.openaiChatStream(modelId, messages),
-      completeFn: (modelId, messages) => tauriApi.openaiChatComplete(modelId, messages),
+      provider: 'openai',
       providerName: 'OpenAI',
     };
   }
@@ -32,6 +32,59 @@ export interface AISettings {
     streamResponses: boolean;
     maxTokens: number;
     temperature: number;
+    endpoints: OpenAiEndpoint[];
+    contextStrategy: ContextStrategy;
+}
+
+/** What to do with old messages once a chat outgrows the model's context window */
+export type ContextStrategy = 'off' | 'truncate' | 'summarize';
+
+export type EndpointAuth =
+    | { type: 'none' }
+    | { type: 'bearer' }
+    | { type: 'header'; name: string };
+
+/** A user-defined OpenAI-compatible server, registered as a provider under `id` */
+export interface OpenAiEndpoint {
+    id: string;
+    name: string;
+    /** API root such as `http://localhost:1234/v1` */
+    baseUrl: string;
+    headers: Record<string, string>;
+    auth: EndpointAuth;
+}
+
+export interface GitSettings {
+    avatarNetworkFetch: boolean;
+    avatarCacheTtlHours: number;
+    credentials: GitCredentialConfig;
+    forgeHosts: ForgeHostConfig[];
+}
+
+export type CredentialSource =
+    | 'stored_token'
+    | 'github_cli'
+    | 'ssh_agent'
+    | 'ssh_key_file'
+    | 'credential_helper'
+    | 'system_default';
+
+/** How clone, fetch, pull and push authenticate; sources are tried in order */
+export interface GitCredentialConfig {
+    chain: CredentialSource[];
+    /** Empty means ~/.ssh/id_ed25519, ~/.ssh/id_ecdsa and ~/.ssh/id_rsa */
+    sshKeyPaths: string[];
+    maxAttempts: number;
+}
+
+export type ForgeKind = 'github' | 'gitea' | 'gitlab';
+
+/** API flavour and root of a forge host, e.g. GitHub Enterprise */
+export interface ForgeHostConfig {
+    host: string;
+    kind: ForgeKind;
+    /** Derived from the host and kind when omitted */
+    apiUrl?: string;
 }
 
 export interface WorkspaceSettings {
@@ -44,6 +97,7 @@ export interface AppSettings {
     ui: UISettings;
     editor: EditorSettings;
     ai: AISettings;
+    git: GitSettings;
     workspace?: WorkspaceSettings;
 }
 
@@ -201,5 +255,17 @@ export const defaultSettings: AppSettings = {
         streamResponses: true,
         maxTokens: 4096,
         temperature: 0.7,
+        endpoints: [],
+        contextStrategy: 'truncate',
+    },
+    git: {
+        avatarNetworkFetch: false,
+        avatarCacheTtlHours: 168,
+        credentials: {
+            chain: ['stored_token', 'github_cli', 'ssh_agent', 'ssh_key_file', 'credential_helper', 'system_default'],
+            sshKeyPaths: [],
+            maxAttempts: 6,
+        },
+        forgeHosts: [],
     },
 };
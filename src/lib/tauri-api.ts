@@ -128,6 +128,71 @@ export type GitPushResult = {
     pushed_refs: string[];
 };
 
+export type GitTag = {
+    name: string;
+    annotated: boolean;
+    target: string;
+    target_summary: string | null;
+    message: string | null;
+    tagger_name: string | null;
+    tagger_email: string | null;
+    timestamp: number;
+};
+
+export type GitStatsQuery = {
+    rev?: string;
+    since?: number;
+    until?: number;
+    interval?: 'day' | 'week' | 'month';
+    max_commits?: number;
+    hotspot_limit?: number;
+    ownership_depth?: number;
+    use_mailmap?: boolean;
+};
+
+export type GitStatsBucket = {
+    start: number;
+    commits: number;
+    insertions: number;
+    deletions: number;
+};
+
+export type GitRepoStats = {
+    total_commits: number;
+    truncated: boolean;
+    authors: {
+        name: string;
+        email: string;
+        commits: number;
+        insertions: number;
+        deletions: number;
+        first_commit: number;
+        last_commit: number;
+        timeline: GitStatsBucket[];
+    }[];
+    /** [weekday (0 = Monday)][hour] commit counts */
+    heatmap: number[][];
+    ownership: {
+        path: string;
+        owners: { name: string; email: string; commits: number; last_touched: number }[];
+    }[];
+    hotspots: {
+        path: string;
+        commits: number;
+        insertions: number;
+        deletions: number;
+        authors: number;
+        last_changed: number;
+    }[];
+};
+
+/** Payload of the `git-avatar-updated` event: `previous` is replaced by `url`. */
+export type GitAvatarUpdate = {
+    key: string;
+    previous: string;
+    url: string;
+};
+
 export type GitCommit = {
     hash: string;
     short_hash: string;
@@ -138,6 +203,8 @@ export type GitCommit = {
     date: string;
     timestamp: number;
     branches: string[];
+    remote_branches: string[];
+    tags: string[];
     is_head: boolean;
     files_changed: number;
     insertions: number;
@@ -189,73 +256,253 @@ export type RunningScript = {
     start_time: string;
 };
 
-// Google API types
-export type GoogleMessage = {
-    role: string;
-    parts: { text: string }[];
+// AI chat types
+export type AiRole = 'system' | 'user' | 'assistant' | 'tool';
+
+export type AiToolDefinition = {
+    name: string;
+    description: string;
+    /** JSON Schema of the arguments */
+    parameters: any;
 };
 
-// AgentRouter types
-export type AgentRouterMessage = {
-    role: string;
-    content: string;
-    name?: string;
+export type AiToolChoice =
+    | { type: 'auto' }
+    | { type: 'none' }
+    | { type: 'required' }
+    | { type: 'tool'; name: string };
+
+export type AiToolCall = {
+    id: string;
+    name: string;
+    arguments: any;
 };
 
-export type AgentRouterChatRequest = {
+export type AiContentPart =
+    | { type: 'text'; text: string }
+    | { type: 'image'; media_type: string; data: string }
+    | ({ type: 'tool_call' } & AiToolCall)
+    | { type: 'tool_result'; tool_call_id: string; name: string; content: string; is_error?: boolean };
+
+export type AiChatMessage = {
+    role: AiRole;
+    content: string | AiContentPart[];
+};
+
+export type AiChatRequest = {
     model: string;
-    messages: AgentRouterMessage[];
-    stream?: boolean;
-    max_tokens?: number;
+    messages: AiChatMessage[];
+    maxTokens?: number;
     temperature?: number;
-    top_p?: number;
-    stop?: string | string[];
-    tools?: AgentRouterTool[];
-    tool_choice?: AgentRouterToolChoice;
-    response_format?: AgentRouterResponseFormat;
+    topP?: number;
+    tools?: AiToolDefinition[];
+    toolChoice?: AiToolChoice;
 };
 
-export type AgentRouterTool = {
-    type: string;
-    function: AgentRouterFunction;
+export type AiUsage = {
+    inputTokens: number;
+    outputTokens: number;
 };
 
-export type AgentRouterFunction = {
+export type AiChatResponse = {
+    content: string;
+    model: string;
+    finishReason?: string;
+    usage?: AiUsage;
+    toolCalls: AiToolCall[];
+};
+
+export type AiProviderInfo = {
+    id: string;
     name: string;
-    description?: string;
-    parameters: any;
+    requiresApiKey: boolean;
 };
 
-export type AgentRouterToolChoice = string | {
-    type: string;
-    function: { name: string };
+export type AiStreamPayload =
+    | { type: 'delta'; content: string }
+    | { type: 'usage'; usage: AiUsage }
+    | { type: 'done'; finishReason?: string; usage?: AiUsage }
+    | { type: 'error'; message: string }
+    | { type: 'toolCallStart'; index: number; id: string; name: string }
+    | { type: 'toolCallDelta'; index: number; arguments: string }
+    | { type: 'toolCall'; toolCall: AiToolCall }
+    | { type: 'contextFitted'; fit: AiContextFit };
+
+/** Older messages dropped, and possibly summarized, to fit the context window */
+export type AiContextFit = {
+    droppedMessages: number;
+    summarized: boolean;
+    summaryUsage?: AiUsage;
 };
 
-export type AgentRouterResponseFormat = {
-    type: string;
+export type AiModelSpec = {
+    provider: string;
+    /** Model ids starting with this belong to the family */
+    modelPrefix: string;
+    contextWindow: number;
+    maxOutputTokens: number;
+    /** USD per million tokens */
+    pricing?: { input: number; output: number };
 };
 
-export type AgentRouterChatResponse = {
-    id: string;
-    object: string;
-    created: number;
+export type AiTokenEstimate = {
+    inputTokens: number;
+    contextWindow?: number;
+    availableOutputTokens?: number;
+    inputCost?: number;
+};
+
+export type AiUsageTotal = {
+    requests: number;
+    inputTokens: number;
+    outputTokens: number;
+    cost: number;
+    unpricedRequests: number;
+};
+
+export type AiUsageSummary = {
+    days: { date: string; provider: string; total: AiUsageTotal }[];
+    providers: Record<string, AiUsageTotal>;
+    total: AiUsageTotal;
+};
+
+export type AiStreamEvent = AiStreamPayload & {
+    requestId: string;
+    provider: string;
+};
+
+// AI agent types
+export type AiAgentRequest = {
+    /** Root folder the agent's tools work in */
+    workspace: string;
     model: string;
-    choices: AgentRouterChoice[];
-    usage: AgentRouterUsage;
+    messages: AiChatMessage[];
+    /** Defaults to 10, at most 50 */
+    maxSteps?: number;
+    maxTokens?: number;
+    temperature?: number;
 };
 
-export type AgentRouterChoice = {
+export type AiAgentToolRun = {
+    call: AiToolCall;
+    approval: 'notRequired' | 'approved' | 'denied';
+    output: string;
+    isError: boolean;
+    durationMs: number;
+};
+
+export type AiAgentStep = {
     index: number;
-    message: AgentRouterMessage;
-    finish_reason?: string;
+    text: string;
+    toolRuns: AiAgentToolRun[];
+    finishReason?: string;
+    usage?: AiUsage;
+    startedAt: number;
+    finishedAt: number;
+};
+
+export type AiAgentOutcome =
+    | { status: 'completed' }
+    | { status: 'stepLimit' }
+    | { status: 'cancelled' }
+    | { status: 'failed'; message: string };
+
+export type AiAgentTranscript = {
+    runId: string;
+    provider: string;
+    model: string;
+    workspace: string;
+    steps: AiAgentStep[];
+    /** The whole conversation, to continue it with another run */
+    messages: AiChatMessage[];
+    response: string;
+    usage: AiUsage;
+    outcome: AiAgentOutcome;
+    startedAt: number;
+    finishedAt: number;
+};
+
+/** Payload of `ai-agent-approval`; answer with `aiAgentApprove` */
+export type AiAgentApprovalRequest = {
+    runId: string;
+    callId: string;
+    tool: string;
+    arguments: any;
+};
+
+/** Payload of `ai-agent-step` */
+export type AiAgentStepEvent = {
+    runId: string;
+    step: AiAgentStep;
+};
+
+// AI conversation history types
+export type AiStoredMessage = {
+    id: string;
+    role: AiRole;
+    content: string | AiContentPart[];
+    model?: string;
+    provider?: string;
+    usage?: AiUsage;
+    createdAt: number;
+};
+
+export type AiNewMessage = Omit<AiStoredMessage, 'id' | 'createdAt'>;
+
+export type AiForkOrigin = {
+    conversationId: string;
+    messageId: string;
 };
 
-export type AgentRouterUsage = {
-    prompt_tokens: number;
-    completion_tokens: number;
-    total_tokens: number;
+export type AiConversation = {
+    id: string;
+    workspace: string;
+    title: string;
+    createdAt: number;
+    updatedAt: number;
+    forkedFrom?: AiForkOrigin;
+    messages: AiStoredMessage[];
+};
+
+export type AiConversationSummary = {
+    id: string;
+    title: string;
+    createdAt: number;
+    updatedAt: number;
+    messageCount: number;
+    forkedFrom?: AiForkOrigin;
+    usage: AiUsage;
 };
 
+export type AiConversationSearchHit = {
+    conversationId: string;
+    title: string;
+    /** Null when only the title matched */
+    messageId?: string;
+    snippet: string;
+};
+
+// API key storage types
+export type ApiKeyInfo = {
+    provider: string;
+    name: string;
+    /** A global key is stored */
+    global: boolean;
+    /** The workspace overrides the global key */
+    workspace: boolean;
+};
+
+export type ApiKeyStorageStatus = {
+    backend: 'keyring' | 'file' | 'memory';
+    /** The encrypted file needs the master passphrase, or the keyring failed to load and needs a retry, before keys can be read or set */
+    locked: boolean;
+    /** False until a master passphrase has been chosen */
+    initialized: boolean;
+    /** Why stored keys could not be loaded; nothing is saved until they are */
+    error?: string;
+};
+
+// AgentRouter types
 export type AgentRouterModel = {
     id: string;
     object: string;
@@ -300,6 +547,11 @@ export const tauriApi = {
     gitDiscardChanges: (repoPath: string, filePath: string) => invoke<void>('git_discard_changes', { repoPath, filePath }),
     gitDiff: (repoPath: string, filePath: string, isStaged: boolean) => invoke<FileDiff>('git_diff', { repoPath, filePath, isStaged }),
     gitContributors: (repoPath: string) => invoke<GitContributor[]>('git_contributors', { repoPath }),
+    gitListTags: (repoPath: string) => invoke<GitTag[]>('git_list_tags', { repoPath }),
+    gitCreateTag: (repoPath: string, request: { name: string; target?: string; message?: string; force?: boolean }) => invoke<GitTag>('git_create_tag', { repoPath, request }),
+    gitDeleteTag: (repoPath: string, name: string, remote?: string) => invoke<void>('git_delete_tag', { repoPath, name, remote }),
+    gitRepoStats: (repoPath: string, query?: GitStatsQuery) => invoke<GitRepoStats>('git_repo_stats', { repoPath, query }),
+    gitAvatarClearCache: () => invoke<void>('git_avatar_clear_cache'),
     gitLog: (repoPath: string, limit?: number) => invoke<GitCommit[]>('git_log', { repoPath, limit }),
     gitListBranches: (repoPath: string) => invoke<GitBranch[]>('git_list_branches', { repoPath }),
     gitCreateBranch: (repoPath: string, request: { name: string; from_branch?: string; from_commit?: string }) => invoke<string>('git_create_branch', { repoPath, request }),
@@ -365,13 +617,44 @@ export const tauriApi = {
     audioStop: () => invoke<void>('audio_stop'),
     audioSeek: (position: number) => invoke<void>('audio_seek', { position }),
     audioSetVolume: (volume: number) => invoke<void>('audio_set_volume', { volume }),
+    // AI chat commands
+    aiListProviders: () => invoke<AiProviderInfo[]>('ai_list_providers'),
+    aiListModels: (provider: string, workspace?: string) =>
+        invoke<string[]>('ai_list_models', { provider, workspace }),
+    aiChat: (provider: string, request: AiChatRequest, workspace?: string) =>
+        invoke<AiChatResponse>('ai_chat', { provider, request, workspace }),
+    aiChatStream: (provider: string, request: AiChatRequest, requestId?: string, workspace?: string) =>
+        invoke<AiChatResponse>('ai_chat_stream', { provider, request, requestId, workspace }),
+    aiCancel: (requestId: string) => invoke<void>('ai_cancel', { requestId }),
+    aiModelCatalog: () => invoke<AiModelSpec[]>('ai_model_catalog'),
+    aiEstimateTokens: (provider: string, request: AiChatRequest) =>
+        invoke<AiTokenEstimate>('ai_estimate_tokens', { provider, request }),
+    /** `from`/`to` in Unix millis */
+    aiUsageSummary: (from?: number, to?: number) => invoke<AiUsageSummary>('ai_usage_summary', { from, to }),
+    aiUsageClear: () => invoke<void>('ai_usage_clear'),
+    aiAgentRun: (provider: string, request: AiAgentRequest, runId?: string) =>
+        invoke<AiAgentTranscript>('ai_agent_run', { provider, request, runId }),
+    aiAgentApprove: (runId: string, callId: string, approved: boolean) =>
+        invoke<void>('ai_agent_approve', { runId, callId, approved }),
+    aiConversationsList: (workspace: string) =>
+        invoke<AiConversationSummary[]>('ai_conversations_list', { workspace }),
+    aiConversationCreate: (workspace: string, title?: string) =>
+        invoke<AiConversation>('ai_conversation_create', { workspace, title }),
+    aiConversationGet: (workspace: string, id: string) =>
+        invoke<AiConversation>('ai_conversation_get', { workspace, id }),
+    aiConversationRename: (workspace: string, id: string, title: string) =>
+        invoke<AiConversationSummary>('ai_conversation_rename', { workspace, id, title }),
+    aiConversationDelete: (workspace: string, id: string) =>
+        invoke<void>('ai_conversation_delete', { workspace, id }),
+    aiConversationAppend: (workspace: string, id: string, messages: AiNewMessage[]) =>
+        invoke<AiStoredMessage[]>('ai_conversation_append', { workspace, id, messages }),
+    aiConversationFork: (workspace: string, id: string, messageId: string, title?: string) =>
+        invoke<AiConversation>('ai_conversation_fork', { workspace, id, messageId, title }),
+    aiConversationsSearch: (workspace: string, query: string) =>
+        invoke<AiConversationSearchHit[]>('ai_conversations_search', { workspace, query }),
+    aiConversationExport: (workspace: string, id: string, format: 'markdown' | 'json') =>
+        invoke<string>('ai_conversation_export', { workspace, id, format }),
     // Ollama commands
-    ollamaChat: (model: string, messages: any[], options?: any) => 
-        invoke<any>('ollama_chat', { model, messages, options }),
-    ollamaChatStream: (model: string, messages: any[], options?: any) => 
-        invoke<string>('ollama_chat_stream', { model, messages, options }),
-    ollamaChatComplete: (model: string, messages: any[], options?: any) => 
-        invoke<string>('ollama_chat_complete', { model, messages, options }),
     ollamaListModels: () => invoke<any[]>('ollama_list_models'),
     ollamaPullModel: (model: string) => invoke<void>('ollama_pull_model', { model }),
     ollamaGenerate: (prompt: string, model: string) => 
@@ -380,49 +663,20 @@ export const tauriApi = {
     // AgentRouter commands
     agentrouterConfigure: (apiKey: string, baseUrl?: string) => 
         invoke<void>('agentrouter_configure', { apiKey, baseUrl }),
-    agentrouterChat: (model: string, messages: AgentRouterMessage[], maxTokens?: number, temperature?: number, topP?: number) => 
-        invoke<AgentRouterChatResponse>('agentrouter_chat', { model, messages, maxTokens, temperature, topP }),
-    agentrouterChatStream: (model: string, messages: AgentRouterMessage[], maxTokens?: number, temperature?: number, topP?: number, tools?: AgentRouterTool[], toolChoice?: AgentRouterToolChoice) => 
-        invoke<string>('agentrouter_chat_stream', { model, messages, maxTokens, temperature, topP, tools, toolChoice }),
-    agentrouterChatComplete: (model: string, messages: any[], maxTokens?: number) => 
-        invoke<string>('agentrouter_chat_complete', { model, messages, maxTokens }),
     agentrouterCreateFile: (filePath: string, content?: string) => 
         invoke<string>('agentrouter_create_file', { filePath, content }),
     agentrouterListModels: () => invoke<AgentRouterModelsResponse>('agentrouter_list_models'),
-    // OpenAI commands
-    openaiChat: (model: string, messages: AgentRouterMessage[], maxTokens?: number, temperature?: number) => 
-        invoke<string>('openai_chat', { model, messages, maxTokens, temperature }),
-    openaiChatStream: (model: string, messages: AgentRouterMessage[], maxTokens?: number, temperature?: number) => 
-        invoke<string>('openai_chat_stream', { model, messages, maxTokens, temperature }),
-    openaiChatStreamWithTools: (model: string, messages: AgentRouterMessage[], tools?: any[], toolChoice?: any, maxTokens?: number, temperature?: number) => 
-        invoke<string>('openai_chat_stream_with_tools', { model, messages, tools, toolChoice, maxTokens, temperature }),
-    openaiChatComplete: (model: string, messages: any[], maxTokens?: number) => 
-        invoke<string>('openai_chat_complete', { model, messages, maxTokens }),
-    // Anthropic commands
-    anthropicChat: (model: string, messages: AgentRouterMessage[], maxTokens?: number, temperature?: number) => 
-        invoke<string>('anthropic_chat', { model, messages, maxTokens, temperature }),
-    anthropicChatStream: (model: string, messages: AgentRouterMessage[], maxTokens?: number, temperature?: number) => 
-        invoke<string>('anthropic_chat_stream', { model, messages, maxTokens, temperature }),
-    anthropicChatComplete: (model: string, messages: any[], maxTokens?: number) => 
-        invoke<string>('anthropic_chat_complete', { model, messages, maxTokens }),
-    // Google commands
-    googleChat: (model: string, messages: GoogleMessage[], maxTokens?: number, temperature?: number) => 
-        invoke<string>('google_chat', { model, messages, maxTokens, temperature }),
-    googleChatStream: (model: string, messages: GoogleMessage[], maxTokens?: number, temperature?: number) => 
-        invoke<string>('google_chat_stream', { model, messages, maxTokens, temperature }),
-    googleChatComplete: (model: string, messages: GoogleMessage[], maxTokens?: number) => 
-        invoke<string>('google_chat_complete', { model, messages, maxTokens }),
-    // xAI commands
-    xaiChat: (model: string, messages: AgentRouterMessage[], maxTokens?: number, temperature?: number) => 
-        invoke<string>('xai_chat', { model, messages, maxTokens, temperature }),
-    xaiChatStream: (model: string, messages: AgentRouterMessage[], maxTokens?: number, temperature?: number) => 
-        invoke<string>('xai_chat_stream', { model, messages, maxTokens, temperature }),
-    xaiChatComplete: (model: string, messages: any[], maxTokens?: number) => 
-        invoke<string>('xai_chat_complete', { model, messages, maxTokens }),
     // API key management
-    setApiKey: (provider: string, key: string) => 
-        invoke<void>('set_api_key', { provider, key }),
-    getApiKeys: () => invoke<Record<string, boolean>>('get_api_keys'),
+    /** Empty `key` removes it; `workspace` sets that workspace's override instead of the global key */
+    setApiKey: (provider: string, key: string, workspace?: string) => 
+        invoke<void>('set_api_key', { provider, key, workspace }),
+    getApiKeys: (workspace?: string) => invoke<Record<string, boolean>>('get_api_keys', { workspace }),
+    apiKeysList: (workspace?: string) => invoke<ApiKeyInfo[]>('api_keys_list', { workspace }),
+    apiKeysStatus: () => invoke<ApiKeyStorageStatus>('api_keys_status'),
+    apiKeysUnlock: (passphrase: string) => invoke<ApiKeyStorageStatus>('api_keys_unlock', { passphrase }),
+    apiKeysLock: () => invoke<ApiKeyStorageStatus>('api_keys_lock'),
+    apiKeysChangePassphrase: (passphrase: string) =>
+        invoke<void>('api_keys_change_passphrase', { passphrase }),
     // Terminal commands
     createTerminal: (terminalType: string, cwd?: string, size?: { rows: number; cols: number }) =>
         invoke<{ terminal_id: string; pid: number; process_name: string }>('create_terminal', { terminalType, cwd, size }),
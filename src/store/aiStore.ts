@@ -1,6 +1,7 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
 import { invoke } from '@tauri-apps/api/core';
+import type { ApiKeyStorageStatus } from '../lib/tauri-api';
 
 export type AIModel = {
     id: string;
@@ -47,7 +48,16 @@ interface AIState {
     availableModels: AIModel[];
     activeModelId: string;
     activeMode: 'responder' | 'agent';
+    /** Keys typed in this session; they are stored by the backend */
     apiKeys: ApiKeys;
+    /**
+     * Keys the backend has not confirmed storing yet, e.g. while its storage is
+     * locked. Only these are persisted here, so nothing is lost before it is saved.
+     */
+    unsavedApiKeys: Partial<ApiKeys>;
+    /** Which providers have a key stored in the backend */
+    apiKeyStatus: Record<string, boolean>;
+    apiKeyStorage: ApiKeyStorageStatus | null;
     ollamaLocalModels: OllamaLocalModel[];
     isAssistantOpen: boolean;
     isLoadingModels: boolean;
@@ -73,6 +83,10 @@ interface AIState {
     addXAIModels: () => void;
     initializeAgentRouter: () => Promise<void>;
     initializeApiKeys: () => Promise<void>;
+    refreshApiKeyStatus: () => Promise<void>;
+    markApiKeySaved: (provider: keyof ApiKeys, key: string) => void;
+    /** Unlocks (or creates) the encrypted key file, then saves any unsaved keys */
+    unlockApiKeys: (passphrase: string) => Promise<void>;
     initializeModels: () => Promise<void>;
     
     // Getters
@@ -97,6 +111,9 @@ export const useAIStore = create<AIState>()(
                 gigachat: '',
                 agentrouter: '',
             },
+            unsavedApiKeys: {},
+            apiKeyStatus: {},
+            apiKeyStorage: null,
             ollamaLocalModels: [],
             isAssistantOpen: false,
             isLoadingModels: false,
@@ -108,14 +125,18 @@ export const useAIStore = create<AIState>()(
                 set((state) => { 
                     const newApiKeys = { ...state.apiKeys, ...keys };
                     
-                    // Call the backend to store API keys
-                    Object.entries(keys).forEach(([provider, key]) => {
-                        if (key !== undefined) {
-                            invoke('set_api_key', { provider, key: key.trim() }).catch(error => {
-                                console.error(`Failed to set ${provider} API key:`, error);
-                            });
-                        }
-                    });
+                    // Call the backend to store API keys; a key stays in
+                    // `unsavedApiKeys` until the backend confirms it
+                    const saves = Object.entries(keys)
+                        .filter(([, key]) => key !== undefined)
+                        .map(([provider, key]) =>
+                            invoke('set_api_key', { provider, key: key!.trim() })
+                                .then(() => get().markApiKeySaved(provider as keyof ApiKeys, key!))
+                                .catch(error => {
+                                    console.error(`Failed to set ${provider} API key:`, error);
+                                })
+                        );
+                    Promise.all(saves).then(() => get().refreshApiKeyStatus());
                     
                     // Configure AgentRouter if API key is provided
                     if (keys.agentrouter && keys.agentrouter.trim()) {
@@ -127,7 +148,7 @@ export const useAIStore = create<AIState>()(
                         });
                     }
                     
-                    return { apiKeys: newApiKeys };
+                    return { apiKeys: newApiKeys, unsavedApiKeys: { ...state.unsavedApiKeys, ...keys } };
                 });
 
                 // Refresh OpenAI models if OpenAI API key was set
@@ -197,20 +218,28 @@ export const useAIStore = create<AIState>()(
                 try {
                     const state = get();
                     
-                    // Sync API keys from frontend store to backend
-                    // This ensures backend has the keys that were persisted in localStorage
-                    const syncPromises = Object.entries(state.apiKeys).map(async ([provider, key]) => {
-                        if (key && key.trim()) {
-                            try {
-                                await invoke('set_api_key', { provider, key: key.trim() });
-                                console.log(`Synced ${provider} API key to backend`);
-                            } catch (error) {
-                                console.error(`Failed to sync ${provider} API key:`, error);
-                            }
+                    // Keys used to be persisted in localStorage, and a key the backend
+                    // could not store yet is still kept there; move them into the
+                    // backend's storage and drop each one only once it is saved
+                    const unsaved: Partial<ApiKeys> = { ...state.unsavedApiKeys };
+                    for (const [provider, key] of Object.entries(state.apiKeys)) {
+                        if (key && key.trim() && unsaved[provider as keyof ApiKeys] === undefined) {
+                            unsaved[provider as keyof ApiKeys] = key;
+                        }
+                    }
+                    set({ unsavedApiKeys: unsaved });
+
+                    const syncPromises = Object.entries(unsaved).map(async ([provider, key]) => {
+                        try {
+                            await invoke('set_api_key', { provider, key: (key ?? '').trim() });
+                            get().markApiKeySaved(provider as keyof ApiKeys, key ?? '');
+                        } catch (error) {
+                            console.error(`Failed to move ${provider} API key to secure storage:`, error);
                         }
                     });
                     
                     await Promise.all(syncPromises);
+                    await get().refreshApiKeyStatus();
                     
                     // Configure AgentRouter if API key exists
                     if (state.apiKeys.agentrouter && state.apiKeys.agentrouter.trim()) {
@@ -225,6 +254,37 @@ export const useAIStore = create<AIState>()(
                     console.error('Failed to initialize API keys:', error);
                 }
             },
+
+            markApiKeySaved: (provider: keyof ApiKeys, key: string) => {
+                set((state) => {
+                    // A newer value typed meanwhile is still unsaved
+                    if (state.unsavedApiKeys[provider] !== key) {
+                        return {};
+                    }
+                    const { [provider]: _saved, ...rest } = state.unsavedApiKeys;
+                    return { unsavedApiKeys: rest };
+                });
+            },
+
+            refreshApiKeyStatus: async () => {
+                try {
+                    const [status, storage] = await Promise.all([
+                        invoke<Record<string, boolean>>('get_api_keys'),
+                        invoke<ApiKeyStorageStatus>('api_keys_status'),
+                    ]);
+                    set({ apiKeyStatus: status, apiKeyStorage: storage });
+                } catch (error) {
+                    console.error('Failed to load API key status:', error);
+                }
+            },
+
+            unlockApiKeys: async (passphrase: string) => {
+                const storage = await invoke<ApiKeyStorageStatus>('api_keys_unlock', { passphrase });
+                set({ apiKeyStorage: storage });
+                if (!storage.locked) {
+                    await get().initializeApiKeys();
+                }
+            },
             
             refreshOllamaModels: async () => {
                 try {
@@ -264,8 +324,7 @@ export const useAIStore = create<AIState>()(
                     );
                     return isDownloaded ? 'available' : 'not-downloaded';
                 } else {
-                    const hasApiKey = state.apiKeys[model.provider as keyof ApiKeys];
-                    return hasApiKey && hasApiKey.trim() ? 'available' : 'no-api-key';
+                    return state.apiKeyStatus[model.provider] ? 'available' : 'no-api-key';
                 }
             },
 
@@ -456,6 +515,19 @@ export const useAIStore = create<AIState>()(
         {
             name: 'ai-storage-v10', // Update version to force refresh with proxy models
             version: 10,
+            // API keys live in the backend's keyring or encrypted file; only keys it
+            // has not confirmed storing are kept here, under the old `apiKeys` name
+            // so they are picked up by `initializeApiKeys` on the next start
+            partialize: ({ apiKeys, unsavedApiKeys, apiKeyStatus, apiKeyStorage, ...rest }) => ({
+                ...rest,
+                apiKeys: unsavedApiKeys,
+            }),
+            // Persisted keys are partial; keep an entry for every provider
+            merge: (persistedState: any, currentState) => ({
+                ...currentState,
+                ...persistedState,
+                apiKeys: { ...currentState.apiKeys, ...persistedState?.apiKeys },
+            }),
             migrate: (persistedState: any, version: number) => {
                 // Clear old state and return fresh state - models will be loaded dynamically
                 if (version < 10) {